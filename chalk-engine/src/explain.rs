@@ -0,0 +1,191 @@
+//! Proof trees for [`SLGSolver::explain`](crate::solve::SLGSolver).
+//!
+//! The SLG solver does not keep track of how the answers of a table were
+//! derived, so the proof tree is rebuilt afterwards: each goal is broken
+//! down the way `Forest::build_table` does it, and each of the resulting
+//! subgoals is explained in turn, along with the solution the forest finds
+//! for its own table.
+
+use crate::forest::Forest;
+use crate::slg::aggregate::AggregateOps;
+use crate::slg::{ResolventOps, SlgContextOps, TruncatingInferenceTable, UnificationOps};
+use crate::{ExClause, Literal};
+use chalk_ir::could_match::CouldMatch;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use chalk_solve::clauses::program_clauses_for_goal;
+use chalk_solve::infer::InferenceTable;
+use chalk_solve::solve::proof_tree::{ClauseApplication, ClauseSource, Derivation, ProofTree};
+use chalk_solve::solve::{Guidance, Solution};
+
+pub(crate) struct Explainer<'forest, I: Interner> {
+    forest: &'forest mut Forest<I>,
+    context: &'forest SlgContextOps<'forest, I>,
+
+    /// The goals being explained, used to detect cycles.
+    stack: Vec<UCanonical<InEnvironment<Goal<I>>>>,
+}
+
+impl<'forest, I: Interner> Explainer<'forest, I> {
+    pub(crate) fn new(
+        forest: &'forest mut Forest<I>,
+        context: &'forest SlgContextOps<'forest, I>,
+    ) -> Self {
+        Explainer {
+            forest,
+            context,
+            stack: vec![],
+        }
+    }
+
+    pub(crate) fn explain_goal(
+        &mut self,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> ProofTree<I> {
+        let solution =
+            self.context
+                .make_solution(goal, self.forest.iter_answers(self.context, goal), || true);
+        let derivation = if self.stack.contains(goal) {
+            Derivation::Cycle
+        } else {
+            self.stack.push(goal.clone());
+            let derivation = self.derivation(goal);
+            self.stack.pop();
+            derivation
+        };
+        ProofTree {
+            goal: goal.clone(),
+            solution,
+            derivation,
+        }
+    }
+
+    fn derivation(&mut self, goal: &UCanonical<InEnvironment<Goal<I>>>) -> Derivation<I> {
+        let interner = self.context.program().interner();
+        let (infer, subst, InEnvironment { environment, goal }) =
+            InferenceTable::from_canonical(interner, goal.universes, &goal.canonical);
+        let mut infer = TruncatingInferenceTable::new(self.context.max_size(), infer);
+
+        match goal.data(interner) {
+            GoalData::DomainGoal(domain_goal) => {
+                let program = self.context.program();
+                let clauses = match program_clauses_for_goal(
+                    program,
+                    &environment,
+                    domain_goal,
+                    &CanonicalVarKinds::empty(interner),
+                ) {
+                    Ok(clauses) => clauses,
+                    Err(Floundered) => return Derivation::Floundered,
+                };
+
+                // `program_clauses_for_goal` lists the clauses from the
+                // environment last.
+                let num_env_clauses = program
+                    .program_clauses_for_env(&environment)
+                    .iter(interner)
+                    .filter(|&c| c.could_match(interner, domain_goal))
+                    .count();
+                let first_env_clause = clauses.len() - num_env_clauses;
+
+                let applications = clauses
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, clause)| {
+                        let source = if index >= first_env_clause {
+                            ClauseSource::Environment
+                        } else {
+                            ClauseSource::Program
+                        };
+                        let mut infer = infer.clone();
+                        match infer.resolvent_clause(
+                            interner,
+                            &environment,
+                            domain_goal,
+                            &subst,
+                            &clause,
+                        ) {
+                            Ok(ex_clause) => {
+                                let subgoals = self.explain_subgoals(&mut infer, ex_clause);
+                                let succeeded = subgoals.iter().all(|s| s.solution.is_some());
+                                ClauseApplication {
+                                    clause,
+                                    source,
+                                    subgoals,
+                                    succeeded,
+                                }
+                            }
+                            Err(_) => ClauseApplication {
+                                clause,
+                                source,
+                                subgoals: vec![],
+                                succeeded: false,
+                            },
+                        }
+                    })
+                    .collect();
+                Derivation::Clauses(applications)
+            }
+            GoalData::Not(subgoal) => {
+                let subgoal = InEnvironment::new(&environment, subgoal.clone());
+                match Forest::abstract_negative_literal(self.context, &mut infer, &subgoal) {
+                    Some((subgoal, _)) => Derivation::Simplified(vec![self.explain_goal(&subgoal)]),
+                    None => Derivation::Floundered,
+                }
+            }
+            _ => match Forest::simplify_goal(self.context, &mut infer, subst, environment, goal) {
+                Ok(ex_clause) => {
+                    Derivation::Simplified(self.explain_subgoals(&mut infer, ex_clause))
+                }
+                Err(_) => Derivation::Simplified(vec![]),
+            },
+        }
+    }
+
+    /// Explains the subgoals of a strand. A negative literal is explained
+    /// as a `Not` goal, whose only subgoal is the goal it negates.
+    fn explain_subgoals(
+        &mut self,
+        infer: &mut TruncatingInferenceTable<I>,
+        ex_clause: ExClause<I>,
+    ) -> Vec<ProofTree<I>> {
+        let interner = self.context.program().interner();
+        ex_clause
+            .subgoals
+            .into_iter()
+            .map(|literal| match literal {
+                Literal::Positive(subgoal) => {
+                    match Forest::abstract_positive_literal(self.context, infer, &subgoal) {
+                        Some((subgoal, _)) => self.explain_goal(&subgoal),
+                        None => self.floundered(infer, subgoal),
+                    }
+                }
+                Literal::Negative(subgoal) => {
+                    let negated = InEnvironment::new(
+                        &subgoal.environment,
+                        GoalData::Not(subgoal.goal).intern(interner),
+                    );
+                    let (negated, _) = infer.fully_canonicalize_goal(interner, &negated);
+                    self.explain_goal(&negated)
+                }
+            })
+            .collect()
+    }
+
+    /// A subgoal that is too large (or, if negative, not ground) flounders:
+    /// the forest does not solve it, and the strand that contains it only
+    /// yields an ambiguous answer.
+    fn floundered(
+        &mut self,
+        infer: &mut TruncatingInferenceTable<I>,
+        subgoal: InEnvironment<Goal<I>>,
+    ) -> ProofTree<I> {
+        let interner = self.context.program().interner();
+        let (goal, _) = infer.fully_canonicalize_goal(interner, &subgoal);
+        ProofTree {
+            goal,
+            solution: Some(Solution::Ambig(Guidance::Unknown)),
+            derivation: Derivation::Floundered,
+        }
+    }
+}
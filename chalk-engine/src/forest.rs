@@ -17,6 +17,10 @@ pub(crate) struct Forest<I: Interner> {
     /// This effectively gives us way to track what depth
     /// and loop a table or strand was last followed.
     pub(crate) clock: TimeStamp,
+
+    /// The number of times the search for a root answer exceeded its
+    /// quantum, for statistics.
+    pub(crate) quantum_exceeded: usize,
 }
 
 impl<I: Interner> Forest<I> {
@@ -24,6 +28,7 @@ impl<I: Interner> Forest<I> {
         Forest {
             tables: Tables::new(),
             clock: TimeStamp::default(),
+            quantum_exceeded: 0,
         }
     }
 
@@ -87,6 +92,7 @@ impl<'me, I: Interner> AnswerStream<I> for ForestSolver<'me, I> {
                 }
 
                 Err(RootSearchFail::QuantumExceeded) => {
+                    self.forest.quantum_exceeded += 1;
                     if !should_continue() {
                         return AnswerResult::QuantumExceeded;
                     }
@@ -66,6 +66,7 @@ use chalk_ir::{
 
 pub mod context;
 mod derived;
+mod explain;
 pub mod forest;
 mod logic;
 mod normalize_deep;
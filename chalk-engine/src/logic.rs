@@ -292,7 +292,7 @@ impl<I: Interner> Forest<I> {
                                     last_pursued_time: TimeStamp::default(),
                                 };
                                 let canonical_strand = Self::canonicalize_strand(context, strand);
-                                table.enqueue_strand(canonical_strand);
+                                table.enqueue_new_strand(canonical_strand);
                             }
                         }
                     }
@@ -330,7 +330,7 @@ impl<I: Interner> Forest<I> {
                         last_pursued_time: TimeStamp::default(),
                     };
                     let canonical_strand = Self::canonicalize_strand(context, strand);
-                    table.enqueue_strand(canonical_strand);
+                    table.enqueue_new_strand(canonical_strand);
                 }
             }
         }
@@ -344,7 +344,7 @@ impl<I: Interner> Forest<I> {
     /// effect, and hence we are simply returning the canonical form
     /// of `subgoal`; but if the subgoal is getting too big, we return
     /// `None`, which causes the subgoal to flounder.
-    fn abstract_positive_literal(
+    pub(super) fn abstract_positive_literal(
         context: &SlgContextOps<I>,
         infer: &mut TruncatingInferenceTable<I>,
         subgoal: &InEnvironment<Goal<I>>,
@@ -363,7 +363,7 @@ impl<I: Interner> Forest<I> {
     /// fail to yield a useful result, for example if free existential
     /// variables appear in `subgoal` (in which case the execution is
     /// said to "flounder").
-    fn abstract_negative_literal(
+    pub(super) fn abstract_negative_literal(
         context: &SlgContextOps<I>,
         infer: &mut TruncatingInferenceTable<I>,
         subgoal: &InEnvironment<Goal<I>>,
@@ -624,7 +624,7 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
                 };
                 let table = self.stack.top().table;
                 let canonical_next_strand = Forest::canonicalize_strand(self.context, next_strand);
-                self.forest.tables[table].enqueue_strand(canonical_next_strand);
+                self.forest.tables[table].enqueue_new_strand(canonical_next_strand);
             }
         }
 
@@ -1017,7 +1017,7 @@ impl<'forest, I: Interner> SolveState<'forest, I> {
 
                         let answer = self.forest.answer(table, answer_index);
                         if let Some(strand) = self.create_refinement_strand(table, answer) {
-                            self.forest.tables[table].enqueue_strand(strand);
+                            self.forest.tables[table].enqueue_new_strand(strand);
                         }
 
                         NoRemainingSubgoalsResult::RootAnswerAvailable
@@ -5,7 +5,7 @@ use crate::{ExClause, Literal, TimeStamp};
 use chalk_ir::cast::Cast;
 use chalk_ir::interner::Interner;
 use chalk_ir::{
-    Environment, Fallible, Goal, GoalData, InEnvironment, QuantifierKind, Substitution,
+    Environment, Fallible, Goal, GoalData, InEnvironment, QuantifierKind, Substitution, Variance,
 };
 use tracing::debug;
 
@@ -72,6 +72,14 @@ impl<I: Interner> Forest<I> {
                     &goal.b,
                     &mut ex_clause,
                 )?,
+                GoalData::SubtypeGoal(goal) => infer.relate_tys_into_ex_clause(
+                    context.program(),
+                    &environment,
+                    Variance::Covariant,
+                    &goal.a,
+                    &goal.b,
+                    &mut ex_clause,
+                )?,
                 GoalData::DomainGoal(domain_goal) => {
                     ex_clause
                         .subgoals
@@ -195,6 +195,21 @@ pub trait UnificationOps<I: Interner> {
         b: &GenericArg<I>,
         ex_clause: &mut ExClause<I>,
     ) -> Fallible<()>;
+
+    /// Relates the types `a` and `b` according to `variance`, adding the
+    /// resulting goals to `ex_clause`.
+    ///
+    /// If the types cannot be related, then `Error` is returned
+    // Used by: simplify
+    fn relate_tys_into_ex_clause(
+        &mut self,
+        db: &dyn RustIrDatabase<I>,
+        environment: &Environment<I>,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+        ex_clause: &mut ExClause<I>,
+    ) -> Fallible<()>;
 }
 
 #[derive(Clone)]
@@ -316,6 +331,23 @@ impl<I: Interner> UnificationOps<I> for TruncatingInferenceTable<I> {
         let result = self.infer.unify(interner, environment, a, b)?;
         Ok(into_ex_clause(interner, result, ex_clause))
     }
+
+    fn relate_tys_into_ex_clause(
+        &mut self,
+        db: &dyn RustIrDatabase<I>,
+        environment: &Environment<I>,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+        ex_clause: &mut ExClause<I>,
+    ) -> Fallible<()> {
+        let interner = db.interner();
+        let result = self
+            .infer
+            .relate(interner, db, environment, variance, a, b)?;
+        into_ex_clause(interner, result, ex_clause);
+        Ok(())
+    }
 }
 
 /// Helper function
@@ -515,8 +547,29 @@ impl<I: Interner> MayInvalidate<'_, I> {
                 !c1.const_eq(new_ty, c2, interner)
             }
 
-            // Only variants left are placeholder = concrete, which always fails
-            (ConstValue::Placeholder(_), _) | (ConstValue::Concrete(_), _) => true,
+            (ConstValue::Projection(proj1), ConstValue::Projection(proj2)) => self
+                .aggregate_name_and_substs(
+                    &proj1.associated_const_id,
+                    &proj1.substitution,
+                    &proj2.associated_const_id,
+                    &proj2.substitution,
+                ),
+
+            (
+                ConstValue::Expr(ConstExpr::Unary(op1, a1)),
+                ConstValue::Expr(ConstExpr::Unary(op2, a2)),
+            ) => op1 != op2 || self.aggregate_consts(a1, a2),
+
+            (
+                ConstValue::Expr(ConstExpr::Binary(op1, a1, b1)),
+                ConstValue::Expr(ConstExpr::Binary(op2, a2, b2)),
+            ) => op1 != op2 || self.aggregate_consts(a1, a2) || self.aggregate_consts(b1, b2),
+
+            // Only variants left are mismatched kinds of consts, which always fail
+            (ConstValue::Placeholder(_), _)
+            | (ConstValue::Concrete(_), _)
+            | (ConstValue::Projection(_), _)
+            | (ConstValue::Expr(_), _) => true,
         }
     }
 
@@ -537,9 +537,21 @@ impl<I: Interner> AntiUnifier<'_, '_, I> {
                 }
             }
 
-            (ConstValue::Placeholder(_), _) | (_, ConstValue::Placeholder(_)) => {
-                self.new_const_variable(ty)
+            (ConstValue::Projection(_), ConstValue::Projection(_))
+            | (ConstValue::Expr(_), ConstValue::Expr(_)) => {
+                if c1 == c2 {
+                    c1.clone()
+                } else {
+                    self.new_const_variable(ty)
+                }
             }
+
+            (ConstValue::Placeholder(_), _)
+            | (_, ConstValue::Placeholder(_))
+            | (ConstValue::Projection(_), _)
+            | (_, ConstValue::Projection(_))
+            | (ConstValue::Expr(_), _)
+            | (_, ConstValue::Expr(_)) => self.new_const_variable(ty),
         }
     }
 
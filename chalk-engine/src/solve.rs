@@ -1,17 +1,33 @@
 use crate::context::{AnswerResult, AnswerStream};
+use crate::explain::Explainer;
 use crate::forest::Forest;
 use crate::slg::aggregate::AggregateOps;
 use crate::slg::SlgContextOps;
+use crate::TableIndex;
 use chalk_ir::interner::Interner;
 use chalk_ir::{Canonical, ConstrainedSubst, Goal, InEnvironment, UCanonical};
+use chalk_solve::solve::proof_tree::ProofTree;
+use chalk_solve::solve::stats::{QueryStats, SlgStats, SolverStats, TableStats};
 use chalk_solve::{RustIrDatabase, Solution, Solver, SubstitutionResult};
 
 use std::fmt;
+use std::time::{Duration, Instant};
 
 pub struct SLGSolver<I: Interner> {
     pub(crate) forest: Forest<I>,
     pub(crate) max_size: usize,
     pub(crate) expected_answers: Option<usize>,
+
+    /// The statistics recorded for each query, if recording is enabled
+    /// (see `Solver::record_stats`).
+    stats: Option<Vec<QueryStats<I>>>,
+}
+
+/// The state of the forest at the start of a query, used to compute the
+/// statistics for the query once it is done.
+struct QueryStart {
+    next_table: TableIndex,
+    quantum_exceeded: usize,
 }
 
 impl<I: Interner> SLGSolver<I> {
@@ -20,8 +36,46 @@ impl<I: Interner> SLGSolver<I> {
             forest: Forest::new(),
             max_size,
             expected_answers,
+            stats: None,
         }
     }
+
+    fn start_query(&self) -> QueryStart {
+        QueryStart {
+            next_table: self.forest.tables.next_index(),
+            quantum_exceeded: self.forest.quantum_exceeded,
+        }
+    }
+
+    /// Records the statistics for a query on `goal` that started at
+    /// `start` and took `time`, if recording is enabled.
+    fn finish_query(
+        &mut self,
+        start: QueryStart,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+        time: Duration,
+    ) {
+        let stats = match &mut self.stats {
+            Some(stats) => stats,
+            None => return,
+        };
+        let tables = &self.forest.tables;
+        let created = TableIndex::iterate_range(start.next_table..tables.next_index());
+        stats.push(QueryStats {
+            goal: goal.clone(),
+            time,
+            details: SolverStats::Slg(SlgStats {
+                tables: created
+                    .map(|index| TableStats {
+                        goal: tables[index].table_goal.clone(),
+                        strands: tables[index].strands_created(),
+                        answers: tables[index].num_answers(),
+                    })
+                    .collect(),
+                quantum_exceeded: self.forest.quantum_exceeded - start.quantum_exceeded,
+            }),
+        });
+    }
 }
 
 impl<I: Interner> fmt::Debug for SLGSolver<I> {
@@ -36,8 +90,12 @@ impl<I: Interner> Solver<I> for SLGSolver<I> {
         program: &dyn RustIrDatabase<I>,
         goal: &UCanonical<InEnvironment<Goal<I>>>,
     ) -> Option<Solution<I>> {
+        let start = self.start_query();
+        let time = Instant::now();
         let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
-        ops.make_solution(goal, self.forest.iter_answers(&ops, goal), || true)
+        let solution = ops.make_solution(goal, self.forest.iter_answers(&ops, goal), || true);
+        self.finish_query(start, goal, time.elapsed());
+        solution
     }
 
     fn solve_limited(
@@ -46,8 +104,22 @@ impl<I: Interner> Solver<I> for SLGSolver<I> {
         goal: &UCanonical<InEnvironment<Goal<I>>>,
         should_continue: &dyn std::ops::Fn() -> bool,
     ) -> Option<Solution<I>> {
+        let start = self.start_query();
+        let time = Instant::now();
         let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
-        ops.make_solution(goal, self.forest.iter_answers(&ops, goal), should_continue)
+        let solution =
+            ops.make_solution(goal, self.forest.iter_answers(&ops, goal), should_continue);
+        self.finish_query(start, goal, time.elapsed());
+        solution
+    }
+
+    fn explain(
+        &mut self,
+        program: &dyn RustIrDatabase<I>,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<ProofTree<I>> {
+        let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
+        Some(Explainer::new(&mut self.forest, &ops).explain_goal(goal))
     }
 
     fn solve_multiple(
@@ -56,34 +128,61 @@ impl<I: Interner> Solver<I> for SLGSolver<I> {
         goal: &UCanonical<InEnvironment<Goal<I>>>,
         f: &mut dyn FnMut(SubstitutionResult<Canonical<ConstrainedSubst<I>>>, bool) -> bool,
     ) -> bool {
+        let start = self.start_query();
+        let mut time = Duration::default();
         let ops = SlgContextOps::new(program, self.max_size, self.expected_answers);
-        let mut answers = self.forest.iter_answers(&ops, goal);
-        loop {
-            let subst = match answers.next_answer(|| true) {
-                AnswerResult::Answer(answer) => {
-                    if !answer.ambiguous {
-                        SubstitutionResult::Definite(answer.subst)
-                    } else if answer
-                        .subst
-                        .value
-                        .subst
-                        .is_identity_subst(ops.program().interner())
-                    {
-                        SubstitutionResult::Floundered
-                    } else {
-                        SubstitutionResult::Ambiguous(answer.subst)
+        let completed = {
+            let mut answers = self.forest.iter_answers(&ops, goal);
+            loop {
+                let answer_start = Instant::now();
+                let answer = answers.next_answer(|| true);
+                time += answer_start.elapsed();
+                let subst = match answer {
+                    AnswerResult::Answer(answer) => {
+                        if !answer.ambiguous {
+                            SubstitutionResult::Definite(answer.subst)
+                        } else if answer
+                            .subst
+                            .value
+                            .subst
+                            .is_identity_subst(ops.program().interner())
+                        {
+                            SubstitutionResult::Floundered
+                        } else {
+                            SubstitutionResult::Ambiguous(answer.subst)
+                        }
                     }
-                }
-                AnswerResult::Floundered => SubstitutionResult::Floundered,
-                AnswerResult::NoMoreSolutions => {
-                    return true;
-                }
-                AnswerResult::QuantumExceeded => continue,
-            };
+                    AnswerResult::Floundered => SubstitutionResult::Floundered,
+                    AnswerResult::NoMoreSolutions => {
+                        break true;
+                    }
+                    AnswerResult::QuantumExceeded => continue,
+                };
 
-            if !f(subst, !answers.peek_answer(|| true).is_no_more_solutions()) {
-                return false;
+                let peek_start = Instant::now();
+                let has_next = !answers.peek_answer(|| true).is_no_more_solutions();
+                time += peek_start.elapsed();
+                if !f(subst, has_next) {
+                    break false;
+                }
             }
+        };
+        self.finish_query(start, goal, time);
+        completed
+    }
+
+    fn record_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.stats = None;
+        } else if self.stats.is_none() {
+            self.stats = Some(vec![]);
+        }
+    }
+
+    fn take_stats(&mut self) -> Vec<QueryStats<I>> {
+        match &mut self.stats {
+            Some(stats) => std::mem::take(stats),
+            None => vec![],
         }
     }
 }
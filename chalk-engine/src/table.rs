@@ -44,6 +44,11 @@ pub(crate) struct Table<I: Interner> {
     /// answers.
     strands: VecDeque<CanonicalStrand<I>>,
 
+    /// The number of strands that have been created for this table, for
+    /// statistics. Strands that are pushed back onto the queue after
+    /// being pursued are not counted again.
+    strands_created: usize,
+
     pub(crate) answer_mode: AnswerMode,
 }
 
@@ -65,6 +70,7 @@ impl<I: Interner> Table<I> {
             floundered: false,
             answers_hash: FxHashMap::default(),
             strands: VecDeque::new(),
+            strands_created: 0,
             answer_mode: AnswerMode::Complete,
         }
     }
@@ -74,6 +80,17 @@ impl<I: Interner> Table<I> {
         self.strands.push_back(strand);
     }
 
+    /// Push a strand that was just created to the back of the queue of
+    /// strands to be processed, counting it in `strands_created`.
+    pub(crate) fn enqueue_new_strand(&mut self, strand: CanonicalStrand<I>) {
+        self.strands_created += 1;
+        self.enqueue_strand(strand);
+    }
+
+    pub(crate) fn strands_created(&self) -> usize {
+        self.strands_created
+    }
+
     pub(crate) fn strands_mut(&mut self) -> impl Iterator<Item = &mut CanonicalStrand<I>> {
         self.strands.iter_mut()
     }
@@ -163,6 +180,10 @@ impl<I: Interner> Table<I> {
         self.answers.get(index.value)
     }
 
+    pub(crate) fn num_answers(&self) -> usize {
+        self.answers.len()
+    }
+
     pub(super) fn next_answer_index(&self) -> AnswerIndex {
         AnswerIndex::from(self.answers.len())
     }
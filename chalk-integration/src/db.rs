@@ -1,21 +1,30 @@
 use crate::{
     error::ChalkError,
+    files::SourceFiles,
     interner::ChalkIr,
     lowering::lower_goal,
     program::Program,
     query::{Lowering, LoweringDatabase},
-    tls, SolverChoice,
+    tls, Identifier, SolverChoice,
 };
 use chalk_ir::{
-    AdtId, AssocTypeId, Binders, Canonical, CanonicalVarKinds, ClosureId, ConstrainedSubst,
-    Environment, FnDefId, GeneratorId, GenericArg, Goal, ImplId, InEnvironment, OpaqueTyId,
-    ProgramClause, ProgramClauses, Substitution, TraitId, Ty, TyKind, UCanonical,
+    AdtId, AssocConstId, AssocTypeId, Binders, Canonical, CanonicalVarKinds, ClosureId,
+    ConstrainedSubst, DomainGoal, Environment, FnDefId, GeneratorId, GenericArg, Goal, ImplId,
+    InEnvironment, OpaqueTyId, ProgramClause, ProgramClauses, Substitution, TraitId, Ty, TyKind,
+    UCanonical, Variance,
 };
 use chalk_solve::rust_ir::{
-    AdtDatum, AdtRepr, AssociatedTyDatum, AssociatedTyValue, AssociatedTyValueId, ClosureKind,
+    AdtDatum, AdtRepr, AssociatedConstDatum, AssociatedConstValue, AssociatedConstValueId,
+    AssociatedFnDatum, AssociatedTyDatum, AssociatedTyValue, AssociatedTyValueId, ClosureKind,
     FnDefDatum, FnDefInputsAndOutputDatum, GeneratorDatum, GeneratorWitnessDatum, ImplDatum,
-    OpaqueTyDatum, TraitDatum, WellKnownTrait,
+    OpaqueTyDatum, TraitDatum, WellKnownAdt, WellKnownTrait,
 };
+use chalk_solve::solve::autoderef::{autoderef, AutoderefChain};
+use chalk_solve::solve::closure_kinds::closure_kind_obligations;
+use chalk_solve::solve::method_probe::{probe_method, MethodCandidate};
+use chalk_solve::solve::proof_tree::ProofTree;
+use chalk_solve::solve::stats::QueryStats;
+use chalk_solve::solve::why_not::{why_not, FailureAnalysis};
 use chalk_solve::{RustIrDatabase, Solution, SubstitutionResult};
 use salsa::Database;
 use std::fmt;
@@ -31,8 +40,13 @@ impl Database for ChalkDatabase {}
 
 impl ChalkDatabase {
     pub fn with(program_text: &str, solver_choice: SolverChoice) -> Self {
+        Self::with_files(SourceFiles::from_text(program_text), solver_choice)
+    }
+
+    /// Creates a database for a program made of several source files.
+    pub fn with_files(files: SourceFiles, solver_choice: SolverChoice) -> Self {
         let mut db = ChalkDatabase::default();
-        db.set_program_text(Arc::new(program_text.to_string()));
+        db.set_program_files(Arc::new(files));
         db.set_solver_choice(solver_choice);
         db
     }
@@ -56,6 +70,69 @@ impl ChalkDatabase {
         solution
     }
 
+    /// Solves a given goal, recording how the solution was derived.
+    pub fn explain(&self, goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>) -> ProofTree<ChalkIr> {
+        let solver = self.solver();
+        let proof_tree = solver.lock().unwrap().explain(self, goal);
+        proof_tree.expect("both solvers can explain their solutions")
+    }
+
+    /// Explains why a given goal cannot be proven, or returns `None` if it
+    /// can be. See [`why_not`] for details.
+    pub fn why_not(
+        &self,
+        goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>,
+    ) -> Option<FailureAnalysis<ChalkIr>> {
+        let solver = self.solver();
+        let mut solver = solver.lock().unwrap();
+        why_not(self, &mut **solver, goal)
+    }
+
+    /// Returns the closure kind obligations that a given goal depends on.
+    /// See [`closure_kind_obligations`] for details.
+    pub fn closure_kind_obligations(
+        &self,
+        goal: &UCanonical<InEnvironment<Goal<ChalkIr>>>,
+    ) -> Vec<UCanonical<InEnvironment<DomainGoal<ChalkIr>>>> {
+        let solver = self.solver();
+        let mut solver = solver.lock().unwrap();
+        closure_kind_obligations(self, &mut **solver, goal)
+    }
+
+    /// Finds the methods named `name` that can be called on `receiver`. See
+    /// [`probe_method`] for details.
+    pub fn probe_method(
+        &self,
+        receiver: &UCanonical<InEnvironment<Ty<ChalkIr>>>,
+        name: &str,
+    ) -> Vec<MethodCandidate<ChalkIr>> {
+        let solver = self.solver();
+        let mut solver = solver.lock().unwrap();
+        probe_method(self, &mut **solver, receiver, &Identifier::from(name))
+    }
+
+    /// Computes the types that `ty` successively dereferences to. See
+    /// [`autoderef`] for details.
+    pub fn autoderef(
+        &self,
+        ty: &UCanonical<InEnvironment<Ty<ChalkIr>>>,
+    ) -> Canonical<AutoderefChain<ChalkIr>> {
+        let solver = self.solver();
+        let mut solver = solver.lock().unwrap();
+        autoderef(self, &mut **solver, ty)
+    }
+
+    /// Starts or stops recording statistics for the goals solved with this
+    /// database. See [`Solver::record_stats`](chalk_solve::Solver::record_stats).
+    pub fn record_stats(&self, enabled: bool) {
+        self.solver().lock().unwrap().record_stats(enabled);
+    }
+
+    /// Returns the statistics recorded since the last call, oldest first.
+    pub fn take_stats(&self) -> Vec<QueryStats<ChalkIr>> {
+        self.solver().lock().unwrap().take_stats()
+    }
+
     /// Solves a given goal, producing the solution. This will do only
     /// as much work towards `goal` as it has to (and that works is
     /// cached for future attempts). Calls provided function `f` to
@@ -80,6 +157,13 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         self.program_ir().unwrap().associated_ty_data(ty)
     }
 
+    fn associated_const_data(
+        &self,
+        id: AssocConstId<ChalkIr>,
+    ) -> Arc<AssociatedConstDatum<ChalkIr>> {
+        self.program_ir().unwrap().associated_const_data(id)
+    }
+
     fn trait_datum(&self, id: TraitId<ChalkIr>) -> Arc<TraitDatum<ChalkIr>> {
         self.program_ir().unwrap().trait_datum(id)
     }
@@ -95,6 +179,13 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         self.program_ir().unwrap().associated_ty_values[&id].clone()
     }
 
+    fn associated_const_value(
+        &self,
+        id: AssociatedConstValueId<ChalkIr>,
+    ) -> Arc<AssociatedConstValue<ChalkIr>> {
+        self.program_ir().unwrap().associated_const_values[&id].clone()
+    }
+
     fn opaque_ty_data(&self, id: OpaqueTyId<ChalkIr>) -> Arc<OpaqueTyDatum<ChalkIr>> {
         self.program_ir().unwrap().opaque_ty_data(id)
     }
@@ -122,10 +213,33 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         self.program_ir().unwrap().adt_repr(id)
     }
 
+    fn adt_variance(&self, id: AdtId<ChalkIr>) -> Vec<Variance> {
+        self.program_ir().unwrap().adt_variance(id)
+    }
+
     fn fn_def_datum(&self, id: FnDefId<ChalkIr>) -> Arc<FnDefDatum<ChalkIr>> {
         self.program_ir().unwrap().fn_def_datum(id)
     }
 
+    fn fn_def_variance(&self, id: FnDefId<ChalkIr>) -> Vec<Variance> {
+        self.program_ir().unwrap().fn_def_variance(id)
+    }
+
+    fn associated_fns(&self, name: &Identifier) -> Vec<Arc<AssociatedFnDatum<ChalkIr>>> {
+        self.program_ir().unwrap().associated_fns(name)
+    }
+
+    fn associated_fn_data(
+        &self,
+        fn_def_id: FnDefId<ChalkIr>,
+    ) -> Option<Arc<AssociatedFnDatum<ChalkIr>>> {
+        self.program_ir().unwrap().associated_fn_data(fn_def_id)
+    }
+
+    fn opaque_ty_variance(&self, id: OpaqueTyId<ChalkIr>) -> Vec<Variance> {
+        self.program_ir().unwrap().opaque_ty_variance(id)
+    }
+
     fn impls_for_trait(
         &self,
         trait_id: TraitId<ChalkIr>,
@@ -143,6 +257,15 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
             .local_impls_to_coherence_check(trait_id)
     }
 
+    fn upstream_impls_to_coherence_check(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+    ) -> Vec<ImplId<ChalkIr>> {
+        self.program_ir()
+            .unwrap()
+            .upstream_impls_to_coherence_check(trait_id)
+    }
+
     fn impl_provided_for(&self, auto_trait_id: TraitId<ChalkIr>, ty: &TyKind<ChalkIr>) -> bool {
         self.program_ir()
             .unwrap()
@@ -155,6 +278,10 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
             .well_known_trait_id(well_known_trait)
     }
 
+    fn well_known_adt_id(&self, well_known_adt: WellKnownAdt) -> Option<AdtId<ChalkIr>> {
+        self.program_ir().unwrap().well_known_adt_id(well_known_adt)
+    }
+
     fn program_clauses_for_env(
         &self,
         environment: &Environment<ChalkIr>,
@@ -184,7 +311,7 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         &self,
         closure_id: ClosureId<ChalkIr>,
         substs: &Substitution<ChalkIr>,
-    ) -> ClosureKind {
+    ) -> Option<ClosureKind> {
         self.program_ir().unwrap().closure_kind(closure_id, substs)
     }
 
@@ -220,6 +347,10 @@ impl RustIrDatabase<ChalkIr> for ChalkDatabase {
         self.program_ir().unwrap().assoc_type_name(assoc_ty_id)
     }
 
+    fn assoc_const_name(&self, assoc_const_id: AssocConstId<ChalkIr>) -> String {
+        self.program_ir().unwrap().assoc_const_name(assoc_const_id)
+    }
+
     fn opaque_type_name(&self, opaque_ty_id: OpaqueTyId<ChalkIr>) -> String {
         self.program_ir().unwrap().opaque_type_name(opaque_ty_id)
     }
@@ -1,53 +1,363 @@
+use crate::files::SourceFiles;
 use crate::interner::ChalkIr;
-use chalk_parse::ast::{Identifier, Kind};
+use crate::program::Program;
+use chalk_parse::ast::{Identifier, Kind, Span};
+use chalk_parse::SyntaxError;
 use chalk_solve::coherence::CoherenceError;
+use chalk_solve::display::{self, WriterState};
 use chalk_solve::wf::WfError;
 use string_cache::DefaultAtom as Atom;
 
 /// Wrapper type for the various errors that can occur during chalk
 /// processing.
+///
+/// Besides the error message, a `ChalkError` records the spans in the
+/// `.chalk` source that the error refers to, so that it can be rendered as
+/// a rustc-style diagnostic with [`ChalkError::render`]. Its `Display`
+/// impl prints only the message.
+///
+/// Several errors can be reported at once by combining them with
+/// [`ChalkError::combine`], e.g. all the syntax errors of a program. The
+/// messages are then displayed one per line, and rendered one after the
+/// other.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ChalkError {
-    /// For now, we just convert the error into a string, which makes
-    /// it trivially hashable etc.
-    error_text: String,
+    // Boxed to keep `Result<_, ChalkError>` small.
+    data: Box<ChalkErrorData>,
 }
 
-impl From<Box<dyn std::error::Error>> for ChalkError {
-    fn from(value: Box<dyn std::error::Error>) -> Self {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ChalkErrorData {
+    kind: ChalkErrorKind,
+    message: String,
+    primary_span: Option<Span>,
+    secondary_spans: Vec<(Span, String)>,
+    notes: Vec<String>,
+    /// The errors reported after this one.
+    more_errors: Vec<ChalkError>,
+}
+
+/// The kind of check that produced a [`ChalkError`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChalkErrorKind {
+    /// The program (or goal) could not be parsed.
+    Parse,
+    /// The program could not be lowered, e.g. because it refers to an
+    /// unknown name.
+    Lowering,
+    /// An item does not meet its well-formedness requirements.
+    WellFormedness,
+    /// Impls overlap or violate the orphan rules.
+    Coherence,
+}
+
+impl ChalkError {
+    pub fn new(kind: ChalkErrorKind, message: impl Into<String>) -> Self {
         ChalkError {
-            error_text: value.to_string(),
+            data: Box::new(ChalkErrorData {
+                kind,
+                message: message.into(),
+                primary_span: None,
+                secondary_spans: vec![],
+                notes: vec![],
+                more_errors: vec![],
+            }),
+        }
+    }
+
+    /// Converts a syntax error found while parsing a program.
+    pub fn from_syntax_error(error: SyntaxError) -> Self {
+        ChalkError::new(ChalkErrorKind::Parse, error.message).with_primary_span(error.span)
+    }
+
+    /// Combines several errors into one that reports all of them, in order.
+    /// Returns `None` if there are no errors.
+    pub fn combine(errors: impl IntoIterator<Item = ChalkError>) -> Option<ChalkError> {
+        let mut errors = errors.into_iter().flat_map(|mut error| {
+            let more_errors = std::mem::take(&mut error.data.more_errors);
+            std::iter::once(error).chain(more_errors)
+        });
+        let mut first = errors.next()?;
+        first.data.more_errors.extend(errors);
+        Some(first)
+    }
+
+    /// The errors reported by this error: itself, followed by the errors it
+    /// was combined with.
+    pub fn errors(&self) -> impl Iterator<Item = &ChalkError> {
+        std::iter::once(self).chain(&self.data.more_errors)
+    }
+
+    /// Converts a coherence error, pointing at the items it refers to. Must
+    /// be called with `program` set as the current program (see
+    /// [`tls::set_current_program`]), so that item names can be printed.
+    ///
+    /// [`tls::set_current_program`]: crate::tls::set_current_program
+    pub(crate) fn from_coherence_error(error: CoherenceError<ChalkIr>, program: &Program) -> Self {
+        let message = error.to_string();
+        let chalk_error = ChalkError::new(ChalkErrorKind::Coherence, message);
+        match error {
+            CoherenceError::OverlappingImpls {
+                impls: (first, second),
+                witness,
+                ..
+            } => {
+                let chalk_error = chalk_error
+                    .with_primary_span(program.item_span(second.0))
+                    .with_secondary_span(program.item_span(first.0), "first impl here");
+                match witness {
+                    Some(witness) if !witness.value.is_empty(&ChalkIr) => {
+                        let mut substitution = String::new();
+                        display::write_canonical_substitution(
+                            &mut substitution,
+                            &WriterState::<ChalkIr, Program, _>::new(program),
+                            &witness,
+                        )
+                        .expect("writing to a string cannot fail");
+                        chalk_error.with_note(format!(
+                            "both impls apply with the substitution `{}`",
+                            substitution
+                        ))
+                    }
+                    _ => chalk_error,
+                }
+            }
+            CoherenceError::FailedOrphanCheck(trait_id) => chalk_error
+                .with_secondary_span(program.item_span(trait_id.0), "trait defined here")
+                .with_note(
+                    "impls of traits defined outside of the current crate \
+                     must mention a local type",
+                ),
+        }
+    }
+
+    /// Converts a well-formedness error, pointing at the items it refers to.
+    /// Like [`ChalkError::from_coherence_error`], must be called with
+    /// `program` set as the current program.
+    pub(crate) fn from_wf_error(error: WfError<ChalkIr>, program: &Program) -> Self {
+        let message = error.to_string();
+        let chalk_error = ChalkError::new(ChalkErrorKind::WellFormedness, message);
+        match error {
+            WfError::IllFormedTypeDecl(adt_id) => {
+                chalk_error.with_primary_span(program.item_span(adt_id.0))
+            }
+            WfError::IllFormedOpaqueTypeDecl(opaque_ty_id) => {
+                chalk_error.with_primary_span(program.item_span(opaque_ty_id.0))
+            }
+            WfError::IllFormedTraitImpl(trait_id) => {
+                chalk_error.with_secondary_span(program.item_span(trait_id.0), "trait defined here")
+            }
+        }
+    }
+
+    /// Sets the span this error primarily refers to. Does nothing if `span`
+    /// is `None`.
+    pub fn with_primary_span(mut self, span: Option<Span>) -> Self {
+        if let Some(span) = span {
+            self.data.primary_span = Some(span);
+        }
+        self
+    }
+
+    /// Adds a labelled span that is related to this error. Does nothing if
+    /// `span` is `None`.
+    pub fn with_secondary_span(mut self, span: Option<Span>, label: impl Into<String>) -> Self {
+        if let Some(span) = span {
+            self.data.secondary_spans.push((span, label.into()));
+        }
+        self
+    }
+
+    /// Adds a note giving additional information about this error.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.data.notes.push(note.into());
+        self
+    }
+
+    pub fn kind(&self) -> ChalkErrorKind {
+        self.data.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.data.message
+    }
+
+    pub fn primary_span(&self) -> Option<Span> {
+        self.data.primary_span
+    }
+
+    pub fn secondary_spans(&self) -> &[(Span, String)] {
+        &self.data.secondary_spans
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.data.notes
+    }
+
+    /// Renders this error as a rustc-style diagnostic, showing the lines of
+    /// the source files that its spans point into. `files` must be the
+    /// files the spans were computed from.
+    ///
+    /// ```text
+    /// error: overlapping impls of trait `Foo`
+    ///  --> foo.chalk:1:7
+    ///   |
+    /// 1 | trait Foo { }
+    ///   |       ^^^
+    /// ```
+    pub fn render(&self, files: &SourceFiles) -> String {
+        let lines = |span: Span| SourceLines::new(&files.get(span.file).text);
+        let gutter = self
+            .data
+            .primary_span
+            .iter()
+            .chain(self.data.secondary_spans.iter().map(|(span, _)| span))
+            .map(|&span| (lines(span).line_index(span.lo) + 1).to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        let mut out = format!("error: {}\n", self.data.message);
+        if let Some(span) = self.data.primary_span {
+            let name = &files.get(span.file).name;
+            lines(span).write_snippet(&mut out, name, gutter, "-->", span, '^', "");
+        }
+        for &(span, ref label) in &self.data.secondary_spans {
+            let name = &files.get(span.file).name;
+            lines(span).write_snippet(&mut out, name, gutter, ":::", span, '-', label);
+        }
+        for note in &self.data.notes {
+            out.push_str(&format!(
+                "{:gutter$} = note: {}\n",
+                "",
+                note,
+                gutter = gutter
+            ));
+        }
+        for error in &self.data.more_errors {
+            out.push('\n');
+            out.push_str(&error.render(files));
+        }
+        out
+    }
+}
+
+/// The lines of a source text, used to render spans.
+struct SourceLines<'s> {
+    source: &'s str,
+    line_starts: Vec<usize>,
+}
+
+impl<'s> SourceLines<'s> {
+    fn new(source: &'s str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        SourceLines {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Returns the (zero-based) index of the line containing `offset`.
+    fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(index) => index,
+            Err(index) => index - 1,
         }
     }
+
+    fn line(&self, index: usize) -> &'s str {
+        let start = self.line_starts[index];
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.source.len());
+        self.source[start..end].trim_end_matches(&['\n', '\r'][..])
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn write_snippet(
+        &self,
+        out: &mut String,
+        name: &str,
+        gutter: usize,
+        arrow: &str,
+        span: Span,
+        marker: char,
+        label: &str,
+    ) {
+        let index = self.line_index(span.lo);
+        let line = self.line(index);
+        let line_start = self.line_starts[index];
+        let column = self.source[line_start..span.lo].chars().count();
+        let span_end = std::cmp::min(span.hi, line_start + line.len());
+        let width = std::cmp::max(self.source[span.lo..span_end].chars().count(), 1);
+
+        out.push_str(&format!(
+            "{:gutter$}{} {}:{}:{}\n",
+            "",
+            arrow,
+            name,
+            index + 1,
+            column + 1,
+            gutter = gutter
+        ));
+        out.push_str(&format!("{:gutter$} |\n", "", gutter = gutter));
+        out.push_str(&format!(
+            "{:>gutter$} | {}\n",
+            index + 1,
+            line,
+            gutter = gutter
+        ));
+        let underline = marker.to_string().repeat(width);
+        out.push_str(
+            format!(
+                "{:gutter$} | {:column$}{} {}",
+                "",
+                "",
+                underline,
+                label,
+                gutter = gutter,
+                column = column
+            )
+            .trim_end(),
+        );
+        out.push('\n');
+    }
+}
+
+impl From<SyntaxError> for ChalkError {
+    fn from(value: SyntaxError) -> Self {
+        ChalkError::from_syntax_error(value)
+    }
 }
 
 impl From<WfError<ChalkIr>> for ChalkError {
     fn from(value: WfError<ChalkIr>) -> Self {
-        ChalkError {
-            error_text: value.to_string(),
-        }
+        ChalkError::new(ChalkErrorKind::WellFormedness, value.to_string())
     }
 }
 
 impl From<CoherenceError<ChalkIr>> for ChalkError {
     fn from(value: CoherenceError<ChalkIr>) -> Self {
-        ChalkError {
-            error_text: value.to_string(),
-        }
+        ChalkError::new(ChalkErrorKind::Coherence, value.to_string())
     }
 }
 
 impl From<RustIrError> for ChalkError {
     fn from(value: RustIrError) -> Self {
-        ChalkError {
-            error_text: value.to_string(),
-        }
+        ChalkError::new(ChalkErrorKind::Lowering, value.to_string()).with_primary_span(value.span())
     }
 }
 
 impl std::fmt::Display for ChalkError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.error_text)
+        write!(f, "{}", self.data.message)?;
+        for error in &self.data.more_errors {
+            write!(f, "\n{}", error.data.message)?;
+        }
+        Ok(())
     }
 }
 
@@ -57,6 +367,7 @@ impl std::error::Error for ChalkError {}
 pub enum RustIrError {
     InvalidParameterName(Identifier),
     InvalidTraitName(Identifier),
+    InvalidCrateName(Identifier),
     NotTrait(Identifier),
     NotStruct(Identifier),
     DuplicateOrShadowedParameters,
@@ -66,6 +377,11 @@ pub enum RustIrError {
     InvalidFundamentalTypesParameters(Identifier),
     NegativeImplAssociatedValues(Identifier),
     MissingAssociatedType(Identifier),
+    MissingAssociatedConst(Identifier),
+    AssociatedConstParameters(Identifier),
+    ConstLiteralOutOfRange(String),
+    DuplicateAssociatedFn(Identifier),
+    TraitAliasImpl(Identifier),
     IncorrectNumberOfTypeParameters {
         identifier: Identifier,
         expected: usize,
@@ -93,6 +409,44 @@ pub enum RustIrError {
     },
     CannotApplyTypeParameter(Identifier),
     InvalidExternAbi(Atom),
+    IncorrectNumberOfVariances {
+        identifier: Identifier,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl RustIrError {
+    /// Returns the span of the identifier this error refers to, if any.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            RustIrError::InvalidParameterName(name)
+            | RustIrError::InvalidTraitName(name)
+            | RustIrError::InvalidCrateName(name)
+            | RustIrError::NotTrait(name)
+            | RustIrError::NotStruct(name)
+            | RustIrError::AutoTraitAssociatedTypes(name)
+            | RustIrError::AutoTraitParameters(name)
+            | RustIrError::AutoTraitWhereClauses(name)
+            | RustIrError::InvalidFundamentalTypesParameters(name)
+            | RustIrError::NegativeImplAssociatedValues(name)
+            | RustIrError::MissingAssociatedType(name)
+            | RustIrError::MissingAssociatedConst(name)
+            | RustIrError::AssociatedConstParameters(name)
+            | RustIrError::DuplicateAssociatedFn(name)
+            | RustIrError::TraitAliasImpl(name)
+            | RustIrError::CannotApplyTypeParameter(name) => Some(name.span),
+            RustIrError::IncorrectNumberOfTypeParameters { identifier, .. }
+            | RustIrError::IncorrectNumberOfAssociatedTypeParameters { identifier, .. }
+            | RustIrError::IncorrectParameterKind { identifier, .. }
+            | RustIrError::IncorrectTraitParameterKind { identifier, .. }
+            | RustIrError::IncorrectAssociatedTypeParameterKind { identifier, .. }
+            | RustIrError::IncorrectNumberOfVariances { identifier, .. } => Some(identifier.span),
+            RustIrError::DuplicateOrShadowedParameters
+            | RustIrError::ConstLiteralOutOfRange(_)
+            | RustIrError::InvalidExternAbi(_) => None,
+        }
+    }
 }
 
 impl std::fmt::Display for RustIrError {
@@ -102,6 +456,11 @@ impl std::fmt::Display for RustIrError {
                 write!(f, "invalid parameter name `{}`", name)
             }
             RustIrError::InvalidTraitName(name) => write!(f, "invalid trait name `{}`", name),
+            RustIrError::InvalidCrateName(name) => write!(
+                f,
+                "invalid crate name `{}`; a crate can only depend on crates declared before it",
+                name
+            ),
             RustIrError::NotTrait(name) => write!(
                 f,
                 "expected a trait, found `{}`, which is not a trait",
@@ -137,6 +496,25 @@ impl std::fmt::Display for RustIrError {
             RustIrError::MissingAssociatedType(name) => {
                 write!(f, "no associated type `{}` defined in trait", name)
             }
+            RustIrError::MissingAssociatedConst(name) => {
+                write!(f, "no associated const `{}` defined in trait", name)
+            }
+            RustIrError::AssociatedConstParameters(name) => {
+                write!(f, "associated const `{}` cannot have parameters", name)
+            }
+            RustIrError::ConstLiteralOutOfRange(literal) => {
+                write!(
+                    f,
+                    "integer literal `{}` is out of range for its type",
+                    literal
+                )
+            }
+            RustIrError::DuplicateAssociatedFn(name) => {
+                write!(f, "duplicate definitions of associated fn `{}`", name)
+            }
+            RustIrError::TraitAliasImpl(name) => {
+                write!(f, "cannot implement trait alias `{}`", name)
+            }
             RustIrError::IncorrectNumberOfTypeParameters {
                 identifier,
                 expected,
@@ -186,6 +564,15 @@ impl std::fmt::Display for RustIrError {
                 write!(f, "cannot apply type parameter `{}`", name)
             }
             RustIrError::InvalidExternAbi(abi) => write!(f, "invalid extern ABI `{}`", abi),
+            RustIrError::IncorrectNumberOfVariances {
+                identifier,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "`{}` has {} parameters, but {} variances were declared",
+                identifier, expected, actual
+            ),
         }
     }
 }
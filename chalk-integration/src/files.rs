@@ -0,0 +1,198 @@
+//! The source files a program is made of.
+//!
+//! A program can be split into several `.chalk` files, which are combined
+//! with `include "path.chalk";` items. The items of an included file are
+//! merged into the program in place of the `include`; each file is merged
+//! only once, no matter how many times it is included, so files can
+//! include each other freely. Every file of a [`SourceFiles`] is part of
+//! the program, in order, whether it is included or not.
+
+use crate::error::{ChalkError, ChalkErrorKind};
+use chalk_parse::ast::{FileId, Item, Program};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A source file of a program.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceFile {
+    /// The name of the file, used to resolve the paths it includes and to
+    /// refer to it in diagnostics.
+    pub name: String,
+    pub text: String,
+}
+
+/// The source files of a program, indexed by [`FileId`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SourceFiles {
+    files: Vec<SourceFile>,
+}
+
+impl SourceFiles {
+    pub fn new() -> Self {
+        SourceFiles::default()
+    }
+
+    /// A program made of a single text, which is not read from a file.
+    pub fn from_text(text: impl Into<String>) -> Self {
+        let mut files = SourceFiles::new();
+        files.add("<program>", text);
+        files
+    }
+
+    /// Reads the files at `paths`, along with all the files they include,
+    /// directly or not.
+    pub fn load(paths: &[impl AsRef<Path>]) -> io::Result<Self> {
+        let mut files = SourceFiles::new();
+        for path in paths {
+            files.load_file(path.as_ref(), None)?;
+        }
+        Ok(files)
+    }
+
+    fn load_file(&mut self, path: &Path, included_from: Option<FileId>) -> io::Result<()> {
+        let name = normalize(path).to_string_lossy().into_owned();
+        if self.find(&name).is_some() {
+            return Ok(());
+        }
+        let text = fs::read_to_string(path).map_err(|e| match included_from {
+            Some(file) => io::Error::new(
+                e.kind(),
+                format!(
+                    "cannot read `{}`, included from `{}`: {}",
+                    name, self.files[file.0].name, e
+                ),
+            ),
+            None => io::Error::new(e.kind(), format!("cannot read `{}`: {}", name, e)),
+        })?;
+        self.add_with_includes(name, text)?;
+        Ok(())
+    }
+
+    /// Adds a file to the program, like [`SourceFiles::add`], and reads the
+    /// files it includes, directly or not.
+    pub fn add_with_includes(
+        &mut self,
+        name: impl Into<String>,
+        text: impl Into<String>,
+    ) -> io::Result<FileId> {
+        let file = self.add(name, text);
+
+        // Syntax errors are reported when the program is lowered.
+        let (program, _) = chalk_parse::parse_file_recovering(file, &self.files[file.0].text);
+        for item in program.items {
+            if let Item::Include(include) = item {
+                let path = self.include_path(file, &include.path);
+                self.load_file(&path, Some(file))?;
+            }
+        }
+        Ok(file)
+    }
+
+    /// Adds a file to the program, and returns its id.
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<String>) -> FileId {
+        self.files.push(SourceFile {
+            name: name.into(),
+            text: text.into(),
+        });
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn get(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SourceFile> {
+        self.files.iter()
+    }
+
+    fn find(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(FileId)
+    }
+
+    /// The path of the file included as `path` by `file`.
+    fn include_path(&self, file: FileId, path: &str) -> PathBuf {
+        let name = Path::new(&self.files[file.0].name);
+        normalize(&name.parent().unwrap_or_else(|| Path::new("")).join(path))
+    }
+
+    /// Parses the files and merges their items into a single program.
+    /// Syntax errors are recovered from, as are missing included files:
+    /// the items that could be parsed are returned along with the errors.
+    pub fn parse(&self) -> (Program, Vec<ChalkError>) {
+        let mut program = Program {
+            items: Vec::new(),
+            item_spans: Vec::new(),
+        };
+        let mut merged = BTreeSet::new();
+        let mut errors = Vec::new();
+        for file in 0..self.files.len() {
+            self.merge(FileId(file), &mut merged, &mut program, &mut errors);
+        }
+        (program, errors)
+    }
+
+    fn merge(
+        &self,
+        file: FileId,
+        merged: &mut BTreeSet<FileId>,
+        merged_program: &mut Program,
+        errors: &mut Vec<ChalkError>,
+    ) {
+        if !merged.insert(file) {
+            return;
+        }
+        let source = &self.files[file.0];
+        let (program, syntax_errors) = chalk_parse::parse_file_recovering(file, &source.text);
+        errors.extend(syntax_errors.into_iter().map(|error| {
+            ChalkError::from_syntax_error(error)
+                .with_note(format!("while parsing `{}`", source.name))
+        }));
+        for (item, span) in program.items.into_iter().zip(program.item_spans) {
+            match item {
+                Item::Include(include) => {
+                    let path = self.include_path(file, &include.path);
+                    match self.find(&path.to_string_lossy()) {
+                        Some(included) => self.merge(included, merged, merged_program, errors),
+                        None => errors.push(
+                            ChalkError::new(
+                                ChalkErrorKind::Lowering,
+                                format!("cannot find included file `{}`", path.display()),
+                            )
+                            .with_primary_span(Some(include.span)),
+                        ),
+                    }
+                }
+                item => {
+                    merged_program.items.push(item);
+                    merged_program.item_spans.push(span);
+                }
+            }
+        }
+    }
+}
+
+/// Removes the `.` and `..` components of a path, without looking at the
+/// file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
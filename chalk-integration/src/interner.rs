@@ -1,20 +1,50 @@
 use crate::tls;
 use chalk_ir::interner::{HasInterner, Interner};
 use chalk_ir::{
-    AdtId, AliasTy, AssocTypeId, CanonicalVarKind, CanonicalVarKinds, ConstData, Constraint,
-    FnDefId, Goals, InEnvironment, Lifetime, OpaqueTy, OpaqueTyId, ProgramClauseImplication,
-    ProgramClauses, ProjectionTy, QuantifiedWhereClauses, SeparatorTraitRef, Substitution, TraitId,
-    Ty, TyData, VariableKind, VariableKinds,
+    AdtId, AliasTy, AssocConstId, AssocTypeId, BinOp, CanonicalVarKind, CanonicalVarKinds, Const,
+    ConstData, ConstExpr, ConstProjection, ConstValue, Constraint, FnDefId, Goals, InEnvironment,
+    Lifetime, OpaqueTy, OpaqueTyId, ProgramClauseImplication, ProgramClauses, ProjectionTy,
+    QuantifiedWhereClauses, Scalar, SeparatorTraitRef, Substitution, TraitId, Ty, TyData, TyKind,
+    UnOp, VariableKind, VariableKinds,
 };
 use chalk_ir::{
     GenericArg, GenericArgData, Goal, GoalData, LifetimeData, ProgramClause, ProgramClauseData,
     QuantifiedWhereClause,
 };
+use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Debug;
-use std::sync::Arc;
 use string_cache::DefaultAtom;
 
+#[cfg(feature = "hash-consing")]
+mod hash_consing;
+#[cfg(feature = "hash-consing")]
+use hash_consing::{intern, intern_list, Interned, InternedList};
+
+#[cfg(not(feature = "hash-consing"))]
+use no_interning::{intern, intern_list, Interned, InternedList};
+
+/// The default representation of interned data, in which no interning
+/// actually occurs: values are reference counted, and lists are vectors.
+#[cfg(not(feature = "hash-consing"))]
+mod no_interning {
+    use std::sync::Arc;
+
+    pub type Interned<T> = Arc<T>;
+
+    pub type InternedList<T> = Vec<T>;
+
+    pub fn intern<T>(value: T) -> Interned<T> {
+        Arc::new(value)
+    }
+
+    pub fn intern_list<T, E>(
+        data: impl IntoIterator<Item = Result<T, E>>,
+    ) -> Result<InternedList<T>, E> {
+        data.into_iter().collect()
+    }
+}
+
 pub type Identifier = DefaultAtom;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -34,26 +64,148 @@ pub enum ChalkFnAbi {
     C,
 }
 
+/// The value of a concrete const of `ChalkIr`. Integers always fit in their
+/// type, and are signed if their type is.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConstScalar {
+    Bool(bool),
+    Char(char),
+    Int(i128),
+    Uint(u128),
+}
+
+impl ConstScalar {
+    /// The size in bits of the integer type `ty`.
+    fn bits(ty: Scalar) -> Option<u32> {
+        use chalk_ir::{IntTy::*, UintTy::*};
+        match ty {
+            Scalar::Int(I8) | Scalar::Uint(U8) => Some(8),
+            Scalar::Int(I16) | Scalar::Uint(U16) => Some(16),
+            Scalar::Int(I32) | Scalar::Uint(U32) => Some(32),
+            Scalar::Int(I64) | Scalar::Int(Isize) | Scalar::Uint(U64) | Scalar::Uint(Usize) => {
+                Some(64)
+            }
+            Scalar::Int(I128) | Scalar::Uint(U128) => Some(128),
+            Scalar::Bool | Scalar::Char | Scalar::Float(_) => None,
+        }
+    }
+
+    /// The integer `value`, of type `ty`, or `None` if it does not fit in
+    /// `ty` or if `ty` is not an integer type.
+    pub fn int(value: i128, ty: Scalar) -> Option<ConstScalar> {
+        match ty {
+            Scalar::Int(_) => {
+                let max = i128::MAX >> (128 - Self::bits(ty)?);
+                if -max - 1 <= value && value <= max {
+                    Some(ConstScalar::Int(value))
+                } else {
+                    None
+                }
+            }
+            Scalar::Uint(_) => ConstScalar::uint(u128::try_from(value).ok()?, ty),
+            _ => None,
+        }
+    }
+
+    /// The unsigned integer `value`, of type `ty`, or `None` if it does not
+    /// fit in `ty` or if `ty` is not an integer type.
+    pub fn uint(value: u128, ty: Scalar) -> Option<ConstScalar> {
+        match ty {
+            Scalar::Uint(_) => {
+                if value <= u128::MAX >> (128 - Self::bits(ty)?) {
+                    Some(ConstScalar::Uint(value))
+                } else {
+                    None
+                }
+            }
+            Scalar::Int(_) => ConstScalar::int(i128::try_from(value).ok()?, ty),
+            _ => None,
+        }
+    }
+
+    /// Evaluates `expr`, of type `ty`. Returns `None` if one of its operands
+    /// is not concrete, or if it overflows or divides by zero.
+    fn evaluate(ty: &TyKind<ChalkIr>, expr: &ConstExpr<ChalkIr>) -> Option<ConstScalar> {
+        let ty = match ty {
+            TyKind::Scalar(ty) => *ty,
+            _ => return None,
+        };
+        let concrete = |c: &Const<ChalkIr>| match &c.data(&ChalkIr).value {
+            ConstValue::Concrete(c) => Some(c.interned),
+            _ => None,
+        };
+        use ConstScalar::*;
+        match expr {
+            ConstExpr::Unary(op, a) => match (op, concrete(a)?) {
+                (UnOp::Not, Bool(a)) => Some(Bool(!a)),
+                (UnOp::Not, Int(a)) => ConstScalar::int(!a, ty),
+                (UnOp::Not, Uint(a)) => {
+                    ConstScalar::uint(!a & (u128::MAX >> (128 - Self::bits(ty)?)), ty)
+                }
+                (UnOp::Neg, Int(a)) => ConstScalar::int(a.checked_neg()?, ty),
+                _ => None,
+            },
+            ConstExpr::Binary(op, a, b) => match (concrete(a)?, concrete(b)?) {
+                (Int(a), Int(b)) => {
+                    let value = match op {
+                        BinOp::Add => a.checked_add(b),
+                        BinOp::Sub => a.checked_sub(b),
+                        BinOp::Mul => a.checked_mul(b),
+                        BinOp::Div => a.checked_div(b),
+                        BinOp::Rem => a.checked_rem(b),
+                    };
+                    ConstScalar::int(value?, ty)
+                }
+                (Uint(a), Uint(b)) => {
+                    let value = match op {
+                        BinOp::Add => a.checked_add(b),
+                        BinOp::Sub => a.checked_sub(b),
+                        BinOp::Mul => a.checked_mul(b),
+                        BinOp::Div => a.checked_div(b),
+                        BinOp::Rem => a.checked_rem(b),
+                    };
+                    ConstScalar::uint(value?, ty)
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Debug for ConstScalar {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstScalar::Bool(value) => write!(fmt, "{}", value),
+            ConstScalar::Char(value) => write!(fmt, "{:?}", value),
+            ConstScalar::Int(value) => write!(fmt, "{}", value),
+            ConstScalar::Uint(value) => write!(fmt, "{}", value),
+        }
+    }
+}
+
 /// The default "interner" and the only interner used by chalk
-/// itself. In this interner, no interning actually occurs.
+/// itself. By default, no interning actually occurs in this interner. With
+/// the `hash-consing` feature, types, goals, substitutions and the like are
+/// hash-consed instead, so that they can be compared by address, as in
+/// rustc.
 #[derive(Debug, Copy, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
 pub struct ChalkIr;
 
 impl Interner for ChalkIr {
-    type InternedType = Arc<TyData<ChalkIr>>;
+    type InternedType = Interned<TyData<ChalkIr>>;
     type InternedLifetime = LifetimeData<ChalkIr>;
-    type InternedConst = Arc<ConstData<ChalkIr>>;
-    type InternedConcreteConst = u32;
+    type InternedConst = Interned<ConstData<ChalkIr>>;
+    type InternedConcreteConst = ConstScalar;
     type InternedGenericArg = GenericArgData<ChalkIr>;
-    type InternedGoal = Arc<GoalData<ChalkIr>>;
-    type InternedGoals = Vec<Goal<ChalkIr>>;
-    type InternedSubstitution = Vec<GenericArg<ChalkIr>>;
-    type InternedProgramClause = ProgramClauseData<ChalkIr>;
-    type InternedProgramClauses = Vec<ProgramClause<ChalkIr>>;
-    type InternedQuantifiedWhereClauses = Vec<QuantifiedWhereClause<ChalkIr>>;
-    type InternedVariableKinds = Vec<VariableKind<ChalkIr>>;
-    type InternedCanonicalVarKinds = Vec<CanonicalVarKind<ChalkIr>>;
-    type InternedConstraints = Vec<InEnvironment<Constraint<ChalkIr>>>;
+    type InternedGoal = Interned<GoalData<ChalkIr>>;
+    type InternedGoals = InternedList<Goal<ChalkIr>>;
+    type InternedSubstitution = InternedList<GenericArg<ChalkIr>>;
+    type InternedProgramClause = Interned<ProgramClauseData<ChalkIr>>;
+    type InternedProgramClauses = InternedList<ProgramClause<ChalkIr>>;
+    type InternedQuantifiedWhereClauses = InternedList<QuantifiedWhereClause<ChalkIr>>;
+    type InternedVariableKinds = InternedList<VariableKind<ChalkIr>>;
+    type InternedCanonicalVarKinds = InternedList<CanonicalVarKind<ChalkIr>>;
+    type InternedConstraints = InternedList<InEnvironment<Constraint<ChalkIr>>>;
     type DefId = RawId;
     type InternedAdtId = RawId;
     type Identifier = Identifier;
@@ -80,6 +232,13 @@ impl Interner for ChalkIr {
         tls::with_current_program(|prog| Some(prog?.debug_assoc_type_id(id, fmt)))
     }
 
+    fn debug_assoc_const_id(
+        id: AssocConstId<ChalkIr>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Option<fmt::Result> {
+        tls::with_current_program(|prog| Some(prog?.debug_assoc_const_id(id, fmt)))
+    }
+
     fn debug_opaque_ty_id(
         id: OpaqueTyId<ChalkIr>,
         fmt: &mut fmt::Formatter<'_>,
@@ -102,6 +261,13 @@ impl Interner for ChalkIr {
         tls::with_current_program(|prog| Some(prog?.debug_projection_ty(proj, fmt)))
     }
 
+    fn debug_const_projection(
+        proj: &ConstProjection<ChalkIr>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Option<fmt::Result> {
+        tls::with_current_program(|prog| Some(prog?.debug_const_projection(proj, fmt)))
+    }
+
     fn debug_opaque_ty(
         opaque_ty: &OpaqueTy<ChalkIr>,
         fmt: &mut fmt::Formatter<'_>,
@@ -205,11 +371,11 @@ impl Interner for ChalkIr {
         tls::with_current_program(|prog| Some(prog?.debug_quantified_where_clauses(clauses, fmt)))
     }
 
-    fn intern_ty(&self, ty: TyData<ChalkIr>) -> Arc<TyData<ChalkIr>> {
-        Arc::new(ty)
+    fn intern_ty(&self, ty: TyData<ChalkIr>) -> Interned<TyData<ChalkIr>> {
+        intern(ty)
     }
 
-    fn ty_data<'a>(&self, ty: &'a Arc<TyData<ChalkIr>>) -> &'a TyData<Self> {
+    fn ty_data<'a>(&self, ty: &'a Interned<TyData<ChalkIr>>) -> &'a TyData<Self> {
         ty
     }
 
@@ -221,18 +387,31 @@ impl Interner for ChalkIr {
         lifetime
     }
 
-    fn intern_const(&self, constant: ConstData<ChalkIr>) -> Arc<ConstData<ChalkIr>> {
-        Arc::new(constant)
+    fn intern_const(&self, constant: ConstData<ChalkIr>) -> Interned<ConstData<ChalkIr>> {
+        intern(constant)
     }
 
-    fn const_data<'a>(&self, constant: &'a Arc<ConstData<ChalkIr>>) -> &'a ConstData<ChalkIr> {
+    fn const_data<'a>(&self, constant: &'a Interned<ConstData<ChalkIr>>) -> &'a ConstData<ChalkIr> {
         constant
     }
 
-    fn const_eq(&self, _ty: &Arc<TyData<ChalkIr>>, c1: &u32, c2: &u32) -> bool {
+    fn const_eq(
+        &self,
+        _ty: &Interned<TyData<ChalkIr>>,
+        c1: &ConstScalar,
+        c2: &ConstScalar,
+    ) -> bool {
         c1 == c2
     }
 
+    fn evaluate_const_expr(
+        &self,
+        ty: &Interned<TyData<ChalkIr>>,
+        expr: &ConstExpr<ChalkIr>,
+    ) -> Option<ConstScalar> {
+        ConstScalar::evaluate(&ty.kind, expr)
+    }
+
     fn intern_generic_arg(&self, generic_arg: GenericArgData<ChalkIr>) -> GenericArgData<ChalkIr> {
         generic_arg
     }
@@ -244,46 +423,49 @@ impl Interner for ChalkIr {
         generic_arg
     }
 
-    fn intern_goal(&self, goal: GoalData<ChalkIr>) -> Arc<GoalData<ChalkIr>> {
-        Arc::new(goal)
+    fn intern_goal(&self, goal: GoalData<ChalkIr>) -> Interned<GoalData<ChalkIr>> {
+        intern(goal)
     }
 
-    fn goal_data<'a>(&self, goal: &'a Arc<GoalData<ChalkIr>>) -> &'a GoalData<ChalkIr> {
+    fn goal_data<'a>(&self, goal: &'a Interned<GoalData<ChalkIr>>) -> &'a GoalData<ChalkIr> {
         goal
     }
 
     fn intern_goals<E>(
         &self,
         data: impl IntoIterator<Item = Result<Goal<ChalkIr>, E>>,
-    ) -> Result<Vec<Goal<ChalkIr>>, E> {
-        data.into_iter().collect()
+    ) -> Result<InternedList<Goal<ChalkIr>>, E> {
+        intern_list(data)
     }
 
-    fn goals_data<'a>(&self, goals: &'a Vec<Goal<ChalkIr>>) -> &'a [Goal<ChalkIr>] {
+    fn goals_data<'a>(&self, goals: &'a InternedList<Goal<ChalkIr>>) -> &'a [Goal<ChalkIr>] {
         goals
     }
 
     fn intern_substitution<E>(
         &self,
         data: impl IntoIterator<Item = Result<GenericArg<ChalkIr>, E>>,
-    ) -> Result<Vec<GenericArg<ChalkIr>>, E> {
-        data.into_iter().collect()
+    ) -> Result<InternedList<GenericArg<ChalkIr>>, E> {
+        intern_list(data)
     }
 
     fn substitution_data<'a>(
         &self,
-        substitution: &'a Vec<GenericArg<ChalkIr>>,
+        substitution: &'a InternedList<GenericArg<ChalkIr>>,
     ) -> &'a [GenericArg<ChalkIr>] {
         substitution
     }
 
-    fn intern_program_clause(&self, data: ProgramClauseData<Self>) -> ProgramClauseData<Self> {
-        data
+    fn intern_program_clause(
+        &self,
+        data: ProgramClauseData<Self>,
+    ) -> Interned<ProgramClauseData<Self>> {
+        intern(data)
     }
 
     fn program_clause_data<'a>(
         &self,
-        clause: &'a ProgramClauseData<Self>,
+        clause: &'a Interned<ProgramClauseData<Self>>,
     ) -> &'a ProgramClauseData<Self> {
         clause
     }
@@ -291,13 +473,13 @@ impl Interner for ChalkIr {
     fn intern_program_clauses<E>(
         &self,
         data: impl IntoIterator<Item = Result<ProgramClause<Self>, E>>,
-    ) -> Result<Vec<ProgramClause<Self>>, E> {
-        data.into_iter().collect()
+    ) -> Result<InternedList<ProgramClause<Self>>, E> {
+        intern_list(data)
     }
 
     fn program_clauses_data<'a>(
         &self,
-        clauses: &'a Vec<ProgramClause<Self>>,
+        clauses: &'a InternedList<ProgramClause<Self>>,
     ) -> &'a [ProgramClause<Self>] {
         clauses
     }
@@ -306,7 +488,7 @@ impl Interner for ChalkIr {
         &self,
         data: impl IntoIterator<Item = Result<QuantifiedWhereClause<Self>, E>>,
     ) -> Result<Self::InternedQuantifiedWhereClauses, E> {
-        data.into_iter().collect()
+        intern_list(data)
     }
 
     fn quantified_where_clauses_data<'a>(
@@ -319,7 +501,7 @@ impl Interner for ChalkIr {
         &self,
         data: impl IntoIterator<Item = Result<VariableKind<ChalkIr>, E>>,
     ) -> Result<Self::InternedVariableKinds, E> {
-        data.into_iter().collect()
+        intern_list(data)
     }
 
     fn variable_kinds_data<'a>(
@@ -333,7 +515,7 @@ impl Interner for ChalkIr {
         &self,
         data: impl IntoIterator<Item = Result<CanonicalVarKind<ChalkIr>, E>>,
     ) -> Result<Self::InternedCanonicalVarKinds, E> {
-        data.into_iter().collect()
+        intern_list(data)
     }
 
     fn canonical_var_kinds_data<'a>(
@@ -347,7 +529,7 @@ impl Interner for ChalkIr {
         &self,
         data: impl IntoIterator<Item = Result<InEnvironment<Constraint<Self>>, E>>,
     ) -> Result<Self::InternedConstraints, E> {
-        data.into_iter().collect()
+        intern_list(data)
     }
 
     fn constraints_data<'a>(
@@ -0,0 +1,161 @@
+//! The hash-consing representation of interned data used by `ChalkIr` when
+//! the `hash-consing` feature is enabled.
+//!
+//! Each interned value is stored once in a global arena, and the interned
+//! handle is a reference to it. Structurally equal values are interned to
+//! the same reference, so equality and hashing only need to look at the
+//! pointer, much like the interner used by rustc. The arenas are never
+//! freed.
+
+use super::ChalkIr;
+use chalk_ir::{
+    CanonicalVarKind, ConstData, Constraint, GenericArg, Goal, GoalData, InEnvironment,
+    ProgramClause, ProgramClauseData, QuantifiedWhereClause, TyData, VariableKind,
+};
+use lazy_static::lazy_static;
+use rustc_hash::FxHashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::ptr;
+use std::sync::Mutex;
+
+/// A hash-consed value, compared and hashed by address.
+pub struct Interned<T: 'static>(&'static T);
+
+impl<T: Internable> Interned<T> {
+    /// Interns `value`, returning the existing copy if an equal value has
+    /// already been interned.
+    pub fn new(value: T) -> Self {
+        let mut arena = T::arena().values.lock().unwrap();
+        if let Some(interned) = arena.get(&value) {
+            return Interned(interned);
+        }
+        let interned: &'static T = Box::leak(Box::new(value));
+        arena.insert(interned);
+        Interned(interned)
+    }
+}
+
+impl<T> Clone for Interned<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Interned<T> {}
+
+impl<T> PartialEq for Interned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl<T> Eq for Interned<T> {}
+
+impl<T> Hash for Interned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.0, state)
+    }
+}
+
+impl<T> Deref for Interned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Interned<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+
+/// The arena holding all the interned values of some type.
+pub struct Arena<T: 'static> {
+    values: Mutex<FxHashSet<&'static T>>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena {
+            values: Mutex::new(FxHashSet::default()),
+        }
+    }
+}
+
+/// A type whose values can be interned.
+pub trait Internable: Eq + Hash + Sized + Sync + 'static {
+    fn arena() -> &'static Arena<Self>;
+}
+
+macro_rules! internable {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Internable for $t {
+                fn arena() -> &'static Arena<Self> {
+                    lazy_static! {
+                        static ref ARENA: Arena<$t> = Arena::default();
+                    }
+                    &ARENA
+                }
+            }
+        )*
+    };
+}
+
+internable!(
+    TyData<ChalkIr>,
+    ConstData<ChalkIr>,
+    GoalData<ChalkIr>,
+    ProgramClauseData<ChalkIr>,
+    Vec<Goal<ChalkIr>>,
+    Vec<GenericArg<ChalkIr>>,
+    Vec<ProgramClause<ChalkIr>>,
+    Vec<QuantifiedWhereClause<ChalkIr>>,
+    Vec<VariableKind<ChalkIr>>,
+    Vec<CanonicalVarKind<ChalkIr>>,
+    Vec<InEnvironment<Constraint<ChalkIr>>>,
+);
+
+pub type InternedList<T> = Interned<Vec<T>>;
+
+pub fn intern<T: Internable>(value: T) -> Interned<T> {
+    Interned::new(value)
+}
+
+pub fn intern_list<T, E>(data: impl IntoIterator<Item = Result<T, E>>) -> Result<InternedList<T>, E>
+where
+    Vec<T>: Internable,
+{
+    Ok(Interned::new(data.into_iter().collect::<Result<_, _>>()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interner::ChalkIr;
+    use chalk_ir::{IntTy, Scalar, Substitution, TyKind, UintTy};
+
+    #[test]
+    fn equal_values_are_interned_once() {
+        let interner = &ChalkIr;
+        let u32_ty = || TyKind::Scalar(Scalar::Uint(UintTy::U32)).intern(interner);
+        let i32_ty = TyKind::Scalar(Scalar::Int(IntTy::I32)).intern(interner);
+        assert!(std::ptr::eq(
+            u32_ty().data(interner),
+            u32_ty().data(interner)
+        ));
+        assert!(!std::ptr::eq(
+            u32_ty().data(interner),
+            i32_ty.data(interner)
+        ));
+
+        let substitution = || Substitution::from_iter(interner, vec![u32_ty(), i32_ty]);
+        assert!(std::ptr::eq(
+            substitution().as_slice(interner),
+            substitution().as_slice(interner)
+        ));
+    }
+}
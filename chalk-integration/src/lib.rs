@@ -3,6 +3,7 @@
 
 pub mod db;
 pub mod error;
+pub mod files;
 pub mod interner;
 pub mod lowering;
 pub mod program;
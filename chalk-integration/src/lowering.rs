@@ -1,4 +1,5 @@
 mod env;
+mod modules;
 mod program_lowerer;
 
 use chalk_ir::cast::{Cast, Caster};
@@ -8,19 +9,26 @@ use chalk_ir::{
 };
 use chalk_parse::ast::*;
 use chalk_solve::rust_ir::{self, IntoWhereClauses};
+use modules::ModuleId;
 use program_lowerer::ProgramLowerer;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use string_cache::DefaultAtom as Atom;
 use tracing::debug;
 
 use crate::error::RustIrError;
-use crate::interner::{ChalkFnAbi, ChalkIr};
+use crate::interner::{ChalkFnAbi, ChalkIr, ConstScalar};
 use crate::program::Program as LoweredProgram;
 use crate::{Identifier as Ident, TypeSort};
 use env::*;
 
+pub use modules::{CrateId, Modules};
+
 const SELF: &str = "Self";
 const FIXME_SELF: &str = "__FIXME_SELF__";
+/// The name of the lifetime of a `&self` receiver that has none. It is not
+/// a valid identifier, so it cannot clash with the other parameters.
+const RECEIVER_LIFETIME: &str = "'<receiver>";
 
 trait LowerWithEnv {
     type Lowered;
@@ -40,13 +48,17 @@ impl Lower for Program {
     fn lower(&self) -> Self::Lowered {
         let mut lowerer = ProgramLowerer::default();
 
+        // Flatten the modules of the program, so that every item is
+        // declared under its fully qualified name:
+        let (modules, items) = Modules::collect(self)?;
+
         // Make a vector mapping each thing in `items` to an id,
         // based just on its position:
-        let raw_ids = self.items.iter().map(|_| lowerer.next_item_id()).collect();
+        let raw_ids = items.iter().map(|_| lowerer.next_item_id()).collect();
 
-        lowerer.extract_associated_types(self, &raw_ids)?;
-        lowerer.extract_ids(self, &raw_ids)?;
-        lowerer.lower(self, &raw_ids)
+        lowerer.extract_associated_types(&items, &raw_ids)?;
+        lowerer.extract_ids(&items, &raw_ids)?;
+        lowerer.lower(modules, &items, &raw_ids)
     }
 }
 
@@ -96,6 +108,8 @@ lower_param_map!(AdtDefn, None);
 lower_param_map!(FnDefn, None);
 lower_param_map!(ClosureDefn, None);
 lower_param_map!(Impl, None);
+lower_param_map!(InherentImpl, None);
+lower_param_map!(AssocFnDefn, None);
 lower_param_map!(AssocTyDefn, None);
 lower_param_map!(AssocTyValue, None);
 lower_param_map!(Clause, None);
@@ -106,9 +120,29 @@ lower_param_map!(
         Atom::from(SELF),
     ))
 );
+lower_param_map!(
+    TraitAliasDefn,
+    Some(chalk_ir::WithKind::new(
+        chalk_ir::VariableKind::Ty(TyVariableKind::General),
+        Atom::from(SELF),
+    ))
+);
+
+fn get_type_of_usize() -> chalk_ir::Ty<ChalkIr> {
+    chalk_ir::TyKind::Scalar(chalk_ir::Scalar::Uint(chalk_ir::UintTy::Usize)).intern(&ChalkIr)
+}
 
-fn get_type_of_u32() -> chalk_ir::Ty<ChalkIr> {
-    chalk_ir::TyKind::Scalar(chalk_ir::Scalar::Uint(chalk_ir::UintTy::U32)).intern(&ChalkIr)
+fn get_scalar_type(ty: ScalarType) -> chalk_ir::Ty<ChalkIr> {
+    chalk_ir::TyKind::Scalar(ty.lower()).intern(&ChalkIr)
+}
+
+/// The type of the values of a const declared with type `ty`. Only scalar
+/// types have values, so consts of other types are treated as `usize`s.
+fn const_ty(ty: &Ty) -> chalk_ir::Ty<ChalkIr> {
+    match ty {
+        Ty::Scalar { ty } => get_scalar_type(*ty),
+        _ => get_type_of_usize(),
+    }
 }
 
 impl Lower for VariableKind {
@@ -128,7 +162,10 @@ impl Lower for VariableKind {
                 n,
             ),
             VariableKind::Lifetime(n) => (chalk_ir::VariableKind::Lifetime, n),
-            VariableKind::Const(ref n) => (chalk_ir::VariableKind::Const(get_type_of_u32()), n),
+            VariableKind::Const(ref n, ty) => (
+                chalk_ir::VariableKind::Const(ty.map_or_else(get_type_of_usize, get_scalar_type)),
+                n,
+            ),
         };
 
         chalk_ir::WithKind::new(kind, n.str.clone())
@@ -212,12 +249,40 @@ impl LowerWithEnv for DomainGoal {
                 .into_iter()
                 .casted(interner)
                 .collect(),
+            DomainGoal::Normalize { projection, ty } if env.is_const_projection(projection)? => {
+                // `<T as Trait>::N -> M` parses `M` as a type.
+                let value = match ty {
+                    Ty::Id { name } => Const::Id(name.clone()),
+                    Ty::Projection { proj } => Const::Projection(proj.clone()),
+                    _ => Err(RustIrError::IncorrectParameterKind {
+                        identifier: projection.name.clone(),
+                        expected: Kind::Const,
+                        actual: Kind::Ty,
+                    })?,
+                };
+                let (alias, ty) = lower_const_projection(projection, env)?;
+                vec![chalk_ir::DomainGoal::NormalizeConst(
+                    chalk_ir::NormalizeConst {
+                        alias,
+                        value: lower_const(&value, Some(&ty), env)?,
+                    },
+                )]
+            }
             DomainGoal::Normalize { projection, ty } => {
                 vec![chalk_ir::DomainGoal::Normalize(chalk_ir::Normalize {
                     alias: chalk_ir::AliasTy::Projection(projection.lower(env)?),
                     ty: ty.lower(env)?,
                 })]
             }
+            DomainGoal::NormalizeConst { projection, value } => {
+                let (alias, ty) = lower_const_projection(projection, env)?;
+                vec![chalk_ir::DomainGoal::NormalizeConst(
+                    chalk_ir::NormalizeConst {
+                        alias,
+                        value: lower_const(value, Some(&ty), env)?,
+                    },
+                )]
+            }
             DomainGoal::TyWellFormed { ty } => vec![chalk_ir::DomainGoal::WellFormed(
                 chalk_ir::WellFormed::Ty(ty.lower(env)?),
             )],
@@ -266,6 +331,11 @@ impl LowerWithEnv for LeafGoal {
                 b: b.lower(env)?.cast(interner),
             }
             .cast::<chalk_ir::Goal<ChalkIr>>(interner),
+            LeafGoal::SubtypeTys { a, b } => chalk_ir::SubtypeGoal {
+                a: a.lower(env)?,
+                b: b.lower(env)?,
+            }
+            .cast::<chalk_ir::Goal<ChalkIr>>(interner),
         })
     }
 }
@@ -323,10 +393,44 @@ impl Lower for AdtRepr {
         rust_ir::AdtRepr {
             repr_c: self.repr_c,
             repr_packed: self.repr_packed,
+            repr_int: self.repr_int.map(|ty| ty.lower()),
         }
     }
 }
 
+impl Lower for Variance {
+    type Lowered = chalk_ir::Variance;
+
+    fn lower(&self) -> Self::Lowered {
+        match self {
+            Variance::Invariant => chalk_ir::Variance::Invariant,
+            Variance::Covariant => chalk_ir::Variance::Covariant,
+            Variance::Contravariant => chalk_ir::Variance::Contravariant,
+        }
+    }
+}
+
+/// Lowers the variances declared with `#[variance(..)]` for the item `name`,
+/// which has `num_parameters` parameters. If no variances were declared, all
+/// the parameters are invariant.
+fn lower_variances(
+    name: &Identifier,
+    variances: &Option<Vec<Variance>>,
+    num_parameters: usize,
+) -> LowerResult<Vec<chalk_ir::Variance>> {
+    match variances {
+        None => Ok(vec![chalk_ir::Variance::Invariant; num_parameters]),
+        Some(variances) if variances.len() != num_parameters => {
+            Err(RustIrError::IncorrectNumberOfVariances {
+                identifier: name.clone(),
+                expected: num_parameters,
+                actual: variances.len(),
+            })
+        }
+        Some(variances) => Ok(variances.iter().map(Variance::lower).collect()),
+    }
+}
+
 impl LowerWithEnv for (&FnDefn, chalk_ir::FnDefId<ChalkIr>) {
     type Lowered = rust_ir::FnDefDatum<ChalkIr>;
 
@@ -362,6 +466,137 @@ impl LowerWithEnv for (&FnDefn, chalk_ir::FnDefId<ChalkIr>) {
     }
 }
 
+/// The item that declares an associated fn.
+#[derive(Copy, Clone)]
+pub(super) enum AssocFnContainer<'a> {
+    Trait(&'a TraitDefn, chalk_ir::TraitId<ChalkIr>),
+    InherentImpl(&'a InherentImpl),
+}
+
+impl AssocFnContainer<'_> {
+    fn all_parameters(&self) -> Vec<chalk_ir::WithKind<ChalkIr, Ident>> {
+        match self {
+            AssocFnContainer::Trait(defn, _) => defn.all_parameters(),
+            AssocFnContainer::InherentImpl(defn) => defn.all_parameters(),
+        }
+    }
+
+    /// The where clauses that the associated fns inherit from the
+    /// container. For a trait, this is `Self: Trait<..>`.
+    fn where_clauses(
+        &self,
+        env: &Env,
+    ) -> LowerResult<Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>>> {
+        let interner = env.interner();
+        match self {
+            AssocFnContainer::Trait(defn, trait_id) => {
+                let where_clause = env.in_binders(vec![], |env| {
+                    let parameters = defn
+                        .all_parameters()
+                        .into_iter()
+                        .map(|parameter| {
+                            env.lookup_generic_arg(&Identifier {
+                                str: parameter.skip_kind().clone(),
+                                span: defn.name.span,
+                            })
+                        })
+                        .collect::<LowerResult<Vec<_>>>()?;
+                    Ok(chalk_ir::WhereClause::Implemented(chalk_ir::TraitRef {
+                        trait_id: *trait_id,
+                        substitution: chalk_ir::Substitution::from_iter(interner, parameters),
+                    }))
+                })?;
+                Ok(vec![where_clause])
+            }
+            AssocFnContainer::InherentImpl(defn) => defn.where_clauses.lower(env),
+        }
+    }
+}
+
+impl LowerWithEnv
+    for (
+        &AssocFnDefn,
+        chalk_ir::FnDefId<ChalkIr>,
+        AssocFnContainer<'_>,
+    )
+{
+    type Lowered = rust_ir::FnDefDatum<ChalkIr>;
+
+    fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
+        let (defn, fn_def_id, container) = self;
+
+        // As for associated types, the parameters of the fn come first,
+        // followed by those of its container.
+        let mut variable_kinds = defn.all_parameters();
+        variable_kinds.extend(container.all_parameters());
+
+        let binders = env.in_binders(variable_kinds, |env| {
+            let mut where_clauses = defn.where_clauses.lower(env)?;
+            where_clauses.extend(container.where_clauses(env)?);
+
+            // A `&self` receiver without a lifetime has a late-bound one.
+            let receiver_lifetime = Identifier {
+                str: Atom::from(RECEIVER_LIFETIME),
+                span: defn.name.span,
+            };
+            let late_bound_lifetimes = match defn.receiver {
+                Some(Receiver::Ref { lifetime: None, .. }) => {
+                    Some(VariableKind::Lifetime(receiver_lifetime.clone()).lower())
+                }
+                _ => None,
+            };
+
+            let inputs_and_output = env.in_binders(late_bound_lifetimes, |env| {
+                let self_ty = Ty::Id {
+                    name: Identifier {
+                        str: Atom::from(SELF),
+                        span: defn.name.span,
+                    },
+                };
+                let receiver_ty = defn.receiver.as_ref().map(|receiver| match receiver {
+                    Receiver::Value => self_ty,
+                    Receiver::Ref {
+                        lifetime,
+                        mutability,
+                    } => Ty::Ref {
+                        mutability: *mutability,
+                        lifetime: lifetime.clone().unwrap_or(Lifetime::Id {
+                            name: receiver_lifetime,
+                        }),
+                        ty: Box::new(self_ty),
+                    },
+                    Receiver::Typed(ty) => ty.clone(),
+                });
+                let args: LowerResult<_> = receiver_ty
+                    .iter()
+                    .chain(&defn.argument_types)
+                    .map(|t| t.lower(env))
+                    .collect();
+                let return_type = defn.return_type.lower(env)?;
+                Ok(rust_ir::FnDefInputsAndOutputDatum {
+                    argument_types: args?,
+                    return_type,
+                })
+            })?;
+            Ok(rust_ir::FnDefDatumBound {
+                inputs_and_output,
+                where_clauses,
+            })
+        })?;
+
+        Ok(rust_ir::FnDefDatum {
+            id: *fn_def_id,
+            sig: FnSig {
+                abi: FnAbi::default(),
+                safety: Safety::Safe,
+                variadic: false,
+            }
+            .lower()?,
+            binders,
+        })
+    }
+}
+
 impl Lower for FnSig {
     type Lowered = LowerResult<chalk_ir::FnSig<ChalkIr>>;
 
@@ -387,7 +622,7 @@ impl Lower for FnAbi {
 
 impl LowerWithEnv for ClosureDefn {
     type Lowered = (
-        rust_ir::ClosureKind,
+        Option<rust_ir::ClosureKind>,
         chalk_ir::Binders<rust_ir::FnDefInputsAndOutputDatum<ChalkIr>>,
     );
 
@@ -401,7 +636,7 @@ impl LowerWithEnv for ClosureDefn {
             })
         })?;
 
-        Ok((self.kind.lower(), inputs_and_output))
+        Ok((self.kind.as_ref().map(Lower::lower), inputs_and_output))
     }
 }
 
@@ -448,7 +683,14 @@ impl LowerWithEnv for TraitBound {
         let parameters = self
             .args_no_self
             .iter()
-            .map(|a| a.lower(env))
+            .zip(
+                k.binders
+                    .binders
+                    .iter(interner)
+                    .map(Some)
+                    .chain(std::iter::repeat(None)),
+            )
+            .map(|(a, kind)| lower_generic_arg(a, kind, env))
             .collect::<LowerResult<Vec<_>>>()?;
 
         if parameters.len() != k.binders.len(interner) {
@@ -568,6 +810,17 @@ impl LowerWithEnv for [QuantifiedInlineBound] {
     }
 }
 
+impl Lower for Movability {
+    type Lowered = rust_ir::Movability;
+
+    fn lower(&self) -> Self::Lowered {
+        match self {
+            Movability::Static => rust_ir::Movability::Static,
+            Movability::Movable => rust_ir::Movability::Movable,
+        }
+    }
+}
+
 impl Lower for Polarity {
     type Lowered = rust_ir::Polarity;
 
@@ -600,6 +853,7 @@ impl Lower for TraitFlags {
             fundamental: self.fundamental,
             non_enumerable: self.non_enumerable,
             coinductive: self.coinductive,
+            alias: false,
         }
     }
 }
@@ -651,6 +905,31 @@ impl LowerWithEnv for ProjectionTy {
     }
 }
 
+/// Lowers `projection`, which names an associated const, e.g. `<T as Foo>::N`.
+/// Returns it along with the type of its value.
+fn lower_const_projection(
+    projection: &ProjectionTy,
+    env: &Env,
+) -> LowerResult<(chalk_ir::ConstProjection<ChalkIr>, chalk_ir::Ty<ChalkIr>)> {
+    let ProjectionTy {
+        ref trait_ref,
+        ref name,
+        ref args,
+    } = *projection;
+    let trait_ref = trait_ref.lower(env)?;
+    let lookup = env.lookup_associated_const(trait_ref.trait_id, name)?;
+    if !args.is_empty() {
+        Err(RustIrError::AssociatedConstParameters(name.clone()))?;
+    }
+    Ok((
+        chalk_ir::ConstProjection {
+            associated_const_id: lookup.id,
+            substitution: trait_ref.substitution,
+        },
+        lookup.ty.clone(),
+    ))
+}
+
 impl LowerWithEnv for Ty {
     type Lowered = chalk_ir::Ty<ChalkIr>;
 
@@ -678,9 +957,10 @@ impl LowerWithEnv for Ty {
                         Atom::from(FIXME_SELF),
                     )),
                     |env| {
-                        Ok(QuantifiedWhereClauses::from_iter(
-                            interner,
-                            bounds.lower(env)?.iter().flat_map(|qil| {
+                        let bounds = bounds
+                            .lower(env)?
+                            .iter()
+                            .flat_map(|qil| {
                                 qil.into_where_clauses(
                                     interner,
                                     chalk_ir::TyKind::BoundVar(BoundVar::new(
@@ -689,7 +969,11 @@ impl LowerWithEnv for Ty {
                                     ))
                                     .intern(interner),
                                 )
-                            }),
+                            })
+                            .collect();
+                        Ok(QuantifiedWhereClauses::from_iter(
+                            interner,
+                            expand_trait_aliases(bounds, env),
                         ))
                     },
                 )?,
@@ -710,7 +994,9 @@ impl LowerWithEnv for Ty {
 
                         let substitution = chalk_ir::Substitution::from_fallible(
                             interner,
-                            args.iter().map(|t| Ok(t.lower(env)?)),
+                            args.iter()
+                                .zip($k.binders.binders.iter(interner))
+                                .map(|(t, kind)| lower_generic_arg(t, Some(kind), env)),
                         )?;
 
                         for (param, arg) in $k
@@ -810,25 +1096,159 @@ impl LowerWithEnv for Const {
     type Lowered = chalk_ir::Const<ChalkIr>;
 
     fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
-        let interner = env.interner();
-        match self {
-            Const::Id(name) => {
-                let parameter = env.lookup_generic_arg(name)?;
-                parameter
-                    .constant(interner)
-                    .ok_or_else(|| RustIrError::IncorrectParameterKind {
-                        identifier: name.clone(),
-                        expected: Kind::Const,
-                        actual: parameter.kind(),
-                    })
-                    .map(|c| c.clone())
+        lower_const(self, None, env)
+    }
+}
+
+/// Lowers `c`, whose type is `expected`, if known. Integer literals and
+/// expressions take the type they are expected to have, unless they have
+/// a type of their own; they default to `usize`.
+fn lower_const(
+    c: &Const,
+    expected: Option<&chalk_ir::Ty<ChalkIr>>,
+    env: &Env,
+) -> LowerResult<chalk_ir::Const<ChalkIr>> {
+    let interner = env.interner();
+    let (ty, value) = match c {
+        Const::Id(name) => {
+            let parameter = env.lookup_generic_arg(name)?;
+            return parameter
+                .constant(interner)
+                .ok_or_else(|| RustIrError::IncorrectParameterKind {
+                    identifier: name.clone(),
+                    expected: Kind::Const,
+                    actual: parameter.kind(),
+                })
+                .map(|c| c.clone());
+        }
+        Const::Value(ConstValue::Bool(value)) => (
+            get_scalar_type(ScalarType::Bool),
+            chalk_ir::ConstValue::Concrete(chalk_ir::ConcreteConst {
+                interned: ConstScalar::Bool(*value),
+            }),
+        ),
+        Const::Value(ConstValue::Char(value)) => (
+            get_scalar_type(ScalarType::Char),
+            chalk_ir::ConstValue::Concrete(chalk_ir::ConcreteConst {
+                interned: ConstScalar::Char(*value),
+            }),
+        ),
+        Const::Value(ConstValue::Int { value, ty }) => {
+            lower_int_literal(*value, *ty, false, expected)?
+        }
+        Const::Expr(expr) => match **expr {
+            // Negative integer literals are values rather than expressions.
+            ConstExpr::Unary(UnOp::Neg, Const::Value(ConstValue::Int { value, ty })) => {
+                lower_int_literal(value, ty, true, expected)?
             }
-            Const::Value(value) => Ok(chalk_ir::ConstData {
-                ty: get_type_of_u32(),
-                value: chalk_ir::ConstValue::Concrete(chalk_ir::ConcreteConst { interned: *value }),
+            ConstExpr::Unary(op, ref c) => {
+                let ty = match expected {
+                    Some(ty) => ty.clone(),
+                    None => natural_const_ty(c, env)?.unwrap_or_else(get_type_of_usize),
+                };
+                let c = lower_const(c, Some(&ty), env)?;
+                let op = match op {
+                    UnOp::Neg => chalk_ir::UnOp::Neg,
+                    UnOp::Not => chalk_ir::UnOp::Not,
+                };
+                (
+                    ty,
+                    chalk_ir::ConstValue::Expr(chalk_ir::ConstExpr::Unary(op, c)),
+                )
             }
-            .intern(interner)),
+            ConstExpr::Binary(op, ref a, ref b) => {
+                let ty = match expected {
+                    Some(ty) => ty.clone(),
+                    None => natural_const_ty(a, env)?
+                        .or(natural_const_ty(b, env)?)
+                        .unwrap_or_else(get_type_of_usize),
+                };
+                let a = lower_const(a, Some(&ty), env)?;
+                let b = lower_const(b, Some(&ty), env)?;
+                let op = match op {
+                    BinOp::Add => chalk_ir::BinOp::Add,
+                    BinOp::Sub => chalk_ir::BinOp::Sub,
+                    BinOp::Mul => chalk_ir::BinOp::Mul,
+                    BinOp::Div => chalk_ir::BinOp::Div,
+                    BinOp::Rem => chalk_ir::BinOp::Rem,
+                };
+                (
+                    ty,
+                    chalk_ir::ConstValue::Expr(chalk_ir::ConstExpr::Binary(op, a, b)),
+                )
+            }
+        },
+        Const::Projection(projection) => {
+            let (projection, ty) = lower_const_projection(projection, env)?;
+            (ty, chalk_ir::ConstValue::Projection(projection))
+        }
+    };
+    Ok(chalk_ir::ConstData { ty, value }.intern(interner))
+}
+
+/// Lowers the integer literal `value`, negated if `negative` is true. Its
+/// type is its suffix `ty`, if any, or else its expected type.
+fn lower_int_literal(
+    value: u128,
+    ty: Option<ScalarType>,
+    negative: bool,
+    expected: Option<&chalk_ir::Ty<ChalkIr>>,
+) -> LowerResult<(chalk_ir::Ty<ChalkIr>, chalk_ir::ConstValue<ChalkIr>)> {
+    let ty = match (ty, expected.map(|ty| ty.kind(&ChalkIr))) {
+        (Some(ty), _) => ty.lower(),
+        (None, Some(chalk_ir::TyKind::Scalar(scalar)))
+            if matches!(scalar, chalk_ir::Scalar::Int(_) | chalk_ir::Scalar::Uint(_)) =>
+        {
+            *scalar
         }
+        _ => chalk_ir::Scalar::Uint(chalk_ir::UintTy::Usize),
+    };
+    let scalar = if negative {
+        i128::try_from(value)
+            .ok()
+            .and_then(|value| ConstScalar::int(-value, ty))
+    } else {
+        ConstScalar::uint(value, ty)
+    };
+    let scalar = scalar.ok_or_else(|| {
+        RustIrError::ConstLiteralOutOfRange(format!("{}{}", if negative { "-" } else { "" }, value))
+    })?;
+    Ok((
+        chalk_ir::TyKind::Scalar(ty).intern(&ChalkIr),
+        chalk_ir::ConstValue::Concrete(chalk_ir::ConcreteConst { interned: scalar }),
+    ))
+}
+
+/// The type of `c`, if it does not depend on the type `c` is expected to
+/// have.
+fn natural_const_ty(c: &Const, env: &Env) -> LowerResult<Option<chalk_ir::Ty<ChalkIr>>> {
+    Ok(match c {
+        Const::Id(_) => Some(lower_const(c, None, env)?.data(env.interner()).ty.clone()),
+        Const::Value(ConstValue::Bool(_)) => Some(get_scalar_type(ScalarType::Bool)),
+        Const::Value(ConstValue::Char(_)) => Some(get_scalar_type(ScalarType::Char)),
+        Const::Value(ConstValue::Int { ty, .. }) => ty.map(get_scalar_type),
+        Const::Projection(projection) => Some(lower_const_projection(projection, env)?.1),
+        Const::Expr(expr) => match **expr {
+            ConstExpr::Unary(_, ref c) => natural_const_ty(c, env)?,
+            ConstExpr::Binary(_, ref a, ref b) => match natural_const_ty(a, env)? {
+                Some(ty) => Some(ty),
+                None => natural_const_ty(b, env)?,
+            },
+        },
+    })
+}
+
+/// Lowers `arg`, the argument of a parameter of kind `kind`, if known.
+fn lower_generic_arg(
+    arg: &GenericArg,
+    kind: Option<&chalk_ir::VariableKind<ChalkIr>>,
+    env: &Env,
+) -> LowerResult<chalk_ir::GenericArg<ChalkIr>> {
+    match (arg, kind) {
+        (GenericArg::Const(c), Some(chalk_ir::VariableKind::Const(ty))) => {
+            Ok(lower_const(c, Some(ty), env)?.cast(env.interner()))
+        }
+        _ => arg.lower(env),
     }
 }
 
@@ -838,6 +1258,10 @@ impl LowerWithEnv for GenericArg {
     fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
         let interner = env.interner();
         match self {
+            // `<T as Foo>::N` parses as a type, even when it names a const.
+            GenericArg::Ty(Ty::Projection { proj }) if env.is_const_projection(proj)? => {
+                Ok(Const::Projection(proj.clone()).lower(env)?.cast(interner))
+            }
             GenericArg::Ty(ref t) => Ok(t.lower(env)?.cast(interner)),
             GenericArg::Lifetime(ref l) => Ok(l.lower(env)?.cast(interner)),
             GenericArg::Id(name) => env.lookup_generic_arg(&name),
@@ -870,18 +1294,33 @@ impl LowerWithEnv for Lifetime {
     }
 }
 
-impl LowerWithEnv for (&Impl, ImplId<ChalkIr>, &AssociatedTyValueIds) {
+impl LowerWithEnv
+    for (
+        &Impl,
+        ImplId<ChalkIr>,
+        &AssociatedTyValueIds,
+        &AssociatedConstValueIds,
+    )
+{
     type Lowered = rust_ir::ImplDatum<ChalkIr>;
 
     fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
-        let (impl_, impl_id, associated_ty_value_ids) = self;
+        let (impl_, impl_id, associated_ty_value_ids, associated_const_value_ids) = self;
 
         let polarity = impl_.polarity.lower();
         let binders = env.in_binders(impl_.all_parameters(), |env| {
             let trait_ref = impl_.trait_ref.lower(env)?;
             debug!(?trait_ref);
 
-            if !polarity.is_positive() && !impl_.assoc_ty_values.is_empty() {
+            if env.trait_alias_bounds(trait_ref.trait_id).is_some() {
+                Err(RustIrError::TraitAliasImpl(
+                    impl_.trait_ref.trait_name.clone(),
+                ))?;
+            }
+
+            if !polarity.is_positive()
+                && (!impl_.assoc_ty_values.is_empty() || !impl_.assoc_const_values.is_empty())
+            {
                 Err(RustIrError::NegativeImplAssociatedValues(
                     impl_.trait_ref.trait_name.clone(),
                 ))?;
@@ -906,11 +1345,18 @@ impl LowerWithEnv for (&Impl, ImplId<ChalkIr>, &AssociatedTyValueIds) {
 
         debug!(?associated_ty_value_ids);
 
+        let associated_const_value_ids = impl_
+            .assoc_const_values
+            .iter()
+            .map(|acv| associated_const_value_ids[&(*impl_id, acv.name.str.clone())])
+            .collect();
+
         Ok(rust_ir::ImplDatum {
             polarity,
             binders,
             impl_type: impl_.impl_type.lower(),
             associated_ty_value_ids,
+            associated_const_value_ids,
         })
     }
 }
@@ -984,11 +1430,25 @@ impl LowerWithEnv for (&TraitDefn, chalk_ir::TraitId<ChalkIr>) {
             .map(|defn| env.lookup_associated_ty(*trait_id, &defn.name).unwrap().id)
             .collect();
 
+        let associated_const_ids: Vec<_> = trait_defn
+            .assoc_const_defns
+            .iter()
+            .map(|defn| {
+                env.lookup_associated_const(*trait_id, &defn.name)
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
         let trait_datum = rust_ir::TraitDatum {
             id: *trait_id,
             binders,
             flags: trait_defn.flags.lower(),
             associated_ty_ids,
+            associated_const_ids,
+            // The ids of the associated fns are filled in by the program
+            // lowerer, which allocates them.
+            associated_fn_ids: vec![],
             well_known: trait_defn.well_known.map(|def| def.lower()),
         };
 
@@ -998,7 +1458,124 @@ impl LowerWithEnv for (&TraitDefn, chalk_ir::TraitId<ChalkIr>) {
     }
 }
 
+impl LowerWithEnv for (&TraitAliasDefn, chalk_ir::TraitId<ChalkIr>) {
+    type Lowered = rust_ir::TraitDatum<ChalkIr>;
+
+    fn lower(&self, env: &Env) -> LowerResult<Self::Lowered> {
+        let (alias_defn, trait_id) = self;
+
+        // The bounds of the alias become where clauses on `Self`, followed
+        // by the where clauses of the alias.
+        let binders = env.in_binders(alias_defn.all_parameters(), |env| {
+            let interner = env.interner();
+            let self_ty = chalk_ir::TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0))
+                .intern(interner);
+            let mut where_clauses: Vec<_> = alias_defn
+                .bounds
+                .lower(env)?
+                .iter()
+                .flat_map(|qil| qil.into_where_clauses(interner, self_ty.clone()))
+                .collect();
+            where_clauses.extend(alias_defn.where_clauses.lower(env)?);
+            Ok(rust_ir::TraitDatumBound { where_clauses })
+        })?;
+
+        let trait_datum = rust_ir::TraitDatum {
+            id: *trait_id,
+            binders,
+            flags: rust_ir::TraitFlags {
+                auto: false,
+                marker: false,
+                upstream: alias_defn.upstream,
+                fundamental: false,
+                non_enumerable: false,
+                coinductive: false,
+                alias: true,
+            },
+            associated_ty_ids: vec![],
+            associated_const_ids: vec![],
+            associated_fn_ids: vec![],
+            well_known: None,
+        };
+
+        debug!(?trait_datum);
+
+        Ok(trait_datum)
+    }
+}
+
+/// Replaces the trait aliases in the bounds of a `dyn` type by their own
+/// bounds, recursively. E.g., with `trait Alias = Foo + Send`, the bounds
+/// of `dyn Alias + Bar` become those of `dyn Foo + Bar + Send`.
+fn expand_trait_aliases(
+    bounds: Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>>,
+    env: &Env,
+) -> Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>> {
+    fn expand(
+        bound: chalk_ir::QuantifiedWhereClause<ChalkIr>,
+        env: &Env,
+        expanding: &mut Vec<chalk_ir::TraitId<ChalkIr>>,
+        out: &mut Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>>,
+    ) {
+        let interner = env.interner();
+        if let chalk_ir::WhereClause::Implemented(trait_ref) = bound.skip_binders() {
+            if let Some(alias_bounds) = env.trait_alias_bounds(trait_ref.trait_id) {
+                // An alias that (indirectly) refers to itself expands to
+                // nothing more.
+                if expanding.contains(&trait_ref.trait_id) {
+                    return;
+                }
+                expanding.push(trait_ref.trait_id);
+                for alias_bound in alias_bounds.substitute(interner, &trait_ref.substitution) {
+                    let alias_bound = chalk_ir::Binders::new(bound.binders.clone(), alias_bound)
+                        .fuse_binders(interner);
+                    expand(alias_bound, env, expanding, out);
+                }
+                expanding.pop();
+                return;
+            }
+        }
+        if !out.contains(&bound) {
+            out.push(bound);
+        }
+    }
+
+    if !bounds.iter().any(|bound| match bound.skip_binders() {
+        chalk_ir::WhereClause::Implemented(trait_ref) => {
+            env.trait_alias_bounds(trait_ref.trait_id).is_some()
+        }
+        _ => false,
+    }) {
+        return bounds;
+    }
+
+    let mut expanded = Vec::new();
+    for bound in bounds {
+        expand(bound, env, &mut vec![], &mut expanded);
+    }
+    // As in `[QuantifiedInlineBound]::lower`, auto traits come last.
+    expanded.sort_by_key(|bound| match bound.skip_binders() {
+        chalk_ir::WhereClause::Implemented(trait_ref) if env.auto_trait(trait_ref.trait_id) => {
+            Some(trait_ref.trait_id)
+        }
+        _ => None,
+    });
+    expanded
+}
+
 pub fn lower_goal(goal: &Goal, program: &LoweredProgram) -> LowerResult<chalk_ir::Goal<ChalkIr>> {
+    lower_in_program(goal, program)
+}
+
+pub fn lower_ty(ty: &Ty, program: &LoweredProgram) -> LowerResult<chalk_ir::Ty<ChalkIr>> {
+    lower_in_program(ty, program)
+}
+
+/// Lowers `value` in the root module of an already lowered program.
+fn lower_in_program<T: LowerWithEnv>(
+    value: &T,
+    program: &LoweredProgram,
+) -> LowerResult<T::Lowered> {
     let interner = &ChalkIr;
     let associated_ty_lookups: BTreeMap<_, _> = program
         .associated_ty_data
@@ -1017,12 +1594,32 @@ pub fn lower_goal(goal: &Goal, program: &LoweredProgram) -> LowerResult<chalk_ir
         })
         .collect();
 
+    let associated_const_lookups: BTreeMap<_, _> = program
+        .associated_const_data
+        .iter()
+        .map(|(&id, datum)| {
+            let ty = match datum.ty.skip_binders().kind(&ChalkIr) {
+                chalk_ir::TyKind::Scalar(_) => datum.ty.skip_binders().clone(),
+                _ => get_type_of_usize(),
+            };
+            let lookup = AssociatedConstLookup { id, ty };
+            ((datum.trait_id, datum.name.clone()), lookup)
+        })
+        .collect();
+
     let auto_traits = program
         .trait_data
         .iter()
         .map(|(&trait_id, datum)| (trait_id, datum.flags.auto))
         .collect();
 
+    let trait_aliases = program
+        .trait_data
+        .iter()
+        .filter(|(_, datum)| datum.is_alias())
+        .map(|(&trait_id, datum)| (trait_id, datum.alias_bounds(interner)))
+        .collect();
+
     let env = Env {
         adt_ids: &program.adt_ids,
         fn_def_ids: &program.fn_def_ids,
@@ -1037,12 +1634,17 @@ pub fn lower_goal(goal: &Goal, program: &LoweredProgram) -> LowerResult<chalk_ir
         trait_kinds: &program.trait_kinds,
         opaque_ty_kinds: &program.opaque_ty_kinds,
         associated_ty_lookups: &associated_ty_lookups,
+        associated_const_lookups: &associated_const_lookups,
         foreign_ty_ids: &program.foreign_ty_ids,
+        modules: &program.modules,
+        module: ModuleId::ROOT,
+        self_ty: None,
         parameter_map: BTreeMap::new(),
         auto_traits: &auto_traits,
+        trait_aliases: &trait_aliases,
     };
 
-    goal.lower(&env)
+    value.lower(&env)
 }
 
 impl LowerWithEnv for Goal {
@@ -1118,6 +1720,21 @@ impl Lower for WellKnownTrait {
             WellKnownTrait::Unsize => rust_ir::WellKnownTrait::Unsize,
             WellKnownTrait::Unpin => rust_ir::WellKnownTrait::Unpin,
             WellKnownTrait::CoerceUnsized => rust_ir::WellKnownTrait::CoerceUnsized,
+            WellKnownTrait::Deref => rust_ir::WellKnownTrait::Deref,
+            WellKnownTrait::DerefMut => rust_ir::WellKnownTrait::DerefMut,
+            WellKnownTrait::Generator => rust_ir::WellKnownTrait::Generator,
+            WellKnownTrait::DiscriminantKind => rust_ir::WellKnownTrait::DiscriminantKind,
+            WellKnownTrait::Pointee => rust_ir::WellKnownTrait::Pointee,
+        }
+    }
+}
+
+impl Lower for WellKnownAdt {
+    type Lowered = rust_ir::WellKnownAdt;
+
+    fn lower(&self) -> Self::Lowered {
+        match self {
+            WellKnownAdt::DynMetadata => rust_ir::WellKnownAdt::DynMetadata,
         }
     }
 }
@@ -5,9 +5,11 @@ use chalk_ir::{
 };
 use chalk_ir::{cast::Cast, ForeignDefId, WithKind};
 use chalk_parse::ast::*;
-use chalk_solve::rust_ir::AssociatedTyValueId;
+use chalk_solve::rust_ir::{AssociatedConstValueId, AssociatedTyValueId};
 use std::collections::BTreeMap;
 
+use super::modules::{ModuleId, Modules};
+use super::{LowerWithEnv, SELF};
 use crate::error::RustIrError;
 use crate::interner::ChalkIr;
 use crate::{Identifier as Ident, TypeKind};
@@ -23,11 +25,20 @@ pub type FnDefKinds = BTreeMap<chalk_ir::FnDefId<ChalkIr>, TypeKind>;
 pub type ClosureKinds = BTreeMap<chalk_ir::ClosureId<ChalkIr>, TypeKind>;
 pub type TraitKinds = BTreeMap<chalk_ir::TraitId<ChalkIr>, TypeKind>;
 pub type AutoTraits = BTreeMap<chalk_ir::TraitId<ChalkIr>, bool>;
+/// The bounds of the trait aliases; see `TraitDatum::alias_bounds`.
+pub type TraitAliases = BTreeMap<
+    chalk_ir::TraitId<ChalkIr>,
+    chalk_ir::Binders<Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>>>,
+>;
 pub type OpaqueTyVariableKinds = BTreeMap<chalk_ir::OpaqueTyId<ChalkIr>, TypeKind>;
 pub type GeneratorKinds = BTreeMap<chalk_ir::GeneratorId<ChalkIr>, TypeKind>;
 pub type AssociatedTyLookups = BTreeMap<(chalk_ir::TraitId<ChalkIr>, Ident), AssociatedTyLookup>;
 pub type AssociatedTyValueIds =
     BTreeMap<(chalk_ir::ImplId<ChalkIr>, Ident), AssociatedTyValueId<ChalkIr>>;
+pub type AssociatedConstLookups =
+    BTreeMap<(chalk_ir::TraitId<ChalkIr>, Ident), AssociatedConstLookup>;
+pub type AssociatedConstValueIds =
+    BTreeMap<(chalk_ir::ImplId<ChalkIr>, Ident), AssociatedConstValueId<ChalkIr>>;
 pub type ForeignIds = BTreeMap<Ident, chalk_ir::ForeignDefId<ChalkIr>>;
 
 pub type ParameterMap = BTreeMap<Ident, chalk_ir::WithKind<ChalkIr, BoundVar>>;
@@ -47,10 +58,18 @@ pub struct Env<'k> {
     pub opaque_ty_ids: &'k OpaqueTyIds,
     pub opaque_ty_kinds: &'k OpaqueTyVariableKinds,
     pub associated_ty_lookups: &'k AssociatedTyLookups,
+    pub associated_const_lookups: &'k AssociatedConstLookups,
     pub auto_traits: &'k AutoTraits,
+    pub trait_aliases: &'k TraitAliases,
     pub foreign_ty_ids: &'k ForeignIds,
     pub generator_ids: &'k GeneratorIds,
     pub generator_kinds: &'k GeneratorKinds,
+    pub modules: &'k Modules,
+    /// The module in which names are resolved.
+    pub module: ModuleId,
+    /// The type `Self` refers to, in the items of an inherent impl. In
+    /// traits, `Self` is a parameter instead.
+    pub self_ty: Option<&'k Ty>,
     /// GenericArg identifiers are used as keys, therefore
     /// all identifiers in an environment must be unique (no shadowing).
     pub parameter_map: ParameterMap,
@@ -75,6 +94,13 @@ pub struct AssociatedTyLookup {
     pub addl_variable_kinds: Vec<chalk_ir::VariableKind<ChalkIr>>,
 }
 
+#[derive(Clone, Debug)]
+pub struct AssociatedConstLookup {
+    pub id: chalk_ir::AssocConstId<ChalkIr>,
+    /// The type of the values of the const; see `const_ty`.
+    pub ty: chalk_ir::Ty<ChalkIr>,
+}
+
 pub enum TypeLookup<'k> {
     Parameter(&'k WithKind<ChalkIr, BoundVar>),
     Adt(AdtId<ChalkIr>),
@@ -115,6 +141,18 @@ impl Env<'_> {
             };
         }
 
+        if let Some(self_ty) = self.self_ty {
+            if name.str == *SELF && !self.parameter_map.contains_key(&name.str) {
+                // The self type cannot refer to `Self` itself.
+                let env = Env {
+                    self_ty: None,
+                    parameter_map: self.parameter_map.clone(),
+                    ..*self
+                };
+                return Ok(self_ty.lower(&env)?.cast(interner));
+            }
+        }
+
         match self.lookup_type(name) {
             Ok(TypeLookup::Parameter(p)) => {
                 let b = p.skip_kind();
@@ -152,20 +190,25 @@ impl Env<'_> {
 
     pub fn lookup_type(&self, name: &Identifier) -> LowerResult<TypeLookup> {
         if let Some(id) = self.parameter_map.get(&name.str) {
-            Ok(TypeLookup::Parameter(id))
-        } else if let Some(id) = self.adt_ids.get(&name.str) {
+            return Ok(TypeLookup::Parameter(id));
+        }
+        let path = self
+            .modules
+            .resolve(self.module, name)
+            .ok_or_else(|| RustIrError::NotStruct(name.clone()))?;
+        if let Some(id) = self.adt_ids.get(&path) {
             Ok(TypeLookup::Adt(*id))
-        } else if let Some(id) = self.fn_def_ids.get(&name.str) {
+        } else if let Some(id) = self.fn_def_ids.get(&path) {
             Ok(TypeLookup::FnDef(*id))
-        } else if let Some(id) = self.closure_ids.get(&name.str) {
+        } else if let Some(id) = self.closure_ids.get(&path) {
             Ok(TypeLookup::Closure(*id))
-        } else if let Some(id) = self.opaque_ty_ids.get(&name.str) {
+        } else if let Some(id) = self.opaque_ty_ids.get(&path) {
             Ok(TypeLookup::Opaque(*id))
-        } else if let Some(id) = self.foreign_ty_ids.get(&name.str) {
+        } else if let Some(id) = self.foreign_ty_ids.get(&path) {
             Ok(TypeLookup::Foreign(*id))
-        } else if let Some(id) = self.trait_ids.get(&name.str) {
+        } else if let Some(id) = self.trait_ids.get(&path) {
             Ok(TypeLookup::Trait(*id))
-        } else if let Some(id) = self.generator_ids.get(&name.str) {
+        } else if let Some(id) = self.generator_ids.get(&path) {
             Ok(TypeLookup::Generator(*id))
         } else {
             Err(RustIrError::NotStruct(name.clone()))
@@ -176,12 +219,26 @@ impl Env<'_> {
         self.auto_traits[&id]
     }
 
+    /// The bounds of the trait alias `id`, or `None` if `id` is not a trait
+    /// alias.
+    pub fn trait_alias_bounds(
+        &self,
+        id: chalk_ir::TraitId<ChalkIr>,
+    ) -> Option<&chalk_ir::Binders<Vec<chalk_ir::QuantifiedWhereClause<ChalkIr>>>> {
+        self.trait_aliases.get(&id)
+    }
+
     pub fn lookup_trait(&self, name: &Identifier) -> LowerResult<TraitId<ChalkIr>> {
         if let Some(_) = self.parameter_map.get(&name.str) {
+            return Err(RustIrError::NotTrait(name.clone()));
+        }
+        let path = self
+            .modules
+            .resolve(self.module, name)
+            .ok_or_else(|| RustIrError::InvalidTraitName(name.clone()))?;
+        if let Some(_) = self.adt_ids.get(&path) {
             Err(RustIrError::NotTrait(name.clone()))
-        } else if let Some(_) = self.adt_ids.get(&name.str) {
-            Err(RustIrError::NotTrait(name.clone()))
-        } else if let Some(id) = self.trait_ids.get(&name.str) {
+        } else if let Some(id) = self.trait_ids.get(&path) {
             Ok(*id)
         } else {
             Err(RustIrError::InvalidTraitName(name.clone()))
@@ -222,6 +279,25 @@ impl Env<'_> {
             .ok_or(RustIrError::MissingAssociatedType(ident.clone()))
     }
 
+    pub fn lookup_associated_const(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+        ident: &Identifier,
+    ) -> LowerResult<&AssociatedConstLookup> {
+        self.associated_const_lookups
+            .get(&(trait_id, ident.str.clone()))
+            .ok_or(RustIrError::MissingAssociatedConst(ident.clone()))
+    }
+
+    /// Whether `projection` names an associated const rather than an
+    /// associated type; the two are written the same way.
+    pub fn is_const_projection(&self, projection: &ProjectionTy) -> LowerResult<bool> {
+        let trait_id = projection.trait_ref.lower(self)?.trait_id;
+        Ok(self
+            .associated_const_lookups
+            .contains_key(&(trait_id, projection.name.str.clone())))
+    }
+
     /// Introduces new parameters, shifting the indices of existing
     /// parameters to accommodate them. The indices of the new binders
     /// will be assigned in order as they are iterated.
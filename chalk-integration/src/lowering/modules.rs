@@ -0,0 +1,351 @@
+//! Name resolution for programs split into `mod` and `crate` blocks.
+//!
+//! Before lowering, the modules of a program are flattened into a single
+//! list of items, and each item is renamed to its fully qualified name,
+//! e.g. `std::vec::Vec`; items at the top level of the program keep their
+//! name. The names used in the items, which may be paths, are then resolved
+//! from the module the item was declared in. Names that cannot be found in
+//! a module are looked up in the root of its crate, which thus acts as a
+//! prelude, and then among the crates it depends on.
+//!
+//! A `crate` block is a module that also starts a new crate. The items at
+//! the top level of the program form the root crate, which depends on all
+//! the declared crates; from its point of view, the items of the declared
+//! crates are upstream.
+
+use super::env::LowerResult;
+use crate::error::RustIrError;
+use chalk_parse::ast::*;
+use std::collections::BTreeMap;
+use string_cache::DefaultAtom as Atom;
+
+/// How many `use` imports may be followed to resolve a single name. This
+/// guards against imports that refer to each other.
+const MAX_IMPORT_DEPTH: usize = 32;
+
+/// Identifies a module of a program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModuleId(usize);
+
+impl ModuleId {
+    /// The root of the program, in which the items outside of any `mod` or
+    /// `crate` block live.
+    pub const ROOT: ModuleId = ModuleId(0);
+}
+
+/// Identifies a crate of a program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CrateId(usize);
+
+impl CrateId {
+    /// The crate of the items outside of any `crate` block.
+    pub const ROOT: CrateId = CrateId(0);
+}
+
+/// The modules and crates of a program, and the names visible in each
+/// module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Modules {
+    modules: Vec<ModuleData>,
+    crates: Vec<CrateData>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ModuleData {
+    parent: Option<ModuleId>,
+    krate: CrateId,
+    /// The fully qualified name of the module, empty for the root.
+    path: String,
+    names: BTreeMap<Atom, Binding>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CrateData {
+    name: Atom,
+    root: ModuleId,
+    /// The crates this crate directly depends on. A crate can only depend
+    /// on crates declared before it, so the dependency graph is acyclic.
+    dependencies: Vec<CrateId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Binding {
+    /// An item, with its fully qualified name.
+    Item(Atom),
+    Module(ModuleId),
+    /// A `use` import of the given path, resolved from the module that
+    /// declares it.
+    Import(Atom),
+}
+
+/// What a path resolves to.
+enum Resolution {
+    Item(Atom),
+    Module(ModuleId),
+}
+
+impl Default for Modules {
+    fn default() -> Self {
+        Modules {
+            modules: vec![ModuleData {
+                parent: None,
+                krate: CrateId::ROOT,
+                path: String::new(),
+                names: BTreeMap::new(),
+            }],
+            crates: vec![CrateData {
+                name: Atom::from("crate"),
+                root: ModuleId::ROOT,
+                dependencies: Vec::new(),
+            }],
+        }
+    }
+}
+
+impl Modules {
+    /// Collects the modules of `program`. Returns them along with the items
+    /// of all the modules, each paired with the module that declares it.
+    /// The items are renamed to their fully qualified names, and the items
+    /// of `#[upstream]` modules and of declared crates are marked as
+    /// upstream.
+    pub fn collect(program: &Program) -> LowerResult<(Modules, Vec<(ModuleId, Item)>)> {
+        let mut modules = Modules::default();
+        let mut items = Vec::new();
+        modules.collect_items(ModuleId::ROOT, &program.items, false, &mut items)?;
+        Ok((modules, items))
+    }
+
+    fn collect_items(
+        &mut self,
+        module: ModuleId,
+        items: &[Item],
+        upstream: bool,
+        out: &mut Vec<(ModuleId, Item)>,
+    ) -> LowerResult<()> {
+        for item in items {
+            let item = match item {
+                Item::Module(defn) => {
+                    let id = ModuleId(self.modules.len());
+                    let path = self.qualify(module, &defn.name);
+                    self.modules.push(ModuleData {
+                        parent: Some(module),
+                        krate: self.modules[module.0].krate,
+                        path: path.to_string(),
+                        names: BTreeMap::new(),
+                    });
+                    self.bind(module, &defn.name, Binding::Module(id));
+                    self.collect_items(id, &defn.items, upstream || defn.upstream, out)?;
+                    continue;
+                }
+                Item::Crate(defn) => {
+                    let dependencies = defn
+                        .dependencies
+                        .iter()
+                        .map(|name| self.crate_named(name))
+                        .collect::<LowerResult<_>>()?;
+                    let krate = CrateId(self.crates.len());
+                    let root = ModuleId(self.modules.len());
+                    self.crates.push(CrateData {
+                        name: defn.name.str.clone(),
+                        root,
+                        dependencies,
+                    });
+                    self.modules.push(ModuleData {
+                        parent: None,
+                        krate,
+                        path: defn.name.str.to_string(),
+                        names: BTreeMap::new(),
+                    });
+                    self.bind(module, &defn.name, Binding::Module(root));
+                    self.collect_items(root, &defn.items, true, out)?;
+                    continue;
+                }
+                // Includes are expanded when the source files of the
+                // program are parsed; see `SourceFiles::parse`.
+                Item::Include(_) => continue,
+                Item::Comment(_) => continue,
+                Item::Template(defn) => {
+                    self.collect_items(module, &defn.expand(), upstream, out)?;
+                    continue;
+                }
+                Item::Use(decl) => {
+                    let name = match &decl.alias {
+                        Some(alias) => alias.str.clone(),
+                        None => Atom::from(decl.path.str.rsplit("::").next().unwrap()),
+                    };
+                    self.modules[module.0]
+                        .names
+                        .insert(name, Binding::Import(decl.path.str.clone()));
+                    continue;
+                }
+                Item::AdtDefn(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    defn.flags.upstream |= upstream;
+                    Item::AdtDefn(defn)
+                }
+                Item::FnDefn(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    Item::FnDefn(defn)
+                }
+                Item::ClosureDefn(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    Item::ClosureDefn(defn)
+                }
+                Item::TraitDefn(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    defn.flags.upstream |= upstream;
+                    Item::TraitDefn(defn)
+                }
+                Item::TraitAlias(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    defn.upstream |= upstream;
+                    Item::TraitAlias(defn)
+                }
+                Item::OpaqueTyDefn(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    Item::OpaqueTyDefn(defn)
+                }
+                Item::GeneratorDefn(defn) => {
+                    let mut defn = defn.clone();
+                    defn.name = self.declare(module, &defn.name);
+                    Item::GeneratorDefn(defn)
+                }
+                Item::Foreign(ForeignDefn(name)) => {
+                    Item::Foreign(ForeignDefn(self.declare(module, name)))
+                }
+                Item::Impl(defn) => {
+                    let mut defn = defn.clone();
+                    if upstream {
+                        defn.impl_type = ImplType::External;
+                    }
+                    Item::Impl(defn)
+                }
+                Item::InherentImpl(_) | Item::Clause(_) => item.clone(),
+            };
+            out.push((module, item));
+        }
+        Ok(())
+    }
+
+    fn crate_named(&self, name: &Identifier) -> LowerResult<CrateId> {
+        self.crates()
+            .find(|krate| self.crates[krate.0].name == name.str)
+            .ok_or_else(|| RustIrError::InvalidCrateName(name.clone()))
+    }
+
+    /// The crates declared with `crate` blocks, i.e. all the crates but
+    /// the root crate.
+    pub fn crates(&self) -> impl Iterator<Item = CrateId> {
+        (1..self.crates.len()).map(CrateId)
+    }
+
+    /// A note for the errors found while checking `krate` as the local
+    /// crate.
+    pub fn crate_note(&self, krate: CrateId) -> String {
+        format!("while checking crate `{}`", self.crates[krate.0].name)
+    }
+
+    /// The crate that `module` belongs to.
+    pub fn crate_of(&self, module: ModuleId) -> CrateId {
+        self.modules[module.0].krate
+    }
+
+    /// Whether `krate` depends on `dependency`, directly or not. The root
+    /// crate depends on all the other crates.
+    pub fn depends_on(&self, krate: CrateId, dependency: CrateId) -> bool {
+        krate == CrateId::ROOT
+            || self.crates[krate.0]
+                .dependencies
+                .iter()
+                .any(|&direct| direct == dependency || self.depends_on(direct, dependency))
+    }
+
+    /// Binds `name` to an item of `module`, and returns the fully qualified
+    /// name of the item.
+    fn declare(&mut self, module: ModuleId, name: &Identifier) -> Identifier {
+        let qualified = Identifier {
+            str: self.qualify(module, name),
+            span: name.span,
+        };
+        self.bind(module, name, Binding::Item(qualified.str.clone()));
+        qualified
+    }
+
+    fn bind(&mut self, module: ModuleId, name: &Identifier, binding: Binding) {
+        self.modules[module.0]
+            .names
+            .insert(name.str.clone(), binding);
+    }
+
+    fn qualify(&self, module: ModuleId, name: &Identifier) -> Atom {
+        let path = &self.modules[module.0].path;
+        if path.is_empty() {
+            name.str.clone()
+        } else {
+            Atom::from(format!("{}::{}", path, name.str))
+        }
+    }
+
+    /// Resolves the name of an item, which may be a path, as seen from
+    /// `module`. Returns the fully qualified name of the item, or `None` if
+    /// the name does not refer to an item.
+    pub fn resolve(&self, module: ModuleId, name: &Identifier) -> Option<Atom> {
+        match self.resolve_path(module, &name.str, 0)? {
+            Resolution::Item(name) => Some(name),
+            Resolution::Module(_) => None,
+        }
+    }
+
+    fn resolve_path(&self, module: ModuleId, path: &str, depth: usize) -> Option<Resolution> {
+        if depth > MAX_IMPORT_DEPTH {
+            return None;
+        }
+        let mut segments = path.split("::");
+        let mut resolution = match segments.next()? {
+            "crate" => Resolution::Module(self.crates[self.crate_of(module).0].root),
+            "super" => Resolution::Module(self.modules[module.0].parent?),
+            name => self
+                .lookup(module, name, depth)
+                .or_else(|| self.lookup_in_prelude(module, name, depth))?,
+        };
+        for segment in segments {
+            let module = match resolution {
+                Resolution::Module(module) => module,
+                Resolution::Item(_) => return None,
+            };
+            resolution = match segment {
+                "super" => Resolution::Module(self.modules[module.0].parent?),
+                name => self.lookup(module, name, depth)?,
+            };
+        }
+        Some(resolution)
+    }
+
+    /// Looks up a name that is not declared in `module`, in the root of its
+    /// crate and then among the crates the crate directly depends on.
+    fn lookup_in_prelude(&self, module: ModuleId, name: &str, depth: usize) -> Option<Resolution> {
+        let krate = &self.crates[self.crate_of(module).0];
+        self.lookup(krate.root, name, depth).or_else(|| {
+            krate
+                .dependencies
+                .iter()
+                .map(|dependency| &self.crates[dependency.0])
+                .find(|dependency| dependency.name == *name)
+                .map(|dependency| Resolution::Module(dependency.root))
+        })
+    }
+
+    fn lookup(&self, module: ModuleId, name: &str, depth: usize) -> Option<Resolution> {
+        match self.modules[module.0].names.get(&Atom::from(name))? {
+            Binding::Item(name) => Some(Resolution::Item(name.clone())),
+            Binding::Module(module) => Some(Resolution::Module(*module)),
+            Binding::Import(path) => self.resolve_path(module, path, depth + 1),
+        }
+    }
+}
@@ -1,23 +1,35 @@
 use chalk_ir::cast::Cast;
 use chalk_ir::{
-    self, AdtId, AssocTypeId, BoundVar, ClosureId, DebruijnIndex, FnDefId, ForeignDefId,
-    GeneratorId, ImplId, OpaqueTyId, TraitId, TyVariableKind, VariableKinds,
+    self, AdtId, AssocConstId, AssocTypeId, BoundVar, ClosureId, DebruijnIndex, FnDefId,
+    ForeignDefId, GeneratorId, ImplId, OpaqueTyId, TraitId, TyVariableKind, VariableKinds,
 };
 use chalk_parse::ast::*;
 use chalk_solve::rust_ir::{
-    self, Anonymize, AssociatedTyValueId, GeneratorDatum, GeneratorInputOutputDatum,
-    GeneratorWitnessDatum, GeneratorWitnessExistential, OpaqueTyDatum, OpaqueTyDatumBound,
+    self, Anonymize, AssociatedConstValueId, AssociatedTyValueId, GeneratorDatum,
+    GeneratorInputOutputDatum, GeneratorWitnessDatum, GeneratorWitnessExistential, OpaqueTyDatum,
+    OpaqueTyDatumBound,
 };
 use rust_ir::IntoWhereClauses;
 use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 use string_cache::DefaultAtom as Atom;
 
-use super::{env::*, Lower, LowerParameterMap, LowerWithEnv, FIXME_SELF};
+use super::modules::{ModuleId, Modules};
+use super::{
+    const_ty, env::*, lower_const, lower_variances, AssocFnContainer, Lower, LowerParameterMap,
+    LowerWithEnv, FIXME_SELF,
+};
 use crate::error::RustIrError;
 use crate::program::Program as LoweredProgram;
-use crate::RawId;
 use crate::{interner::ChalkIr, TypeKind, TypeSort};
+use crate::{Identifier as Ident, RawId};
+
+/// The lowered associated fns of a trait or of an inherent impl; see
+/// `ProgramLowerer::lower_associated_fns`.
+type AssociatedFns = Vec<(
+    Arc<rust_ir::FnDefDatum<ChalkIr>>,
+    Arc<rust_ir::AssociatedFnDatum<ChalkIr>>,
+)>;
 
 #[derive(Default)]
 pub(super) struct ProgramLowerer {
@@ -25,6 +37,10 @@ pub(super) struct ProgramLowerer {
 
     associated_ty_lookups: AssociatedTyLookups,
     associated_ty_value_ids: AssociatedTyValueIds,
+    associated_const_lookups: AssociatedConstLookups,
+    associated_const_value_ids: AssociatedConstValueIds,
+    /// The ids of the associated fns, by container and name.
+    associated_fn_ids: BTreeMap<(RawId, Ident), FnDefId<ChalkIr>>,
     adt_ids: AdtIds,
     fn_def_ids: FnDefIds,
     closure_ids: ClosureIds,
@@ -40,6 +56,7 @@ pub(super) struct ProgramLowerer {
     opaque_ty_kinds: OpaqueTyVariableKinds,
     object_safe_traits: HashSet<TraitId<ChalkIr>>,
     foreign_ty_ids: ForeignIds,
+    item_spans: BTreeMap<RawId, Span>,
 }
 
 impl ProgramLowerer {
@@ -49,13 +66,14 @@ impl ProgramLowerer {
         RawId { index }
     }
 
-    /// Create ids for associated type declarations and values
+    /// Create ids for associated type and const declarations and values,
+    /// and for associated fns
     pub fn extract_associated_types(
         &mut self,
-        program: &Program,
+        items: &[(ModuleId, Item)],
         raw_ids: &Vec<RawId>,
     ) -> LowerResult<()> {
-        for (item, &raw_id) in program.items.iter().zip(raw_ids) {
+        for ((_, item), &raw_id) in items.iter().zip(raw_ids) {
             match item {
                 Item::TraitDefn(d) => {
                     if d.flags.auto && !d.assoc_ty_defns.is_empty() {
@@ -70,6 +88,19 @@ impl ProgramLowerer {
                         self.associated_ty_lookups
                             .insert((TraitId(raw_id), defn.name.str.clone()), lookup);
                     }
+                    for defn in &d.assoc_const_defns {
+                        let lookup = AssociatedConstLookup {
+                            id: AssocConstId(self.next_item_id()),
+                            ty: const_ty(&defn.ty),
+                        };
+                        self.associated_const_lookups
+                            .insert((TraitId(raw_id), defn.name.str.clone()), lookup);
+                    }
+                    self.extract_associated_fns(raw_id, &d.assoc_fn_defns)?;
+                }
+
+                Item::InherentImpl(d) => {
+                    self.extract_associated_fns(raw_id, &d.assoc_fn_defns)?;
                 }
 
                 Item::Impl(d) => {
@@ -78,6 +109,11 @@ impl ProgramLowerer {
                         self.associated_ty_value_ids
                             .insert((ImplId(raw_id), atv.name.str.clone()), atv_id);
                     }
+                    for acv in &d.assoc_const_values {
+                        let acv_id = AssociatedConstValueId(self.next_item_id());
+                        self.associated_const_value_ids
+                            .insert((ImplId(raw_id), acv.name.str.clone()), acv_id);
+                    }
                 }
 
                 _ => {}
@@ -86,30 +122,80 @@ impl ProgramLowerer {
         Ok(())
     }
 
-    pub fn extract_ids(&mut self, program: &Program, raw_ids: &Vec<RawId>) -> LowerResult<()> {
-        for (item, &raw_id) in program.items.iter().zip(raw_ids) {
+    fn extract_associated_fns(&mut self, raw_id: RawId, defns: &[AssocFnDefn]) -> LowerResult<()> {
+        for defn in defns {
+            let id = FnDefId(self.next_item_id());
+            if self
+                .associated_fn_ids
+                .insert((raw_id, defn.name.str.clone()), id)
+                .is_some()
+            {
+                Err(RustIrError::DuplicateAssociatedFn(defn.name.clone()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the kinds of the associated fns of a container with the
+    /// given parameters. Associated fns are not added to `fn_def_ids`,
+    /// since they cannot be named on their own.
+    fn extract_associated_fn_kinds(
+        &mut self,
+        raw_id: RawId,
+        defns: &[AssocFnDefn],
+        container_parameters: Vec<chalk_ir::WithKind<ChalkIr, Ident>>,
+    ) {
+        for defn in defns {
+            let id = self.associated_fn_ids[&(raw_id, defn.name.str.clone())];
+            let mut variable_kinds = defn.all_parameters();
+            variable_kinds.extend(container_parameters.iter().cloned());
+            self.item_spans.insert(id.0, defn.name.span);
+            self.fn_def_kinds.insert(
+                id,
+                TypeKind {
+                    sort: TypeSort::FnDef,
+                    name: defn.name.str.clone(),
+                    binders: chalk_ir::Binders::new(
+                        VariableKinds::from_iter(&ChalkIr, variable_kinds.anonymize()),
+                        crate::Unit,
+                    ),
+                },
+            );
+        }
+    }
+
+    pub fn extract_ids(
+        &mut self,
+        items: &[(ModuleId, Item)],
+        raw_ids: &Vec<RawId>,
+    ) -> LowerResult<()> {
+        for ((_, item), &raw_id) in items.iter().zip(raw_ids) {
             match item {
                 Item::AdtDefn(defn) => {
                     let type_kind = defn.lower_type_kind()?;
                     let id = AdtId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
                     self.adt_ids.insert(type_kind.name.clone(), id);
                     self.adt_kinds.insert(id, type_kind);
                 }
                 Item::FnDefn(defn) => {
                     let type_kind = defn.lower_type_kind()?;
                     let id = FnDefId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
                     self.fn_def_ids.insert(type_kind.name.clone(), id);
                     self.fn_def_kinds.insert(id, type_kind);
                 }
                 Item::ClosureDefn(defn) => {
                     let type_kind = defn.lower_type_kind()?;
                     let id = ClosureId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
                     self.closure_ids.insert(defn.name.str.clone(), id);
                     self.closure_kinds.insert(id, type_kind);
                 }
                 Item::TraitDefn(defn) => {
                     let type_kind = defn.lower_type_kind()?;
                     let id = TraitId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
                     self.trait_ids.insert(type_kind.name.clone(), id);
                     self.trait_kinds.insert(id, type_kind);
                     self.auto_traits.insert(id, defn.flags.auto);
@@ -117,76 +203,132 @@ impl ProgramLowerer {
                     if defn.flags.object_safe {
                         self.object_safe_traits.insert(id);
                     }
+
+                    self.extract_associated_fn_kinds(
+                        raw_id,
+                        &defn.assoc_fn_defns,
+                        defn.all_parameters(),
+                    );
+                }
+                Item::TraitAlias(defn) => {
+                    let type_kind = defn.lower_type_kind()?;
+                    let id = TraitId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
+                    self.trait_ids.insert(type_kind.name.clone(), id);
+                    self.trait_kinds.insert(id, type_kind);
+                    self.auto_traits.insert(id, false);
                 }
                 Item::OpaqueTyDefn(defn) => {
                     let type_kind = defn.lower_type_kind()?;
                     let id = OpaqueTyId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
                     self.opaque_ty_ids.insert(defn.name.str.clone(), id);
                     self.opaque_ty_kinds.insert(id, type_kind);
                 }
                 Item::Foreign(ForeignDefn(ref ident)) => {
+                    self.item_spans.insert(raw_id, ident.span);
                     self.foreign_ty_ids
                         .insert(ident.str.clone(), ForeignDefId(raw_id));
                 }
                 Item::GeneratorDefn(defn) => {
                     let id = GeneratorId(raw_id);
+                    self.item_spans.insert(raw_id, defn.name.span);
                     self.generator_ids.insert(defn.name.str.clone(), id);
                     self.generator_kinds.insert(id, defn.lower_type_kind()?);
                 }
-                Item::Impl(_) => continue,
-                Item::Clause(_) => continue,
+                Item::Impl(ref defn) => {
+                    self.item_spans.insert(raw_id, defn.span);
+                }
+                Item::InherentImpl(ref defn) => {
+                    self.item_spans.insert(raw_id, defn.span);
+                    self.extract_associated_fn_kinds(
+                        raw_id,
+                        &defn.assoc_fn_defns,
+                        defn.all_parameters(),
+                    );
+                }
+                Item::Clause(_)
+                | Item::Module(_)
+                | Item::Use(_)
+                | Item::Crate(_)
+                | Item::Include(_)
+                | Item::Template(_)
+                | Item::Comment(_) => continue,
             };
         }
         Ok(())
     }
 
-    pub fn lower(self, program: &Program, raw_ids: &Vec<RawId>) -> LowerResult<LoweredProgram> {
+    pub fn lower(
+        self,
+        modules: Modules,
+        items: &[(ModuleId, Item)],
+        raw_ids: &Vec<RawId>,
+    ) -> LowerResult<LoweredProgram> {
         let mut adt_data = BTreeMap::new();
         let mut adt_reprs = BTreeMap::new();
+        let mut adt_variances = BTreeMap::new();
         let mut fn_def_data = BTreeMap::new();
+        let mut fn_def_variances = BTreeMap::new();
         let mut closure_inputs_and_output = BTreeMap::new();
         let mut closure_closure_kind = BTreeMap::new();
         let mut closure_upvars = BTreeMap::new();
         let mut trait_data = BTreeMap::new();
         let mut well_known_traits = BTreeMap::new();
+        let mut well_known_adts = BTreeMap::new();
         let mut impl_data = BTreeMap::new();
         let mut associated_ty_data = BTreeMap::new();
         let mut associated_ty_values = BTreeMap::new();
+        let mut associated_const_data = BTreeMap::new();
+        let mut associated_const_values = BTreeMap::new();
+        let mut associated_fn_data = BTreeMap::new();
         let mut opaque_ty_data = BTreeMap::new();
+        let mut opaque_ty_variances = BTreeMap::new();
         let mut generator_data = BTreeMap::new();
         let mut generator_witness_data = BTreeMap::new();
         let mut hidden_opaque_types = BTreeMap::new();
         let mut custom_clauses = Vec::new();
+        let mut item_crates = BTreeMap::new();
+
+        // The bounds of the trait aliases are needed to lower `dyn` types,
+        // so the aliases are lowered first, with `dyn` types in their own
+        // bounds left unexpanded.
+        let mut trait_aliases = TraitAliases::new();
+        let no_trait_aliases = TraitAliases::new();
+        for ((module, item), &raw_id) in items.iter().zip(raw_ids) {
+            if let Item::TraitAlias(ref defn) = *item {
+                let empty_env = self.empty_env(&modules, *module, &no_trait_aliases);
+                let trait_id = TraitId(raw_id);
+                let trait_datum = (defn, trait_id).lower(&empty_env)?;
+                trait_aliases.insert(trait_id, trait_datum.alias_bounds(&ChalkIr));
+            }
+        }
 
-        for (item, &raw_id) in program.items.iter().zip(raw_ids) {
-            let empty_env = Env {
-                adt_ids: &self.adt_ids,
-                adt_kinds: &self.adt_kinds,
-                fn_def_ids: &self.fn_def_ids,
-                fn_def_kinds: &self.fn_def_kinds,
-                closure_ids: &self.closure_ids,
-                closure_kinds: &self.closure_kinds,
-                trait_ids: &self.trait_ids,
-                trait_kinds: &self.trait_kinds,
-                opaque_ty_ids: &self.opaque_ty_ids,
-                opaque_ty_kinds: &self.opaque_ty_kinds,
-                generator_ids: &self.generator_ids,
-                generator_kinds: &self.generator_kinds,
-                associated_ty_lookups: &self.associated_ty_lookups,
-                parameter_map: BTreeMap::new(),
-                auto_traits: &self.auto_traits,
-                foreign_ty_ids: &self.foreign_ty_ids,
-            };
+        for ((module, item), &raw_id) in items.iter().zip(raw_ids) {
+            item_crates.insert(raw_id, modules.crate_of(*module));
+
+            let empty_env = self.empty_env(&modules, *module, &trait_aliases);
 
             match *item {
                 Item::AdtDefn(ref d) => {
                     let adt_id = AdtId(raw_id);
                     adt_data.insert(adt_id, Arc::new((d, adt_id).lower(&empty_env)?));
                     adt_reprs.insert(adt_id, d.repr.lower());
+                    if let Some(well_known) = d.well_known {
+                        well_known_adts.insert(well_known.lower(), adt_id);
+                    }
+                    adt_variances.insert(
+                        adt_id,
+                        lower_variances(&d.name, &d.variances, d.all_parameters().len())?,
+                    );
                 }
                 Item::FnDefn(ref defn) => {
                     let fn_def_id = FnDefId(raw_id);
                     fn_def_data.insert(fn_def_id, Arc::new((defn, fn_def_id).lower(&empty_env)?));
+                    fn_def_variances.insert(
+                        fn_def_id,
+                        lower_variances(&defn.name, &defn.variances, defn.all_parameters().len())?,
+                    );
                 }
                 Item::ClosureDefn(ref defn) => {
                     let closure_def_id = ClosureId(raw_id);
@@ -207,7 +349,12 @@ impl ProgramLowerer {
                 }
                 Item::TraitDefn(ref trait_defn) => {
                     let trait_id = TraitId(raw_id);
-                    let trait_datum = (trait_defn, trait_id).lower(&empty_env)?;
+                    let mut trait_datum = (trait_defn, trait_id).lower(&empty_env)?;
+                    trait_datum.associated_fn_ids = trait_defn
+                        .assoc_fn_defns
+                        .iter()
+                        .map(|defn| self.associated_fn_ids[&(raw_id, defn.name.str.clone())])
+                        .collect();
 
                     if let Some(well_known) = trait_datum.well_known {
                         well_known_traits.insert(well_known, trait_id);
@@ -259,11 +406,52 @@ impl ProgramLowerer {
                             }),
                         );
                     }
+
+                    // The parameters in scope for the associated const
+                    // definitions are those from the trait.
+                    for assoc_const_defn in &trait_defn.assoc_const_defns {
+                        let id = self.associated_const_lookups
+                            [&(trait_id, assoc_const_defn.name.str.clone())]
+                            .id;
+                        let ty = empty_env.in_binders(trait_defn.all_parameters(), |env| {
+                            assoc_const_defn.ty.lower(env)
+                        })?;
+                        associated_const_data.insert(
+                            id,
+                            Arc::new(rust_ir::AssociatedConstDatum {
+                                trait_id,
+                                id,
+                                name: assoc_const_defn.name.str.clone(),
+                                ty,
+                            }),
+                        );
+                    }
+
+                    for (fn_def_datum, datum) in self.lower_associated_fns(
+                        &empty_env,
+                        raw_id,
+                        &trait_defn.assoc_fn_defns,
+                        AssocFnContainer::Trait(trait_defn, trait_id),
+                    )? {
+                        fn_def_variances.insert(datum.id, self.associated_fn_variances(datum.id));
+                        fn_def_data.insert(datum.id, fn_def_datum);
+                        associated_fn_data.insert(datum.id, datum);
+                    }
+                }
+                Item::TraitAlias(ref defn) => {
+                    let trait_id = TraitId(raw_id);
+                    trait_data.insert(trait_id, Arc::new((defn, trait_id).lower(&empty_env)?));
                 }
                 Item::Impl(ref impl_defn) => {
                     let impl_id = ImplId(raw_id);
                     let impl_datum = Arc::new(
-                        (impl_defn, impl_id, &self.associated_ty_value_ids).lower(&empty_env)?,
+                        (
+                            impl_defn,
+                            impl_id,
+                            &self.associated_ty_value_ids,
+                            &self.associated_const_value_ids,
+                        )
+                            .lower(&empty_env)?,
                     );
                     impl_data.insert(impl_id, impl_datum.clone());
                     let trait_id = impl_datum.trait_id();
@@ -295,6 +483,49 @@ impl ProgramLowerer {
                             }),
                         );
                     }
+
+                    // As for associated types, the parameters in scope for
+                    // the associated const values are those from the impl.
+                    for acv in &impl_defn.assoc_const_values {
+                        let acv_id =
+                            self.associated_const_value_ids[&(impl_id, acv.name.str.clone())];
+                        let lookup = empty_env.lookup_associated_const(trait_id, &acv.name)?;
+                        let associated_const_id = lookup.id;
+                        let value = empty_env.in_binders(impl_defn.all_parameters(), |env| {
+                            acv.ty.lower(env)?;
+                            lower_const(&acv.value, Some(&lookup.ty), env)
+                        })?;
+
+                        associated_const_values.insert(
+                            acv_id,
+                            Arc::new(rust_ir::AssociatedConstValue {
+                                impl_id,
+                                associated_const_id,
+                                value,
+                            }),
+                        );
+                    }
+                }
+                Item::InherentImpl(ref impl_defn) => {
+                    let env = Env {
+                        self_ty: Some(&impl_defn.self_ty),
+                        ..empty_env.clone()
+                    };
+                    // Check the self type even if the impl has no fns.
+                    env.in_binders(impl_defn.all_parameters(), |env| {
+                        impl_defn.self_ty.lower(env)
+                    })?;
+
+                    for (fn_def_datum, datum) in self.lower_associated_fns(
+                        &env,
+                        raw_id,
+                        &impl_defn.assoc_fn_defns,
+                        AssocFnContainer::InherentImpl(impl_defn),
+                    )? {
+                        fn_def_variances.insert(datum.id, self.associated_fn_variances(datum.id));
+                        fn_def_data.insert(datum.id, fn_def_datum);
+                        associated_fn_data.insert(datum.id, datum);
+                    }
                 }
                 Item::Clause(ref clause) => {
                     custom_clauses.extend(clause.lower(&empty_env)?);
@@ -306,6 +537,14 @@ impl ProgramLowerer {
                             .iter()
                             .map(|k| k.lower())
                             .collect::<Vec<_>>();
+                        opaque_ty_variances.insert(
+                            opaque_ty_id,
+                            lower_variances(
+                                &opaque_ty.name,
+                                &opaque_ty.variances,
+                                variable_kinds.len(),
+                            )?,
+                        );
 
                         // Introduce the parameters declared on the opaque type definition.
                         // So if we have `type Foo<P1..Pn> = impl Trait<T1..Tn>`, this would introduce `P1..Pn`
@@ -403,14 +642,23 @@ impl ProgramLowerer {
                         Ok(GeneratorWitnessExistential { types: witnesses })
                     })?;
 
-                    let generator_datum = GeneratorDatum { input_output };
+                    let generator_datum = GeneratorDatum {
+                        movability: defn.movability.lower(),
+                        input_output,
+                    };
                     let generator_witness = GeneratorWitnessDatum { inner_types };
 
                     let id = self.generator_ids[&defn.name.str];
                     generator_data.insert(id, Arc::new(generator_datum));
                     generator_witness_data.insert(id, Arc::new(generator_witness));
                 }
-                Item::Foreign(_) => {}
+                Item::Foreign(_)
+                | Item::Module(_)
+                | Item::Use(_)
+                | Item::Crate(_)
+                | Item::Include(_)
+                | Item::Template(_)
+                | Item::Comment(_) => {}
             }
         }
 
@@ -426,7 +674,9 @@ impl ProgramLowerer {
             trait_kinds: self.trait_kinds,
             adt_data,
             adt_reprs,
+            adt_variances,
             fn_def_data,
+            fn_def_variances,
             closure_inputs_and_output,
             closure_closure_kind,
             generator_ids: self.generator_ids,
@@ -435,20 +685,103 @@ impl ProgramLowerer {
             generator_witness_data,
             trait_data,
             well_known_traits,
+            well_known_adts,
             impl_data,
             associated_ty_values,
             associated_ty_data,
+            associated_const_data,
+            associated_const_values,
+            associated_fn_data,
             opaque_ty_ids: self.opaque_ty_ids,
             opaque_ty_kinds: self.opaque_ty_kinds,
             opaque_ty_data,
+            opaque_ty_variances,
             hidden_opaque_types,
             custom_clauses,
             object_safe_traits: self.object_safe_traits,
             foreign_ty_ids: self.foreign_ty_ids,
+            item_spans: self.item_spans,
+            item_crates,
+            modules,
         })
     }
 }
 
+impl ProgramLowerer {
+    /// The environment in which the items of `module` are lowered.
+    fn empty_env<'k>(
+        &'k self,
+        modules: &'k Modules,
+        module: ModuleId,
+        trait_aliases: &'k TraitAliases,
+    ) -> Env<'k> {
+        Env {
+            adt_ids: &self.adt_ids,
+            adt_kinds: &self.adt_kinds,
+            fn_def_ids: &self.fn_def_ids,
+            fn_def_kinds: &self.fn_def_kinds,
+            closure_ids: &self.closure_ids,
+            closure_kinds: &self.closure_kinds,
+            trait_ids: &self.trait_ids,
+            trait_kinds: &self.trait_kinds,
+            opaque_ty_ids: &self.opaque_ty_ids,
+            opaque_ty_kinds: &self.opaque_ty_kinds,
+            generator_ids: &self.generator_ids,
+            generator_kinds: &self.generator_kinds,
+            associated_ty_lookups: &self.associated_ty_lookups,
+            associated_const_lookups: &self.associated_const_lookups,
+            parameter_map: BTreeMap::new(),
+            auto_traits: &self.auto_traits,
+            trait_aliases,
+            foreign_ty_ids: &self.foreign_ty_ids,
+            modules,
+            module,
+            self_ty: None,
+        }
+    }
+
+    /// Lowers the associated fns of a trait or of an inherent impl.
+    fn lower_associated_fns(
+        &self,
+        env: &Env,
+        raw_id: RawId,
+        defns: &[AssocFnDefn],
+        container: AssocFnContainer<'_>,
+    ) -> LowerResult<AssociatedFns> {
+        defns
+            .iter()
+            .map(|defn| {
+                let id = self.associated_fn_ids[&(raw_id, defn.name.str.clone())];
+                let fn_def_datum = (defn, id, container).lower(env)?;
+                let datum = rust_ir::AssociatedFnDatum {
+                    id,
+                    name: defn.name.str.clone(),
+                    container: match container {
+                        AssocFnContainer::Trait(_, trait_id) => {
+                            rust_ir::AssociatedFnContainer::Trait(trait_id)
+                        }
+                        AssocFnContainer::InherentImpl(impl_defn) => {
+                            let self_ty = env.in_binders(impl_defn.all_parameters(), |env| {
+                                impl_defn.self_ty.lower(env)
+                            })?;
+                            rust_ir::AssociatedFnContainer::InherentImpl(self_ty)
+                        }
+                    },
+                    has_receiver: defn.receiver.is_some(),
+                };
+                Ok((Arc::new(fn_def_datum), Arc::new(datum)))
+            })
+            .collect()
+    }
+
+    /// Associated fns cannot declare variances, so all their parameters are
+    /// invariant.
+    fn associated_fn_variances(&self, id: FnDefId<ChalkIr>) -> Vec<chalk_ir::Variance> {
+        let num_parameters = self.fn_def_kinds[&id].binders.len(&ChalkIr);
+        vec![chalk_ir::Variance::Invariant; num_parameters]
+    }
+}
+
 trait LowerTypeKind {
     fn lower_type_kind(&self) -> LowerResult<TypeKind>;
 }
@@ -484,6 +817,11 @@ lower_type_kind!(TraitDefn, Trait, |defn: &TraitDefn| defn
     .iter()
     .map(|k| k.lower())
     .collect::<Vec<_>>());
+lower_type_kind!(TraitAliasDefn, Trait, |defn: &TraitAliasDefn| defn
+    .variable_kinds
+    .iter()
+    .map(|k| k.lower())
+    .collect::<Vec<_>>());
 lower_type_kind!(OpaqueTyDefn, Opaque, |defn: &OpaqueTyDefn| defn
     .variable_kinds
     .iter()
@@ -1,17 +1,20 @@
 use crate::interner::ChalkIr;
-use crate::{tls, Identifier, TypeKind};
+use crate::lowering::{CrateId, Modules};
+use crate::{tls, Identifier, RawId, TypeKind};
 use chalk_ir::could_match::CouldMatch;
 use chalk_ir::debug::Angle;
 use chalk_ir::{
-    debug::SeparatorTraitRef, AdtId, AliasTy, AssocTypeId, Binders, CanonicalVarKinds, ClosureId,
-    FnDefId, ForeignDefId, GeneratorId, GenericArg, Goal, Goals, ImplId, Lifetime, OpaqueTy,
-    OpaqueTyId, ProgramClause, ProgramClauseImplication, ProgramClauses, ProjectionTy,
-    Substitution, TraitId, Ty, TyKind,
+    debug::SeparatorTraitRef, AdtId, AliasTy, AssocConstId, AssocTypeId, Binders,
+    CanonicalVarKinds, ClosureId, ConstProjection, FnDefId, ForeignDefId, GeneratorId, GenericArg,
+    Goal, Goals, ImplId, Lifetime, OpaqueTy, OpaqueTyId, ProgramClause, ProgramClauseImplication,
+    ProgramClauses, ProjectionTy, Substitution, TraitId, Ty, TyKind, Variance,
 };
+use chalk_parse::ast::Span;
 use chalk_solve::rust_ir::{
-    AdtDatum, AdtRepr, AssociatedTyDatum, AssociatedTyValue, AssociatedTyValueId, ClosureKind,
+    AdtDatum, AdtRepr, AssociatedConstDatum, AssociatedConstValue, AssociatedConstValueId,
+    AssociatedFnDatum, AssociatedTyDatum, AssociatedTyValue, AssociatedTyValueId, ClosureKind,
     FnDefDatum, FnDefInputsAndOutputDatum, GeneratorDatum, GeneratorWitnessDatum, ImplDatum,
-    ImplType, OpaqueTyDatum, TraitDatum, WellKnownTrait,
+    ImplType, OpaqueTyDatum, TraitDatum, TraitFlags, WellKnownAdt, WellKnownTrait,
 };
 use chalk_solve::split::Split;
 use chalk_solve::RustIrDatabase;
@@ -57,13 +60,18 @@ pub struct Program {
 
     pub adt_reprs: BTreeMap<AdtId<ChalkIr>, AdtRepr>,
 
+    pub adt_variances: BTreeMap<AdtId<ChalkIr>, Vec<Variance>>,
+
     pub fn_def_data: BTreeMap<FnDefId<ChalkIr>, Arc<FnDefDatum<ChalkIr>>>,
 
+    pub fn_def_variances: BTreeMap<FnDefId<ChalkIr>, Vec<Variance>>,
+
     pub closure_inputs_and_output:
         BTreeMap<ClosureId<ChalkIr>, Binders<FnDefInputsAndOutputDatum<ChalkIr>>>,
 
     // Weird name, but otherwise would overlap with `closure_kinds` above.
-    pub closure_closure_kind: BTreeMap<ClosureId<ChalkIr>, ClosureKind>,
+    /// `None` for the closures whose kind is not known yet.
+    pub closure_closure_kind: BTreeMap<ClosureId<ChalkIr>, Option<ClosureKind>>,
 
     /// For each impl:
     pub impl_data: BTreeMap<ImplId<ChalkIr>, Arc<ImplDatum<ChalkIr>>>,
@@ -72,6 +80,10 @@ pub struct Program {
     pub associated_ty_values:
         BTreeMap<AssociatedTyValueId<ChalkIr>, Arc<AssociatedTyValue<ChalkIr>>>,
 
+    /// For each associated const value `const N: T = XXX` found in an impl:
+    pub associated_const_values:
+        BTreeMap<AssociatedConstValueId<ChalkIr>, Arc<AssociatedConstValue<ChalkIr>>>,
+
     // From opaque type name to item-id. Used during lowering only.
     pub opaque_ty_ids: BTreeMap<Identifier, OpaqueTyId<ChalkIr>>,
 
@@ -81,6 +93,9 @@ pub struct Program {
     /// For each opaque type:
     pub opaque_ty_data: BTreeMap<OpaqueTyId<ChalkIr>, Arc<OpaqueTyDatum<ChalkIr>>>,
 
+    /// For each opaque type:
+    pub opaque_ty_variances: BTreeMap<OpaqueTyId<ChalkIr>, Vec<Variance>>,
+
     /// Stores the hidden types for opaque types
     pub hidden_opaque_types: BTreeMap<OpaqueTyId<ChalkIr>, Arc<Ty<ChalkIr>>>,
 
@@ -90,9 +105,19 @@ pub struct Program {
     /// For each trait lang item
     pub well_known_traits: BTreeMap<WellKnownTrait, TraitId<ChalkIr>>,
 
+    /// For each ADT lang item
+    pub well_known_adts: BTreeMap<WellKnownAdt, AdtId<ChalkIr>>,
+
     /// For each associated ty declaration `type Foo` found in a trait:
     pub associated_ty_data: BTreeMap<AssocTypeId<ChalkIr>, Arc<AssociatedTyDatum<ChalkIr>>>,
 
+    /// For each associated const declaration `const N: T` found in a trait:
+    pub associated_const_data: BTreeMap<AssocConstId<ChalkIr>, Arc<AssociatedConstDatum<ChalkIr>>>,
+
+    /// For each associated fn declared in a trait or an inherent impl. The
+    /// signatures of these fns are in `fn_def_data`.
+    pub associated_fn_data: BTreeMap<FnDefId<ChalkIr>, Arc<AssociatedFnDatum<ChalkIr>>>,
+
     /// For each user-specified clause
     pub custom_clauses: Vec<ProgramClause<ChalkIr>>,
 
@@ -101,6 +126,17 @@ pub struct Program {
 
     /// For each foreign type `extern { type A; }`
     pub foreign_ty_ids: BTreeMap<Identifier, ForeignDefId<ChalkIr>>,
+
+    /// For each item, the span of its name (or of its header, for impls).
+    /// Used to point at items when reporting errors.
+    pub item_spans: BTreeMap<RawId, Span>,
+
+    /// For each item, the crate that declares it.
+    pub item_crates: BTreeMap<RawId, CrateId>,
+
+    /// The modules and crates of the program. Used to resolve the names in
+    /// goals.
+    pub modules: Modules,
 }
 
 impl Program {
@@ -112,6 +148,116 @@ impl Program {
             .map(|(&impl_id, _)| impl_id)
             .collect()
     }
+
+    /// Returns the span of the item with the given id, if it was declared
+    /// in the source program.
+    pub fn item_span(&self, id: RawId) -> Option<Span> {
+        self.item_spans.get(&id).copied()
+    }
+
+    /// Returns true if one of `impls` is an impl of the auto trait
+    /// `auto_trait_id` for `impl_ty`.
+    fn impl_provided_among<'a>(
+        &self,
+        impls: impl IntoIterator<Item = &'a Arc<ImplDatum<ChalkIr>>>,
+        auto_trait_id: TraitId<ChalkIr>,
+        impl_ty: &TyKind<ChalkIr>,
+    ) -> bool {
+        let interner = self.interner();
+
+        // we don't compare actual substitutions as
+        // - given a `struct S<T>`; an implementation for `S<A>` should suppress an auto impl for `S<B>`, and
+        // - an implementation for `[A]` should suppress an auto impl for `[B]`, and
+        // - an implementation for `(A, B, C)` should suppress an auto impl for `(D, E, F)`
+        // this may change later
+        impls.into_iter().any(|impl_datum| {
+            if impl_datum.trait_id() != auto_trait_id {
+                return false;
+            }
+
+            let ty = impl_datum
+                .binders
+                .skip_binders()
+                .trait_ref
+                .self_type_parameter(interner);
+            match (impl_ty, ty.kind(interner)) {
+                (TyKind::Adt(id_a, _), TyKind::Adt(id_b, _)) => id_a == id_b,
+                (TyKind::AssociatedType(id_a, _), TyKind::AssociatedType(id_b, _)) => id_a == id_b,
+                (TyKind::Scalar(scalar_a), TyKind::Scalar(scalar_b)) => scalar_a == scalar_b,
+                (TyKind::Str, TyKind::Str) => true,
+                (TyKind::Tuple(arity_a, _), TyKind::Tuple(arity_b, _)) => arity_a == arity_b,
+                (TyKind::OpaqueType(id_a, _), TyKind::OpaqueType(id_b, _)) => id_a == id_b,
+                (TyKind::Slice(_), TyKind::Slice(_)) => true,
+                (TyKind::FnDef(id_a, _), TyKind::FnDef(id_b, _)) => id_a == id_b,
+                (TyKind::Ref(id_a, _, _), TyKind::Ref(id_b, _, _)) => id_a == id_b,
+                (TyKind::Raw(id_a, _), TyKind::Raw(id_b, _)) => id_a == id_b,
+                (TyKind::Never, TyKind::Never) => true,
+                (TyKind::Array(_, _), TyKind::Array(_, _)) => true,
+                (TyKind::Closure(id_a, _), TyKind::Closure(id_b, _)) => id_a == id_b,
+                (TyKind::Generator(id_a, _), TyKind::Generator(id_b, _)) => id_a == id_b,
+                (TyKind::GeneratorWitness(id_a, _), TyKind::GeneratorWitness(id_b, _)) => {
+                    id_a == id_b
+                }
+                (TyKind::Foreign(id_a), TyKind::Foreign(id_b)) => id_a == id_b,
+                (TyKind::Error, TyKind::Error) => true,
+                (_, _) => false,
+            }
+        })
+    }
+
+    /// Returns the program as seen from `krate`, for checking its
+    /// coherence.
+    pub fn crate_view(&self, krate: CrateId) -> CrateView<'_> {
+        CrateView {
+            program: self,
+            krate,
+        }
+    }
+}
+
+/// A view of a program from one of its crates: the items of that crate are
+/// local, and those of the other crates are upstream. Only the impls
+/// declared in the crate or in the crates it depends on are visible.
+#[derive(Copy, Clone, Debug)]
+pub struct CrateView<'p> {
+    program: &'p Program,
+    krate: CrateId,
+}
+
+impl CrateView<'_> {
+    fn is_local(&self, id: RawId) -> bool {
+        self.program.item_crates[&id] == self.krate
+    }
+
+    fn is_visible(&self, impl_id: ImplId<ChalkIr>) -> bool {
+        let impl_crate = self.program.item_crates[&impl_id.0];
+        impl_crate == self.krate || self.program.modules.depends_on(self.krate, impl_crate)
+    }
+
+    /// Returns the ids for all impls declared in the crate.
+    pub(crate) fn local_impl_ids(&self) -> Vec<ImplId<ChalkIr>> {
+        self.program
+            .impl_data
+            .keys()
+            .copied()
+            .filter(|impl_id| self.is_local(impl_id.0))
+            .collect()
+    }
+
+    /// Returns the ids of the visible impls of `trait_id` that are declared
+    /// in the crate if `local` is true, or in an upstream crate otherwise.
+    fn impls_of_trait(&self, trait_id: TraitId<ChalkIr>, local: bool) -> Vec<ImplId<ChalkIr>> {
+        self.program
+            .impl_data
+            .iter()
+            .filter(|(&impl_id, impl_datum)| {
+                impl_datum.trait_id() == trait_id
+                    && self.is_visible(impl_id)
+                    && self.is_local(impl_id.0) == local
+            })
+            .map(|(&impl_id, _)| impl_id)
+            .collect()
+    }
 }
 
 impl tls::DebugContext for Program {
@@ -157,6 +303,20 @@ impl tls::DebugContext for Program {
         }
     }
 
+    fn debug_assoc_const_id(
+        &self,
+        assoc_const_id: AssocConstId<ChalkIr>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Result<(), fmt::Error> {
+        if let Some(d) = self.associated_const_data.get(&assoc_const_id) {
+            write!(fmt, "({:?}::{})", d.trait_id, d.name)
+        } else {
+            fmt.debug_struct("InvalidAssocConstId")
+                .field("index", &assoc_const_id.0)
+                .finish()
+        }
+    }
+
     fn debug_opaque_ty_id(
         &self,
         opaque_ty_id: OpaqueTyId<ChalkIr>,
@@ -213,6 +373,24 @@ impl tls::DebugContext for Program {
         )
     }
 
+    fn debug_const_projection(
+        &self,
+        const_projection: &ConstProjection<ChalkIr>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Result<(), fmt::Error> {
+        let associated_const_data =
+            &self.associated_const_data[&const_projection.associated_const_id];
+        let trait_params = const_projection.substitution.as_slice(self.interner());
+        write!(
+            fmt,
+            "<{:?} as {:?}{:?}>::{}",
+            &trait_params[0],
+            associated_const_data.trait_id,
+            Angle(&trait_params[1..]),
+            associated_const_data.name,
+        )
+    }
+
     fn debug_opaque_ty(
         &self,
         opaque_ty: &OpaqueTy<ChalkIr>,
@@ -353,6 +531,13 @@ impl RustIrDatabase<ChalkIr> for Program {
         self.associated_ty_data[&ty].clone()
     }
 
+    fn associated_const_data(
+        &self,
+        id: AssocConstId<ChalkIr>,
+    ) -> Arc<AssociatedConstDatum<ChalkIr>> {
+        self.associated_const_data[&id].clone()
+    }
+
     fn trait_datum(&self, id: TraitId<ChalkIr>) -> Arc<TraitDatum<ChalkIr>> {
         self.trait_data[&id].clone()
     }
@@ -368,6 +553,13 @@ impl RustIrDatabase<ChalkIr> for Program {
         self.associated_ty_values[&id].clone()
     }
 
+    fn associated_const_value(
+        &self,
+        id: AssociatedConstValueId<ChalkIr>,
+    ) -> Arc<AssociatedConstValue<ChalkIr>> {
+        self.associated_const_values[&id].clone()
+    }
+
     fn opaque_ty_data(&self, id: OpaqueTyId<ChalkIr>) -> Arc<OpaqueTyDatum<ChalkIr>> {
         self.opaque_ty_data[&id].clone()
     }
@@ -395,10 +587,37 @@ impl RustIrDatabase<ChalkIr> for Program {
         self.adt_reprs[&id]
     }
 
+    fn adt_variance(&self, id: AdtId<ChalkIr>) -> Vec<Variance> {
+        self.adt_variances[&id].clone()
+    }
+
     fn fn_def_datum(&self, id: FnDefId<ChalkIr>) -> Arc<FnDefDatum<ChalkIr>> {
         self.fn_def_data[&id].clone()
     }
 
+    fn fn_def_variance(&self, id: FnDefId<ChalkIr>) -> Vec<Variance> {
+        self.fn_def_variances[&id].clone()
+    }
+
+    fn associated_fns(&self, name: &Identifier) -> Vec<Arc<AssociatedFnDatum<ChalkIr>>> {
+        self.associated_fn_data
+            .values()
+            .filter(|datum| datum.name == *name)
+            .cloned()
+            .collect()
+    }
+
+    fn associated_fn_data(
+        &self,
+        fn_def_id: FnDefId<ChalkIr>,
+    ) -> Option<Arc<AssociatedFnDatum<ChalkIr>>> {
+        self.associated_fn_data.get(&fn_def_id).cloned()
+    }
+
+    fn opaque_ty_variance(&self, id: OpaqueTyId<ChalkIr>) -> Vec<Variance> {
+        self.opaque_ty_variances[&id].clone()
+    }
+
     fn impls_for_trait(
         &self,
         trait_id: TraitId<ChalkIr>,
@@ -433,57 +652,36 @@ impl RustIrDatabase<ChalkIr> for Program {
             .collect()
     }
 
+    /// The impls of the declared crates, which the root crate depends on.
+    fn upstream_impls_to_coherence_check(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+    ) -> Vec<ImplId<ChalkIr>> {
+        self.impl_data
+            .iter()
+            .filter(|(impl_id, impl_datum)| {
+                impl_datum.trait_id() == trait_id && self.item_crates[&impl_id.0] != CrateId::ROOT
+            })
+            .map(|(&impl_id, _)| impl_id)
+            .collect()
+    }
+
     fn impl_provided_for(
         &self,
         auto_trait_id: TraitId<ChalkIr>,
         impl_ty: &TyKind<ChalkIr>,
     ) -> bool {
-        let interner = self.interner();
-
-        // we don't compare actual substitutions as
-        // - given a `struct S<T>`; an implementation for `S<A>` should suppress an auto impl for `S<B>`, and
-        // - an implementation for `[A]` should suppress an auto impl for `[B]`, and
-        // - an implementation for `(A, B, C)` should suppress an auto impl for `(D, E, F)`
-        // this may change later
-        self.impl_data.values().any(|impl_datum| {
-            if impl_datum.trait_id() != auto_trait_id {
-                return false;
-            }
-
-            let ty = impl_datum
-                .binders
-                .skip_binders()
-                .trait_ref
-                .self_type_parameter(interner);
-            match (impl_ty, ty.kind(interner)) {
-                (TyKind::Adt(id_a, _), TyKind::Adt(id_b, _)) => id_a == id_b,
-                (TyKind::AssociatedType(id_a, _), TyKind::AssociatedType(id_b, _)) => id_a == id_b,
-                (TyKind::Scalar(scalar_a), TyKind::Scalar(scalar_b)) => scalar_a == scalar_b,
-                (TyKind::Str, TyKind::Str) => true,
-                (TyKind::Tuple(arity_a, _), TyKind::Tuple(arity_b, _)) => arity_a == arity_b,
-                (TyKind::OpaqueType(id_a, _), TyKind::OpaqueType(id_b, _)) => id_a == id_b,
-                (TyKind::Slice(_), TyKind::Slice(_)) => true,
-                (TyKind::FnDef(id_a, _), TyKind::FnDef(id_b, _)) => id_a == id_b,
-                (TyKind::Ref(id_a, _, _), TyKind::Ref(id_b, _, _)) => id_a == id_b,
-                (TyKind::Raw(id_a, _), TyKind::Raw(id_b, _)) => id_a == id_b,
-                (TyKind::Never, TyKind::Never) => true,
-                (TyKind::Array(_, _), TyKind::Array(_, _)) => true,
-                (TyKind::Closure(id_a, _), TyKind::Closure(id_b, _)) => id_a == id_b,
-                (TyKind::Generator(id_a, _), TyKind::Generator(id_b, _)) => id_a == id_b,
-                (TyKind::GeneratorWitness(id_a, _), TyKind::GeneratorWitness(id_b, _)) => {
-                    id_a == id_b
-                }
-                (TyKind::Foreign(id_a), TyKind::Foreign(id_b)) => id_a == id_b,
-                (TyKind::Error, TyKind::Error) => true,
-                (_, _) => false,
-            }
-        })
+        self.impl_provided_among(self.impl_data.values(), auto_trait_id, impl_ty)
     }
 
     fn well_known_trait_id(&self, well_known_trait: WellKnownTrait) -> Option<TraitId<ChalkIr>> {
         self.well_known_traits.get(&well_known_trait).copied()
     }
 
+    fn well_known_adt_id(&self, well_known_adt: WellKnownAdt) -> Option<AdtId<ChalkIr>> {
+        self.well_known_adts.get(&well_known_adt).copied()
+    }
+
     fn program_clauses_for_env(
         &self,
         environment: &chalk_ir::Environment<ChalkIr>,
@@ -516,7 +714,7 @@ impl RustIrDatabase<ChalkIr> for Program {
         &self,
         closure_id: ClosureId<ChalkIr>,
         _substs: &Substitution<ChalkIr>,
-    ) -> ClosureKind {
+    ) -> Option<ClosureKind> {
         self.closure_closure_kind[&closure_id]
     }
 
@@ -549,4 +747,227 @@ impl RustIrDatabase<ChalkIr> for Program {
             .name
             .to_string()
     }
+
+    fn assoc_const_name(&self, assoc_const_id: AssocConstId<ChalkIr>) -> String {
+        self.associated_const_data
+            .get(&assoc_const_id)
+            .unwrap()
+            .name
+            .to_string()
+    }
+}
+
+impl RustIrDatabase<ChalkIr> for CrateView<'_> {
+    fn custom_clauses(&self) -> Vec<ProgramClause<ChalkIr>> {
+        self.program.custom_clauses()
+    }
+
+    fn associated_ty_data(&self, ty: AssocTypeId<ChalkIr>) -> Arc<AssociatedTyDatum<ChalkIr>> {
+        self.program.associated_ty_data(ty)
+    }
+
+    fn associated_const_data(
+        &self,
+        id: AssocConstId<ChalkIr>,
+    ) -> Arc<AssociatedConstDatum<ChalkIr>> {
+        self.program.associated_const_data(id)
+    }
+
+    fn trait_datum(&self, id: TraitId<ChalkIr>) -> Arc<TraitDatum<ChalkIr>> {
+        let datum = self.program.trait_datum(id);
+        Arc::new(TraitDatum {
+            id: datum.id,
+            binders: datum.binders.clone(),
+            flags: TraitFlags {
+                upstream: !self.is_local(id.0),
+                ..datum.flags.clone()
+            },
+            associated_ty_ids: datum.associated_ty_ids.clone(),
+            associated_const_ids: datum.associated_const_ids.clone(),
+            associated_fn_ids: datum.associated_fn_ids.clone(),
+            well_known: datum.well_known,
+        })
+    }
+
+    fn impl_datum(&self, id: ImplId<ChalkIr>) -> Arc<ImplDatum<ChalkIr>> {
+        let mut impl_datum = ImplDatum::clone(&self.program.impl_datum(id));
+        impl_datum.impl_type = if self.is_local(id.0) {
+            ImplType::Local
+        } else {
+            ImplType::External
+        };
+        Arc::new(impl_datum)
+    }
+
+    fn associated_ty_value(
+        &self,
+        id: AssociatedTyValueId<ChalkIr>,
+    ) -> Arc<AssociatedTyValue<ChalkIr>> {
+        self.program.associated_ty_value(id)
+    }
+
+    fn associated_const_value(
+        &self,
+        id: AssociatedConstValueId<ChalkIr>,
+    ) -> Arc<AssociatedConstValue<ChalkIr>> {
+        self.program.associated_const_value(id)
+    }
+
+    fn opaque_ty_data(&self, id: OpaqueTyId<ChalkIr>) -> Arc<OpaqueTyDatum<ChalkIr>> {
+        self.program.opaque_ty_data(id)
+    }
+
+    fn hidden_opaque_type(&self, id: OpaqueTyId<ChalkIr>) -> Ty<ChalkIr> {
+        self.program.hidden_opaque_type(id)
+    }
+
+    fn adt_datum(&self, id: AdtId<ChalkIr>) -> Arc<AdtDatum<ChalkIr>> {
+        let mut adt_datum = AdtDatum::clone(&self.program.adt_datum(id));
+        adt_datum.flags.upstream = !self.is_local(id.0);
+        Arc::new(adt_datum)
+    }
+
+    fn generator_datum(&self, id: GeneratorId<ChalkIr>) -> Arc<GeneratorDatum<ChalkIr>> {
+        self.program.generator_datum(id)
+    }
+
+    fn generator_witness_datum(
+        &self,
+        id: GeneratorId<ChalkIr>,
+    ) -> Arc<GeneratorWitnessDatum<ChalkIr>> {
+        self.program.generator_witness_datum(id)
+    }
+
+    fn adt_repr(&self, id: AdtId<ChalkIr>) -> AdtRepr {
+        self.program.adt_repr(id)
+    }
+
+    fn adt_variance(&self, id: AdtId<ChalkIr>) -> Vec<Variance> {
+        self.program.adt_variance(id)
+    }
+
+    fn fn_def_datum(&self, id: FnDefId<ChalkIr>) -> Arc<FnDefDatum<ChalkIr>> {
+        self.program.fn_def_datum(id)
+    }
+
+    fn fn_def_variance(&self, id: FnDefId<ChalkIr>) -> Vec<Variance> {
+        self.program.fn_def_variance(id)
+    }
+
+    fn associated_fns(&self, name: &Identifier) -> Vec<Arc<AssociatedFnDatum<ChalkIr>>> {
+        self.program.associated_fns(name)
+    }
+
+    fn associated_fn_data(
+        &self,
+        fn_def_id: FnDefId<ChalkIr>,
+    ) -> Option<Arc<AssociatedFnDatum<ChalkIr>>> {
+        self.program.associated_fn_data(fn_def_id)
+    }
+
+    fn opaque_ty_variance(&self, id: OpaqueTyId<ChalkIr>) -> Vec<Variance> {
+        self.program.opaque_ty_variance(id)
+    }
+
+    fn impls_for_trait(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+        parameters: &[GenericArg<ChalkIr>],
+        binders: &CanonicalVarKinds<ChalkIr>,
+    ) -> Vec<ImplId<ChalkIr>> {
+        self.program
+            .impls_for_trait(trait_id, parameters, binders)
+            .into_iter()
+            .filter(|&impl_id| self.is_visible(impl_id))
+            .collect()
+    }
+
+    fn local_impls_to_coherence_check(&self, trait_id: TraitId<ChalkIr>) -> Vec<ImplId<ChalkIr>> {
+        self.impls_of_trait(trait_id, true)
+    }
+
+    fn upstream_impls_to_coherence_check(
+        &self,
+        trait_id: TraitId<ChalkIr>,
+    ) -> Vec<ImplId<ChalkIr>> {
+        self.impls_of_trait(trait_id, false)
+    }
+
+    fn impl_provided_for(
+        &self,
+        auto_trait_id: TraitId<ChalkIr>,
+        impl_ty: &TyKind<ChalkIr>,
+    ) -> bool {
+        let visible_impls = self
+            .program
+            .impl_data
+            .iter()
+            .filter(|(&impl_id, _)| self.is_visible(impl_id))
+            .map(|(_, impl_datum)| impl_datum);
+        self.program
+            .impl_provided_among(visible_impls, auto_trait_id, impl_ty)
+    }
+
+    fn well_known_trait_id(&self, well_known_trait: WellKnownTrait) -> Option<TraitId<ChalkIr>> {
+        self.program.well_known_trait_id(well_known_trait)
+    }
+
+    fn well_known_adt_id(&self, well_known_adt: WellKnownAdt) -> Option<AdtId<ChalkIr>> {
+        self.program.well_known_adt_id(well_known_adt)
+    }
+
+    fn program_clauses_for_env(
+        &self,
+        environment: &chalk_ir::Environment<ChalkIr>,
+    ) -> ProgramClauses<ChalkIr> {
+        chalk_solve::program_clauses_for_env(self, environment)
+    }
+
+    fn interner(&self) -> &ChalkIr {
+        &ChalkIr
+    }
+
+    fn is_object_safe(&self, trait_id: TraitId<ChalkIr>) -> bool {
+        self.program.is_object_safe(trait_id)
+    }
+
+    fn closure_inputs_and_output(
+        &self,
+        closure_id: ClosureId<ChalkIr>,
+        substs: &Substitution<ChalkIr>,
+    ) -> Binders<FnDefInputsAndOutputDatum<ChalkIr>> {
+        self.program.closure_inputs_and_output(closure_id, substs)
+    }
+
+    fn closure_kind(
+        &self,
+        closure_id: ClosureId<ChalkIr>,
+        substs: &Substitution<ChalkIr>,
+    ) -> Option<ClosureKind> {
+        self.program.closure_kind(closure_id, substs)
+    }
+
+    fn closure_upvars(
+        &self,
+        closure_id: ClosureId<ChalkIr>,
+        substs: &Substitution<ChalkIr>,
+    ) -> Binders<Ty<ChalkIr>> {
+        self.program.closure_upvars(closure_id, substs)
+    }
+
+    fn closure_fn_substitution(
+        &self,
+        closure_id: ClosureId<ChalkIr>,
+        substs: &Substitution<ChalkIr>,
+    ) -> Substitution<ChalkIr> {
+        self.program.closure_fn_substitution(closure_id, substs)
+    }
+
+    fn assoc_type_name(&self, assoc_type_id: AssocTypeId<ChalkIr>) -> String {
+        self.program.assoc_type_name(assoc_type_id)
+    }
+
+    fn assoc_const_name(&self, assoc_const_id: AssocConstId<ChalkIr>) -> String {
+        self.program.assoc_const_name(assoc_const_id)
+    }
 }
@@ -2,13 +2,14 @@
 // hello world https://github.com/salsa-rs/salsa/blob/master/examples/hello_world/main.rs
 
 use crate::error::ChalkError;
+use crate::files::SourceFiles;
 use crate::interner::ChalkIr;
 use crate::lowering::Lower;
 use crate::program::Program;
 use crate::program_environment::ProgramEnvironment;
 use crate::tls;
 use crate::SolverChoice;
-use chalk_ir::{Substitution, TraitId};
+use chalk_ir::{ImplId, Substitution, TraitId};
 use chalk_solve::clauses::builder::ClauseBuilder;
 use chalk_solve::clauses::program_clauses::ToProgramClauses;
 use chalk_solve::coherence::orphan;
@@ -28,8 +29,9 @@ use std::sync::Mutex;
 pub trait LoweringDatabase:
     RustIrDatabase<ChalkIr> + Database + Upcast<dyn RustIrDatabase<ChalkIr>>
 {
+    /// The source files of the program.
     #[salsa::input]
-    fn program_text(&self) -> Arc<String>;
+    fn program_files(&self) -> Arc<SourceFiles>;
 
     #[salsa::input]
     fn solver_choice(&self) -> SolverChoice;
@@ -123,18 +125,52 @@ impl<T> Clone for ArcEq<T> {
 }
 
 fn program_ir(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError> {
-    let text = db.program_text();
-    Ok(Arc::new(chalk_parse::parse_program(&text)?.lower()?))
+    // The items that could be parsed are lowered even if there are syntax
+    // errors, so that the errors found while lowering them are reported too.
+    let (program, mut errors) = db.program_files().parse();
+    match program.lower() {
+        Ok(program) if errors.is_empty() => return Ok(Arc::new(program)),
+        Ok(_) => {}
+        Err(error) => errors.push(error.into()),
+    }
+    Err(ChalkError::combine(errors).unwrap())
 }
 
 fn orphan_check(db: &dyn LoweringDatabase) -> Result<(), ChalkError> {
     let program = db.program_ir()?;
+    let solver_choice = db.solver_choice();
+
+    check_orphans(
+        db.upcast(),
+        &program,
+        program.local_impl_ids(),
+        solver_choice,
+    )?;
+
+    // Each declared crate is checked as the local crate, seeing only the
+    // impls of the crates it depends on.
+    for krate in program.modules.crates() {
+        let view = program.crate_view(krate);
+        check_orphans(&view, &program, view.local_impl_ids(), solver_choice)
+            .map_err(|e| e.with_note(program.modules.crate_note(krate)))?;
+    }
+    Ok(())
+}
 
-    tls::set_current_program(&program, || -> Result<(), ChalkError> {
-        let local_impls = program.local_impl_ids();
+/// Performs the orphan check on `local_impls`, using `db` to solve goals.
+fn check_orphans(
+    db: &dyn RustIrDatabase<ChalkIr>,
+    program: &Arc<Program>,
+    local_impls: Vec<ImplId<ChalkIr>>,
+    solver_choice: SolverChoice,
+) -> Result<(), ChalkError> {
+    tls::set_current_program(program, || -> Result<(), ChalkError> {
         for impl_id in local_impls {
-            let mut solver = db.solver_choice().into_solver();
-            orphan::perform_orphan_check::<ChalkIr>(db.upcast(), &mut *solver, impl_id)?;
+            let mut solver = solver_choice.into_solver();
+            orphan::perform_orphan_check::<ChalkIr>(db, &mut *solver, impl_id).map_err(|e| {
+                ChalkError::from_coherence_error(e, program)
+                    .with_primary_span(program.item_span(impl_id.0))
+            })?;
         }
         Ok(())
     })
@@ -145,23 +181,42 @@ fn coherence(
 ) -> Result<BTreeMap<TraitId<ChalkIr>, Arc<SpecializationPriorities<ChalkIr>>>, ChalkError> {
     let program = db.program_ir()?;
     let solver_choice = db.solver_choice();
-    let priorities_map = tls::set_current_program(&program, || -> Result<_, ChalkError> {
+    let priorities_map = check_overlap(db.upcast(), &program, solver_choice);
+    let () = db.orphan_check()?;
+    let priorities_map = priorities_map?;
+
+    // Each declared crate is checked as the local crate, seeing only the
+    // impls of the crates it depends on.
+    for krate in program.modules.crates() {
+        check_overlap(&program.crate_view(krate), &program, solver_choice)
+            .map_err(|e| e.with_note(program.modules.crate_note(krate)))?;
+    }
+
+    Ok(priorities_map)
+}
+
+/// Checks that the local impls of `db` do not overlap, and computes their
+/// specialization priorities.
+fn check_overlap(
+    db: &dyn RustIrDatabase<ChalkIr>,
+    program: &Arc<Program>,
+    solver_choice: SolverChoice,
+) -> Result<BTreeMap<TraitId<ChalkIr>, Arc<SpecializationPriorities<ChalkIr>>>, ChalkError> {
+    tls::set_current_program(program, || -> Result<_, ChalkError> {
         let solver_builder = || solver_choice.into_solver();
-        let priorities_map: Result<BTreeMap<_, _>, ChalkError> = program
+        program
             .trait_data
             .keys()
             .map(|&trait_id| {
                 let solver: CoherenceSolver<ChalkIr> =
-                    CoherenceSolver::new(db.upcast(), &solver_builder, trait_id);
-                let priorities = solver.specialization_priorities()?;
+                    CoherenceSolver::new(db, &solver_builder, trait_id);
+                let priorities = solver
+                    .specialization_priorities()
+                    .map_err(|e| ChalkError::from_coherence_error(e, program))?;
                 Ok((trait_id, priorities))
             })
-            .collect();
-        let priorities_map = priorities_map?;
-        Ok(priorities_map)
-    });
-    let () = db.orphan_check()?;
-    priorities_map
+            .collect()
+    })
 }
 
 fn checked_program(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError> {
@@ -173,16 +228,22 @@ fn checked_program(db: &dyn LoweringDatabase) -> Result<Arc<Program>, ChalkError
     let () = tls::set_current_program(&program, || -> Result<(), ChalkError> {
         let solver_builder = || solver_choice.into_solver();
         let solver: wf::WfSolver<ChalkIr> = wf::WfSolver::new(db.upcast(), &solver_builder);
+        let wf_error = |e| ChalkError::from_wf_error(e, &program);
+
         for &id in program.adt_data.keys() {
-            solver.verify_adt_decl(id)?;
+            solver.verify_adt_decl(id).map_err(wf_error)?;
         }
 
         for &opaque_ty_id in program.opaque_ty_data.keys() {
-            solver.verify_opaque_ty_decl(opaque_ty_id)?;
+            solver
+                .verify_opaque_ty_decl(opaque_ty_id)
+                .map_err(wf_error)?;
         }
 
         for &impl_id in program.impl_data.keys() {
-            solver.verify_trait_impl(impl_id)?;
+            solver
+                .verify_trait_impl(impl_id)
+                .map_err(|e| wf_error(e).with_primary_span(program.item_span(impl_id.0)))?;
         }
 
         Ok(())
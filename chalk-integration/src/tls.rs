@@ -1,8 +1,9 @@
 use crate::interner::ChalkIr;
 use chalk_ir::{
-    debug::SeparatorTraitRef, AdtId, AliasTy, AssocTypeId, CanonicalVarKinds, FnDefId, GenericArg,
-    Goal, Goals, Lifetime, OpaqueTy, OpaqueTyId, ProgramClause, ProgramClauseImplication,
-    ProgramClauses, ProjectionTy, QuantifiedWhereClauses, Substitution, TraitId, Ty, VariableKinds,
+    debug::SeparatorTraitRef, AdtId, AliasTy, AssocConstId, AssocTypeId, CanonicalVarKinds,
+    ConstProjection, FnDefId, GenericArg, Goal, Goals, Lifetime, OpaqueTy, OpaqueTyId,
+    ProgramClause, ProgramClauseImplication, ProgramClauses, ProjectionTy, QuantifiedWhereClauses,
+    Substitution, TraitId, Ty, VariableKinds,
 };
 use std::cell::RefCell;
 use std::fmt;
@@ -31,6 +32,12 @@ pub trait DebugContext {
         fmt: &mut fmt::Formatter<'_>,
     ) -> Result<(), fmt::Error>;
 
+    fn debug_assoc_const_id(
+        &self,
+        id: AssocConstId<ChalkIr>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Result<(), fmt::Error>;
+
     fn debug_opaque_ty_id(
         &self,
         id: OpaqueTyId<ChalkIr>,
@@ -61,6 +68,12 @@ pub trait DebugContext {
         fmt: &mut fmt::Formatter<'_>,
     ) -> Result<(), fmt::Error>;
 
+    fn debug_const_projection(
+        &self,
+        proj: &ConstProjection<ChalkIr>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Result<(), fmt::Error>;
+
     fn debug_ty(&self, ty: &Ty<ChalkIr>, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error>;
 
     fn debug_lifetime(
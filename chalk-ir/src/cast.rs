@@ -136,6 +136,12 @@ impl<I: Interner> CastTo<DomainGoal<I>> for Normalize<I> {
     }
 }
 
+impl<I: Interner> CastTo<DomainGoal<I>> for NormalizeConst<I> {
+    fn cast_to(self, _interner: &I) -> DomainGoal<I> {
+        DomainGoal::NormalizeConst(self)
+    }
+}
+
 impl<I: Interner> CastTo<DomainGoal<I>> for WellFormed<I> {
     fn cast_to(self, _interner: &I) -> DomainGoal<I> {
         DomainGoal::WellFormed(self)
@@ -154,6 +160,12 @@ impl<I: Interner> CastTo<Goal<I>> for EqGoal<I> {
     }
 }
 
+impl<I: Interner> CastTo<Goal<I>> for SubtypeGoal<I> {
+    fn cast_to(self, interner: &I) -> Goal<I> {
+        GoalData::SubtypeGoal(self).intern(interner)
+    }
+}
+
 impl<I: Interner, T: HasInterner<Interner = I> + CastTo<Goal<I>>> CastTo<Goal<I>> for Binders<T> {
     fn cast_to(self, interner: &I) -> Goal<I> {
         GoalData::Quantified(
@@ -23,6 +23,13 @@ impl<I: Interner> Debug for AssocTypeId<I> {
     }
 }
 
+impl<I: Interner> Debug for AssocConstId<I> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        I::debug_assoc_const_id(*self, fmt)
+            .unwrap_or_else(|| write!(fmt, "AssocConstId({:?})", self.0))
+    }
+}
+
 impl<I: Interner> Debug for FnDefId<I> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
         I::debug_fn_def_id(*self, fmt).unwrap_or_else(|| write!(fmt, "FnDefId({:?})", self.0))
@@ -144,6 +151,27 @@ impl<I: Interner> Debug for ProjectionTy<I> {
     }
 }
 
+impl<I: Interner> Debug for ConstProjection<I> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        I::debug_const_projection(self, fmt).unwrap_or_else(|| {
+            write!(
+                fmt,
+                "({:?}){:?}",
+                self.associated_const_id, self.substitution
+            )
+        })
+    }
+}
+
+impl<I: Interner> Debug for ConstExpr<I> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ConstExpr::Unary(op, a) => write!(fmt, "{}{:?}", op.as_str(), a),
+            ConstExpr::Binary(op, a, b) => write!(fmt, "{:?} {} {:?}", a, op.as_str(), b),
+        }
+    }
+}
+
 impl<I: Interner> Debug for OpaqueTy<I> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         I::debug_opaque_ty(self, fmt).unwrap_or_else(|| {
@@ -344,6 +372,8 @@ impl<I: Interner> Debug for ConstData<I> {
             ConstValue::InferenceVar(var) => write!(fmt, "{:?}", var),
             ConstValue::Placeholder(index) => write!(fmt, "{:?}", index),
             ConstValue::Concrete(evaluated) => write!(fmt, "{:?}", evaluated),
+            ConstValue::Projection(projection) => write!(fmt, "{:?}", projection),
+            ConstValue::Expr(expr) => write!(fmt, "{{ {:?} }}", expr),
         }
     }
 }
@@ -362,6 +392,7 @@ impl<I: Interner> Debug for GoalData<I> {
             GoalData::All(ref goals) => write!(fmt, "all{:?}", goals),
             GoalData::Not(ref g) => write!(fmt, "not {{ {:?} }}", g),
             GoalData::EqGoal(ref wc) => write!(fmt, "{:?}", wc),
+            GoalData::SubtypeGoal(ref wc) => write!(fmt, "{:?}", wc),
             GoalData::DomainGoal(ref wc) => write!(fmt, "{:?}", wc),
             GoalData::CannotProve => write!(fmt, r"¯\_(ツ)_/¯"),
         }
@@ -750,6 +781,12 @@ impl<I: Interner> Debug for Normalize<I> {
     }
 }
 
+impl<I: Interner> Debug for NormalizeConst<I> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(fmt, "NormalizeConst({:?} -> {:?})", self.alias, self.value)
+    }
+}
+
 impl<I: Interner> Debug for AliasEq<I> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         write!(fmt, "AliasEq({:?} = {:?})", self.alias, self.ty)
@@ -792,6 +829,7 @@ impl<I: Interner> Debug for DomainGoal<I> {
             DomainGoal::WellFormed(n) => write!(fmt, "{:?}", n),
             DomainGoal::FromEnv(n) => write!(fmt, "{:?}", n),
             DomainGoal::Normalize(n) => write!(fmt, "{:?}", n),
+            DomainGoal::NormalizeConst(n) => write!(fmt, "{:?}", n),
             DomainGoal::IsLocal(n) => write!(fmt, "IsLocal({:?})", n),
             DomainGoal::IsUpstream(n) => write!(fmt, "IsUpstream({:?})", n),
             DomainGoal::IsFullyVisible(n) => write!(fmt, "IsFullyVisible({:?})", n),
@@ -802,6 +840,7 @@ impl<I: Interner> Debug for DomainGoal<I> {
             DomainGoal::DownstreamType(n) => write!(fmt, "DownstreamType({:?})", n),
             DomainGoal::Reveal => write!(fmt, "Reveal"),
             DomainGoal::ObjectSafe(n) => write!(fmt, "ObjectSafe({:?})", n),
+            DomainGoal::ClosureKind(ty, kind) => write!(fmt, "ClosureKind({:?}, {:?})", ty, kind),
         }
     }
 }
@@ -812,6 +851,12 @@ impl<I: Interner> Debug for EqGoal<I> {
     }
 }
 
+impl<I: Interner> Debug for SubtypeGoal<I> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(fmt, "Subtype({:?}, {:?})", self.a, self.b)
+    }
+}
+
 impl<T: HasInterner + Debug> Debug for Binders<T> {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         let Binders {
@@ -617,6 +617,16 @@ where
                 }),
             }
             .intern(folder.target_interner())),
+            ConstValue::Projection(projection) => Ok(ConstData {
+                ty: fold_ty()?,
+                value: ConstValue::Projection(projection.fold_with(folder, outer_binder)?),
+            }
+            .intern(folder.target_interner())),
+            ConstValue::Expr(expr) => Ok(ConstData {
+                ty: fold_ty()?,
+                value: ConstValue::Expr(expr.fold_with(folder, outer_binder)?),
+            }
+            .intern(folder.target_interner())),
         }
     }
 }
@@ -267,6 +267,9 @@ copy_fold!(FloatTy);
 copy_fold!(Scalar);
 copy_fold!(ClausePriority);
 copy_fold!(Mutability);
+copy_fold!(ClosureKind);
+copy_fold!(BinOp);
+copy_fold!(UnOp);
 copy_fold!(Safety);
 
 #[doc(hidden)]
@@ -300,6 +303,7 @@ id_fold!(ImplId);
 id_fold!(AdtId, transfer_adt_id);
 id_fold!(TraitId);
 id_fold!(AssocTypeId);
+id_fold!(AssocConstId);
 id_fold!(OpaqueTyId);
 id_fold!(FnDefId);
 id_fold!(ClosureId);
@@ -1,10 +1,13 @@
 //! Encapsulates the concrete representation of core types such as types and goals.
 use crate::AdtId;
 use crate::AliasTy;
+use crate::AssocConstId;
 use crate::AssocTypeId;
 use crate::CanonicalVarKind;
 use crate::CanonicalVarKinds;
 use crate::ClosureId;
+use crate::ConstExpr;
+use crate::ConstProjection;
 use crate::Constraint;
 use crate::Constraints;
 use crate::FnDefId;
@@ -216,6 +219,16 @@ pub trait Interner: Debug + Copy + Eq + Ord + Hash {
         None
     }
 
+    /// Prints the debug representation of an associated const id.
+    /// Returns `None` to fallback to the default debug output.
+    #[allow(unused_variables)]
+    fn debug_assoc_const_id(
+        const_id: AssocConstId<Self>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Option<fmt::Result> {
+        None
+    }
+
     /// Prints the debug representation of an opaque type.
     /// Returns `None` to fallback to the default debug output.
     #[allow(unused_variables)]
@@ -288,6 +301,16 @@ pub trait Interner: Debug + Copy + Eq + Ord + Hash {
         None
     }
 
+    /// Prints the debug representation of a ConstProjection.
+    /// Returns `None` to fallback to the default debug output.
+    #[allow(unused_variables)]
+    fn debug_const_projection(
+        const_projection: &ConstProjection<Self>,
+        fmt: &mut fmt::Formatter<'_>,
+    ) -> Option<fmt::Result> {
+        None
+    }
+
     /// Prints the debug representation of an OpaqueTy.
     /// Returns `None` to fallback to the default debug output.
     #[allow(unused_variables)]
@@ -480,6 +503,21 @@ pub trait Interner: Debug + Copy + Eq + Ord + Hash {
         c2: &Self::InternedConcreteConst,
     ) -> bool;
 
+    /// Evaluates the const expression `expr`, of type `ty`. The operands
+    /// that could be evaluated are concrete; returns `None` if some are not,
+    /// or if the expression cannot be evaluated, e.g. because it overflows.
+    ///
+    /// By default, expressions are never evaluated, and are only compared
+    /// structurally.
+    fn evaluate_const_expr(
+        &self,
+        ty: &Self::InternedType,
+        expr: &ConstExpr<Self>,
+    ) -> Option<Self::InternedConcreteConst> {
+        let _ = (ty, expr);
+        None
+    }
+
     /// Create an "interned" parameter from `data`. This is not
     /// normally invoked directly; instead, you invoke
     /// `GenericArgData::intern` (which will ultimately call this
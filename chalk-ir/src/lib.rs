@@ -211,6 +211,105 @@ pub enum Mutability {
     Not,
 }
 
+/// Indicates the "most permissive" Fn-like trait that the closure implements.
+/// If the closure kind for a closure is FnMut, for example, then the closure
+/// implements FnMut and FnOnce.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum ClosureKind {
+    /// The closure implements `Fn`, `FnMut` and `FnOnce`.
+    Fn,
+    /// The closure implements `FnMut` and `FnOnce`.
+    FnMut,
+    /// The closure implements `FnOnce` only.
+    FnOnce,
+}
+
+/// A binary operator of a const expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BinOp {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Sub,
+    /// `a * b`
+    Mul,
+    /// `a / b`
+    Div,
+    /// `a % b`
+    Rem,
+}
+
+impl BinOp {
+    /// The operator, as written in Rust.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+        }
+    }
+}
+
+/// A unary operator of a const expression.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UnOp {
+    /// `-a`
+    Neg,
+    /// `!a`
+    Not,
+}
+
+impl UnOp {
+    /// The operator, as written in Rust.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnOp::Neg => "-",
+            UnOp::Not => "!",
+        }
+    }
+}
+
+/// The variance of a type or lifetime parameter: how subtyping between two
+/// values of the parameter relates to subtyping between the types that
+/// contain them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Variance {
+    /// `a <: b` implies `F<a> <: F<b>`, e.g. `T` in `&'a T`.
+    Covariant,
+    /// `F<a> <: F<b>` only if `a == b`, e.g. `T` in `&'a mut T`.
+    Invariant,
+    /// `a <: b` implies `F<b> <: F<a>`, e.g. `T` in `fn(T)`.
+    Contravariant,
+}
+
+impl Variance {
+    /// Combines the variance of a position with the variance of a parameter
+    /// within it: given `self`, the variance with which some `F<X>` is
+    /// related, and `other`, the variance of `F` in `X`, returns the
+    /// variance with which `X` must be related. For example, the `T` in
+    /// `fn(fn(T))` is related covariantly, since it appears in a
+    /// contravariant position within a contravariant position.
+    pub fn xform(self, other: Variance) -> Variance {
+        match (self, other) {
+            (Variance::Invariant, _) | (_, Variance::Invariant) => Variance::Invariant,
+            (Variance::Covariant, other) => other,
+            (Variance::Contravariant, other) => other.invert(),
+        }
+    }
+
+    /// Flips the direction of the variance, so that relating `a` and `b`
+    /// with the result is the same as relating `b` and `a` with `self`.
+    pub fn invert(self) -> Variance {
+        match self {
+            Variance::Covariant => Variance::Contravariant,
+            Variance::Invariant => Variance::Invariant,
+            Variance::Contravariant => Variance::Covariant,
+        }
+    }
+}
+
 /// An universe index is how a universally quantified parameter is
 /// represented when it's binder is moved into the environment.
 /// An example chain of transformations would be:
@@ -299,6 +398,13 @@ pub struct ClauseId<I: Interner>(pub I::DefId);
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct AssocTypeId<I: Interner>(pub I::DefId);
 
+/// The id for the associated const member of a trait. The details of the
+/// const can be found by invoking the [`associated_const_data`] method.
+///
+/// [`associated_const_data`]: ../chalk_solve/trait.RustIrDatabase.html#tymethod.associated_const_data
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssocConstId<I: Interner>(pub I::DefId);
+
 /// Id for an opaque type.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OpaqueTyId<I: Interner>(pub I::DefId);
@@ -954,6 +1060,8 @@ impl<I: Interner> Const<I> {
             ConstValue::InferenceVar(_) => false,
             ConstValue::Placeholder(_) => false,
             ConstValue::Concrete(_) => false,
+            ConstValue::Projection(_) => false,
+            ConstValue::Expr(_) => false,
         }
     }
 }
@@ -978,9 +1086,21 @@ pub enum ConstValue<I: Interner> {
     Placeholder(PlaceholderIndex),
     /// Concrete constant value.
     Concrete(ConcreteConst<I>),
+    /// An associated const of a trait, like `<T as Trait>::N`, which can be
+    /// normalized to its value in an impl.
+    Projection(ConstProjection<I>),
+    /// An expression, like `{ N + 1 }`, whose operands are not all known
+    /// yet.
+    Expr(ConstExpr<I>),
 }
 
-impl<I: Interner> Copy for ConstValue<I> where I::InternedConcreteConst: Copy {}
+impl<I: Interner> Copy for ConstValue<I>
+where
+    I::InternedConcreteConst: Copy,
+    I::InternedSubstitution: Copy,
+    I::InternedConst: Copy,
+{
+}
 
 impl<I: Interner> ConstData<I> {
     /// Wraps the constant data in a `Const`.
@@ -1004,6 +1124,21 @@ impl<I: Interner> ConcreteConst<I> {
     }
 }
 
+/// An unevaluated const expression, like `{ N + 1 }`.
+///
+/// Two expressions are equal when they are structurally equal. Once their
+/// operands are known, expressions are evaluated with
+/// [`Interner::evaluate_const_expr`], and compared to other consts by value.
+#[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+pub enum ConstExpr<I: Interner> {
+    /// A unary operation, like `-a`.
+    Unary(UnOp, Const<I>),
+    /// A binary operation, like `a + b`.
+    Binary(BinOp, Const<I>, Const<I>),
+}
+
+impl<I: Interner> Copy for ConstExpr<I> where I::InternedConst: Copy {}
+
 /// A Rust lifetime.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, HasInterner)]
 pub struct Lifetime<I: Interner> {
@@ -1368,6 +1503,28 @@ pub struct ProjectionTy<I: Interner> {
 
 impl<I: Interner> Copy for ProjectionTy<I> where I::InternedSubstitution: Copy {}
 
+/// A projection `<P0 as TraitName<P1..Pn>>::ASSOC_CONST`.
+#[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
+pub struct ConstProjection<I: Interner> {
+    /// The id for the associated const member.
+    pub associated_const_id: AssocConstId<I>,
+    /// The substitution for the projection.
+    pub substitution: Substitution<I>,
+}
+
+impl<I: Interner> Copy for ConstProjection<I> where I::InternedSubstitution: Copy {}
+
+impl<I: Interner> ConstProjection<I> {
+    /// Gets the type parameters of the `Self` type in this projection.
+    pub fn self_type_parameter(&self, interner: &I) -> Ty<I> {
+        self.substitution
+            .iter(interner)
+            .find_map(move |p| p.ty(interner))
+            .unwrap()
+            .clone()
+    }
+}
+
 /// An opaque type `opaque type T<..>: Trait = HiddenTy`.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, HasInterner, Zip)]
 pub struct OpaqueTy<I: Interner> {
@@ -1561,6 +1718,9 @@ pub enum DomainGoal<I: Interner> {
     /// True if the alias type can be normalized to some other type
     Normalize(Normalize<I>),
 
+    /// True if the const projection can be normalized to some other const
+    NormalizeConst(NormalizeConst<I>),
+
     /// True if a type is considered to have been "defined" by the current crate. This is true for
     /// a `struct Foo { }` but false for a `#[upstream] struct Foo { }`. However, for fundamental types
     /// like `Box<T>`, it is true if `T` is local.
@@ -1617,6 +1777,12 @@ pub enum DomainGoal<I: Interner> {
 
     /// Used to indicate that a trait is object safe.
     ObjectSafe(TraitId<I>),
+
+    /// Used to indicate that a closure type has (at most) the given closure
+    /// kind, e.g. `ClosureKind(C, FnMut)` for a closure `C` that implements
+    /// `FnMut`. The `Fn` and `FnMut` impls of closures whose kind is not yet
+    /// known depend on it; it is ambiguous until the kind is known.
+    ClosureKind(Ty<I>, ClosureKind),
 }
 
 impl<I: Interner> Copy for DomainGoal<I>
@@ -1624,6 +1790,7 @@ where
     I::InternedSubstitution: Copy,
     I::InternedLifetime: Copy,
     I::InternedType: Copy,
+    I::InternedConst: Copy,
 {
 }
 
@@ -1715,6 +1882,16 @@ pub struct EqGoal<I: Interner> {
 
 impl<I: Interner> Copy for EqGoal<I> where I::InternedGenericArg: Copy {}
 
+/// Subtype goal: tries to prove that `a` is a subtype of `b`.
+#[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
+#[allow(missing_docs)]
+pub struct SubtypeGoal<I: Interner> {
+    pub a: Ty<I>,
+    pub b: Ty<I>,
+}
+
+impl<I: Interner> Copy for SubtypeGoal<I> where I::InternedType: Copy {}
+
 /// Proves that the given type alias **normalizes** to the given
 /// type. A projection `T::Foo` normalizes to the type `U` if we can
 /// **match it to an impl** and that impl has a `type Foo = V` where
@@ -1733,6 +1910,24 @@ where
 {
 }
 
+/// Proves that the given const projection **normalizes** to the given
+/// const. A projection `<T as Trait>::N` normalizes to the const `M` if
+/// we can **match it to an impl** and that impl has a `const N: _ = V`
+/// where `M = V`.
+#[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
+#[allow(missing_docs)]
+pub struct NormalizeConst<I: Interner> {
+    pub alias: ConstProjection<I>,
+    pub value: Const<I>,
+}
+
+impl<I: Interner> Copy for NormalizeConst<I>
+where
+    I::InternedSubstitution: Copy,
+    I::InternedConst: Copy,
+{
+}
+
 /// Proves **equality** between an alias and a type.
 #[derive(Clone, PartialEq, Eq, Hash, Fold, Visit, Zip)]
 #[allow(missing_docs)]
@@ -2288,6 +2483,11 @@ pub enum GoalData<I: Interner> {
     /// Make two things equal; the rules for doing so are well known to the logic
     EqGoal(EqGoal<I>),
 
+    /// Make one type a subtype of another. Like `EqGoal`, the rules for
+    /// doing so are well known to the logic, but depend on the variance of
+    /// the types' parameters.
+    SubtypeGoal(SubtypeGoal<I>),
+
     /// A "domain goal" indicates some base sort of goal that can be
     /// proven via program clauses
     DomainGoal(DomainGoal<I>),
@@ -2306,6 +2506,7 @@ impl<I: Interner> Copy for GoalData<I>
 where
     I::InternedType: Copy,
     I::InternedLifetime: Copy,
+    I::InternedConst: Copy,
     I::InternedGenericArg: Copy,
     I::InternedSubstitution: Copy,
     I::InternedGoal: Copy,
@@ -214,6 +214,7 @@ macro_rules! eq_zip {
 eq_zip!(I => AdtId<I>);
 eq_zip!(I => TraitId<I>);
 eq_zip!(I => AssocTypeId<I>);
+eq_zip!(I => AssocConstId<I>);
 eq_zip!(I => OpaqueTyId<I>);
 eq_zip!(I => GeneratorId<I>);
 eq_zip!(I => ForeignDefId<I>);
@@ -224,6 +225,9 @@ eq_zip!(I => PhantomData<I>);
 eq_zip!(I => PlaceholderIndex);
 eq_zip!(I => ClausePriority);
 eq_zip!(I => Mutability);
+eq_zip!(I => ClosureKind);
+eq_zip!(I => BinOp);
+eq_zip!(I => UnOp);
 eq_zip!(I => Scalar);
 
 impl<T: HasInterner<Interner = I> + Zip<I>, I: Interner> Zip<I> for InEnvironment<T> {
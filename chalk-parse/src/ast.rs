@@ -1,21 +1,29 @@
 use std::fmt;
 use string_cache::DefaultAtom as Atom;
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+/// Identifies one of the source files a program is made of. Programs
+/// parsed from a single text only have `FileId(0)`.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct FileId(pub usize);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Span {
+    pub file: FileId,
     pub lo: usize,
     pub hi: usize,
 }
 
 impl Span {
-    pub fn new(lo: usize, hi: usize) -> Self {
-        Span { lo, hi }
+    pub fn new(file: FileId, lo: usize, hi: usize) -> Self {
+        Span { file, lo, hi }
     }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Program {
     pub items: Vec<Item>,
+    /// The spans of `items`, in the same order.
+    pub item_spans: Vec<Span>,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -24,16 +32,76 @@ pub enum Item {
     FnDefn(FnDefn),
     ClosureDefn(ClosureDefn),
     TraitDefn(TraitDefn),
+    TraitAlias(TraitAliasDefn),
     OpaqueTyDefn(OpaqueTyDefn),
     GeneratorDefn(GeneratorDefn),
     Impl(Impl),
+    InherentImpl(InherentImpl),
     Clause(Clause),
     Foreign(ForeignDefn),
+    Module(ModuleDefn),
+    Use(UseDecl),
+    Crate(CrateDefn),
+    Include(Include),
+    Template(TemplateDefn),
+    /// A `// ...` comment, with the text that follows the slashes. Comments
+    /// are only kept for formatting, and are ignored when lowering.
+    Comment(String),
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ForeignDefn(pub Identifier);
 
+/// A `mod foo { ... }` block.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ModuleDefn {
+    pub name: Identifier,
+    pub items: Vec<Item>,
+    /// The spans of `items`, in the same order.
+    pub item_spans: Vec<Span>,
+    /// Set by `#[upstream]`; all the items of the module, including those
+    /// of its submodules, are then upstream.
+    pub upstream: bool,
+}
+
+/// A `crate foo depends bar, baz { ... }` block, declaring a crate of the
+/// program along with the crates it depends on.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CrateDefn {
+    pub name: Identifier,
+    pub dependencies: Vec<Identifier>,
+    pub items: Vec<Item>,
+    /// The spans of `items`, in the same order.
+    pub item_spans: Vec<Span>,
+}
+
+/// An `include "path.chalk";` item, which merges the items of another
+/// file into the program. The path is relative to the including file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Include {
+    pub path: String,
+    pub span: Span,
+}
+
+/// A `for T in [u8, u16] { ... }` template, whose items are repeated for
+/// each of the arguments, with the variable replaced by the argument. See
+/// [`TemplateDefn::expand`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TemplateDefn {
+    pub var: Identifier,
+    pub args: Vec<GenericArg>,
+    pub items: Vec<Item>,
+    /// The spans of `items`, in the same order.
+    pub item_spans: Vec<Span>,
+}
+
+/// A `use a::b::C;` or `use a::b::C as D;` import.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UseDecl {
+    pub path: Identifier,
+    pub alias: Option<Identifier>,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct AdtDefn {
     pub name: Identifier,
@@ -42,6 +110,8 @@ pub struct AdtDefn {
     pub variants: Vec<Variant>,
     pub flags: AdtFlags,
     pub repr: AdtRepr,
+    pub variances: Option<Vec<Variance>>,
+    pub well_known: Option<WellKnownAdt>,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -53,6 +123,7 @@ pub struct Variant {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct GeneratorDefn {
     pub name: Identifier,
+    pub movability: Movability,
     pub variable_kinds: Vec<VariableKind>,
     pub upvars: Vec<Ty>,
     pub resume_ty: Ty,
@@ -62,6 +133,14 @@ pub struct GeneratorDefn {
     pub witness_lifetimes: Vec<Identifier>,
 }
 
+/// Whether a generator can be moved once it has been resumed. `static`
+/// generators cannot.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Movability {
+    Static,
+    Movable,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct AdtFlags {
     pub upstream: bool,
@@ -81,6 +160,21 @@ pub enum AdtKind {
 pub struct AdtRepr {
     pub repr_c: bool,
     pub repr_packed: bool,
+    pub repr_int: Option<ScalarType>,
+}
+
+/// An argument of a `#[repr(..)]` attribute: `C`, `packed` or an integer type.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ReprArg {
+    Named(Atom),
+    Int(ScalarType),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Variance {
+    Invariant,
+    Covariant,
+    Contravariant,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -95,16 +189,23 @@ pub struct FnDefn {
     pub name: Identifier,
     pub variable_kinds: Vec<VariableKind>,
     pub where_clauses: Vec<QuantifiedWhereClause>,
+    /// The names of the arguments, including the variadic one, if any.
+    pub argument_names: Vec<Identifier>,
     pub argument_types: Vec<Ty>,
     pub return_type: Ty,
     pub sig: FnSig,
+    pub variances: Option<Vec<Variance>>,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ClosureDefn {
     pub name: Identifier,
-    pub kind: ClosureKind,
+    /// The kind of the closure, given by its receiver, e.g. `&mut self` for
+    /// `FnMut`. `None` if the kind is not known yet, written `_`.
+    pub kind: Option<ClosureKind>,
     pub variable_kinds: Vec<VariableKind>,
+    /// The names of the arguments, `self` excluded.
+    pub argument_names: Vec<Identifier>,
     pub argument_types: Vec<Ty>,
     pub return_type: Ty,
     pub upvars: Vec<Ty>,
@@ -125,10 +226,24 @@ pub struct TraitDefn {
     pub variable_kinds: Vec<VariableKind>,
     pub where_clauses: Vec<QuantifiedWhereClause>,
     pub assoc_ty_defns: Vec<AssocTyDefn>,
+    pub assoc_const_defns: Vec<AssocConstDefn>,
+    pub assoc_fn_defns: Vec<AssocFnDefn>,
     pub flags: TraitFlags,
     pub well_known: Option<WellKnownTrait>,
 }
 
+/// A trait alias, e.g. `trait Alias<T> = Foo<T> + Bar where T: Baz;`. A type
+/// implements the alias when it implements all the bounds and the where
+/// clauses hold.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TraitAliasDefn {
+    pub name: Identifier,
+    pub variable_kinds: Vec<VariableKind>,
+    pub bounds: Vec<QuantifiedInlineBound>,
+    pub where_clauses: Vec<QuantifiedWhereClause>,
+    pub upstream: bool,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum WellKnownTrait {
     Sized,
@@ -141,6 +256,16 @@ pub enum WellKnownTrait {
     Unsize,
     Unpin,
     CoerceUnsized,
+    Deref,
+    DerefMut,
+    Generator,
+    DiscriminantKind,
+    Pointee,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum WellKnownAdt {
+    DynMetadata,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -162,6 +287,50 @@ pub struct AssocTyDefn {
     pub where_clauses: Vec<QuantifiedWhereClause>,
 }
 
+/// An associated const of a trait, e.g. `const N: usize;`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssocConstDefn {
+    pub name: Identifier,
+    pub ty: Ty,
+}
+
+/// An associated fn of a trait or of an inherent impl, e.g.
+/// `fn get<U>(&self, index: U) -> T;`. Associated fns have no body.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssocFnDefn {
+    pub name: Identifier,
+    pub variable_kinds: Vec<VariableKind>,
+    /// The `self` parameter, if the fn is a method.
+    pub receiver: Option<Receiver>,
+    /// The names of the arguments, `self` excluded.
+    pub argument_names: Vec<Identifier>,
+    pub argument_types: Vec<Ty>,
+    pub return_type: Ty,
+    pub where_clauses: Vec<QuantifiedWhereClause>,
+}
+
+/// The `self` parameter of a method.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Receiver {
+    /// `self`
+    Value,
+    /// `&self` or `&mut self`, with an optional lifetime.
+    Ref {
+        lifetime: Option<Lifetime>,
+        mutability: Mutability,
+    },
+    /// `self: Ty`, e.g. `self: Box<Self>`.
+    Typed(Ty),
+}
+
+/// An item in the body of a trait.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TraitItem {
+    AssocTy(AssocTyDefn),
+    AssocConst(AssocConstDefn),
+    AssocFn(AssocFnDefn),
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct OpaqueTyDefn {
     pub ty: Ty,
@@ -169,6 +338,7 @@ pub struct OpaqueTyDefn {
     pub name: Identifier,
     pub bounds: Vec<QuantifiedInlineBound>,
     pub where_clauses: Vec<QuantifiedWhereClause>,
+    pub variances: Option<Vec<Variance>>,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -177,7 +347,8 @@ pub enum VariableKind {
     IntegerTy(Identifier),
     FloatTy(Identifier),
     Lifetime(Identifier),
-    Const(Identifier),
+    /// A const parameter, e.g. `const N: u8`. Its type defaults to `usize`.
+    Const(Identifier, Option<ScalarType>),
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -191,7 +362,44 @@ pub enum GenericArg {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Const {
     Id(Identifier),
-    Value(u32),
+    Value(ConstValue),
+    /// An associated const, e.g. `<T as Trait>::N`.
+    Projection(ProjectionTy),
+    /// An expression, e.g. `N + 1`.
+    Expr(Box<ConstExpr>),
+}
+
+/// A literal const value, e.g. `true`, `'a'`, `3` or `3u8`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConstValue {
+    Bool(bool),
+    Char(char),
+    /// An integer, with the type of its suffix, if any.
+    Int {
+        value: u128,
+        ty: Option<ScalarType>,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ConstExpr {
+    Unary(UnOp, Const),
+    Binary(BinOp, Const, Const),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum UnOp {
+    Neg,
+    Not,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -244,14 +452,28 @@ impl fmt::Display for Kind {
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Impl {
+    /// The span of the impl header, e.g. `impl<T> Foo for Bar<T>`.
+    pub span: Span,
     pub variable_kinds: Vec<VariableKind>,
     pub trait_ref: TraitRef,
     pub polarity: Polarity,
     pub where_clauses: Vec<QuantifiedWhereClause>,
     pub assoc_ty_values: Vec<AssocTyValue>,
+    pub assoc_const_values: Vec<AssocConstValue>,
     pub impl_type: ImplType,
 }
 
+/// An inherent impl, e.g. `impl<T> Vec<T> { fn len(&self) -> usize; }`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InherentImpl {
+    /// The span of the impl header, e.g. `impl<T> Vec<T>`.
+    pub span: Span,
+    pub variable_kinds: Vec<VariableKind>,
+    pub self_ty: Ty,
+    pub where_clauses: Vec<QuantifiedWhereClause>,
+    pub assoc_fn_defns: Vec<AssocFnDefn>,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum ImplType {
     Local,
@@ -266,6 +488,21 @@ pub struct AssocTyValue {
     pub default: bool,
 }
 
+/// The value of an associated const in an impl, e.g. `const N: usize = 3;`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssocConstValue {
+    pub name: Identifier,
+    pub ty: Ty,
+    pub value: Const,
+}
+
+/// An item of a trait impl, used only while parsing.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ImplItem {
+    AssocTy(AssocTyValue),
+    AssocConst(AssocConstValue),
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Ty {
     Id {
@@ -404,6 +641,8 @@ impl Polarity {
     }
 }
 
+/// An identifier. Where the name of an item is expected, this may also be a
+/// path such as `a::b::C`, whose segments are joined with `::`.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Identifier {
     pub str: Atom,
@@ -426,26 +665,56 @@ pub enum WhereClause {
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum DomainGoal {
-    Holds { where_clause: WhereClause },
-    Normalize { projection: ProjectionTy, ty: Ty },
-    TraitRefWellFormed { trait_ref: TraitRef },
-    TyWellFormed { ty: Ty },
-    TyFromEnv { ty: Ty },
-    TraitRefFromEnv { trait_ref: TraitRef },
-    IsLocal { ty: Ty },
-    IsUpstream { ty: Ty },
-    IsFullyVisible { ty: Ty },
-    LocalImplAllowed { trait_ref: TraitRef },
+    Holds {
+        where_clause: WhereClause,
+    },
+    Normalize {
+        projection: ProjectionTy,
+        ty: Ty,
+    },
+    NormalizeConst {
+        projection: ProjectionTy,
+        value: Const,
+    },
+    TraitRefWellFormed {
+        trait_ref: TraitRef,
+    },
+    TyWellFormed {
+        ty: Ty,
+    },
+    TyFromEnv {
+        ty: Ty,
+    },
+    TraitRefFromEnv {
+        trait_ref: TraitRef,
+    },
+    IsLocal {
+        ty: Ty,
+    },
+    IsUpstream {
+        ty: Ty,
+    },
+    IsFullyVisible {
+        ty: Ty,
+    },
+    LocalImplAllowed {
+        trait_ref: TraitRef,
+    },
     Compatible,
-    DownstreamType { ty: Ty },
+    DownstreamType {
+        ty: Ty,
+    },
     Reveal,
-    ObjectSafe { id: Identifier },
+    ObjectSafe {
+        id: Identifier,
+    },
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum LeafGoal {
     DomainGoal { goal: DomainGoal },
     UnifyGenericArgs { a: GenericArg, b: GenericArg },
+    SubtypeTys { a: Ty, b: Ty },
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -0,0 +1,925 @@
+//! A formatter for `.chalk` programs, which prints a parsed program back in
+//! a canonical style. See [`format_program`].
+
+use crate::ast::*;
+
+const INDENT: &str = "    ";
+
+/// Formats `program`, which was parsed from `text`.
+///
+/// Items are printed one after the other, with their bodies indented by four
+/// spaces, and empty bodies written `{ }`. Blank lines between two items are
+/// kept, though several of them are merged into one; the text is only used to
+/// find them. Comments are kept, each on its own line; the grammar only
+/// allows them between items, not inside one. The rest of the layout is
+/// canonical: e.g. the associated items of a trait are reordered, types
+/// first, then consts and fns, and `-> ()` return types are omitted. Formatting a formatted program gives back the
+/// same text.
+pub fn format_program(program: &Program, text: &str) -> String {
+    let mut formatter = Formatter {
+        text,
+        out: String::new(),
+        indent: 0,
+    };
+    formatter.items(&program.items, &program.item_spans);
+    formatter.out
+}
+
+struct Formatter<'t> {
+    text: &'t str,
+    out: String,
+    indent: usize,
+}
+
+impl Formatter<'_> {
+    fn line(&mut self, line: impl AsRef<str>) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(line.as_ref());
+        self.out.push('\n');
+    }
+
+    /// Prints `header {}`, or `header {`, the body and `}` on separate lines
+    /// if the body is not empty.
+    fn block(&mut self, header: String, empty: bool, body: impl FnOnce(&mut Self)) {
+        if empty {
+            self.line(format!("{} {{ }}", header));
+            return;
+        }
+        self.line(format!("{} {{", header));
+        self.indent += 1;
+        body(self);
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    fn items(&mut self, items: &[Item], spans: &[Span]) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 && self.blank_line_between(spans.get(i - 1), spans.get(i)) {
+                self.out.push('\n');
+            }
+            self.item(item);
+        }
+    }
+
+    fn blank_line_between(&self, previous: Option<&Span>, next: Option<&Span>) -> bool {
+        let between = match (previous, next) {
+            (Some(previous), Some(next)) => self.text.get(previous.hi..next.lo),
+            _ => None,
+        };
+        match between {
+            Some(between) => between.matches('\n').count() > 1,
+            None => false,
+        }
+    }
+
+    fn item(&mut self, item: &Item) {
+        match item {
+            Item::AdtDefn(defn) => self.adt_defn(defn),
+            Item::FnDefn(defn) => {
+                if let Some(variances) = &defn.variances {
+                    self.line(variances_attr(variances));
+                }
+                self.line(format!(
+                    "{}fn {}{}({}){}{};",
+                    fn_sig(&defn.sig),
+                    defn.name,
+                    angle(&defn.variable_kinds, variable_kind),
+                    fn_args(
+                        &defn.argument_names,
+                        &defn.argument_types,
+                        defn.sig.variadic
+                    ),
+                    return_type(&defn.return_type),
+                    where_clauses(&defn.where_clauses),
+                ));
+            }
+            Item::ClosureDefn(defn) => {
+                let receiver = match defn.kind {
+                    Some(ClosureKind::FnOnce) => "self",
+                    Some(ClosureKind::FnMut) => "&mut self",
+                    Some(ClosureKind::Fn) => "&self",
+                    None => "_",
+                };
+                let upvars = if defn.upvars.is_empty() {
+                    "{ }".to_string()
+                } else {
+                    format!("{{ {} }}", list(&defn.upvars, "; ", ty))
+                };
+                // The receiver is always followed by a comma.
+                let args = fn_args(&defn.argument_names, &defn.argument_types, false);
+                let separator = if args.is_empty() { "," } else { ", " };
+                self.line(format!(
+                    "closure {}{}({}{}{}){} {}",
+                    defn.name,
+                    angle(&defn.variable_kinds, variable_kind),
+                    receiver,
+                    separator,
+                    args,
+                    return_type(&defn.return_type),
+                    upvars,
+                ));
+            }
+            Item::TraitDefn(defn) => self.trait_defn(defn),
+            Item::TraitAlias(defn) => {
+                if defn.upstream {
+                    self.line("#[upstream]");
+                }
+                let bounds = if defn.bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", list(&defn.bounds, " + ", quantified_inline_bound))
+                };
+                self.line(format!(
+                    "trait {}{} ={}{};",
+                    defn.name,
+                    angle(&defn.variable_kinds, variable_kind),
+                    bounds,
+                    where_clauses(&defn.where_clauses),
+                ));
+            }
+            Item::OpaqueTyDefn(defn) => {
+                if let Some(variances) = &defn.variances {
+                    self.line(variances_attr(variances));
+                }
+                self.line(format!(
+                    "opaque type {}{}{}{} = {};",
+                    defn.name,
+                    angle(&defn.variable_kinds, variable_kind),
+                    bounds(&defn.bounds),
+                    where_clauses(&defn.where_clauses),
+                    ty(&defn.ty),
+                ));
+            }
+            Item::GeneratorDefn(defn) => {
+                let header = format!(
+                    "{}generator {}{}[resume = {}, yield = {}]{}",
+                    match defn.movability {
+                        Movability::Static => "static ",
+                        Movability::Movable => "",
+                    },
+                    defn.name,
+                    angle(&defn.variable_kinds, variable_kind),
+                    ty(&defn.resume_ty),
+                    ty(&defn.yield_ty),
+                    return_type(&defn.return_ty),
+                );
+                self.block(header, false, |f| {
+                    f.line(format!("upvars [{}]", list(&defn.upvars, "; ", ty)));
+                    let lifetimes = if defn.witness_lifetimes.is_empty() {
+                        String::new()
+                    } else {
+                        format!("exists<{}> ", list(&defn.witness_lifetimes, ", ", id))
+                    };
+                    f.line(format!(
+                        "witnesses {}[{}]",
+                        lifetimes,
+                        list(&defn.witness_types, "; ", ty)
+                    ));
+                });
+            }
+            Item::Impl(defn) => self.impl_defn(defn),
+            Item::InherentImpl(defn) => {
+                let header = format!(
+                    "impl{} {}{}",
+                    angle(&defn.variable_kinds, variable_kind),
+                    ty(&defn.self_ty),
+                    where_clauses(&defn.where_clauses),
+                );
+                self.block(header, defn.assoc_fn_defns.is_empty(), |f| {
+                    for defn in &defn.assoc_fn_defns {
+                        f.line(assoc_fn_defn(defn));
+                    }
+                });
+            }
+            Item::Clause(clause) => {
+                let conditions = if clause.conditions.is_empty() {
+                    String::new()
+                } else {
+                    format!(" if {}", list(&clause.conditions, ", ", |g| goal1(g)))
+                };
+                self.line(format!(
+                    "forall{} {{ {}{} }}",
+                    angle(&clause.variable_kinds, variable_kind),
+                    domain_goal(&clause.consequence),
+                    conditions,
+                ));
+            }
+            Item::Foreign(ForeignDefn(name)) => self.line(format!("extern type {};", name)),
+            Item::Module(defn) => {
+                if defn.upstream {
+                    self.line("#[upstream]");
+                }
+                self.block(format!("mod {}", defn.name), defn.items.is_empty(), |f| {
+                    f.items(&defn.items, &defn.item_spans)
+                });
+            }
+            Item::Use(decl) => match &decl.alias {
+                Some(alias) => self.line(format!("use {} as {};", decl.path, alias)),
+                None => self.line(format!("use {};", decl.path)),
+            },
+            Item::Crate(defn) => {
+                let mut header = format!("crate {}", defn.name);
+                if !defn.dependencies.is_empty() {
+                    header += &format!(" depends {}", list(&defn.dependencies, ", ", id));
+                }
+                self.block(header, defn.items.is_empty(), |f| {
+                    f.items(&defn.items, &defn.item_spans)
+                });
+            }
+            Item::Include(include) => self.line(format!("include \"{}\";", include.path)),
+            Item::Template(defn) => {
+                let header = format!(
+                    "for {} in [{}]",
+                    defn.var,
+                    list(&defn.args, ", ", generic_arg)
+                );
+                self.block(header, defn.items.is_empty(), |f| {
+                    f.items(&defn.items, &defn.item_spans)
+                });
+            }
+            Item::Comment(text) => self.line(format!("//{}", text.trim_end())),
+        }
+    }
+
+    fn adt_defn(&mut self, defn: &AdtDefn) {
+        let flags = &defn.flags;
+        for (set, attr) in &[
+            (flags.upstream, "#[upstream]"),
+            (flags.fundamental, "#[fundamental]"),
+            (flags.phantom_data, "#[phantom_data]"),
+        ] {
+            if *set {
+                self.line(attr);
+            }
+        }
+        if let Some(WellKnownAdt::DynMetadata) = defn.well_known {
+            self.line("#[lang(dyn_metadata)]");
+        }
+        for (set, attr) in &[
+            (defn.repr.repr_c, "#[repr(C)]"),
+            (defn.repr.repr_packed, "#[repr(packed)]"),
+        ] {
+            if *set {
+                self.line(attr);
+            }
+        }
+        if let Some(ty) = defn.repr.repr_int {
+            self.line(format!("#[repr({})]", scalar_type(ty)));
+        }
+        if let Some(variances) = &defn.variances {
+            self.line(variances_attr(variances));
+        }
+        let keyword = match flags.kind {
+            AdtKind::Struct => "struct",
+            AdtKind::Enum => "enum",
+            AdtKind::Union => "union",
+        };
+        let header = format!(
+            "{} {}{}{}",
+            keyword,
+            defn.name,
+            angle(&defn.variable_kinds, variable_kind),
+            where_clauses(&defn.where_clauses),
+        );
+        match flags.kind {
+            AdtKind::Struct | AdtKind::Union => {
+                let fields = defn
+                    .variants
+                    .first()
+                    .map_or(&[][..], |variant| &variant.fields);
+                self.block(header, fields.is_empty(), |f| {
+                    for field in fields {
+                        f.line(format!("{}: {},", field.name, ty(&field.ty)));
+                    }
+                });
+            }
+            AdtKind::Enum => self.block(header, defn.variants.is_empty(), |f| {
+                for variant in &defn.variants {
+                    f.line(format!("{},", self::variant(variant)));
+                }
+            }),
+        }
+    }
+
+    fn trait_defn(&mut self, defn: &TraitDefn) {
+        let flags = &defn.flags;
+        for (set, attr) in &[
+            (flags.auto, "#[auto]"),
+            (flags.marker, "#[marker]"),
+            (flags.upstream, "#[upstream]"),
+            (flags.fundamental, "#[fundamental]"),
+            (flags.non_enumerable, "#[non_enumerable]"),
+            (flags.coinductive, "#[coinductive]"),
+            (flags.object_safe, "#[object_safe]"),
+        ] {
+            if *set {
+                self.line(attr);
+            }
+        }
+        if let Some(well_known) = defn.well_known {
+            self.line(format!("#[lang({})]", lang_item(well_known)));
+        }
+        let header = format!(
+            "trait {}{}{}",
+            defn.name,
+            angle(&defn.variable_kinds, variable_kind),
+            where_clauses(&defn.where_clauses),
+        );
+        let empty = defn.assoc_ty_defns.is_empty()
+            && defn.assoc_const_defns.is_empty()
+            && defn.assoc_fn_defns.is_empty();
+        self.block(header, empty, |f| {
+            for defn in &defn.assoc_ty_defns {
+                f.line(format!(
+                    "type {}{}{}{};",
+                    defn.name,
+                    angle(&defn.variable_kinds, variable_kind),
+                    bounds(&defn.bounds),
+                    where_clauses(&defn.where_clauses),
+                ));
+            }
+            for defn in &defn.assoc_const_defns {
+                f.line(format!("const {}: {};", defn.name, ty(&defn.ty)));
+            }
+            for defn in &defn.assoc_fn_defns {
+                f.line(assoc_fn_defn(defn));
+            }
+        });
+    }
+
+    fn impl_defn(&mut self, defn: &Impl) {
+        if defn.impl_type == ImplType::External {
+            self.line("#[upstream]");
+        }
+        let (self_ty, args) = defn
+            .trait_ref
+            .args
+            .split_first()
+            .expect("an impl has a self type");
+        let polarity = match defn.polarity {
+            Polarity::Positive => "",
+            Polarity::Negative => "!",
+        };
+        let header = format!(
+            "impl{} {}{}{} for {}{}",
+            angle(&defn.variable_kinds, variable_kind),
+            polarity,
+            defn.trait_ref.trait_name,
+            angle(args, generic_arg),
+            generic_arg(self_ty),
+            where_clauses(&defn.where_clauses),
+        );
+        let empty = defn.assoc_ty_values.is_empty() && defn.assoc_const_values.is_empty();
+        self.block(header, empty, |f| {
+            for value in &defn.assoc_ty_values {
+                f.line(format!(
+                    "{}type {}{} = {};",
+                    if value.default { "default " } else { "" },
+                    value.name,
+                    angle(&value.variable_kinds, variable_kind),
+                    ty(&value.value),
+                ));
+            }
+            for value in &defn.assoc_const_values {
+                f.line(format!(
+                    "const {}: {} = {};",
+                    value.name,
+                    ty(&value.ty),
+                    const_expr(&value.value, Precedence::Sum),
+                ));
+            }
+        });
+    }
+}
+
+fn list<T>(items: &[T], separator: &str, f: impl Fn(&T) -> String) -> String {
+    items.iter().map(f).collect::<Vec<_>>().join(separator)
+}
+
+/// Prints `<A, B>`, or nothing if there are no items.
+fn angle<T>(items: &[T], f: impl Fn(&T) -> String) -> String {
+    if items.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", list(items, ", ", f))
+    }
+}
+
+fn id(id: &Identifier) -> String {
+    id.to_string()
+}
+
+fn variances_attr(variances: &[Variance]) -> String {
+    let variances = list(variances, ", ", |variance| {
+        match variance {
+            Variance::Invariant => "Invariant",
+            Variance::Covariant => "Covariant",
+            Variance::Contravariant => "Contravariant",
+        }
+        .to_string()
+    });
+    format!("#[variance({})]", variances)
+}
+
+fn lang_item(well_known: WellKnownTrait) -> &'static str {
+    match well_known {
+        WellKnownTrait::Sized => "sized",
+        WellKnownTrait::Copy => "copy",
+        WellKnownTrait::Clone => "clone",
+        WellKnownTrait::Drop => "drop",
+        WellKnownTrait::FnOnce => "fn_once",
+        WellKnownTrait::FnMut => "fn_mut",
+        WellKnownTrait::Fn => "fn",
+        WellKnownTrait::Unsize => "unsize",
+        WellKnownTrait::Unpin => "unpin",
+        WellKnownTrait::CoerceUnsized => "coerce_unsized",
+        WellKnownTrait::Deref => "deref",
+        WellKnownTrait::DerefMut => "deref_mut",
+        WellKnownTrait::Generator => "generator",
+        WellKnownTrait::DiscriminantKind => "discriminant_kind",
+        WellKnownTrait::Pointee => "pointee",
+    }
+}
+
+fn variant(variant: &Variant) -> String {
+    // The fields of tuple variants are named after their index.
+    let is_tuple = variant
+        .fields
+        .iter()
+        .any(|field| field.name.str.starts_with(|c: char| c.is_ascii_digit()));
+    if variant.fields.is_empty() {
+        variant.name.to_string()
+    } else if is_tuple {
+        let fields = list(&variant.fields, ", ", |field| ty(&field.ty));
+        format!("{}({})", variant.name, fields)
+    } else {
+        let fields = list(&variant.fields, ", ", |field| {
+            format!("{}: {}", field.name, ty(&field.ty))
+        });
+        format!("{} {{ {} }}", variant.name, fields)
+    }
+}
+
+/// Prints the `unsafe extern "C" ` prefix of a fn.
+fn fn_sig(sig: &FnSig) -> String {
+    let mut prefix = String::new();
+    if sig.safety == Safety::Unsafe {
+        prefix.push_str("unsafe ");
+    }
+    if sig.abi != FnAbi::default() {
+        prefix.push_str(&format!("extern \"{}\" ", sig.abi.0));
+    }
+    prefix
+}
+
+fn fn_args(names: &[Identifier], types: &[Ty], variadic: bool) -> String {
+    let mut args: Vec<_> = names
+        .iter()
+        .zip(types)
+        .map(|(name, arg_ty)| format!("{}: {}", name, ty(arg_ty)))
+        .collect();
+    if variadic {
+        args.push(format!("{}: ...", names[types.len()]));
+    }
+    args.join(", ")
+}
+
+/// Prints ` -> R`, or nothing if `R` is `()`.
+fn return_type(return_ty: &Ty) -> String {
+    match return_ty {
+        Ty::Tuple { types } if types.is_empty() => String::new(),
+        _ => format!(" -> {}", ty(return_ty)),
+    }
+}
+
+fn assoc_fn_defn(defn: &AssocFnDefn) -> String {
+    let mut args: Vec<_> = defn.receiver.iter().map(receiver).collect();
+    args.extend(
+        defn.argument_names
+            .iter()
+            .zip(&defn.argument_types)
+            .map(|(name, arg_ty)| format!("{}: {}", name, ty(arg_ty))),
+    );
+    format!(
+        "fn {}{}({}){}{};",
+        defn.name,
+        angle(&defn.variable_kinds, variable_kind),
+        args.join(", "),
+        return_type(&defn.return_type),
+        where_clauses(&defn.where_clauses),
+    )
+}
+
+fn receiver(receiver: &Receiver) -> String {
+    match receiver {
+        Receiver::Value => "self".to_string(),
+        Receiver::Ref {
+            lifetime: region,
+            mutability,
+        } => {
+            let mut receiver = "&".to_string();
+            if let Some(region) = region {
+                receiver.push_str(&lifetime(region));
+                receiver.push(' ');
+            }
+            if *mutability == Mutability::Mut {
+                receiver.push_str("mut ");
+            }
+            receiver + "self"
+        }
+        Receiver::Typed(self_ty) => format!("self: {}", ty(self_ty)),
+    }
+}
+
+fn variable_kind(kind: &VariableKind) -> String {
+    match kind {
+        VariableKind::Ty(name) | VariableKind::Lifetime(name) => name.to_string(),
+        VariableKind::IntegerTy(name) => format!("int {}", name),
+        VariableKind::FloatTy(name) => format!("float {}", name),
+        VariableKind::Const(name, None) => format!("const {}", name),
+        VariableKind::Const(name, Some(ty)) => format!("const {}: {}", name, scalar_type(*ty)),
+    }
+}
+
+fn where_clauses(where_clauses: &[QuantifiedWhereClause]) -> String {
+    if where_clauses.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " where {}",
+            list(where_clauses, ", ", quantified_where_clause)
+        )
+    }
+}
+
+fn quantified_where_clause(where_clause: &QuantifiedWhereClause) -> String {
+    quantified(
+        &where_clause.variable_kinds,
+        self::where_clause(&where_clause.where_clause),
+    )
+}
+
+/// Prints `forall<..> value`, or `value` if there are no variables.
+fn quantified(variable_kinds: &[VariableKind], value: String) -> String {
+    if variable_kinds.is_empty() {
+        value
+    } else {
+        format!("forall{} {}", angle(variable_kinds, variable_kind), value)
+    }
+}
+
+fn where_clause(where_clause: &WhereClause) -> String {
+    match where_clause {
+        WhereClause::Implemented { trait_ref } => self::trait_ref(trait_ref, ": "),
+        WhereClause::ProjectionEq { projection, ty } => {
+            let (self_ty, args) = projection
+                .trait_ref
+                .args
+                .split_first()
+                .expect("a trait ref has a self type");
+            let mut args: Vec<_> = args.iter().map(generic_arg).collect();
+            args.push(format!(
+                "{}{} = {}",
+                projection.name,
+                angle(&projection.args, generic_arg),
+                self::ty(ty)
+            ));
+            format!(
+                "{}: {}<{}>",
+                generic_arg(self_ty),
+                projection.trait_ref.trait_name,
+                args.join(", ")
+            )
+        }
+        WhereClause::LifetimeOutlives { a, b } => format!("{}: {}", lifetime(a), lifetime(b)),
+        WhereClause::TypeOutlives { ty, lifetime } => {
+            format!("{}: {}", self::ty(ty), self::lifetime(lifetime))
+        }
+    }
+}
+
+/// Prints `Self: Trait<A>`, or `Self as Trait<A>` with `" as "` as the
+/// separator.
+fn trait_ref(trait_ref: &TraitRef, separator: &str) -> String {
+    let (self_ty, args) = trait_ref
+        .args
+        .split_first()
+        .expect("a trait ref has a self type");
+    format!(
+        "{}{}{}{}",
+        generic_arg(self_ty),
+        separator,
+        trait_ref.trait_name,
+        angle(args, generic_arg)
+    )
+}
+
+fn bounds(bounds: &[QuantifiedInlineBound]) -> String {
+    if bounds.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", list(bounds, " + ", quantified_inline_bound))
+    }
+}
+
+fn quantified_inline_bound(bound: &QuantifiedInlineBound) -> String {
+    let inline_bound = match &bound.bound {
+        InlineBound::TraitBound(bound) => trait_bound(bound),
+        InlineBound::AliasEqBound(bound) => {
+            let mut args: Vec<_> = bound
+                .trait_bound
+                .args_no_self
+                .iter()
+                .map(generic_arg)
+                .collect();
+            args.push(format!(
+                "{}{} = {}",
+                bound.name,
+                angle(&bound.args, generic_arg),
+                ty(&bound.value)
+            ));
+            format!("{}<{}>", bound.trait_bound.trait_name, args.join(", "))
+        }
+    };
+    quantified(&bound.variable_kinds, inline_bound)
+}
+
+fn trait_bound(bound: &TraitBound) -> String {
+    format!(
+        "{}{}",
+        bound.trait_name,
+        angle(&bound.args_no_self, generic_arg)
+    )
+}
+
+fn ty(ty: &Ty) -> String {
+    match ty {
+        Ty::Id { name } => name.to_string(),
+        Ty::Dyn { bounds, lifetime } => format!(
+            "dyn {} + {}",
+            list(bounds, " + ", quantified_inline_bound),
+            self::lifetime(lifetime)
+        ),
+        Ty::Apply { name, args } => format!("{}<{}>", name, list(args, ", ", generic_arg)),
+        Ty::Projection { proj } => projection_ty(proj),
+        Ty::ForAll {
+            lifetime_names,
+            types,
+            sig,
+        } => {
+            let (return_ty, args) = types.split_last().expect("a fn type has a return type");
+            let mut args: Vec<_> = args.iter().map(|arg| self::ty(arg)).collect();
+            if sig.variadic {
+                args.push("...".to_string());
+            }
+            let lifetimes = if lifetime_names.is_empty() {
+                String::new()
+            } else {
+                format!("for<{}> ", list(lifetime_names, ", ", id))
+            };
+            format!(
+                "{}{}fn({}){}",
+                lifetimes,
+                fn_sig(sig),
+                args.join(", "),
+                return_type(return_ty)
+            )
+        }
+        Ty::Tuple { types } if types.len() == 1 => format!("({},)", self::ty(&types[0])),
+        Ty::Tuple { types } => format!("({})", list(types, ", ", |ty| self::ty(ty))),
+        Ty::Scalar { ty } => scalar_type(*ty).to_string(),
+        Ty::Slice { ty } => format!("[{}]", self::ty(ty)),
+        Ty::Array { ty, len } => {
+            format!("[{}; {}]", self::ty(ty), const_expr(len, Precedence::Sum))
+        }
+        Ty::Raw { mutability, ty } => match mutability {
+            Mutability::Mut => format!("*mut {}", self::ty(ty)),
+            Mutability::Not => format!("*const {}", self::ty(ty)),
+        },
+        Ty::Ref {
+            mutability,
+            lifetime,
+            ty,
+        } => match mutability {
+            Mutability::Mut => format!("&{} mut {}", self::lifetime(lifetime), self::ty(ty)),
+            Mutability::Not => format!("&{} {}", self::lifetime(lifetime), self::ty(ty)),
+        },
+        Ty::Str => "str".to_string(),
+        Ty::Never => "!".to_string(),
+    }
+}
+
+fn scalar_type(ty: ScalarType) -> &'static str {
+    match ty {
+        ScalarType::Bool => "bool",
+        ScalarType::Char => "char",
+        ScalarType::Int(IntTy::Isize) => "isize",
+        ScalarType::Int(IntTy::I8) => "i8",
+        ScalarType::Int(IntTy::I16) => "i16",
+        ScalarType::Int(IntTy::I32) => "i32",
+        ScalarType::Int(IntTy::I64) => "i64",
+        ScalarType::Int(IntTy::I128) => "i128",
+        ScalarType::Uint(UintTy::Usize) => "usize",
+        ScalarType::Uint(UintTy::U8) => "u8",
+        ScalarType::Uint(UintTy::U16) => "u16",
+        ScalarType::Uint(UintTy::U32) => "u32",
+        ScalarType::Uint(UintTy::U64) => "u64",
+        ScalarType::Uint(UintTy::U128) => "u128",
+        ScalarType::Float(FloatTy::F32) => "f32",
+        ScalarType::Float(FloatTy::F64) => "f64",
+    }
+}
+
+fn lifetime(lifetime: &Lifetime) -> String {
+    match lifetime {
+        Lifetime::Id { name } => name.to_string(),
+        Lifetime::Static => "'static".to_string(),
+    }
+}
+
+fn projection_ty(proj: &ProjectionTy) -> String {
+    format!(
+        "<{}>::{}{}",
+        trait_ref(&proj.trait_ref, " as "),
+        proj.name,
+        angle(&proj.args, generic_arg)
+    )
+}
+
+fn generic_arg(arg: &GenericArg) -> String {
+    match arg {
+        GenericArg::Ty(arg) => ty(arg),
+        GenericArg::Lifetime(arg) => lifetime(arg),
+        GenericArg::Id(arg) => arg.to_string(),
+        GenericArg::Const(value) => match value {
+            // Negative literals are the only expressions that need no braces.
+            Const::Expr(expr) if matches!(**expr, ConstExpr::Unary(UnOp::Neg, Const::Value(_))) => {
+                const_expr(value, Precedence::Sum)
+            }
+            _ => braced_const(value),
+        },
+    }
+}
+
+/// Prints a const where only literals can appear without braces.
+fn braced_const(value: &Const) -> String {
+    match value {
+        Const::Value(value) => const_value(*value),
+        _ => format!("{{ {} }}", const_expr(value, Precedence::Sum)),
+    }
+}
+
+/// The precedence levels of const expressions, from the loosest to the
+/// tightest.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Product,
+    Unary,
+    Atom,
+}
+
+/// Prints a const expression, parenthesized if it binds less tightly than
+/// `precedence`.
+fn const_expr(value: &Const, precedence: Precedence) -> String {
+    let (expr, own) = match value {
+        Const::Id(name) => (name.to_string(), Precedence::Atom),
+        Const::Value(value) => (const_value(*value), Precedence::Atom),
+        Const::Projection(proj) => (projection_ty(proj), Precedence::Atom),
+        Const::Expr(expr) => match &**expr {
+            ConstExpr::Unary(op, operand) => {
+                let op = match op {
+                    UnOp::Neg => "-",
+                    UnOp::Not => "!",
+                };
+                let operand = const_expr(operand, Precedence::Unary);
+                (format!("{}{}", op, operand), Precedence::Unary)
+            }
+            ConstExpr::Binary(op, a, b) => {
+                let (op, own, next) = match op {
+                    BinOp::Add => ("+", Precedence::Sum, Precedence::Product),
+                    BinOp::Sub => ("-", Precedence::Sum, Precedence::Product),
+                    BinOp::Mul => ("*", Precedence::Product, Precedence::Unary),
+                    BinOp::Div => ("/", Precedence::Product, Precedence::Unary),
+                    BinOp::Rem => ("%", Precedence::Product, Precedence::Unary),
+                };
+                let expr = format!("{} {} {}", const_expr(a, own), op, const_expr(b, next));
+                (expr, own)
+            }
+        },
+    };
+    if own < precedence {
+        format!("({})", expr)
+    } else {
+        expr
+    }
+}
+
+fn const_value(value: ConstValue) -> String {
+    match value {
+        ConstValue::Bool(value) => value.to_string(),
+        ConstValue::Char(value) => format!("'{}'", value),
+        ConstValue::Int { value, ty: None } => value.to_string(),
+        ConstValue::Int {
+            value,
+            ty: Some(ty),
+        } => format!("{}{}", value, scalar_type(ty)),
+    }
+}
+
+fn domain_goal(goal: &DomainGoal) -> String {
+    match goal {
+        DomainGoal::Holds { where_clause } => self::where_clause(where_clause),
+        DomainGoal::Normalize { projection, ty } => {
+            format!(
+                "Normalize({} -> {})",
+                projection_ty(projection),
+                self::ty(ty)
+            )
+        }
+        DomainGoal::NormalizeConst { projection, value } => format!(
+            "Normalize({} -> {})",
+            projection_ty(projection),
+            braced_const(value)
+        ),
+        DomainGoal::TraitRefWellFormed { trait_ref } => {
+            format!("WellFormed({})", self::trait_ref(trait_ref, ": "))
+        }
+        DomainGoal::TyWellFormed { ty } => format!("WellFormed({})", self::ty(ty)),
+        DomainGoal::TyFromEnv { ty } => format!("FromEnv({})", self::ty(ty)),
+        DomainGoal::TraitRefFromEnv { trait_ref } => {
+            format!("FromEnv({})", self::trait_ref(trait_ref, ": "))
+        }
+        DomainGoal::IsLocal { ty } => format!("IsLocal({})", self::ty(ty)),
+        DomainGoal::IsUpstream { ty } => format!("IsUpstream({})", self::ty(ty)),
+        DomainGoal::IsFullyVisible { ty } => format!("IsFullyVisible({})", self::ty(ty)),
+        DomainGoal::LocalImplAllowed { trait_ref } => {
+            format!("LocalImplAllowed({})", self::trait_ref(trait_ref, ": "))
+        }
+        DomainGoal::Compatible => "Compatible".to_string(),
+        DomainGoal::DownstreamType { ty } => format!("DownstreamType({})", self::ty(ty)),
+        DomainGoal::Reveal => "Reveal".to_string(),
+        DomainGoal::ObjectSafe { id } => format!("ObjectSafe({})", id),
+    }
+}
+
+fn goal(goal: &Goal) -> String {
+    match goal {
+        Goal::And(first, rest) => {
+            let mut goals = vec![goal1(first)];
+            goals.extend(rest.iter().map(|goal| goal1(goal)));
+            goals.join(", ")
+        }
+        _ => goal1(goal),
+    }
+}
+
+/// Prints a goal where conjunctions must be parenthesized, e.g. as the
+/// condition of a clause.
+fn goal1(goal: &Goal) -> String {
+    match goal {
+        Goal::ForAll(variable_kinds, goal) => format!(
+            "forall<{}> {{ {} }}",
+            list(variable_kinds, ", ", variable_kind),
+            self::goal(goal)
+        ),
+        Goal::Exists(variable_kinds, goal) => format!(
+            "exists<{}> {{ {} }}",
+            list(variable_kinds, ", ", variable_kind),
+            self::goal(goal)
+        ),
+        Goal::Implies(clauses, goal) => format!(
+            "if ({}) {{ {} }}",
+            list(clauses, "; ", inline_clause),
+            self::goal(goal)
+        ),
+        Goal::And(..) => format!("({})", self::goal(goal)),
+        Goal::Not(goal) => format!("not {{ {} }}", self::goal(goal)),
+        Goal::Compatible(goal) => format!("compatible {{ {} }}", self::goal(goal)),
+        Goal::Leaf(LeafGoal::DomainGoal { goal }) => domain_goal(goal),
+        Goal::Leaf(LeafGoal::UnifyGenericArgs { a, b }) => {
+            format!("{} = {}", generic_arg(a), generic_arg(b))
+        }
+        Goal::Leaf(LeafGoal::SubtypeTys { a, b }) => format!("Subtype({}, {})", ty(a), ty(b)),
+    }
+}
+
+/// Prints a clause of an `if (..) { .. }` goal, e.g. `A :- B, C`.
+fn inline_clause(clause: &Clause) -> String {
+    let mut inline_clause = domain_goal(&clause.consequence);
+    if !clause.conditions.is_empty() {
+        inline_clause.push_str(" :- ");
+        inline_clause.push_str(&list(&clause.conditions, ", ", |goal| goal1(goal)));
+    }
+    if clause.variable_kinds.is_empty() {
+        inline_clause
+    } else {
+        format!(
+            "forall<{}> {{ {} }}",
+            list(&clause.variable_kinds, ", ", variable_kind),
+            inline_clause
+        )
+    }
+}
@@ -5,65 +5,130 @@
 extern crate lalrpop_util;
 
 pub mod ast;
+pub mod format;
+mod template;
 #[rustfmt::skip]
 lalrpop_mod!(pub parser);
 
 use lalrpop_util::ParseError;
-use std::fmt::Write;
+use std::fmt::{self, Write};
 
-type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+type Result<T> = std::result::Result<T, SyntaxError>;
+
+/// A syntax error found while parsing a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+    /// The text the error points at, if known.
+    pub span: Option<ast::Span>,
+}
+
+impl SyntaxError {
+    fn new<T: fmt::Display>(file: ast::FileId, error: ParseError<usize, T, &'static str>) -> Self {
+        let span = match error {
+            ParseError::InvalidToken { location }
+            | ParseError::UnrecognizedEOF { location, .. } => {
+                Some(ast::Span::new(file, location, location))
+            }
+            ParseError::UnrecognizedToken {
+                token: (lo, _, hi), ..
+            }
+            | ParseError::ExtraToken { token: (lo, _, hi) } => Some(ast::Span::new(file, lo, hi)),
+            ParseError::User { .. } => None,
+        };
+        SyntaxError {
+            message: format!("parse error: {}", error),
+            span,
+        }
+    }
+
+    /// Converts an error found while parsing `text`, which is not part of a
+    /// source file (e.g. a goal). The error has no span, as it would not
+    /// point into a source file; the message shows where the error is in
+    /// `text` instead.
+    fn in_text<T: fmt::Display>(text: &str, error: ParseError<usize, T, &'static str>) -> Self {
+        let mut error = SyntaxError::new(ast::FileId::default(), error);
+        if let Some(span) = error.span.take() {
+            let text = text.replace("\n", " ").replace("\r", " ");
+            let width = std::cmp::max(span.hi - span.lo, 1);
+            write!(
+                error.message,
+                "\nposition: `{}`\n{}{}\n",
+                text,
+                " ".repeat(11 + span.lo),
+                "^".repeat(width)
+            )
+            .expect("str-write cannot fail");
+        }
+        error
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
 
 pub fn parse_program(text: &str) -> Result<ast::Program> {
-    match parser::ProgramParser::new().parse(text) {
-        Ok(v) => Ok(v),
-        Err(e) => Err(format!("parse error: {:?}", e))?,
+    parse_file(ast::FileId::default(), text)
+}
+
+/// Parses the text of one of the source files of a program, recording
+/// `file` in the spans of the items. Fails with the first syntax error, if
+/// any; see [`parse_file_recovering`] to get all of them.
+pub fn parse_file(file: ast::FileId, text: &str) -> Result<ast::Program> {
+    let (program, errors) = parse_file_recovering(file, text);
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(program),
     }
 }
 
+/// Parses the text of one of the source files of a program like
+/// [`parse_file`], but recovers from syntax errors: an item that cannot be
+/// parsed is skipped up to the next `;` or `}`, and parsing resumes after
+/// it. Returns the items that could be parsed along with all the syntax
+/// errors. Unexpected characters still end the parse, as the lexer cannot
+/// recover from them; only the items before them are then returned.
+pub fn parse_file_recovering(file: ast::FileId, text: &str) -> (ast::Program, Vec<SyntaxError>) {
+    let mut recovered = Vec::new();
+    let result = parser::ProgramParser::new().parse(file, &mut recovered, text);
+    let mut errors: Vec<_> = recovered
+        .into_iter()
+        .map(|recovery| SyntaxError::new(file, recovery.error))
+        .collect();
+    let program = result.unwrap_or_else(|error| {
+        let error = SyntaxError::new(file, error);
+        // The parse cannot go past the error, but the text before it can
+        // still be parsed; its errors were already recorded above.
+        let prefix = error
+            .span
+            .filter(|span| span.lo < text.len())
+            .and_then(|span| {
+                parser::ProgramParser::new()
+                    .parse(file, &mut Vec::new(), &text[..span.lo])
+                    .ok()
+            });
+        errors.push(error);
+        prefix.unwrap_or(ast::Program {
+            items: Vec::new(),
+            item_spans: Vec::new(),
+        })
+    });
+    (program, errors)
+}
+
 pub fn parse_ty(text: &str) -> Result<ast::Ty> {
-    match parser::TyParser::new().parse(text) {
-        Ok(v) => Ok(v),
-        Err(e) => Err(format!("error parsing `{}`: {:?}", text, e))?,
-    }
+    parser::TyParser::new()
+        .parse(ast::FileId::default(), &mut Vec::new(), text)
+        .map_err(|e| SyntaxError::in_text(text, e))
 }
 
 pub fn parse_goal(text: &str) -> Result<Box<ast::Goal>> {
-    match parser::GoalParser::new().parse(text) {
-        Ok(v) => Ok(v),
-        Err(e) => {
-            let position_string = |start: usize, end: usize| {
-                let mut output = String::new();
-                let text = text.replace("\n", " ").replace("\r", " ");
-                writeln!(output, "position: `{}`", text).expect("str-write cannot fail");
-                output.push_str(&" ".repeat(11 + start));
-                output.push_str(&"^".repeat(end - start));
-                output.push_str("\n");
-                output
-            };
-            match e {
-                ParseError::InvalidToken { location } => Err(format!(
-                    "parse error: {:?}\n{}",
-                    e,
-                    position_string(location, location + 1)
-                ))?,
-                ParseError::UnrecognizedToken {
-                    token: (start, _, end),
-                    ..
-                } => Err(format!(
-                    "parse error: {:?}\n{}",
-                    e,
-                    position_string(start, end)
-                ))?,
-                ParseError::ExtraToken {
-                    token: (start, _, end),
-                    ..
-                } => Err(format!(
-                    "parse error: {:?}\n{}",
-                    e,
-                    position_string(start, end)
-                ))?,
-                _ => Err(format!("parse error: {:?}", e))?,
-            }
-        }
-    }
+    parser::GoalParser::new()
+        .parse(ast::FileId::default(), &mut Vec::new(), text)
+        .map_err(|e| SyntaxError::in_text(text, e))
 }
@@ -0,0 +1,343 @@
+//! Expansion of `for T in [...] { ... }` templates. See
+//! [`TemplateDefn::expand`].
+
+use crate::ast::*;
+use string_cache::DefaultAtom as Atom;
+
+impl TemplateDefn {
+    /// Expands the template: its items are repeated for each argument, in
+    /// order, with the variable replaced by the argument. The expanded items
+    /// keep the spans of the template, except for the arguments, which keep
+    /// the spans they have in the argument list.
+    ///
+    /// The replacement is syntactic: the variable is replaced wherever it is
+    /// used as a type, a generic argument, a const, or the name of a trait
+    /// or of a generic type, even if an item of the template declares a
+    /// parameter with the same name. A nested template with the same
+    /// variable shadows it, though. Nested templates are not expanded.
+    pub fn expand(&self) -> Vec<Item> {
+        let mut expanded = Vec::with_capacity(self.args.len() * self.items.len());
+        for arg in &self.args {
+            let subst = Subst {
+                var: &self.var.str,
+                arg,
+            };
+            for item in &self.items {
+                let mut item = item.clone();
+                subst.item(&mut item);
+                expanded.push(item);
+            }
+        }
+        expanded
+    }
+}
+
+/// Replaces `var` with `arg` in the items of a template.
+struct Subst<'a> {
+    var: &'a Atom,
+    arg: &'a GenericArg,
+}
+
+impl Subst<'_> {
+    fn is_var(&self, id: &Identifier) -> bool {
+        id.str == *self.var
+    }
+
+    fn item(&self, item: &mut Item) {
+        match item {
+            Item::AdtDefn(defn) => {
+                self.where_clauses(&mut defn.where_clauses);
+                for variant in &mut defn.variants {
+                    for field in &mut variant.fields {
+                        self.ty(&mut field.ty);
+                    }
+                }
+            }
+            Item::FnDefn(defn) => {
+                self.where_clauses(&mut defn.where_clauses);
+                self.tys(&mut defn.argument_types);
+                self.ty(&mut defn.return_type);
+            }
+            Item::ClosureDefn(defn) => {
+                self.tys(&mut defn.argument_types);
+                self.ty(&mut defn.return_type);
+                self.tys(&mut defn.upvars);
+            }
+            Item::TraitDefn(defn) => {
+                self.where_clauses(&mut defn.where_clauses);
+                for defn in &mut defn.assoc_ty_defns {
+                    self.bounds(&mut defn.bounds);
+                    self.where_clauses(&mut defn.where_clauses);
+                }
+                for defn in &mut defn.assoc_const_defns {
+                    self.ty(&mut defn.ty);
+                }
+                for defn in &mut defn.assoc_fn_defns {
+                    self.assoc_fn_defn(defn);
+                }
+            }
+            Item::TraitAlias(defn) => {
+                self.bounds(&mut defn.bounds);
+                self.where_clauses(&mut defn.where_clauses);
+            }
+            Item::OpaqueTyDefn(defn) => {
+                self.ty(&mut defn.ty);
+                self.bounds(&mut defn.bounds);
+                self.where_clauses(&mut defn.where_clauses);
+            }
+            Item::GeneratorDefn(defn) => {
+                self.tys(&mut defn.upvars);
+                self.ty(&mut defn.resume_ty);
+                self.ty(&mut defn.yield_ty);
+                self.ty(&mut defn.return_ty);
+                self.tys(&mut defn.witness_types);
+            }
+            Item::Impl(defn) => {
+                self.trait_ref(&mut defn.trait_ref);
+                self.where_clauses(&mut defn.where_clauses);
+                for value in &mut defn.assoc_ty_values {
+                    self.ty(&mut value.value);
+                }
+                for value in &mut defn.assoc_const_values {
+                    self.ty(&mut value.ty);
+                    self.const_(&mut value.value);
+                }
+            }
+            Item::InherentImpl(defn) => {
+                self.ty(&mut defn.self_ty);
+                self.where_clauses(&mut defn.where_clauses);
+                for defn in &mut defn.assoc_fn_defns {
+                    self.assoc_fn_defn(defn);
+                }
+            }
+            Item::Clause(clause) => self.clause(clause),
+            Item::Module(defn) => {
+                for item in &mut defn.items {
+                    self.item(item);
+                }
+            }
+            Item::Template(defn) => {
+                for arg in &mut defn.args {
+                    self.generic_arg(arg);
+                }
+                if !self.is_var(&defn.var) {
+                    for item in &mut defn.items {
+                        self.item(item);
+                    }
+                }
+            }
+            Item::Foreign(_)
+            | Item::Use(_)
+            | Item::Crate(_)
+            | Item::Include(_)
+            | Item::Comment(_) => {}
+        }
+    }
+
+    fn assoc_fn_defn(&self, defn: &mut AssocFnDefn) {
+        if let Some(Receiver::Typed(ty)) = &mut defn.receiver {
+            self.ty(ty);
+        }
+        self.tys(&mut defn.argument_types);
+        self.ty(&mut defn.return_type);
+        self.where_clauses(&mut defn.where_clauses);
+    }
+
+    fn where_clauses(&self, where_clauses: &mut [QuantifiedWhereClause]) {
+        for where_clause in where_clauses {
+            self.where_clause(&mut where_clause.where_clause);
+        }
+    }
+
+    fn where_clause(&self, where_clause: &mut WhereClause) {
+        match where_clause {
+            WhereClause::Implemented { trait_ref } => self.trait_ref(trait_ref),
+            WhereClause::ProjectionEq { projection, ty } => {
+                self.projection_ty(projection);
+                self.ty(ty);
+            }
+            WhereClause::LifetimeOutlives { .. } => {}
+            WhereClause::TypeOutlives { ty, .. } => self.ty(ty),
+        }
+    }
+
+    fn bounds(&self, bounds: &mut [QuantifiedInlineBound]) {
+        for bound in bounds {
+            match &mut bound.bound {
+                InlineBound::TraitBound(bound) => self.trait_bound(bound),
+                InlineBound::AliasEqBound(bound) => {
+                    self.trait_bound(&mut bound.trait_bound);
+                    self.generic_args(&mut bound.args);
+                    self.ty(&mut bound.value);
+                }
+            }
+        }
+    }
+
+    fn trait_bound(&self, bound: &mut TraitBound) {
+        self.name(&mut bound.trait_name);
+        self.generic_args(&mut bound.args_no_self);
+    }
+
+    fn trait_ref(&self, trait_ref: &mut TraitRef) {
+        self.name(&mut trait_ref.trait_name);
+        self.generic_args(&mut trait_ref.args);
+    }
+
+    /// The names of traits and types can only be replaced by a name.
+    fn name(&self, name: &mut Identifier) {
+        if let GenericArg::Id(arg) = self.arg {
+            if self.is_var(name) {
+                *name = arg.clone();
+            }
+        }
+    }
+
+    fn projection_ty(&self, proj: &mut ProjectionTy) {
+        self.trait_ref(&mut proj.trait_ref);
+        self.generic_args(&mut proj.args);
+    }
+
+    fn tys(&self, tys: &mut [Ty]) {
+        for ty in tys {
+            self.ty(ty);
+        }
+    }
+
+    fn ty(&self, ty: &mut Ty) {
+        match ty {
+            Ty::Id { name } => {
+                if self.is_var(name) {
+                    match self.arg {
+                        GenericArg::Ty(arg) => *ty = arg.clone(),
+                        GenericArg::Id(arg) => *name = arg.clone(),
+                        // Left as is, lowering reports the unknown name.
+                        GenericArg::Lifetime(_) | GenericArg::Const(_) => {}
+                    }
+                }
+            }
+            Ty::Dyn { bounds, .. } => self.bounds(bounds),
+            Ty::Apply { name, args } => {
+                self.name(name);
+                self.generic_args(args);
+            }
+            Ty::Projection { proj } => self.projection_ty(proj),
+            Ty::ForAll { types, .. } | Ty::Tuple { types } => {
+                for ty in types {
+                    self.ty(ty);
+                }
+            }
+            Ty::Slice { ty } | Ty::Raw { ty, .. } | Ty::Ref { ty, .. } => self.ty(ty),
+            Ty::Array { ty, len } => {
+                self.ty(ty);
+                self.const_(len);
+            }
+            Ty::Scalar { .. } | Ty::Str | Ty::Never => {}
+        }
+    }
+
+    fn generic_args(&self, args: &mut [GenericArg]) {
+        for arg in args {
+            self.generic_arg(arg);
+        }
+    }
+
+    fn generic_arg(&self, arg: &mut GenericArg) {
+        match arg {
+            GenericArg::Id(name) => {
+                if self.is_var(name) {
+                    *arg = self.arg.clone();
+                }
+            }
+            GenericArg::Ty(ty) => self.ty(ty),
+            GenericArg::Const(value) => self.const_(value),
+            GenericArg::Lifetime(_) => {}
+        }
+    }
+
+    fn const_(&self, value: &mut Const) {
+        match value {
+            Const::Id(name) => {
+                if self.is_var(name) {
+                    match self.arg {
+                        GenericArg::Const(arg) => *value = arg.clone(),
+                        GenericArg::Id(arg) => *name = arg.clone(),
+                        // Left as is, lowering reports the unknown name.
+                        GenericArg::Ty(_) | GenericArg::Lifetime(_) => {}
+                    }
+                }
+            }
+            Const::Value(_) => {}
+            Const::Projection(proj) => self.projection_ty(proj),
+            Const::Expr(expr) => match &mut **expr {
+                ConstExpr::Unary(_, value) => self.const_(value),
+                ConstExpr::Binary(_, a, b) => {
+                    self.const_(a);
+                    self.const_(b);
+                }
+            },
+        }
+    }
+
+    fn clause(&self, clause: &mut Clause) {
+        self.domain_goal(&mut clause.consequence);
+        for goal in &mut clause.conditions {
+            self.goal(goal);
+        }
+    }
+
+    fn goal(&self, goal: &mut Goal) {
+        match goal {
+            Goal::ForAll(_, goal)
+            | Goal::Exists(_, goal)
+            | Goal::Not(goal)
+            | Goal::Compatible(goal) => self.goal(goal),
+            Goal::Implies(clauses, goal) => {
+                for clause in clauses {
+                    self.clause(clause);
+                }
+                self.goal(goal);
+            }
+            Goal::And(goal, goals) => {
+                self.goal(goal);
+                for goal in goals {
+                    self.goal(goal);
+                }
+            }
+            Goal::Leaf(LeafGoal::DomainGoal { goal }) => self.domain_goal(goal),
+            Goal::Leaf(LeafGoal::UnifyGenericArgs { a, b }) => {
+                self.generic_arg(a);
+                self.generic_arg(b);
+            }
+            Goal::Leaf(LeafGoal::SubtypeTys { a, b }) => {
+                self.ty(a);
+                self.ty(b);
+            }
+        }
+    }
+
+    fn domain_goal(&self, goal: &mut DomainGoal) {
+        match goal {
+            DomainGoal::Holds { where_clause } => self.where_clause(where_clause),
+            DomainGoal::Normalize { projection, ty } => {
+                self.projection_ty(projection);
+                self.ty(ty);
+            }
+            DomainGoal::NormalizeConst { projection, value } => {
+                self.projection_ty(projection);
+                self.const_(value);
+            }
+            DomainGoal::TraitRefWellFormed { trait_ref }
+            | DomainGoal::TraitRefFromEnv { trait_ref }
+            | DomainGoal::LocalImplAllowed { trait_ref } => self.trait_ref(trait_ref),
+            DomainGoal::TyWellFormed { ty }
+            | DomainGoal::TyFromEnv { ty }
+            | DomainGoal::IsLocal { ty }
+            | DomainGoal::IsUpstream { ty }
+            | DomainGoal::IsFullyVisible { ty }
+            | DomainGoal::DownstreamType { ty } => self.ty(ty),
+            DomainGoal::ObjectSafe { id } => self.name(id),
+            DomainGoal::Compatible | DomainGoal::Reveal => {}
+        }
+    }
+}
@@ -8,9 +8,10 @@ use chalk_ir::zip::Zip;
 use chalk_ir::{
     Binders, Canonical, ConstrainedSubst, Constraint, Constraints, DomainGoal, Environment, EqGoal,
     Fallible, GenericArg, Goal, GoalData, InEnvironment, NoSolution, ProgramClauseImplication,
-    QuantifierKind, Substitution, UCanonical, UniverseMap,
+    QuantifierKind, Substitution, SubtypeGoal, Ty, UCanonical, UniverseMap, Variance,
 };
 use chalk_solve::debug_span;
+use chalk_solve::RustIrDatabase;
 use rustc_hash::FxHashSet;
 use std::fmt::Debug;
 use tracing::{debug, instrument};
@@ -103,6 +104,16 @@ pub(super) trait RecursiveInferenceTable<I: Interner> {
     where
         T: ?Sized + Zip<I>;
 
+    fn relate(
+        &mut self,
+        interner: &I,
+        db: &dyn RustIrDatabase<I>,
+        environment: &Environment<I>,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+    ) -> Fallible<Vec<InEnvironment<Goal<I>>>>;
+
     fn instantiate_canonical<T>(&mut self, interner: &I, bound: &Canonical<T>) -> T::Result
     where
         T: HasInterner<Interner = I> + Fold<I> + Debug;
@@ -275,6 +286,34 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>, Infer: RecursiveInferenceTable<I
         Ok(())
     }
 
+    /// Relates `a` and `b` according to `variance` in the given environment.
+    ///
+    /// Wraps `InferenceTable::relate`; as with `unify`, the resulting goals
+    /// are added into our list of pending obligations.
+    fn relate(
+        &mut self,
+        environment: &Environment<I>,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+    ) -> Fallible<()> {
+        let goals = self.infer.relate(
+            self.solver.interner(),
+            self.solver.db(),
+            environment,
+            variance,
+            a,
+            b,
+        )?;
+        debug!("relate({:?}, {:?}, {:?}) succeeded", variance, a, b);
+        debug!("relate: goals={:?}", goals);
+        for goal in goals {
+            let goal = goal.cast(self.solver.interner());
+            self.push_obligation(Obligation::Prove(goal));
+        }
+        Ok(())
+    }
+
     /// Create obligations for the given goal in the given environment. This may
     /// ultimately create any number of obligations.
     #[instrument(level = "debug", skip(self))]
@@ -318,6 +357,9 @@ impl<'s, I: Interner, Solver: SolveDatabase<I>, Infer: RecursiveInferenceTable<I
             GoalData::EqGoal(EqGoal { a, b }) => {
                 self.unify(&environment, &a, &b)?;
             }
+            GoalData::SubtypeGoal(SubtypeGoal { a, b }) => {
+                self.relate(environment, Variance::Covariant, a, b)?;
+            }
             GoalData::CannotProve => {
                 debug!("Pushed a CannotProve goal, setting cannot_prove = true");
                 self.cannot_prove = true;
@@ -10,6 +10,7 @@ pub type UCanonicalGoal<I> = UCanonical<InEnvironment<Goal<I>>>;
 
 mod combine;
 mod fulfill;
+mod proof_tree;
 mod recursive;
 mod search_graph;
 pub mod solve;
@@ -166,6 +167,18 @@ impl<I: Interner> Solution<I> {
         }
     }
 
+    /// Converts this solution into the `Solution` type of `chalk_solve`.
+    pub(crate) fn into_chalk_solve(self) -> chalk_solve::Solution<I> {
+        match self {
+            Solution::Unique(c) => chalk_solve::Solution::Unique(c),
+            Solution::Ambig(g) => chalk_solve::Solution::Ambig(match g {
+                Guidance::Definite(g) => chalk_solve::Guidance::Definite(g),
+                Guidance::Suggested(g) => chalk_solve::Guidance::Suggested(g),
+                Guidance::Unknown => chalk_solve::Guidance::Unknown,
+            }),
+        }
+    }
+
     pub fn is_unique(&self) -> bool {
         match *self {
             Solution::Unique(..) => true,
@@ -0,0 +1,109 @@
+use crate::{Solution, UCanonicalGoal};
+use chalk_ir::interner::Interner;
+use chalk_ir::{Fallible, ProgramClause};
+use chalk_solve::solve::proof_tree::{ClauseApplication, ClauseSource, Derivation, ProofTree};
+
+/// Records the goals solved by the recursive solver, building up a
+/// `ProofTree` for `RecursiveSolver::explain`.
+///
+/// The solver reports events as it goes: a goal is started when
+/// `solve_goal` is entered and finished when it returns, so the goals
+/// currently being solved form a stack. A finished goal becomes a subgoal
+/// of the goal below it on the stack -- more precisely, of the clause that
+/// was being applied to that goal, if any.
+pub(crate) struct ProofTreeBuilder<I: Interner> {
+    stack: Vec<ProofTree<I>>,
+    root: Option<ProofTree<I>>,
+}
+
+impl<I: Interner> ProofTreeBuilder<I> {
+    pub(crate) fn new() -> Self {
+        ProofTreeBuilder {
+            stack: vec![],
+            root: None,
+        }
+    }
+
+    /// Returns the tree for the root goal, once it has been finished.
+    pub(crate) fn into_proof_tree(self) -> Option<ProofTree<I>> {
+        self.root
+    }
+
+    pub(crate) fn start_goal(&mut self, goal: &UCanonicalGoal<I>) {
+        self.stack.push(ProofTree {
+            goal: goal.clone(),
+            solution: None,
+            derivation: Derivation::Simplified(vec![]),
+        });
+    }
+
+    /// Called when the solver starts a new iteration for the current goal,
+    /// which happens when it is part of a cycle. Only the derivation from
+    /// the final iteration is kept.
+    pub(crate) fn start_iteration(&mut self) {
+        self.current().derivation = Derivation::Simplified(vec![]);
+    }
+
+    pub(crate) fn floundered(&mut self) {
+        self.current().derivation = Derivation::Floundered;
+    }
+
+    pub(crate) fn start_clause(&mut self, clause: &ProgramClause<I>, source: ClauseSource) {
+        let current = self.current();
+        if !matches!(current.derivation, Derivation::Clauses(_)) {
+            current.derivation = Derivation::Clauses(vec![]);
+        }
+        if let Derivation::Clauses(applications) = &mut current.derivation {
+            applications.push(ClauseApplication {
+                clause: clause.clone(),
+                source,
+                subgoals: vec![],
+                succeeded: false,
+            });
+        }
+    }
+
+    pub(crate) fn finish_clause(&mut self, succeeded: bool) {
+        if let Derivation::Clauses(applications) = &mut self.current().derivation {
+            if let Some(application) = applications.last_mut() {
+                application.succeeded = succeeded;
+            }
+        }
+    }
+
+    /// Finishes the current goal with the given `solution`. If `cycle` is
+    /// true, the solution was taken from an enclosing attempt to solve the
+    /// same goal.
+    pub(crate) fn finish_goal(&mut self, solution: &Fallible<Solution<I>>, cycle: bool) {
+        let mut tree = self
+            .stack
+            .pop()
+            .expect("finish_goal called without a goal being solved");
+        tree.solution = solution.clone().ok().map(Solution::into_chalk_solve);
+        if cycle {
+            tree.derivation = Derivation::Cycle;
+        }
+
+        let parent = match self.stack.last_mut() {
+            Some(parent) => parent,
+            None => {
+                self.root = Some(tree);
+                return;
+            }
+        };
+        match &mut parent.derivation {
+            Derivation::Simplified(subgoals) => subgoals.push(tree),
+            Derivation::Clauses(applications) => match applications.last_mut() {
+                Some(application) => application.subgoals.push(tree),
+                None => unreachable!("clauses derivation without clauses"),
+            },
+            Derivation::Floundered | Derivation::Cycle => {}
+        }
+    }
+
+    fn current(&mut self) -> &mut ProofTree<I> {
+        self.stack
+            .last_mut()
+            .expect("no goal is currently being solved")
+    }
+}
@@ -1,14 +1,18 @@
+use crate::proof_tree::ProofTreeBuilder;
 use crate::search_graph::DepthFirstNumber;
 use crate::search_graph::SearchGraph;
 use crate::solve::{SolveDatabase, SolveIteration};
 use crate::stack::{Stack, StackDepth};
-use crate::{combine, Guidance, Minimums, Solution, UCanonicalGoal};
+use crate::{combine, Minimums, Solution, UCanonicalGoal};
 use chalk_ir::interner::Interner;
 use chalk_ir::Fallible;
 use chalk_ir::{Canonical, ConstrainedSubst, Constraints, Goal, InEnvironment, UCanonical};
+use chalk_solve::solve::proof_tree::ProofTree;
+use chalk_solve::solve::stats::{QueryStats, RecursiveStats, SolverStats};
 use chalk_solve::{coinductive_goal::IsCoinductive, RustIrDatabase};
 use rustc_hash::FxHashMap;
 use std::fmt;
+use std::time::Instant;
 use tracing::debug;
 use tracing::{info, instrument};
 
@@ -25,6 +29,14 @@ struct RecursiveContext<I: Interner> {
     cache: FxHashMap<UCanonicalGoal<I>, Fallible<Solution<I>>>,
 
     caching_enabled: bool,
+
+    /// Records the goals that are solved, when explaining a solution (see
+    /// `RecursiveSolver::explain`).
+    proof_tree: Option<ProofTreeBuilder<I>>,
+
+    /// The statistics for the current query, if they are being recorded
+    /// (see `RecursiveSolver::record_stats`).
+    stats: Option<RecursiveStats>,
 }
 
 /// A Solver is the basic context in which you can propose goals for a given
@@ -39,14 +51,41 @@ struct Solver<'me, I: Interner> {
 
 pub struct RecursiveSolver<I: Interner> {
     ctx: Box<RecursiveContext<I>>,
+
+    /// The statistics recorded for each query, if recording is enabled.
+    stats: Option<Vec<QueryStats<I>>>,
 }
 
 impl<I: Interner> RecursiveSolver<I> {
     pub fn new(overflow_depth: usize, caching_enabled: bool) -> Self {
         Self {
             ctx: Box::new(RecursiveContext::new(overflow_depth, caching_enabled)),
+            stats: None,
         }
     }
+
+    /// Runs `op`, a query on `goal`, recording its statistics if recording
+    /// is enabled.
+    fn record_query<R>(
+        &mut self,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+        op: impl FnOnce(&mut RecursiveContext<I>) -> R,
+    ) -> R {
+        let stats = match &mut self.stats {
+            Some(stats) => stats,
+            None => return op(&mut self.ctx),
+        };
+        self.ctx.stats = Some(RecursiveStats::default());
+        let start = Instant::now();
+        let result = op(&mut self.ctx);
+        let time = start.elapsed();
+        stats.push(QueryStats {
+            goal: goal.clone(),
+            time,
+            details: SolverStats::Recursive(self.ctx.stats.take().unwrap()),
+        });
+        result
+    }
 }
 
 impl<I: Interner> fmt::Debug for RecursiveSolver<I> {
@@ -82,6 +121,8 @@ impl<I: Interner> RecursiveContext<I> {
             search_graph: SearchGraph::new(),
             cache: FxHashMap::default(),
             caching_enabled,
+            proof_tree: None,
+            stats: None,
         }
     }
 
@@ -122,6 +163,50 @@ impl<'me, I: Interner> Solver<'me, I> {
         self.solve_goal(canonical_goal.clone(), minimums)
     }
 
+    /// Solves a canonical goal like `solve_root_goal`, but returns the
+    /// distinct candidate solutions found for each program clause instead of
+    /// combining them into a single solution. For example, given the program
+    ///
+    /// ```ignore
+    /// struct u8 { }
+    /// struct u16 { }
+    /// trait Foo { }
+    /// impl Foo for u8 { }
+    /// impl Foo for u16 { }
+    /// ```
+    ///
+    /// and the goal `exists<T> { T: Foo }`, this function will return the
+    /// solutions `?0 := u8` and `?0 := u16`, where `solve_root_goal` would
+    /// return an ambiguous solution.
+    pub(crate) fn solve_root_goal_multiple(
+        &mut self,
+        canonical_goal: &UCanonicalGoal<I>,
+    ) -> Vec<Solution<I>> {
+        debug!(
+            "solve_root_goal_multiple(canonical_goal={:?})",
+            canonical_goal
+        );
+        assert!(self.context.stack.is_empty());
+        let minimums = &mut Minimums::new();
+        let mut candidates = self.solve_iteration_candidates(canonical_goal, minimums);
+        let mut seen = vec![];
+        candidates.retain(|candidate| {
+            if seen.contains(candidate) {
+                false
+            } else {
+                seen.push(candidate.clone());
+                true
+            }
+        });
+        candidates
+    }
+
+    fn finish_proof_tree_goal(&mut self, result: &Fallible<Solution<I>>, cycle: bool) {
+        if let Some(proof_tree) = &mut self.context.proof_tree {
+            proof_tree.finish_goal(result, cycle);
+        }
+    }
+
     #[instrument(level = "debug", skip(self))]
     fn solve_new_subgoal(
         &mut self,
@@ -139,6 +224,10 @@ impl<'me, I: Interner> Solver<'me, I> {
         // the function which maps the loop iteration to `answer` is a nondecreasing function
         // so this function will eventually be constant and the loop terminates.
         loop {
+            if let Some(proof_tree) = &mut self.context.proof_tree {
+                proof_tree.start_iteration();
+            }
+
             let minimums = &mut Minimums::new();
             let (current_answer, current_prio) = self.solve_iteration(&canonical_goal, minimums);
 
@@ -191,6 +280,9 @@ impl<'me, I: Interner> Solver<'me, I> {
 
             // Otherwise: rollback the search tree and try again.
             self.context.search_graph.rollback_to(dfn + 1);
+            if let Some(stats) = &mut self.context.stats {
+                stats.fixpoint_iterations += 1;
+            }
         }
     }
 }
@@ -208,9 +300,16 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
         // First check the cache.
         if let Some(value) = self.context.cache.get(&goal) {
             debug!("solve_reduced_goal: cache hit, value={:?}", value);
+            if let Some(stats) = &mut self.context.stats {
+                stats.cache_hits += 1;
+            }
             return value.clone();
         }
 
+        if let Some(proof_tree) = &mut self.context.proof_tree {
+            proof_tree.start_goal(&goal);
+        }
+
         // Next, check if the goal is in the search tree already.
         if let Some(dfn) = self.context.search_graph.lookup(&goal) {
             // Check if this table is still on the stack.
@@ -226,10 +325,12 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
                         constraints: Constraints::empty(self.program.interner()),
                     };
                     debug!("applying coinductive semantics");
-                    return Ok(Solution::Unique(Canonical {
+                    let result = Ok(Solution::Unique(Canonical {
                         value,
                         binders: goal.canonical.binders,
                     }));
+                    self.finish_proof_tree_goal(&result, true);
+                    return result;
                 }
 
                 self.context.stack[depth].flag_cycle();
@@ -244,6 +345,7 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
                 "solve_goal: cycle detected, previous solution {:?} with prio {:?}",
                 previous_solution, previous_solution_priority
             );
+            self.finish_proof_tree_goal(&previous_solution, true);
             previous_solution
         } else {
             // Otherwise, push the goal onto the stack and create a table.
@@ -251,6 +353,10 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
             let coinductive_goal = goal.is_coinductive(self.program);
             let depth = self.context.stack.push(coinductive_goal);
             let dfn = self.context.search_graph.insert(&goal, depth);
+            if let Some(stats) = &mut self.context.stats {
+                stats.search_graph_size += 1;
+                stats.max_stack_depth = stats.max_stack_depth.max(self.context.stack.len());
+            }
             let subgoal_minimums = self.solve_new_subgoal(goal, depth, dfn);
             self.context.search_graph[dfn].links = subgoal_minimums;
             self.context.search_graph[dfn].stack_depth = None;
@@ -280,6 +386,7 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
             }
 
             info!("solve_goal: solution = {:?} prio {:?}", result, priority);
+            self.finish_proof_tree_goal(&result, false);
             result
         }
     }
@@ -288,6 +395,10 @@ impl<'me, I: Interner> SolveDatabase<I> for Solver<'me, I> {
         &self.program.interner()
     }
 
+    fn proof_tree(&mut self) -> Option<&mut ProofTreeBuilder<I>> {
+        self.context.proof_tree.as_mut()
+    }
+
     fn db(&self) -> &dyn RustIrDatabase<I> {
         self.program
     }
@@ -299,18 +410,9 @@ impl<I: Interner> chalk_solve::Solver<I> for RecursiveSolver<I> {
         program: &dyn RustIrDatabase<I>,
         goal: &UCanonical<InEnvironment<Goal<I>>>,
     ) -> Option<chalk_solve::Solution<I>> {
-        self.ctx
-            .solver(program)
-            .solve_root_goal(goal)
+        self.record_query(goal, |ctx| ctx.solver(program).solve_root_goal(goal))
             .ok()
-            .map(|s| match s {
-                Solution::Unique(c) => chalk_solve::Solution::Unique(c),
-                Solution::Ambig(g) => chalk_solve::Solution::Ambig(match g {
-                    Guidance::Definite(g) => chalk_solve::Guidance::Definite(g),
-                    Guidance::Suggested(g) => chalk_solve::Guidance::Suggested(g),
-                    Guidance::Unknown => chalk_solve::Guidance::Unknown,
-                }),
-            })
+            .map(Solution::into_chalk_solve)
     }
 
     fn solve_limited(
@@ -320,29 +422,76 @@ impl<I: Interner> chalk_solve::Solver<I> for RecursiveSolver<I> {
         _should_continue: &dyn std::ops::Fn() -> bool,
     ) -> Option<chalk_solve::Solution<I>> {
         // TODO support should_continue in recursive solver
-        self.ctx
-            .solver(program)
-            .solve_root_goal(goal)
+        self.record_query(goal, |ctx| ctx.solver(program).solve_root_goal(goal))
             .ok()
-            .map(|s| match s {
-                Solution::Unique(c) => chalk_solve::Solution::Unique(c),
-                Solution::Ambig(g) => chalk_solve::Solution::Ambig(match g {
-                    Guidance::Definite(g) => chalk_solve::Guidance::Definite(g),
-                    Guidance::Suggested(g) => chalk_solve::Guidance::Suggested(g),
-                    Guidance::Unknown => chalk_solve::Guidance::Unknown,
-                }),
-            })
+            .map(Solution::into_chalk_solve)
+    }
+
+    fn explain(
+        &mut self,
+        program: &dyn RustIrDatabase<I>,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<ProofTree<I>> {
+        // Goals answered from the cache would have no derivation, so we
+        // explain in a fresh context, with caching disabled.
+        let mut ctx = RecursiveContext::new(self.ctx.stack.overflow_depth(), false);
+        ctx.proof_tree = Some(ProofTreeBuilder::new());
+        let _ = ctx.solver(program).solve_root_goal(goal);
+        ctx.proof_tree.and_then(ProofTreeBuilder::into_proof_tree)
     }
 
     fn solve_multiple(
         &mut self,
-        _program: &dyn RustIrDatabase<I>,
-        _goal: &UCanonical<InEnvironment<Goal<I>>>,
-        _f: &mut dyn FnMut(
+        program: &dyn RustIrDatabase<I>,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+        f: &mut dyn FnMut(
             chalk_solve::SubstitutionResult<Canonical<ConstrainedSubst<I>>>,
             bool,
         ) -> bool,
     ) -> bool {
-        unimplemented!("Recursive solver doesn't support multiple answers")
+        let interner = program.interner();
+        let mut candidates = self
+            .record_query(goal, |ctx| {
+                ctx.solver(program).solve_root_goal_multiple(goal)
+            })
+            .into_iter()
+            .peekable();
+        while let Some(candidate) = candidates.next() {
+            // An ambiguous candidate without guidance constrains nothing.
+            let constrained_subst =
+                candidate
+                    .constrained_subst(interner)
+                    .unwrap_or_else(|| Canonical {
+                        value: ConstrainedSubst {
+                            subst: goal.trivial_substitution(interner),
+                            constraints: Constraints::empty(interner),
+                        },
+                        binders: goal.canonical.binders.clone(),
+                    });
+            let subst = if candidate.is_unique() {
+                chalk_solve::SubstitutionResult::Definite(constrained_subst)
+            } else {
+                chalk_solve::SubstitutionResult::Ambiguous(constrained_subst)
+            };
+            if !f(subst, candidates.peek().is_some()) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn record_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.stats = None;
+        } else if self.stats.is_none() {
+            self.stats = Some(vec![]);
+        }
+    }
+
+    fn take_stats(&mut self) -> Vec<QueryStats<I>> {
+        match &mut self.stats {
+            Some(stats) => std::mem::take(stats),
+            None => vec![],
+        }
     }
 }
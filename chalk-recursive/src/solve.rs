@@ -1,6 +1,8 @@
 use super::combine;
 use super::fulfill::{Fulfill, RecursiveInferenceTable};
+use crate::proof_tree::ProofTreeBuilder;
 use crate::{Guidance, Minimums, Solution, UCanonicalGoal};
+use chalk_ir::could_match::CouldMatch;
 use chalk_ir::fold::Fold;
 use chalk_ir::interner::{HasInterner, Interner};
 use chalk_ir::visit::Visit;
@@ -8,11 +10,12 @@ use chalk_ir::zip::Zip;
 use chalk_ir::{
     Binders, Canonical, ClausePriority, DomainGoal, Environment, Fallible, Floundered, GenericArg,
     Goal, GoalData, InEnvironment, NoSolution, ProgramClause, ProgramClauseData,
-    ProgramClauseImplication, Substitution, UCanonical, UniverseMap,
+    ProgramClauseImplication, Substitution, Ty, UCanonical, UniverseMap, Variance,
 };
 use chalk_solve::clauses::program_clauses_for_goal;
 use chalk_solve::debug_span;
 use chalk_solve::infer::{InferenceTable, ParameterEnaVariableExt};
+use chalk_solve::solve::proof_tree::ClauseSource;
 use chalk_solve::{solve::truncate, RustIrDatabase};
 use std::fmt::Debug;
 use tracing::{debug, instrument};
@@ -27,6 +30,10 @@ pub(super) trait SolveDatabase<I: Interner>: Sized {
     fn interner(&self) -> &I;
 
     fn db(&self) -> &dyn RustIrDatabase<I>;
+
+    /// The proof tree being recorded, if the solver is explaining a
+    /// solution.
+    fn proof_tree(&mut self) -> Option<&mut ProofTreeBuilder<I>>;
 }
 
 /// The `solve_iteration` method -- implemented for any type that implements
@@ -76,6 +83,9 @@ pub(super) trait SolveIteration<I: Interner>: SolveDatabase<I> {
                     match prog_clauses {
                         Ok(clauses) => self.solve_from_clauses(&canonical_goal, clauses, minimums),
                         Err(Floundered) => {
+                            if let Some(proof_tree) = self.proof_tree() {
+                                proof_tree.floundered();
+                            }
                             (Ok(Solution::Ambig(Guidance::Unknown)), ClausePriority::High)
                         }
                     }
@@ -98,6 +108,75 @@ pub(super) trait SolveIteration<I: Interner>: SolveDatabase<I> {
             }
         }
     }
+
+    /// Like `solve_iteration`, but instead of combining the solutions
+    /// obtained from each program clause, returns one solution per clause
+    /// that applies. Goals that are not domain goals are solved by
+    /// simplification and yield at most one candidate.
+    #[instrument(level = "debug", skip(self))]
+    fn solve_iteration_candidates(
+        &mut self,
+        canonical_goal: &UCanonicalGoal<I>,
+        minimums: &mut Minimums,
+    ) -> Vec<Solution<I>> {
+        let UCanonical {
+            universes,
+            canonical:
+                Canonical {
+                    binders,
+                    value: InEnvironment { environment, goal },
+                },
+        } = canonical_goal.clone();
+
+        match goal.data(self.interner()) {
+            GoalData::DomainGoal(domain_goal) => {
+                let canonical_goal = UCanonical {
+                    universes,
+                    canonical: Canonical {
+                        binders,
+                        value: InEnvironment {
+                            environment,
+                            goal: domain_goal.clone(),
+                        },
+                    },
+                };
+
+                let clauses = match self.program_clauses_for_goal(&canonical_goal) {
+                    Ok(clauses) => clauses,
+                    Err(Floundered) => return vec![Solution::Ambig(Guidance::Unknown)],
+                };
+
+                let mut candidates = vec![];
+                for program_clause in clauses {
+                    debug_span!("solve_iteration_candidates", clause = ?program_clause);
+
+                    let ProgramClauseData(implication) = program_clause.data(self.interner());
+                    if let (Ok(solution), _) =
+                        self.solve_via_implication(&canonical_goal, implication, minimums)
+                    {
+                        debug!(?solution, "Ok");
+                        candidates.push(solution);
+                    } else {
+                        debug!("Error");
+                    }
+                }
+                candidates
+            }
+
+            _ => {
+                let canonical_goal = UCanonical {
+                    universes,
+                    canonical: Canonical {
+                        binders,
+                        value: InEnvironment { environment, goal },
+                    },
+                };
+
+                let (solution, _) = self.solve_via_simplification(&canonical_goal, minimums);
+                solution.into_iter().collect()
+            }
+        }
+    }
 }
 
 impl<S, I> SolveIteration<I> for S
@@ -134,8 +213,25 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
     where
         C: IntoIterator<Item = ProgramClause<I>>,
     {
+        // When explaining, we record which clauses come from the environment.
+        // `program_clauses_for_goal` lists those last, so they are the ones
+        // starting at `first_env_clause`.
+        let clauses: Vec<_> = clauses.into_iter().collect();
+        let first_env_clause = if self.proof_tree().is_some() {
+            let interner = self.interner();
+            let environment = &canonical_goal.canonical.value.environment;
+            let env_clauses = self.db().program_clauses_for_env(environment);
+            let num_env_clauses = env_clauses
+                .iter(interner)
+                .filter(|&c| c.could_match(interner, &canonical_goal.canonical.value.goal))
+                .count();
+            clauses.len() - num_env_clauses
+        } else {
+            clauses.len()
+        };
+
         let mut cur_solution = None;
-        for program_clause in clauses {
+        for (index, program_clause) in clauses.into_iter().enumerate() {
             debug_span!("solve_from_clauses", clause = ?program_clause);
 
             // If we have a completely ambiguous answer, it's not going to get better, so stop
@@ -143,9 +239,22 @@ trait SolveIterationHelpers<I: Interner>: SolveDatabase<I> {
                 return (Ok(Solution::Ambig(Guidance::Unknown)), ClausePriority::High);
             }
 
+            if let Some(proof_tree) = self.proof_tree() {
+                let source = if index >= first_env_clause {
+                    ClauseSource::Environment
+                } else {
+                    ClauseSource::Program
+                };
+                proof_tree.start_clause(&program_clause, source);
+            }
+
             let ProgramClauseData(implication) = program_clause.data(self.interner());
             let res = self.solve_via_implication(canonical_goal, implication, minimums);
 
+            if let Some(proof_tree) = self.proof_tree() {
+                proof_tree.finish_clause(res.0.is_ok());
+            }
+
             if let (Ok(solution), priority) = res {
                 debug!(?solution, ?priority, "Ok");
                 cur_solution = Some(match cur_solution {
@@ -290,6 +399,21 @@ impl<I: Interner> RecursiveInferenceTable<I> for RecursiveInferenceTableImpl<I>
         Ok(res.goals)
     }
 
+    fn relate(
+        &mut self,
+        interner: &I,
+        db: &dyn RustIrDatabase<I>,
+        environment: &Environment<I>,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+    ) -> Fallible<Vec<InEnvironment<Goal<I>>>> {
+        let res = self
+            .infer
+            .relate(interner, db, environment, variance, a, b)?;
+        Ok(res.goals)
+    }
+
     fn instantiate_canonical<T>(&mut self, interner: &I, bound: &Canonical<T>) -> T::Result
     where
         T: HasInterner<Interner = I> + Fold<I> + Debug,
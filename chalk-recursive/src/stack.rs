@@ -35,10 +35,18 @@ impl Stack {
         }
     }
 
+    pub(crate) fn overflow_depth(&self) -> usize {
+        self.overflow_depth
+    }
+
     pub(crate) fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
     pub(crate) fn push(&mut self, coinductive_goal: bool) -> StackDepth {
         let depth = StackDepth {
             depth: self.entries.len(),
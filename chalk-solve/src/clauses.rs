@@ -2,6 +2,7 @@ use self::builder::ClauseBuilder;
 use self::env_elaborator::elaborate_env_clauses;
 use self::program_clauses::ToProgramClauses;
 use crate::goal_builder::GoalBuilder;
+use crate::rust_ir::{Movability, WellKnownTrait};
 use crate::split::Split;
 use crate::RustIrDatabase;
 use chalk_ir::cast::{Cast, Caster};
@@ -171,6 +172,18 @@ pub fn push_auto_trait_impls<I: Interner>(
             Ok(())
         }
 
+        // Movable generators are always `Unpin`, and `static` ones never
+        // are, whatever their upvars and witness types.
+        TyKind::Generator(generator_id, _)
+            if builder.db.well_known_trait_id(WellKnownTrait::Unpin) == Some(auto_trait_id) =>
+        {
+            match builder.db.generator_datum(*generator_id).movability {
+                Movability::Movable => builder.push_fact(consequence),
+                Movability::Static => {}
+            }
+            Ok(())
+        }
+
         TyKind::GeneratorWitness(generator_id, _) => {
             push_auto_trait_impls_generator_witness(builder, auto_trait_id, *generator_id);
             Ok(())
@@ -536,6 +549,20 @@ fn program_clauses_that_could_match<I: Interner>(
                 builder.push_fact(DomainGoal::ObjectSafe(*trait_id));
             }
         }
+        DomainGoal::ClosureKind(ty, kind) => match ty.kind(interner) {
+            TyKind::Closure(closure_id, substitution) => {
+                let consequence = DomainGoal::ClosureKind(ty.clone(), *kind);
+                match db.closure_kind(*closure_id, substitution) {
+                    Some(closure_kind) if closure_kind <= *kind => builder.push_fact(consequence),
+                    Some(_) => {}
+                    // Ambiguous until the kind is known.
+                    None => builder
+                        .push_clause(consequence, Some(GoalData::CannotProve.intern(interner))),
+                }
+            }
+            _ if ty.is_general_var(interner, binders) => return Err(Floundered),
+            _ => {}
+        },
         DomainGoal::WellFormed(WellFormed::Ty(ty))
         | DomainGoal::IsUpstream(ty)
         | DomainGoal::DownstreamType(ty)
@@ -598,6 +625,28 @@ fn program_clauses_that_could_match<I: Interner>(
             }
             AliasTy::Opaque(_) => (),
         },
+        DomainGoal::NormalizeConst(NormalizeConst { alias, value: _ }) => {
+            // As for associated types, `NormalizeConst` goals derive from
+            // the `AssociatedConstValue` datums found in the impls of the
+            // trait.
+            let associated_const_datum = db.associated_const_data(alias.associated_const_id);
+            let trait_id = associated_const_datum.trait_id;
+            let trait_datum = db.trait_datum(trait_id);
+
+            // Flounder if the self-type is unknown and the trait is non-enumerable.
+            let self_ty = alias.self_type_parameter(interner);
+            if self_ty.is_general_var(interner, binders) && trait_datum.is_non_enumerable_trait() {
+                return Err(Floundered);
+            }
+
+            push_program_clauses_for_associated_const_values_in_impls_of(
+                builder,
+                environment,
+                trait_id,
+                alias.substitution.as_slice(interner),
+                binders,
+            );
+        }
         DomainGoal::Compatible | DomainGoal::Reveal => (),
     };
 
@@ -702,6 +751,34 @@ fn push_program_clauses_for_associated_type_values_in_impls_of<I: Interner>(
     }
 }
 
+/// Generate program clauses from the associated-const values found in
+/// impls of the given trait, i.e. from each `const N: usize = ...;` found
+/// in any impl of `Len`.
+#[instrument(level = "debug", skip(builder))]
+fn push_program_clauses_for_associated_const_values_in_impls_of<I: Interner>(
+    builder: &mut ClauseBuilder<'_, I>,
+    environment: &Environment<I>,
+    trait_id: TraitId<I>,
+    trait_parameters: &[GenericArg<I>],
+    binders: &CanonicalVarKinds<I>,
+) {
+    for impl_id in builder
+        .db
+        .impls_for_trait(trait_id, trait_parameters, binders)
+    {
+        let impl_datum = builder.db.impl_datum(impl_id);
+        if !impl_datum.is_positive() {
+            continue;
+        }
+
+        for &value_id in &impl_datum.associated_const_value_ids {
+            let value = builder.db.associated_const_value(value_id);
+            debug!(?value_id, ?value);
+            value.to_program_clauses(builder, environment);
+        }
+    }
+}
+
 /// Examine `T` and push clauses that may be relevant to proving the
 /// following sorts of goals (and maybe others):
 ///
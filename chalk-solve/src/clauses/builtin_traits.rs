@@ -3,8 +3,13 @@ use crate::{CanonicalVarKinds, Interner, RustIrDatabase, TraitRef, WellKnownTrai
 use chalk_ir::{Floundered, Substitution, Ty};
 
 mod clone;
+mod coerce_unsized;
 mod copy;
+mod deref;
+mod discriminant_kind;
 mod fn_family;
+mod generator;
+mod pointee;
 mod sized;
 mod unsize;
 
@@ -41,15 +46,29 @@ pub fn add_builtin_program_clauses<I: Interner>(
             WellKnownTrait::Unsize => {
                 unsize::add_unsize_program_clauses(db, builder, &trait_ref, ty)
             }
+            WellKnownTrait::Generator => {
+                generator::add_generator_program_clauses(db, builder, self_ty)?
+            }
+            WellKnownTrait::CoerceUnsized => {
+                coerce_unsized::add_coerce_unsized_program_clauses(db, builder, &trait_ref, ty)?
+            }
+            WellKnownTrait::Deref | WellKnownTrait::DerefMut => {
+                deref::add_deref_program_clauses(db, builder, well_known, self_ty)?
+            }
+            WellKnownTrait::DiscriminantKind => {
+                discriminant_kind::add_discriminant_kind_program_clauses(db, builder, self_ty)?
+            }
+            WellKnownTrait::Pointee => pointee::add_pointee_program_clauses(db, builder, self_ty)?,
             // There are no builtin impls provided for the following traits:
-            WellKnownTrait::Unpin | WellKnownTrait::Drop | WellKnownTrait::CoerceUnsized => (),
+            WellKnownTrait::Unpin | WellKnownTrait::Drop => (),
         }
         Ok(())
     })
 }
 
 /// Like `add_builtin_program_clauses`, but for `DomainGoal::Normalize` involving
-/// a projection (e.g. `<fn(u8) as FnOnce<(u8,)>>::Output`)
+/// a projection (e.g. `<fn(u8) as FnOnce<(u8,)>>::Output`,
+/// `<G as Generator<R>>::Yield` or `<&T as Deref>::Target`)
 pub fn add_builtin_assoc_program_clauses<I: Interner>(
     db: &dyn RustIrDatabase<I>,
     builder: &mut ClauseBuilder<'_, I>,
@@ -67,6 +86,35 @@ pub fn add_builtin_assoc_program_clauses<I: Interner>(
                 Ok(())
             })
         }
+        WellKnownTrait::Generator => {
+            let generalized = generalize::Generalize::apply(db.interner(), &self_ty);
+
+            builder.push_binders(&generalized, |builder, self_ty| {
+                generator::add_generator_program_clauses(db, builder, self_ty)?;
+                Ok(())
+            })
+        }
+        WellKnownTrait::Deref => {
+            let generalized = generalize::Generalize::apply(db.interner(), &self_ty);
+
+            builder.push_binders(&generalized, |builder, self_ty| {
+                deref::add_deref_program_clauses(db, builder, well_known, self_ty)
+            })
+        }
+        WellKnownTrait::DiscriminantKind => {
+            let generalized = generalize::Generalize::apply(db.interner(), &self_ty);
+
+            builder.push_binders(&generalized, |builder, self_ty| {
+                discriminant_kind::add_discriminant_kind_program_clauses(db, builder, self_ty)
+            })
+        }
+        WellKnownTrait::Pointee => {
+            let generalized = generalize::Generalize::apply(db.interner(), &self_ty);
+
+            builder.push_binders(&generalized, |builder, self_ty| {
+                pointee::add_pointee_program_clauses(db, builder, self_ty)
+            })
+        }
         _ => Ok(()),
     }
 }
@@ -0,0 +1,121 @@
+use std::iter;
+
+use crate::clauses::ClauseBuilder;
+use crate::{Interner, RustIrDatabase, TraitRef, WellKnownTrait};
+use chalk_ir::cast::Cast;
+use chalk_ir::{
+    Floundered, Goal, Lifetime, LifetimeOutlives, Mutability, Substitution, Ty, TyKind, WhereClause,
+};
+
+/// Whether a pointer with mutability `source` may be coerced into a pointer
+/// with mutability `target`; mutability can be dropped but never gained.
+fn mutability_coerces(source: Mutability, target: Mutability) -> bool {
+    !(source == Mutability::Not && target == Mutability::Mut)
+}
+
+fn is_unknown<I: Interner>(ty: &TyKind<I>) -> bool {
+    matches!(
+        ty,
+        TyKind::InferenceVar(..) | TyKind::BoundVar(_) | TyKind::Alias(..)
+    )
+}
+
+fn may_be_pointer<I: Interner>(ty: &TyKind<I>) -> bool {
+    is_unknown(ty) || matches!(ty, TyKind::Ref(..) | TyKind::Raw(..))
+}
+
+/// Add the built-in `CoerceUnsized` impls for references and raw pointers,
+/// which live in libcore:
+///
+/// ```notrust
+/// impl<'a, 'b: 'a, T: Unsize<U>, U> CoerceUnsized<&'a mut U> for &'b mut T {}
+/// impl<'a, 'b: 'a, T: Unsize<U>, U> CoerceUnsized<&'a U> for &'b mut T {}
+/// impl<'a, T: Unsize<U>, U> CoerceUnsized<*mut U> for &'a mut T {}
+/// impl<'a, T: Unsize<U>, U> CoerceUnsized<*const U> for &'a mut T {}
+/// impl<'a, 'b: 'a, T: Unsize<U>, U> CoerceUnsized<&'a U> for &'b T {}
+/// impl<'a, T: Unsize<U>, U> CoerceUnsized<*const U> for &'a T {}
+/// impl<T: Unsize<U>, U> CoerceUnsized<*mut U> for *mut T {}
+/// impl<T: Unsize<U>, U> CoerceUnsized<*const U> for *mut T {}
+/// impl<T: Unsize<U>, U> CoerceUnsized<*const U> for *const T {}
+/// ```
+///
+/// Impls for user smart pointers such as `Rc<T>` are still written in the
+/// program; their WF check relies on these impls for the pointer fields.
+pub fn add_coerce_unsized_program_clauses<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    builder: &mut ClauseBuilder<'_, I>,
+    trait_ref: &TraitRef<I>,
+    ty: &TyKind<I>,
+) -> Result<(), Floundered> {
+    let interner = db.interner();
+
+    let target_ty = trait_ref
+        .substitution
+        .at(interner, 1)
+        .assert_ty_ref(interner);
+
+    // Without `Unsize` no pointee can be unsized, so there is nothing to coerce.
+    let unsize_trait_id = match db.well_known_trait_id(WellKnownTrait::Unsize) {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+    let unsize_goal = |source_pointee: &Ty<I>, target_pointee: &Ty<I>| -> Goal<I> {
+        TraitRef {
+            trait_id: unsize_trait_id,
+            substitution: Substitution::from_iter(
+                interner,
+                &[
+                    source_pointee.clone().cast(interner),
+                    target_pointee.clone().cast(interner),
+                ],
+            ),
+        }
+        .cast(interner)
+    };
+    let outlives_goal = |a: &Lifetime<I>, b: &Lifetime<I>| -> Goal<I> {
+        WhereClause::LifetimeOutlives(LifetimeOutlives {
+            a: a.clone(),
+            b: b.clone(),
+        })
+        .cast(interner)
+    };
+
+    match (ty, target_ty.kind(interner)) {
+        // &'a T -> &'b U
+        (
+            TyKind::Ref(mutability_a, lifetime_a, pointee_a),
+            TyKind::Ref(mutability_b, lifetime_b, pointee_b),
+        ) if mutability_coerces(*mutability_a, *mutability_b) => {
+            builder.push_clause(
+                trait_ref.clone(),
+                iter::once(unsize_goal(pointee_a, pointee_b))
+                    .chain(iter::once(outlives_goal(lifetime_a, lifetime_b))),
+            );
+        }
+
+        // &'a T -> *U, *T -> *U
+        (TyKind::Ref(mutability_a, _, pointee_a), TyKind::Raw(mutability_b, pointee_b))
+        | (TyKind::Raw(mutability_a, pointee_a), TyKind::Raw(mutability_b, pointee_b))
+            if mutability_coerces(*mutability_a, *mutability_b) =>
+        {
+            builder.push_clause(
+                trait_ref.clone(),
+                iter::once(unsize_goal(pointee_a, pointee_b)),
+            );
+        }
+
+        // Either side may still turn out to be a pointer, so we can't
+        // enumerate the impls.
+        (source, target)
+            if (is_unknown(source) || is_unknown(target))
+                && may_be_pointer(source)
+                && may_be_pointer(target) =>
+        {
+            return Err(Floundered)
+        }
+
+        _ => {}
+    }
+
+    Ok(())
+}
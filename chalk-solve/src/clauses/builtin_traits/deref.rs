@@ -0,0 +1,57 @@
+use crate::clauses::ClauseBuilder;
+use crate::rust_ir::WellKnownTrait;
+use crate::{Interner, RustIrDatabase, TraitRef};
+use chalk_ir::{
+    AliasTy, Floundered, Mutability, Normalize, ProjectionTy, Substitution, Ty, TyKind,
+};
+
+/// Add the built-in `Deref` and `DerefMut` impls of references, which live
+/// in libcore:
+///
+/// ```notrust
+/// impl<T> Deref for &T { type Target = T; }
+/// impl<T> Deref for &mut T { type Target = T; }
+/// impl<T> DerefMut for &mut T { }
+/// ```
+pub fn add_deref_program_clauses<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    builder: &mut ClauseBuilder<'_, I>,
+    well_known: WellKnownTrait,
+    self_ty: Ty<I>,
+) -> Result<(), Floundered> {
+    let interner = db.interner();
+
+    match self_ty.kind(interner) {
+        TyKind::Ref(mutability, _, referent) => {
+            if well_known == WellKnownTrait::DerefMut && *mutability == Mutability::Not {
+                return Ok(());
+            }
+
+            let trait_id = db.well_known_trait_id(well_known).unwrap();
+            let substitution = Substitution::from1(interner, self_ty.clone());
+            builder.push_fact(TraitRef {
+                trait_id,
+                substitution: substitution.clone(),
+            });
+
+            if well_known == WellKnownTrait::Deref {
+                // `Deref::Target`
+                if let Some(&target_id) = db.trait_datum(trait_id).associated_ty_ids.first() {
+                    builder.push_fact(Normalize {
+                        alias: AliasTy::Projection(ProjectionTy {
+                            associated_ty_id: target_id,
+                            substitution,
+                        }),
+                        ty: referent.clone(),
+                    });
+                }
+            }
+
+            Ok(())
+        }
+
+        // Whether the self type is a reference isn't known yet
+        TyKind::InferenceVar(..) | TyKind::BoundVar(_) | TyKind::Alias(..) => Err(Floundered),
+        _ => Ok(()),
+    }
+}
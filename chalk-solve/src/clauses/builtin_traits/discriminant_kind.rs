@@ -0,0 +1,64 @@
+use crate::clauses::ClauseBuilder;
+use crate::rust_ir::{AdtKind, WellKnownTrait};
+use crate::{Interner, RustIrDatabase, TraitRef};
+use chalk_ir::{
+    AliasTy, Floundered, IntTy, Normalize, ProjectionTy, Scalar, Substitution, Ty, TyKind, UintTy,
+};
+
+/// Add the built-in impl of `DiscriminantKind`, which every type implements.
+///
+/// The discriminant of an enum is given by its `#[repr]`, and is `isize`
+/// by default. Other types have no discriminant, which is `u8`:
+///
+/// ```notrust
+/// Implemented(T: DiscriminantKind).
+/// Normalize(<T as DiscriminantKind>::Discriminant -> D).
+/// ```
+///
+/// The discriminant of placeholders, opaque types and associated types is
+/// not known, so their projection is not normalized.
+pub fn add_discriminant_kind_program_clauses<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    builder: &mut ClauseBuilder<'_, I>,
+    self_ty: Ty<I>,
+) -> Result<(), Floundered> {
+    let interner = db.interner();
+
+    let discriminant = match self_ty.kind(interner) {
+        TyKind::Adt(adt_id, _) if db.adt_datum(*adt_id).kind == AdtKind::Enum => Some(
+            db.adt_repr(*adt_id)
+                .repr_int
+                .unwrap_or(Scalar::Int(IntTy::Isize)),
+        ),
+        TyKind::Placeholder(_) | TyKind::OpaqueType(..) | TyKind::AssociatedType(..) => None,
+
+        // The type isn't known yet
+        TyKind::InferenceVar(..) | TyKind::BoundVar(_) | TyKind::Alias(..) => {
+            return Err(Floundered)
+        }
+        _ => Some(Scalar::Uint(UintTy::U8)),
+    };
+
+    let trait_id = db
+        .well_known_trait_id(WellKnownTrait::DiscriminantKind)
+        .unwrap();
+    let substitution = Substitution::from1(interner, self_ty);
+    builder.push_fact(TraitRef {
+        trait_id,
+        substitution: substitution.clone(),
+    });
+
+    // `DiscriminantKind::Discriminant`
+    let discriminant_id = db.trait_datum(trait_id).associated_ty_ids.first().copied();
+    if let (Some(discriminant_id), Some(discriminant)) = (discriminant_id, discriminant) {
+        builder.push_fact(Normalize {
+            alias: AliasTy::Projection(ProjectionTy {
+                associated_ty_id: discriminant_id,
+                substitution,
+            }),
+            ty: TyKind::Scalar(discriminant).intern(interner),
+        });
+    }
+
+    Ok(())
+}
@@ -4,27 +4,42 @@ use crate::rust_ir::{ClosureKind, FnDefInputsAndOutputDatum, WellKnownTrait};
 use crate::{Interner, RustIrDatabase, TraitRef};
 use chalk_ir::cast::Cast;
 use chalk_ir::{
-    AliasTy, Binders, Floundered, Normalize, ProjectionTy, Safety, Substitution, TraitId, Ty,
-    TyKind, VariableKinds,
+    AliasTy, Binders, DomainGoal, Floundered, Normalize, ProjectionTy, Safety, Substitution,
+    TraitId, Ty, TyKind, VariableKinds,
 };
 
+/// The signature of a callable type, for which the clauses of an `Fn`
+/// trait are pushed.
+struct FnSignature<I: Interner> {
+    self_ty: Ty<I>,
+    arg_sub: Substitution<I>,
+    return_type: Ty<I>,
+}
+
 fn push_clauses<I: Interner>(
     db: &dyn RustIrDatabase<I>,
     builder: &mut ClauseBuilder<'_, I>,
     well_known: WellKnownTrait,
     trait_id: TraitId<I>,
-    self_ty: Ty<I>,
-    arg_sub: Substitution<I>,
-    return_type: Ty<I>,
+    signature: FnSignature<I>,
+    condition: Option<DomainGoal<I>>,
 ) {
     let interner = db.interner();
+    let FnSignature {
+        self_ty,
+        arg_sub,
+        return_type,
+    } = signature;
     let tupled = TyKind::Tuple(arg_sub.len(interner), arg_sub).intern(interner);
     let substitution =
         Substitution::from_iter(interner, &[self_ty.cast(interner), tupled.cast(interner)]);
-    builder.push_fact(TraitRef {
-        trait_id,
-        substitution: substitution.clone(),
-    });
+    builder.push_clause(
+        TraitRef {
+            trait_id,
+            substitution: substitution.clone(),
+        },
+        condition,
+    );
 
     // The `Output` type is defined on the `FnOnce`
     if let WellKnownTrait::FnOnce = well_known {
@@ -56,6 +71,7 @@ fn push_clauses_for_apply<I: Interner>(
     trait_id: TraitId<I>,
     self_ty: Ty<I>,
     inputs_and_output: &Binders<FnDefInputsAndOutputDatum<I>>,
+    condition: Option<DomainGoal<I>>,
 ) {
     let interner = db.interner();
     builder.push_binders(inputs_and_output, |builder, inputs_and_output| {
@@ -64,12 +80,13 @@ fn push_clauses_for_apply<I: Interner>(
             .iter()
             .cloned()
             .map(|ty| ty.cast(interner));
-        let arg_sub = Substitution::from_iter(interner, arg_sub);
-        let output_ty = inputs_and_output.return_type;
+        let signature = FnSignature {
+            self_ty,
+            arg_sub: Substitution::from_iter(interner, arg_sub),
+            return_type: inputs_and_output.return_type,
+        };
 
-        push_clauses(
-            db, builder, well_known, trait_id, self_ty, arg_sub, output_ty,
-        );
+        push_clauses(db, builder, well_known, trait_id, signature, condition);
     });
 }
 
@@ -81,7 +98,11 @@ fn push_clauses_for_apply<I: Interner>(
 /// If `trait_id` is `FnOnce`, we also push a clause for the output type of the form:
 /// `Normalize(<fn(A) -> B as FnOnce<(A,)>>::Output -> B)`
 /// We do not add the usual `Implemented(fn(A) -> b as FnOnce<(A,)>` clause
-/// as a condition, since we already called `push_fact` with it
+/// as a condition, since we already pushed a clause for it
+///
+/// If `self_ty` is a closure whose kind is not known yet, the `Fn` and
+/// `FnMut` clauses get a `ClosureKind(C, Fn)` (resp. `FnMut`) condition,
+/// which is ambiguous until the kind is known.
 pub fn add_fn_trait_program_clauses<I: Interner>(
     db: &dyn RustIrDatabase<I>,
     builder: &mut ClauseBuilder<'_, I>,
@@ -105,22 +126,26 @@ pub fn add_fn_trait_program_clauses<I: Interner>(
                     trait_id,
                     self_ty,
                     &bound.inputs_and_output,
+                    None,
                 );
             }
             Ok(())
         }
         TyKind::Closure(closure_id, substitution) => {
-            let closure_kind = db.closure_kind(*closure_id, &substitution);
-            let trait_matches = match (well_known, closure_kind) {
-                (WellKnownTrait::Fn, ClosureKind::Fn) => true,
-                (WellKnownTrait::FnMut, ClosureKind::FnMut)
-                | (WellKnownTrait::FnMut, ClosureKind::Fn) => true,
-                (WellKnownTrait::FnOnce, _) => true,
-                _ => false,
+            let trait_kind = match well_known {
+                WellKnownTrait::Fn => ClosureKind::Fn,
+                WellKnownTrait::FnMut => ClosureKind::FnMut,
+                _ => ClosureKind::FnOnce,
+            };
+            // A closure implements the traits of its kind and of the more
+            // restrictive kinds. If the kind is not known yet, all closures
+            // implement `FnOnce`, but the other impls depend on the kind.
+            let condition = match db.closure_kind(*closure_id, &substitution) {
+                Some(closure_kind) if closure_kind <= trait_kind => None,
+                Some(_) => return Ok(()),
+                None if trait_kind == ClosureKind::FnOnce => None,
+                None => Some(DomainGoal::ClosureKind(self_ty.clone(), trait_kind)),
             };
-            if !trait_matches {
-                return Ok(());
-            }
             let closure_inputs_and_output =
                 db.closure_inputs_and_output(*closure_id, &substitution);
             push_clauses_for_apply(
@@ -130,6 +155,7 @@ pub fn add_fn_trait_program_clauses<I: Interner>(
                 trait_id,
                 self_ty,
                 &closure_inputs_and_output,
+                condition,
             );
             Ok(())
         }
@@ -141,18 +167,13 @@ pub fn add_fn_trait_program_clauses<I: Interner>(
                 let (arg_sub, fn_output_ty) = orig_sub
                     .as_slice(interner)
                     .split_at(orig_sub.len(interner) - 1);
-                let arg_sub = Substitution::from_iter(interner, arg_sub);
-                let output_ty = fn_output_ty[0].assert_ty_ref(interner).clone();
+                let signature = FnSignature {
+                    self_ty: self_ty.clone(),
+                    arg_sub: Substitution::from_iter(interner, arg_sub),
+                    return_type: fn_output_ty[0].assert_ty_ref(interner).clone(),
+                };
 
-                push_clauses(
-                    db,
-                    builder,
-                    well_known,
-                    trait_id,
-                    self_ty.clone(),
-                    arg_sub,
-                    output_ty,
-                );
+                push_clauses(db, builder, well_known, trait_id, signature, None);
             });
             Ok(())
         }
@@ -0,0 +1,148 @@
+use std::iter;
+
+use crate::clauses::ClauseBuilder;
+use crate::rust_ir::{AdtKind, WellKnownAdt, WellKnownTrait};
+use crate::{Interner, RustIrDatabase, TraitRef};
+use chalk_ir::{
+    AliasTy, Floundered, Normalize, ProjectionTy, Scalar, Substitution, Ty, TyKind, UintTy,
+};
+
+/// The metadata of pointers to a type.
+enum Metadata<I: Interner> {
+    /// The metadata is the given type.
+    Is(Ty<I>),
+    /// The metadata is the one of the given type, which is the last field of
+    /// a struct or the last element of a tuple.
+    SameAs(Ty<I>),
+    /// The metadata is `()` if the type is `Sized`, and is not known
+    /// otherwise.
+    UnitIfSized,
+    /// The metadata cannot be computed.
+    Unknown,
+}
+
+/// Add the built-in impl of `Pointee`, which every type implements:
+///
+/// ```notrust
+/// Implemented(T: Pointee).
+/// Normalize(<T as Pointee>::Metadata -> M).
+/// ```
+///
+/// The metadata `M` is `usize` for slices and `str`, `DynMetadata<T>` for
+/// trait objects and `()` for sized types. The metadata of a struct or a
+/// tuple is the one of its last field, which is the only one that may be
+/// unsized.
+pub fn add_pointee_program_clauses<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    builder: &mut ClauseBuilder<'_, I>,
+    self_ty: Ty<I>,
+) -> Result<(), Floundered> {
+    let interner = db.interner();
+    let unit = || TyKind::Tuple(0, Substitution::empty(interner)).intern(interner);
+
+    let metadata = match self_ty.kind(interner) {
+        TyKind::Slice(_) | TyKind::Str => {
+            Metadata::Is(TyKind::Scalar(Scalar::Uint(UintTy::Usize)).intern(interner))
+        }
+        TyKind::Dyn(_) => match db.well_known_adt_id(WellKnownAdt::DynMetadata) {
+            Some(adt_id) => Metadata::Is(
+                TyKind::Adt(adt_id, Substitution::from1(interner, self_ty.clone()))
+                    .intern(interner),
+            ),
+            None => Metadata::Unknown,
+        },
+        TyKind::Adt(adt_id, substitution) => {
+            let adt_datum = db.adt_datum(*adt_id);
+            // Only the last field of a struct may be unsized, see `Sized`.
+            let last_field = if adt_datum.kind == AdtKind::Struct {
+                adt_datum
+                    .binders
+                    .map_ref(|b| &b.variants)
+                    .substitute(interner, substitution)
+                    .into_iter()
+                    .take(1)
+                    .flat_map(|mut v| v.fields.pop())
+                    .next()
+            } else {
+                None
+            };
+            match last_field {
+                Some(ty) => Metadata::SameAs(ty),
+                None => Metadata::Is(unit()),
+            }
+        }
+        TyKind::Tuple(arity, substitution) if *arity > 0 => Metadata::SameAs(
+            substitution
+                .iter(interner)
+                .last()
+                .unwrap()
+                .assert_ty_ref(interner)
+                .clone(),
+        ),
+        TyKind::Placeholder(_) | TyKind::OpaqueType(..) | TyKind::AssociatedType(..) => {
+            Metadata::UnitIfSized
+        }
+
+        // The type isn't known yet
+        TyKind::InferenceVar(..) | TyKind::BoundVar(_) | TyKind::Alias(..) => {
+            return Err(Floundered)
+        }
+        _ => Metadata::Is(unit()),
+    };
+
+    let trait_id = db.well_known_trait_id(WellKnownTrait::Pointee).unwrap();
+    let substitution = Substitution::from1(interner, self_ty.clone());
+    builder.push_fact(TraitRef {
+        trait_id,
+        substitution: substitution.clone(),
+    });
+
+    // `Pointee::Metadata`
+    let metadata_id = match db.trait_datum(trait_id).associated_ty_ids.first() {
+        Some(&id) => id,
+        None => return Ok(()),
+    };
+    let metadata_alias = |substitution| {
+        AliasTy::Projection(ProjectionTy {
+            associated_ty_id: metadata_id,
+            substitution,
+        })
+    };
+    match metadata {
+        Metadata::Is(ty) => builder.push_fact(Normalize {
+            alias: metadata_alias(substitution),
+            ty,
+        }),
+        Metadata::SameAs(last_ty) => builder.push_bound_ty(|builder, ty| {
+            let last_normalize = Normalize {
+                alias: metadata_alias(Substitution::from1(interner, last_ty)),
+                ty: ty.clone(),
+            };
+            builder.push_clause(
+                Normalize {
+                    alias: metadata_alias(substitution),
+                    ty,
+                },
+                iter::once(last_normalize),
+            );
+        }),
+        Metadata::UnitIfSized => {
+            if let Some(sized_id) = db.well_known_trait_id(WellKnownTrait::Sized) {
+                let sized = TraitRef {
+                    trait_id: sized_id,
+                    substitution: Substitution::from1(interner, self_ty),
+                };
+                builder.push_clause(
+                    Normalize {
+                        alias: metadata_alias(substitution),
+                        ty: unit(),
+                    },
+                    iter::once(sized),
+                );
+            }
+        }
+        Metadata::Unknown => {}
+    }
+
+    Ok(())
+}
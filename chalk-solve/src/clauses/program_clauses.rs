@@ -127,6 +127,63 @@ impl<I: Interner> ToProgramClauses<I> for AssociatedTyValue<I> {
     }
 }
 
+impl<I: Interner> ToProgramClauses<I> for AssociatedConstValue<I> {
+    /// Given the following trait:
+    ///
+    /// ```notrust
+    /// trait Len {
+    ///     const N: usize;
+    /// }
+    /// ```
+    ///
+    /// Then for the following impl:
+    /// ```notrust
+    /// impl<T> Len for Vec<T> where T: Clone {
+    ///     const N: usize = 3;
+    /// }
+    /// ```
+    ///
+    /// we generate:
+    ///
+    /// ```notrust
+    /// -- Rule NormalizeConst-From-Impl
+    /// forall<T> {
+    ///     NormalizeConst(<Vec<T> as Len>::N -> 3) :-
+    ///         Implemented(T: Clone).
+    /// }
+    /// ```
+    fn to_program_clauses(
+        &self,
+        builder: &mut ClauseBuilder<'_, I>,
+        _environment: &Environment<I>,
+    ) {
+        let impl_datum = builder.db.impl_datum(self.impl_id);
+
+        builder.push_binders(&self.value, |builder, value| {
+            let interner = builder.interner();
+            let impl_params = builder.substitution_in_scope();
+            let ImplDatumBound {
+                trait_ref,
+                where_clauses,
+            } = impl_datum
+                .binders
+                .clone()
+                .substitute(interner, &impl_params);
+
+            builder.push_clause(
+                NormalizeConst {
+                    alias: ConstProjection {
+                        associated_const_id: self.associated_const_id,
+                        substitution: trait_ref.substitution,
+                    },
+                    value,
+                },
+                where_clauses,
+            );
+        });
+    }
+}
+
 impl<I: Interner> ToProgramClauses<I> for OpaqueTyDatum<I> {
     /// Given `opaque type T<U>: A + B = HiddenTy where U: C;`, we generate:
     ///
@@ -597,6 +654,23 @@ impl<I: Interner> ToProgramClauses<I> for TraitDatum<I> {
                     .chain(Some(trait_ref.clone().cast(interner))),
             );
 
+            // A trait alias is implemented exactly when its where clauses,
+            // which include its bounds, hold. E.g., for `trait Alias<T> =
+            // Foo<T> + Bar where T: Baz`, we generate:
+            //
+            // ```
+            // Implemented(Self: Alias<T>) :-
+            //     Implemented(Self: Foo<T>),
+            //     Implemented(Self: Bar),
+            //     Implemented(T: Baz).
+            // ```
+            if self.flags.alias {
+                builder.push_clause(
+                    trait_ref.clone(),
+                    where_clauses.iter().cloned().casted::<Goal<_>>(interner),
+                );
+            }
+
             // The number of parameters will always be at least 1
             // because of the Self parameter that is automatically
             // added to every trait. This is important because
@@ -604,7 +678,9 @@ impl<I: Interner> ToProgramClauses<I> for TraitDatum<I> {
             // conditions.
             let type_parameters: Vec<_> = trait_ref.type_parameters(interner).collect();
 
-            if environment.has_compatible_clause(interner) {
+            // Trait aliases cannot be implemented, neither downstream nor
+            // upstream, so that the clause above is all there is to them.
+            if environment.has_compatible_clause(interner) && !self.flags.alias {
                 // Note: even though we do check for a `Compatible` clause here,
                 // we also keep it as a condition for the clauses below, purely
                 // for logical consistency. But really, it's not needed and could be
@@ -3,7 +3,7 @@ use petgraph::prelude::*;
 use crate::solve::Solver;
 use crate::RustIrDatabase;
 use chalk_ir::interner::Interner;
-use chalk_ir::{self, ImplId, TraitId};
+use chalk_ir::{self, Canonical, ImplId, Substitution, TraitId};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
@@ -19,15 +19,26 @@ pub struct CoherenceSolver<'a, I: Interner> {
 
 #[derive(Debug)]
 pub enum CoherenceError<I: Interner> {
-    OverlappingImpls(TraitId<I>),
+    /// Two impls of the trait apply to the same types, and neither
+    /// specializes the other.
+    OverlappingImpls {
+        trait_id: TraitId<I>,
+        /// The overlapping impls, in the order they were checked.
+        impls: (ImplId<I>, ImplId<I>),
+        /// If the overlap can be shown without considering downstream
+        /// crates, a substitution under which both impls apply: it gives the
+        /// generic parameters of the first impl, followed by those of the
+        /// second impl.
+        witness: Option<Canonical<Substitution<I>>>,
+    },
     FailedOrphanCheck(TraitId<I>),
 }
 
 impl<I: Interner> fmt::Display for CoherenceError<I> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            CoherenceError::OverlappingImpls(id) => {
-                write!(f, "overlapping impls of trait `{:?}`", id)
+            CoherenceError::OverlappingImpls { trait_id, .. } => {
+                write!(f, "overlapping impls of trait `{:?}`", trait_id)
             }
             CoherenceError::FailedOrphanCheck(id) => {
                 write!(f, "impl for trait `{:?}` violates the orphan rules", id)
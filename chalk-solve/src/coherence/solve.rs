@@ -1,6 +1,8 @@
 use crate::coherence::{CoherenceError, CoherenceSolver};
 use crate::debug_span;
 use crate::ext::*;
+use crate::infer::ucanonicalize::UniverseMapExt;
+use crate::infer::{InferenceTable, ParameterEnaVariableExt};
 use crate::rust_ir::*;
 use crate::{goal_builder::GoalBuilder, Solution};
 use chalk_ir::cast::*;
@@ -21,9 +23,16 @@ impl<I: Interner> CoherenceSolver<'_, I> {
             return Ok(());
         }
 
-        // Iterate over every pair of impls for the same trait.
+        // Iterate over every pair of local impls for the same trait, and over
+        // every pair of a local impl and an upstream one.
         let impls = self.db.local_impls_to_coherence_check(self.trait_id);
-        for (l_id, r_id) in impls.into_iter().tuple_combinations() {
+        let upstream_impls = self.db.upstream_impls_to_coherence_check(self.trait_id);
+        let pairs = impls
+            .iter()
+            .copied()
+            .tuple_combinations()
+            .chain(impls.iter().copied().cartesian_product(upstream_impls));
+        for (l_id, r_id) in pairs {
             let lhs = &self.db.impl_datum(l_id);
             let rhs = &self.db.impl_datum(r_id);
 
@@ -40,7 +49,11 @@ impl<I: Interner> CoherenceSolver<'_, I> {
                     (true, false) => record_specialization(l_id, r_id),
                     (false, true) => record_specialization(r_id, l_id),
                     (_, _) => {
-                        return Err(CoherenceError::OverlappingImpls(self.trait_id));
+                        return Err(CoherenceError::OverlappingImpls {
+                            trait_id: self.trait_id,
+                            impls: (l_id, r_id),
+                            witness: self.overlap_witness(lhs, rhs),
+                        });
                     }
                 }
             }
@@ -145,6 +158,97 @@ impl<I: Interner> CoherenceSolver<'_, I> {
         result
     }
 
+    // Given two impls that `disjoint` found to overlap, tries to find a substitution for the
+    // parameters of both impls under which they apply to the same trait reference.
+    //
+    // This is the goal from `disjoint`, without the `compatible` modality and the negation, and
+    // with the binders of both impls instantiated with inference variables, so that we can read
+    // their values back out of the solution. If the impls only overlap in some compatible world
+    // (e.g. because a downstream crate could implement a trait), or if the solution is ambiguous,
+    // there is no witness.
+    //
+    // Example:
+    //
+    //  Impls:
+    //      impl<T> Foo for T { }   // lhs
+    //      impl Foo for i32 { }    // rhs
+    //  Witness:
+    //      [?0 := i32]
+    #[instrument(level = "debug", skip(self))]
+    fn overlap_witness(
+        &self,
+        lhs: &ImplDatum<I>,
+        rhs: &ImplDatum<I>,
+    ) -> Option<Canonical<Substitution<I>>> {
+        let interner = self.db.interner();
+        let mut infer = InferenceTable::new();
+
+        let fresh_subst = |infer: &mut InferenceTable<I>, binders: &VariableKinds<I>| {
+            let kinds: Vec<_> = binders
+                .iter(interner)
+                .cloned()
+                .map(|kind| CanonicalVarKind::new(kind, UniverseIndex::root()))
+                .collect();
+            infer.fresh_subst(interner, &kinds)
+        };
+        let lhs_subst = fresh_subst(&mut infer, &lhs.binders.binders);
+        let rhs_subst = fresh_subst(&mut infer, &rhs.binders.binders);
+        let lhs_bound = lhs.binders.clone().substitute(interner, &lhs_subst);
+        let rhs_bound = rhs.binders.clone().substitute(interner, &rhs_subst);
+
+        let params_goals = lhs_bound
+            .trait_ref
+            .substitution
+            .iter(interner)
+            .cloned()
+            .zip(rhs_bound.trait_ref.substitution.iter(interner).cloned())
+            .map(|(a, b)| GoalData::EqGoal(EqGoal { a, b }).intern(interner));
+        let wc_goals = lhs_bound
+            .where_clauses
+            .into_iter()
+            .chain(rhs_bound.where_clauses)
+            .map(|wc| wc.cast(interner));
+        let goal = Goal::all(interner, params_goals.chain(wc_goals));
+
+        let environment = Environment::new(interner);
+        let canonicalized = infer.canonicalize(interner, &InEnvironment::new(&environment, goal));
+        let u_canonicalized = infer.u_canonicalize(interner, &canonicalized.quantified);
+        let mut fresh_solver = (self.solver_builder)();
+        let answer = match fresh_solver.solve(self.db, &u_canonicalized.quantified) {
+            Some(Solution::Unique(answer)) => answer,
+            _ => return None,
+        };
+        let answer = u_canonicalized
+            .universes
+            .map_from_canonical(interner, &answer);
+
+        // Bind the variables of the goal to the values from the answer, then read back the
+        // values of the impl parameters.
+        let answer_subst = infer.instantiate_canonical(
+            interner,
+            &Canonical {
+                binders: answer.binders,
+                value: answer.value.subst,
+            },
+        );
+        for (var, value) in canonicalized
+            .free_vars
+            .iter()
+            .zip(answer_subst.iter(interner))
+        {
+            infer
+                .unify(interner, &environment, &var.to_generic_arg(interner), value)
+                .ok()?;
+        }
+        let witness = Substitution::from_iter(
+            interner,
+            lhs_subst.iter(interner).chain(rhs_subst.iter(interner)),
+        );
+        let witness = infer.canonicalize(interner, &witness).quantified;
+        debug!("overlap_witness: witness = {:?}", witness);
+        Some(witness)
+    }
+
     // Creates a goal which, if provable, means "more special" impl specializes the "less special" one.
     //
     // # General rule
@@ -70,7 +70,8 @@ where
     P: Borrow<DB>,
     T: IntoIterator<Item = RecordedItemId<I>>,
 {
-    for id in ids {
+    let mut ids = ids.into_iter().peekable();
+    while let Some(id) = ids.next() {
         match id {
             RecordedItemId::Impl(id) => {
                 let v = ws.db().impl_datum(id);
@@ -88,10 +89,32 @@ where
                 let v = ws.db().opaque_ty_data(id);
                 write_item(f, &InternalWriterState::new(ws), &*v)?;
             }
-            RecordedItemId::FnDef(id) => {
-                let v = ws.db().fn_def_datum(id);
-                write_item(f, &InternalWriterState::new(ws), &*v)?;
-            }
+            RecordedItemId::FnDef(id) => match ws.db().associated_fn_data(id) {
+                None => {
+                    let v = ws.db().fn_def_datum(id);
+                    write_item(f, &InternalWriterState::new(ws), &*v)?;
+                }
+                Some(datum) => match &datum.container {
+                    // Trait fns are written as part of their trait.
+                    AssociatedFnContainer::Trait(_) => {}
+                    AssociatedFnContainer::InherentImpl(self_ty) => {
+                        // Consecutive fns of the same inherent impl are
+                        // written as one impl.
+                        let mut fns = vec![datum.clone()];
+                        while let Some(&RecordedItemId::FnDef(next_id)) = ids.peek() {
+                            match ws.db().associated_fn_data(next_id) {
+                                Some(next) if next.container == datum.container => {
+                                    fns.push(next);
+                                    ids.next();
+                                }
+                                _ => break,
+                            }
+                        }
+                        let v = items::InherentImplFns { self_ty, fns: &fns };
+                        write_item(f, &InternalWriterState::new(ws), &v)?;
+                    }
+                },
+            },
             RecordedItemId::Generator(id) => {
                 let generator = ws.db().generator_datum(id);
                 let witness = ws.db().generator_witness_datum(id);
@@ -102,6 +125,34 @@ where
     Ok(())
 }
 
+/// Writes a canonical substitution as `[?0 := A, ?1 := B]`, with each value
+/// written as it would be in a program (e.g. `i32` rather than `Int(I32)`).
+/// Variables bound by the canonical binders are given made up names.
+pub fn write_canonical_substitution<F, I, DB, P>(
+    f: &mut F,
+    ws: &WriterState<I, DB, P>,
+    substitution: &Canonical<Substitution<I>>,
+) -> Result
+where
+    F: std::fmt::Write + ?Sized,
+    I: Interner,
+    DB: RustIrDatabase<I>,
+    P: Borrow<DB>,
+{
+    let s = &InternalWriterState::new(ws).add_debrujin_index(None);
+    let interner = s.db().interner();
+    write!(
+        f,
+        "[{}]",
+        substitution
+            .value
+            .iter(interner)
+            .enumerate()
+            .map(|(i, arg)| format!("?{} := {}", i, arg.display(s)))
+            .format(", ")
+    )
+}
+
 /// Displays a set of bounds, all targeting `Self`, as just the trait names,
 /// separated by `+`.
 ///
@@ -4,10 +4,12 @@
 //! single top-level item.
 
 use std::fmt::{Formatter, Result};
+use std::sync::Arc;
 
 use crate::rust_ir::*;
 use crate::split::Split;
 use chalk_ir::interner::Interner;
+use chalk_ir::{Binders, Ty};
 use itertools::Itertools;
 
 use super::{
@@ -63,6 +65,23 @@ macro_rules! write_flags {
     };
 }
 
+/// Writes the `#[variance(..)]` attribute of an item, unless all of its
+/// parameters are invariant, which is the default.
+fn write_variances(f: &mut Formatter<'_>, variances: &[chalk_ir::Variance]) -> Result {
+    if variances
+        .iter()
+        .all(|variance| *variance == chalk_ir::Variance::Invariant)
+    {
+        return Ok(());
+    }
+    let variances = variances.iter().map(|variance| match variance {
+        chalk_ir::Variance::Invariant => "Invariant",
+        chalk_ir::Variance::Covariant => "Covariant",
+        chalk_ir::Variance::Contravariant => "Contravariant",
+    });
+    writeln!(f, "#[variance({})]", variances.format(", "))
+}
+
 impl<'a, I: Interner> RenderAsRust<I> for (&'a GeneratorDatum<I>, &'a GeneratorWitnessDatum<I>) {
     fn fmt(&self, _s: &InternalWriterState<'_, I>, _f: &'_ mut Formatter<'_>) -> Result {
         unimplemented!()
@@ -88,17 +107,25 @@ impl<I: Interner> RenderAsRust<I> for AdtDatum<I> {
             }
         );
 
+        // well-known
+        if s.db().well_known_adt_id(WellKnownAdt::DynMetadata) == Some(self.id) {
+            writeln!(f, "#[lang(dyn_metadata)]")?;
+        }
+
         // repr
         let repr = s.db().adt_repr(self.id);
+        if repr.repr_c {
+            writeln!(f, "#[repr(C)]")?;
+        }
+        if repr.repr_packed {
+            writeln!(f, "#[repr(packed)]")?;
+        }
+        if let Some(int) = repr.repr_int {
+            writeln!(f, "#[repr({})]", int.display(s))?;
+        }
 
-        write_flags!(
-            f,
-            repr,
-            AdtRepr {
-                repr_c: "repr(C)",
-                repr_packed: "repr(packed)"
-            }
-        );
+        // variances
+        write_variances(f, &s.db().adt_variance(self.id))?;
 
         // name
         match self.kind {
@@ -169,6 +196,23 @@ impl<I: Interner> RenderAsRust<I> for TraitDatum<I> {
         let s = &s.add_debrujin_index(Some(0));
         let value = self.binders.skip_binders();
 
+        // trait aliases, whose bounds are written as where clauses on `Self`
+        if self.flags.alias {
+            if self.flags.upstream {
+                writeln!(f, "#[upstream]")?;
+            }
+            let binders = s.binder_var_display(&self.binders.binders).skip(1);
+            write!(f, "trait {}", self.id.display(s))?;
+            write_joined_non_empty_list!(f, "<{}>", binders, ", ")?;
+            write!(f, " =")?;
+            if !value.where_clauses.is_empty() {
+                let s = &s.add_indent();
+                write!(f, "\nwhere\n{}\n", value.where_clauses.display(s))?;
+            }
+            write!(f, ";")?;
+            return Ok(());
+        }
+
         // flags
         write_flags!(
             f,
@@ -179,7 +223,8 @@ impl<I: Interner> RenderAsRust<I> for TraitDatum<I> {
                 upstream,
                 fundamental,
                 non_enumerable,
-                coinductive
+                coinductive,
+                alias
             }
         );
 
@@ -201,6 +246,11 @@ impl<I: Interner> RenderAsRust<I> for TraitDatum<I> {
                 WellKnownTrait::Unsize => "unsize",
                 WellKnownTrait::Unpin => "unpin",
                 WellKnownTrait::CoerceUnsized => "coerce_unsized",
+                WellKnownTrait::Deref => "deref",
+                WellKnownTrait::DerefMut => "deref_mut",
+                WellKnownTrait::Generator => "generator",
+                WellKnownTrait::DiscriminantKind => "discriminant_kind",
+                WellKnownTrait::Pointee => "pointee",
             };
             writeln!(f, "#[lang({})]", name)?;
         }
@@ -224,10 +274,23 @@ impl<I: Interner> RenderAsRust<I> for TraitDatum<I> {
         write_joined_non_empty_list!(
             f,
             "\n{}\n",
-            self.associated_ty_ids.iter().map(|assoc_ty_id| {
-                let assoc_ty_data = s.db().associated_ty_data(*assoc_ty_id);
-                format!("{}{}", s.indent(), (*assoc_ty_data).display(s))
-            }),
+            self.associated_ty_ids
+                .iter()
+                .map(|assoc_ty_id| {
+                    let assoc_ty_data = s.db().associated_ty_data(*assoc_ty_id);
+                    format!("{}{}", s.indent(), (*assoc_ty_data).display(s))
+                })
+                .chain(self.associated_const_ids.iter().map(|assoc_const_id| {
+                    let assoc_const_data = s.db().associated_const_data(*assoc_const_id);
+                    format!("{}{}", s.indent(), (*assoc_const_data).display(s))
+                }))
+                .chain(self.associated_fn_ids.iter().map(|assoc_fn_id| {
+                    let assoc_fn_data = s
+                        .db()
+                        .associated_fn_data(*assoc_fn_id)
+                        .expect("trait fns have an associated fn datum");
+                    format!("{}{}", s.indent(), (*assoc_fn_data).display(s))
+                })),
             "\n"
         )?;
         write!(f, "}}")?;
@@ -301,7 +364,21 @@ impl<I: Interner> RenderAsRust<I> for ImplDatum<I> {
                     .display(s)
                     .to_string()
             });
-            write_joined_non_empty_list!(f, "\n{}\n", assoc_ty_values, "\n")?;
+            let assoc_const_values =
+                self.associated_const_value_ids
+                    .iter()
+                    .map(|assoc_const_value| {
+                        s.db()
+                            .associated_const_value(*assoc_const_value)
+                            .display(s)
+                            .to_string()
+                    });
+            write_joined_non_empty_list!(
+                f,
+                "\n{}\n",
+                assoc_ty_values.chain(assoc_const_values),
+                "\n"
+            )?;
         }
         write!(f, "}}")?;
         Ok(())
@@ -312,6 +389,7 @@ impl<I: Interner> RenderAsRust<I> for OpaqueTyDatum<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &mut Formatter<'_>) -> Result {
         let s = &s.add_debrujin_index(None);
         let bounds = self.bound.skip_binders();
+        write_variances(f, &s.db().opaque_ty_variance(self.opaque_ty_id))?;
         write!(f, "opaque type {}", self.opaque_ty_id.display(s))?;
         write_joined_non_empty_list!(f, "<{}>", s.binder_var_display(&self.bound.binders), ", ")?;
         {
@@ -445,6 +523,158 @@ impl<I: Interner> RenderAsRust<I> for AssociatedTyValue<I> {
     }
 }
 
+impl<I: Interner> RenderAsRust<I> for AssociatedConstDatum<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        // The binders of an associated const are exactly the parameters of
+        // its trait; see `AssociatedTyDatum` for why they need a mapping.
+        let trait_datum = s.db().trait_datum(self.trait_id);
+        let trait_param_names_in_trait_env = s.binder_var_indices(&trait_datum.binders.binders);
+        let s = &s.add_debrujin_index(None);
+        let param_names_in_assoc_const_env = s.binder_var_indices(&self.ty.binders);
+        let s = &s.add_parameter_mapping(
+            param_names_in_assoc_const_env,
+            trait_param_names_in_trait_env,
+        );
+        write!(
+            f,
+            "const {}: {};",
+            self.id.display(s),
+            self.ty.skip_binders().display(s)
+        )
+    }
+}
+
+impl<I: Interner> RenderAsRust<I> for AssociatedConstValue<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        // The binders of an associated const value are exactly the
+        // parameters of its impl; see `AssociatedTyValue`.
+        let interner = s.db().interner();
+        let assoc_const_data = s.db().associated_const_data(self.associated_const_id);
+        let impl_datum = s.db().impl_datum(self.impl_id);
+        let impl_param_names_in_impl_env = s.binder_var_indices(&impl_datum.binders.binders);
+        let s = &s.add_debrujin_index(None);
+        let param_names_in_assoc_const_value_env = s.binder_var_indices(&self.value.binders);
+        let s = &s.add_parameter_mapping(
+            param_names_in_assoc_const_value_env,
+            impl_param_names_in_impl_env,
+        );
+        let ty = assoc_const_data.ty.substitute(
+            interner,
+            &impl_datum.binders.skip_binders().trait_ref.substitution,
+        );
+        write!(
+            f,
+            "{}const {}: {} = {};",
+            s.indent(),
+            assoc_const_data.id.display(s),
+            ty.display(s),
+            self.value.skip_binders().display(s)
+        )
+    }
+}
+
+/// The associated fns of an inherent impl with the self type `self_ty`,
+/// written as that impl.
+pub(super) struct InherentImplFns<'a, I: Interner> {
+    pub(super) self_ty: &'a Binders<Ty<I>>,
+    pub(super) fns: &'a [Arc<AssociatedFnDatum<I>>],
+}
+
+impl<I: Interner> RenderAsRust<I> for InherentImplFns<'_, I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        // The where clauses of the impl are written on each of its fns,
+        // which lower to the same where clauses.
+        let s = &s.add_debrujin_index(None);
+        write!(f, "impl")?;
+        write_joined_non_empty_list!(f, "<{}>", s.binder_var_display(&self.self_ty.binders), ", ")?;
+        write!(f, " {} {{", self.self_ty.skip_binders().display(s))?;
+        let s = &s.add_indent();
+        for assoc_fn in self.fns {
+            write!(f, "\n{}{}", s.indent(), (**assoc_fn).display(s))?;
+        }
+        write!(f, "\n}}")
+    }
+}
+
+impl<I: Interner> RenderAsRust<I> for AssociatedFnDatum<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        // The binders of an associated fn are its own parameters followed by
+        // those of its container, which are mapped back to the names they
+        // have in the container, as for `AssociatedTyDatum`.
+        let interner = s.db().interner();
+        let fn_def_datum = s.db().fn_def_datum(self.id);
+        let container_binders = match &self.container {
+            AssociatedFnContainer::Trait(trait_id) => {
+                s.db().trait_datum(*trait_id).binders.binders.clone()
+            }
+            AssociatedFnContainer::InherentImpl(self_ty) => self_ty.binders.clone(),
+        };
+        let container_param_names = s.binder_var_indices(&container_binders);
+        let s = &s.add_debrujin_index(None);
+        let param_names_in_fn_env = s
+            .binder_var_indices(&fn_def_datum.binders.binders)
+            .collect::<Vec<_>>();
+        let num_fn_params = param_names_in_fn_env.len() - container_binders.len(interner);
+        let s = &s.add_parameter_mapping(
+            param_names_in_fn_env[num_fn_params..].iter().copied(),
+            container_param_names,
+        );
+        let bound_datum = fn_def_datum.binders.skip_binders();
+
+        // fn get<U>(&self, arg_0: U) -> Option<T>
+        write!(f, "fn {}", s.db().fn_def_name(self.id))?;
+        let fn_params = s
+            .binder_var_display(&fn_def_datum.binders.binders)
+            .take(num_fn_params);
+        write_joined_non_empty_list!(f, "<{}>", fn_params, ", ")?;
+        {
+            // A `&self` receiver has a late-bound lifetime, the only one an
+            // associated fn can have.
+            let late_bound = !bound_datum.inputs_and_output.binders.is_empty(interner);
+            let s = &s.add_debrujin_index(None);
+            let inputs_and_output = bound_datum.inputs_and_output.skip_binders();
+            let mut argument_types = inputs_and_output.argument_types.iter();
+            let receiver = if self.has_receiver {
+                argument_types.next().map(|ty| match ty.kind(interner) {
+                    chalk_ir::TyKind::Ref(chalk_ir::Mutability::Not, _, _) if late_bound => {
+                        "&self".to_owned()
+                    }
+                    chalk_ir::TyKind::Ref(chalk_ir::Mutability::Mut, _, _) if late_bound => {
+                        "&mut self".to_owned()
+                    }
+                    _ => format!("self: {}", ty.display(s)),
+                })
+            } else {
+                None
+            };
+            let arguments = receiver.into_iter().chain(
+                argument_types
+                    .enumerate()
+                    .map(|(idx, arg)| format!("arg_{}: {}", idx, arg.display(s))),
+            );
+            write!(f, "({})", arguments.format(", "))?;
+            write!(f, " -> {}", inputs_and_output.return_type.display(s))?;
+        }
+
+        // The last where clause of a trait fn is the `Self: Trait` clause
+        // added when lowering it.
+        let mut where_clauses = bound_datum.where_clauses.clone();
+        if let AssociatedFnContainer::Trait(_) = self.container {
+            where_clauses.pop();
+        }
+        if !where_clauses.is_empty() {
+            let where_s = &s.add_indent();
+            write!(
+                f,
+                "\n{}where\n{}",
+                s.indent(),
+                where_clauses.display(where_s)
+            )?;
+        }
+        write!(f, ";")
+    }
+}
+
 impl<I: Interner> RenderAsRust<I> for FnDefDatum<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &mut Formatter<'_>) -> Result {
         let s = &s.add_debrujin_index(None);
@@ -453,6 +683,7 @@ impl<I: Interner> RenderAsRust<I> for FnDefDatum<I> {
         // declaration
         // fn foo<T>(arg: u32, arg2: T) -> Result<T> where T: Bar
         // ^^^^^^
+        write_variances(f, &s.db().fn_def_variance(self.id))?;
         write!(f, "fn {}", s.db().fn_def_name(self.id))?;
 
         // binders
@@ -10,6 +10,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use super::render_trait::RenderAsRust;
 use crate::RustIrDatabase;
 use chalk_ir::{interner::Interner, *};
 use itertools::Itertools;
@@ -344,7 +345,9 @@ impl<'a, I: Interner> InternalWriterState<'a, I> {
             .map(move |(parameter, var)| match parameter {
                 VariableKind::Ty(_) => format!("{}", self.apply_mappings(var)),
                 VariableKind::Lifetime => format!("'{}", self.apply_mappings(var)),
-                VariableKind::Const(_ty) => format!("const {}", self.apply_mappings(var)),
+                VariableKind::Const(ty) => {
+                    format!("const {}: {}", self.apply_mappings(var), ty.display(self))
+                }
             })
     }
 }
@@ -45,6 +45,13 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         Arc::new(v)
     }
 
+    fn associated_const_data(
+        &self,
+        id: chalk_ir::AssocConstId<I>,
+    ) -> std::sync::Arc<crate::rust_ir::AssociatedConstDatum<I>> {
+        self.db.associated_const_data(id)
+    }
+
     fn trait_datum(
         &self,
         trait_id: chalk_ir::TraitId<I>,
@@ -79,6 +86,32 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         self.db.adt_repr(id)
     }
 
+    fn adt_variance(&self, adt_id: chalk_ir::AdtId<I>) -> Vec<chalk_ir::Variance> {
+        self.db.adt_variance(adt_id)
+    }
+
+    fn fn_def_variance(&self, fn_def_id: chalk_ir::FnDefId<I>) -> Vec<chalk_ir::Variance> {
+        self.db.fn_def_variance(fn_def_id)
+    }
+
+    fn associated_fns(
+        &self,
+        name: &I::Identifier,
+    ) -> Vec<std::sync::Arc<crate::rust_ir::AssociatedFnDatum<I>>> {
+        self.db.associated_fns(name)
+    }
+
+    fn associated_fn_data(
+        &self,
+        fn_def_id: chalk_ir::FnDefId<I>,
+    ) -> Option<std::sync::Arc<crate::rust_ir::AssociatedFnDatum<I>>> {
+        self.db.associated_fn_data(fn_def_id)
+    }
+
+    fn opaque_ty_variance(&self, id: chalk_ir::OpaqueTyId<I>) -> Vec<chalk_ir::Variance> {
+        self.db.opaque_ty_variance(id)
+    }
+
     fn fn_def_datum(
         &self,
         fn_def_id: chalk_ir::FnDefId<I>,
@@ -108,6 +141,13 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         unreachable!("associated type values should never be stubbed")
     }
 
+    fn associated_const_value(
+        &self,
+        _id: crate::rust_ir::AssociatedConstValueId<I>,
+    ) -> std::sync::Arc<crate::rust_ir::AssociatedConstValue<I>> {
+        unreachable!("associated const values should never be stubbed")
+    }
+
     fn opaque_ty_data(
         &self,
         id: chalk_ir::OpaqueTyId<I>,
@@ -148,6 +188,13 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         self.db.local_impls_to_coherence_check(trait_id)
     }
 
+    fn upstream_impls_to_coherence_check(
+        &self,
+        trait_id: chalk_ir::TraitId<I>,
+    ) -> Vec<chalk_ir::ImplId<I>> {
+        self.db.upstream_impls_to_coherence_check(trait_id)
+    }
+
     fn impl_provided_for(
         &self,
         _auto_trait_id: chalk_ir::TraitId<I>,
@@ -165,6 +212,13 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         self.db.well_known_trait_id(well_known_trait)
     }
 
+    fn well_known_adt_id(
+        &self,
+        well_known_adt: crate::rust_ir::WellKnownAdt,
+    ) -> Option<chalk_ir::AdtId<I>> {
+        self.db.well_known_adt_id(well_known_adt)
+    }
+
     fn program_clauses_for_env(
         &self,
         environment: &chalk_ir::Environment<I>,
@@ -184,7 +238,7 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         &self,
         _closure_id: chalk_ir::ClosureId<I>,
         _substs: &chalk_ir::Substitution<I>,
-    ) -> crate::rust_ir::ClosureKind {
+    ) -> Option<crate::rust_ir::ClosureKind> {
         unimplemented!("cannot stub closures")
     }
 
@@ -235,6 +289,10 @@ impl<I: Interner, DB: RustIrDatabase<I>> RustIrDatabase<I> for StubWrapper<'_, D
         self.db.assoc_type_name(assoc_ty_id)
     }
 
+    fn assoc_const_name(&self, assoc_const_id: chalk_ir::AssocConstId<I>) -> String {
+        self.db.assoc_const_name(assoc_const_id)
+    }
+
     fn opaque_type_name(&self, opaque_ty_id: chalk_ir::OpaqueTyId<I>) -> String {
         self.db.opaque_type_name(opaque_ty_id)
     }
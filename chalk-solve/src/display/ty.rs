@@ -160,6 +160,33 @@ impl<I: Interner> RenderAsRust<I> for ProjectionTy<I> {
     }
 }
 
+impl<I: Interner> RenderAsRust<I> for ConstProjection<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        // <X as Y<A1, A2, A3>>::N
+        let interner = s.db().interner();
+        let assoc_const_datum = s.db().associated_const_data(self.associated_const_id);
+        let trait_params = self.substitution.as_slice(interner);
+        write!(
+            f,
+            "<{} as {}>::{}",
+            trait_params[0].display(s),
+            display_type_with_generics(s, assoc_const_datum.trait_id, &trait_params[1..]),
+            assoc_const_datum.id.display(s),
+        )
+    }
+}
+
+impl<I: Interner> RenderAsRust<I> for ConstExpr<I> {
+    fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
+        match self {
+            ConstExpr::Unary(op, a) => write!(f, "{}{}", op.as_str(), a.display(s)),
+            ConstExpr::Binary(op, a, b) => {
+                write!(f, "{} {} {}", a.display(s), op.as_str(), b.display(s))
+            }
+        }
+    }
+}
+
 impl<I: Interner> RenderAsRust<I> for OpaqueTy<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
         let interner = s.db().interner();
@@ -249,7 +276,17 @@ impl<I: Interner> RenderAsRust<I> for LifetimeData<I> {
 
 impl<I: Interner> RenderAsRust<I> for ConstData<I> {
     fn fmt(&self, s: &InternalWriterState<'_, I>, f: &'_ mut Formatter<'_>) -> Result {
-        write!(f, "{}", self.value.display(s))
+        write!(f, "{}", self.value.display(s))?;
+        // Integers are written with a suffix, so that they keep their type
+        // when reparsed.
+        if let ConstValue::Concrete(_) = self.value {
+            if let TyKind::Scalar(scalar @ Scalar::Int(_))
+            | TyKind::Scalar(scalar @ Scalar::Uint(_)) = self.ty.kind(s.db().interner())
+            {
+                write!(f, "{}", scalar.display(s))?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -259,7 +296,9 @@ impl<I: Interner> RenderAsRust<I> for ConstValue<I> {
             ConstValue::BoundVar(v) => write!(f, "{}", s.display_bound_var(v)),
             ConstValue::InferenceVar(_) => write!(f, "_"),
             ConstValue::Placeholder(_) => write!(f, "<const placeholder>"),
-            ConstValue::Concrete(_value) => unimplemented!("const values"),
+            ConstValue::Concrete(value) => write!(f, "{:?}", value),
+            ConstValue::Projection(projection) => write!(f, "{}", projection.display(s)),
+            ConstValue::Expr(expr) => write!(f, "{{ {} }}", expr.display(s)),
         }
     }
 }
@@ -2,6 +2,7 @@ use super::var::*;
 use super::*;
 use crate::debug_span;
 use crate::infer::instantiate::IntoBindersAndValue;
+use crate::RustIrDatabase;
 use chalk_ir::cast::Cast;
 use chalk_ir::fold::{Fold, Folder};
 use chalk_ir::interner::{HasInterner, Interner};
@@ -21,7 +22,38 @@ impl<I: Interner> InferenceTable<I> {
         T: ?Sized + Zip<I>,
     {
         let snapshot = self.snapshot();
-        match Unifier::new(interner, self, environment).unify(a, b) {
+        match Unifier::new(interner, None, self, environment).unify(a, b) {
+            Ok(r) => {
+                self.commit(snapshot);
+                Ok(r)
+            }
+            Err(e) => {
+                self.rollback_to(snapshot);
+                Err(e)
+            }
+        }
+    }
+
+    /// Relates the types `a` and `b` according to `variance`: with
+    /// `Variance::Covariant`, `a` must be a subtype of `b`; with
+    /// `Variance::Contravariant`, `b` must be a subtype of `a`; and with
+    /// `Variance::Invariant`, the two types are unified. The variances of
+    /// the parameters of ADTs, fn defs and opaque types are taken from `db`.
+    ///
+    /// Subtyping in Rust only arises from lifetimes, so the result contains
+    /// `LifetimeOutlives` goals (e.g. `'a: 'b` for `&'a T <: &'b T`).
+    #[instrument(level = "debug", skip(self, interner, db, environment))]
+    pub fn relate(
+        &mut self,
+        interner: &I,
+        db: &dyn RustIrDatabase<I>,
+        environment: &Environment<I>,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+    ) -> Fallible<UnificationResult<I>> {
+        let snapshot = self.snapshot();
+        match Unifier::new(interner, Some(db), self, environment).relate(variance, a, b) {
             Ok(r) => {
                 self.commit(snapshot);
                 Ok(r)
@@ -39,6 +71,7 @@ struct Unifier<'t, I: Interner> {
     environment: &'t Environment<I>,
     goals: Vec<InEnvironment<Goal<I>>>,
     interner: &'t I,
+    db: Option<&'t dyn RustIrDatabase<I>>,
 }
 
 #[derive(Debug)]
@@ -49,6 +82,7 @@ pub struct UnificationResult<I: Interner> {
 impl<'t, I: Interner> Unifier<'t, I> {
     fn new(
         interner: &'t I,
+        db: Option<&'t dyn RustIrDatabase<I>>,
         table: &'t mut InferenceTable<I>,
         environment: &'t Environment<I>,
     ) -> Self {
@@ -57,6 +91,7 @@ impl<'t, I: Interner> Unifier<'t, I> {
             table,
             goals: vec![],
             interner,
+            db,
         }
     }
 
@@ -71,7 +106,19 @@ impl<'t, I: Interner> Unifier<'t, I> {
         Ok(UnificationResult { goals: self.goals })
     }
 
-    fn unify_ty_ty(&mut self, a: &Ty<I>, b: &Ty<I>) -> Fallible<()> {
+    /// Like `unify`, but relates `a` and `b` according to `variance`
+    /// rather than requiring them to be equal.
+    fn relate(
+        mut self,
+        variance: Variance,
+        a: &Ty<I>,
+        b: &Ty<I>,
+    ) -> Fallible<UnificationResult<I>> {
+        self.relate_ty_ty(variance, a, b)?;
+        Ok(UnificationResult { goals: self.goals })
+    }
+
+    fn relate_ty_ty(&mut self, variance: Variance, a: &Ty<I>, b: &Ty<I>) -> Fallible<()> {
         let interner = self.interner;
 
         let n_a = self.table.normalize_ty_shallow(interner, a);
@@ -79,11 +126,12 @@ impl<'t, I: Interner> Unifier<'t, I> {
         let a = n_a.as_ref().unwrap_or(a);
         let b = n_b.as_ref().unwrap_or(b);
 
-        debug_span!("unify_ty_ty", ?a, ?b);
+        debug_span!("relate_ty_ty", ?variance, ?a, ?b);
 
         match (a.kind(interner), b.kind(interner)) {
-            // Unifying two inference variables: unify them in the underlying
-            // ena table.
+            // Relating two inference variables: unify them in the underlying
+            // ena table. (For subtyping, this is an approximation: the two
+            // types could differ in their lifetimes.)
             (&TyKind::InferenceVar(var1, kind1), &TyKind::InferenceVar(var2, kind2)) => {
                 if kind1 == kind2 {
                     self.unify_var_var(var1, var2)
@@ -100,10 +148,10 @@ impl<'t, I: Interner> Unifier<'t, I> {
                 }
             }
 
-            // Unifying `forall<X> { T }` with some other forall type `forall<X> { U }`
+            // Relating `forall<X> { T }` with some other forall type `forall<X> { U }`
             (&TyKind::Function(ref fn1), &TyKind::Function(ref fn2)) => {
                 if fn1.sig == fn2.sig {
-                    self.unify_binders(fn1, fn2)
+                    self.relate_fn_ptrs(variance, fn1, fn2)
                 } else {
                     Err(NoSolution)
                 }
@@ -125,16 +173,23 @@ impl<'t, I: Interner> Unifier<'t, I> {
             (_, &TyKind::Alias(ref alias)) => self.unify_alias_ty(alias, a),
             (&TyKind::Alias(ref alias), _) => self.unify_alias_ty(alias, b),
 
-            // Unifying an inference variable with a non-inference variable.
+            // Relating an inference variable with a non-inference variable.
             (&TyKind::InferenceVar(var, kind), ty_data @ _)
             | (ty_data @ _, &TyKind::InferenceVar(var, kind)) => {
                 let ty = ty_data.clone().intern(interner);
 
                 match (kind, ty.is_integer(interner), ty.is_float(interner)) {
-                    // General inference variables can unify with any type
-                    (TyVariableKind::General, _, _)
+                    // General inference variables can be related to any type
+                    (TyVariableKind::General, _, _) => {
+                        if variance == Variance::Invariant {
+                            self.unify_var_ty(var, &ty)
+                        } else {
+                            let a_is_var = matches!(a.kind(interner), TyKind::InferenceVar(..));
+                            self.relate_var_ty(variance, var, &ty, a_is_var)
+                        }
+                    }
                     // Integer inference variables can only unify with integer types
-                    | (TyVariableKind::Integer, true, _)
+                    (TyVariableKind::Integer, true, _)
                     // Float inference variables can only unify with float types
                     | (TyVariableKind::Float, _, true) => self.unify_var_ty(var, &ty),
                     _ => Err(NoSolution),
@@ -153,7 +208,13 @@ impl<'t, I: Interner> Unifier<'t, I> {
 
             (TyKind::Adt(id_a, substitution_a), TyKind::Adt(id_b, substitution_b)) => {
                 Zip::zip_with(self, id_a, id_b)?;
-                Zip::zip_with(self, substitution_a, substitution_b)
+                let variances = self.variances(variance, |db| db.adt_variance(*id_a));
+                self.relate_substitutions(
+                    variance,
+                    variances.as_deref(),
+                    substitution_a,
+                    substitution_b,
+                )
             }
             (
                 TyKind::AssociatedType(assoc_ty_a, substitution_a),
@@ -166,38 +227,61 @@ impl<'t, I: Interner> Unifier<'t, I> {
                 Zip::zip_with(self, scalar_a, scalar_b)
             }
             (TyKind::Str, TyKind::Str) => Ok(()),
-            (TyKind::Tuple(_arity_a, substitution_a), TyKind::Tuple(_arity_b, substitution_b)) => {
-                Zip::zip_with(self, substitution_a, substitution_b)
+            (TyKind::Tuple(arity_a, substitution_a), TyKind::Tuple(arity_b, substitution_b)) => {
+                if arity_a != arity_b {
+                    return Err(NoSolution);
+                }
+                self.relate_substitutions(variance, None, substitution_a, substitution_b)
             }
             (
                 TyKind::OpaqueType(opaque_ty_a, substitution_a),
                 TyKind::OpaqueType(opaque_ty_b, substitution_b),
             ) => {
                 Zip::zip_with(self, opaque_ty_a, opaque_ty_b)?;
-                Zip::zip_with(self, substitution_a, substitution_b)
-            }
-            (TyKind::Slice(substitution_a), TyKind::Slice(substitution_b)) => {
-                Zip::zip_with(self, substitution_a, substitution_b)
+                let variances = self.variances(variance, |db| db.opaque_ty_variance(*opaque_ty_a));
+                self.relate_substitutions(
+                    variance,
+                    variances.as_deref(),
+                    substitution_a,
+                    substitution_b,
+                )
             }
+            (TyKind::Slice(ty_a), TyKind::Slice(ty_b)) => self.relate_ty_ty(variance, ty_a, ty_b),
             (TyKind::FnDef(fn_def_a, substitution_a), TyKind::FnDef(fn_def_b, substitution_b)) => {
                 Zip::zip_with(self, fn_def_a, fn_def_b)?;
-                Zip::zip_with(self, substitution_a, substitution_b)
+                let variances = self.variances(variance, |db| db.fn_def_variance(*fn_def_a));
+                self.relate_substitutions(
+                    variance,
+                    variances.as_deref(),
+                    substitution_a,
+                    substitution_b,
+                )
             }
             (
                 TyKind::Ref(mutability_a, lifetime_a, ty_a),
                 TyKind::Ref(mutability_b, lifetime_b, ty_b),
             ) => {
                 Zip::zip_with(self, mutability_a, mutability_b)?;
-                Zip::zip_with(self, lifetime_a, lifetime_b)?;
-                Zip::zip_with(self, ty_a, ty_b)
+                // `&'a T` is covariant in `'a`, and `&'a mut T` is covariant in
+                // `'a` but invariant in `T`.
+                self.relate_lifetime_lifetime(variance, lifetime_a, lifetime_b)?;
+                let ty_variance = match mutability_a {
+                    Mutability::Not => variance,
+                    Mutability::Mut => Variance::Invariant,
+                };
+                self.relate_ty_ty(ty_variance, ty_a, ty_b)
             }
             (TyKind::Raw(mutability_a, ty_a), TyKind::Raw(mutability_b, ty_b)) => {
                 Zip::zip_with(self, mutability_a, mutability_b)?;
-                Zip::zip_with(self, ty_a, ty_b)
+                let ty_variance = match mutability_a {
+                    Mutability::Not => variance,
+                    Mutability::Mut => Variance::Invariant,
+                };
+                self.relate_ty_ty(ty_variance, ty_a, ty_b)
             }
             (TyKind::Never, TyKind::Never) => Ok(()),
             (TyKind::Array(ty_a, const_a), TyKind::Array(ty_b, const_b)) => {
-                Zip::zip_with(self, ty_a, ty_b)?;
+                self.relate_ty_ty(variance, ty_a, ty_b)?;
                 Zip::zip_with(self, const_a, const_b)
             }
             (TyKind::Closure(id_a, substitution_a), TyKind::Closure(id_b, substitution_b)) => {
@@ -222,6 +306,150 @@ impl<'t, I: Interner> Unifier<'t, I> {
         }
     }
 
+    /// Looks up the declared variances of the parameters of some item,
+    /// using `f`. When relating invariantly, or when no database is
+    /// available, this returns `None`, and the parameters are related
+    /// with `variance` itself.
+    fn variances(
+        &self,
+        variance: Variance,
+        f: impl FnOnce(&dyn RustIrDatabase<I>) -> Vec<Variance>,
+    ) -> Option<Vec<Variance>> {
+        match (variance, self.db) {
+            (Variance::Invariant, _) | (_, None) => None,
+            (_, Some(db)) => Some(f(db)),
+        }
+    }
+
+    /// Relates the parameters `a` and `b` of some type. The `i`th
+    /// parameter is related with `variance.xform(variances[i])`, or
+    /// with `variance` if `variances` is `None`. Parameters that
+    /// `variances` has no entry for are invariant.
+    fn relate_substitutions(
+        &mut self,
+        variance: Variance,
+        variances: Option<&[Variance]>,
+        a: &Substitution<I>,
+        b: &Substitution<I>,
+    ) -> Fallible<()> {
+        let interner = self.interner;
+        let a = a.as_slice(interner);
+        let b = b.as_slice(interner);
+        if a.len() != b.len() {
+            return Err(NoSolution);
+        }
+        for (i, (a, b)) in a.iter().zip(b).enumerate() {
+            let param_variance = match variances {
+                Some(variances) => {
+                    variance.xform(variances.get(i).copied().unwrap_or(Variance::Invariant))
+                }
+                None => variance,
+            };
+            self.relate_generic_arg(param_variance, a, b)?;
+        }
+        Ok(())
+    }
+
+    fn relate_generic_arg(
+        &mut self,
+        variance: Variance,
+        a: &GenericArg<I>,
+        b: &GenericArg<I>,
+    ) -> Fallible<()> {
+        let interner = self.interner;
+        match (a.data(interner), b.data(interner)) {
+            (GenericArgData::Ty(a), GenericArgData::Ty(b)) => self.relate_ty_ty(variance, a, b),
+            (GenericArgData::Lifetime(a), GenericArgData::Lifetime(b)) => {
+                self.relate_lifetime_lifetime(variance, a, b)
+            }
+            (GenericArgData::Const(a), GenericArgData::Const(b)) => self.unify_const_const(a, b),
+            (_, _) => Err(NoSolution),
+        }
+    }
+
+    /// Relates two function pointers. The arguments are related
+    /// contravariantly and the return type covariantly.
+    ///
+    /// ```notrust
+    /// for<'a...> fn(A) -> R <: for<'b...> fn(B) -> S
+    /// ```
+    ///
+    /// holds if, for all `'b...`, there exist `'a...` such that
+    /// `B <: A` and `R <: S`.
+    fn relate_fn_ptrs(
+        &mut self,
+        variance: Variance,
+        a: &FnPointer<I>,
+        b: &FnPointer<I>,
+    ) -> Fallible<()> {
+        let interner = self.interner;
+        let (a, b) = match variance {
+            Variance::Invariant => return self.unify_binders(a, b),
+            Variance::Covariant => {
+                let b_universal = self.table.instantiate_binders_universally(interner, b);
+                let a_existential = self.table.instantiate_binders_existentially(interner, a);
+                (a_existential, b_universal)
+            }
+            Variance::Contravariant => {
+                let a_universal = self.table.instantiate_binders_universally(interner, a);
+                let b_existential = self.table.instantiate_binders_existentially(interner, b);
+                (a_universal, b_existential)
+            }
+        };
+
+        // The last parameter of a function pointer is its return type.
+        let a = a.as_slice(interner);
+        let b = b.as_slice(interner);
+        if a.len() != b.len() {
+            return Err(NoSolution);
+        }
+        let inputs_variance = variance.xform(Variance::Contravariant);
+        let len = a.len();
+        for (i, (a, b)) in a.iter().zip(b).enumerate() {
+            let param_variance = if i + 1 == len {
+                variance
+            } else {
+                inputs_variance
+            };
+            self.relate_generic_arg(param_variance, a, b)?;
+        }
+        Ok(())
+    }
+
+    /// Relates a general inference variable `var` with some non-inference
+    /// variable `ty`, where `var` was on the left-hand side of the relation
+    /// if `var_is_a` is true. As `var` need not be equal to `ty`, we first
+    /// bind `var` to a copy of `ty` in which all lifetimes have been
+    /// replaced by fresh lifetime variables, and then relate that copy with
+    /// `ty`. For example, relating `?X <: &'a u32` binds `?X` to `&'?0 u32`
+    /// and requires `'?0: 'a`.
+    fn relate_var_ty(
+        &mut self,
+        variance: Variance,
+        var: InferenceVar,
+        ty: &Ty<I>,
+        var_is_a: bool,
+    ) -> Fallible<()> {
+        debug_span!("relate_var_ty", ?variance, ?var, ?ty);
+
+        let universe_index = self.table.universe_of_unbound_var(EnaVariable::from(var));
+        let generalized = ty.fold_with(
+            &mut Generalize {
+                table: &mut *self.table,
+                interner: self.interner,
+                universe_index,
+            },
+            DebruijnIndex::INNERMOST,
+        )?;
+        self.unify_var_ty(var, &generalized)?;
+
+        if var_is_a {
+            self.relate_ty_ty(variance, &generalized, ty)
+        } else {
+            self.relate_ty_ty(variance, ty, &generalized)
+        }
+    }
+
     /// Unify two inference variables
     #[instrument(level = "debug", skip(self))]
     fn unify_var_var(&mut self, a: InferenceVar, b: InferenceVar) -> Fallible<()> {
@@ -397,6 +625,35 @@ impl<'t, I: Interner> Unifier<'t, I> {
         }
     }
 
+    /// Relates two lifetimes according to `variance`. For
+    /// `Variance::Covariant`, `a` must outlive `b`.
+    fn relate_lifetime_lifetime(
+        &mut self,
+        variance: Variance,
+        a: &Lifetime<I>,
+        b: &Lifetime<I>,
+    ) -> Fallible<()> {
+        let interner = self.interner;
+
+        let n_a = self.table.normalize_lifetime_shallow(interner, a);
+        let n_b = self.table.normalize_lifetime_shallow(interner, b);
+        let a = n_a.as_ref().unwrap_or(a);
+        let b = n_b.as_ref().unwrap_or(b);
+
+        debug_span!("relate_lifetime_lifetime", ?variance, ?a, ?b);
+
+        if a == b {
+            return Ok(());
+        }
+
+        match variance {
+            Variance::Invariant => return self.unify_lifetime_lifetime(a, b),
+            Variance::Covariant => self.push_lifetime_outlives_goal(a.clone(), b.clone()),
+            Variance::Contravariant => self.push_lifetime_outlives_goal(b.clone(), a.clone()),
+        }
+        Ok(())
+    }
+
     #[instrument(level = "debug", skip(self, a, b))]
     fn unify_lifetime_var(
         &mut self,
@@ -430,10 +687,8 @@ impl<'t, I: Interner> Unifier<'t, I> {
     fn unify_const_const<'a>(&mut self, a: &'a Const<I>, b: &'a Const<I>) -> Fallible<()> {
         let interner = self.interner;
 
-        let n_a = self.table.normalize_const_shallow(interner, a);
-        let n_b = self.table.normalize_const_shallow(interner, b);
-        let a = n_a.as_ref().unwrap_or(a);
-        let b = n_b.as_ref().unwrap_or(b);
+        let a = &self.resolve_const(a);
+        let b = &self.resolve_const(b);
 
         debug_span!("unify_const_const", ?a, ?b);
 
@@ -446,7 +701,7 @@ impl<'t, I: Interner> Unifier<'t, I> {
             value: b_val,
         } = b.data(interner);
 
-        self.unify_ty_ty(a_ty, b_ty)?;
+        self.relate_ty_ty(Variance::Invariant, a_ty, b_ty)?;
 
         match (a_val, b_val) {
             // Unifying two inference variables: unify them in the underlying
@@ -464,17 +719,33 @@ impl<'t, I: Interner> Unifier<'t, I> {
 
             // Unifying an inference variables with a non-inference variable.
             (&ConstValue::InferenceVar(var), &ConstValue::Concrete(_))
-            | (&ConstValue::InferenceVar(var), &ConstValue::Placeholder(_)) => {
+            | (&ConstValue::InferenceVar(var), &ConstValue::Placeholder(_))
+            | (&ConstValue::InferenceVar(var), &ConstValue::Projection(_))
+            | (&ConstValue::InferenceVar(var), &ConstValue::Expr(_)) => {
                 debug!(?var, ty=?b, "unify_var_ty");
                 self.unify_var_const(var, b)
             }
 
             (&ConstValue::Concrete(_), &ConstValue::InferenceVar(var))
-            | (&ConstValue::Placeholder(_), &ConstValue::InferenceVar(var)) => {
+            | (&ConstValue::Placeholder(_), &ConstValue::InferenceVar(var))
+            | (&ConstValue::Projection(_), &ConstValue::InferenceVar(var))
+            | (&ConstValue::Expr(_), &ConstValue::InferenceVar(var)) => {
                 debug!(?var, ty=?a, "unify_var_ty");
                 self.unify_var_const(var, a)
             }
 
+            // The same projection, e.g. `<T as Trait>::N` where `T` is a
+            // placeholder, is equal to itself even if it cannot be
+            // normalized.
+            (ConstValue::Projection(p1), ConstValue::Projection(p2)) if p1 == p2 => Ok(()),
+
+            // Unifying a const projection with some other const.
+            (ConstValue::Projection(projection), _) => self.unify_const_projection(projection, b),
+            (_, ConstValue::Projection(projection)) => self.unify_const_projection(projection, a),
+
+            // Unifying a const expression that could not be evaluated.
+            (ConstValue::Expr(_), _) | (_, ConstValue::Expr(_)) => self.unify_const_expr(a, b),
+
             (&ConstValue::Placeholder(p1), &ConstValue::Placeholder(p2)) => {
                 Zip::zip_with(self, &p1, &p2)
             }
@@ -497,6 +768,98 @@ impl<'t, I: Interner> Unifier<'t, I> {
         }
     }
 
+    /// Unify a const projection like `<T as Trait>::N` with some other
+    /// const `c` (which might also be a projection). Creates a goal like
+    ///
+    /// ```notrust
+    /// NormalizeConst(<T as Trait>::N -> C)
+    /// ```
+    fn unify_const_projection(
+        &mut self,
+        projection: &ConstProjection<I>,
+        c: &Const<I>,
+    ) -> Fallible<()> {
+        let interner = self.interner;
+        Ok(self.goals.push(InEnvironment::new(
+            self.environment,
+            NormalizeConst {
+                alias: projection.clone(),
+                value: c.clone(),
+            }
+            .cast(interner),
+        )))
+    }
+
+    /// Resolves the inference variables of `c` that are bound, and evaluates
+    /// the const expressions whose operands are all known.
+    fn resolve_const(&mut self, c: &Const<I>) -> Const<I> {
+        let interner = self.interner;
+        let c = self
+            .table
+            .normalize_const_shallow(interner, c)
+            .unwrap_or_else(|| c.clone());
+        let ConstData { ty, value } = c.data(interner);
+        let expr = match value {
+            ConstValue::Expr(ConstExpr::Unary(op, a)) => {
+                ConstExpr::Unary(*op, self.resolve_const(a))
+            }
+            ConstValue::Expr(ConstExpr::Binary(op, a, b)) => {
+                ConstExpr::Binary(*op, self.resolve_const(a), self.resolve_const(b))
+            }
+            _ => return c,
+        };
+        let value = match interner.evaluate_const_expr(ty.interned(), &expr) {
+            Some(interned) => ConstValue::Concrete(ConcreteConst { interned }),
+            None => ConstValue::Expr(expr),
+        };
+        ConstData {
+            ty: ty.clone(),
+            value,
+        }
+        .intern(interner)
+    }
+
+    /// Unifies `a` and `b`, one of which is a const expression that could
+    /// not be evaluated. Two expressions are equal if they are structurally
+    /// equal. Otherwise, they may still turn out to be equal once their
+    /// operands are known, in which case the result is ambiguous.
+    fn unify_const_expr(&mut self, a: &Const<I>, b: &Const<I>) -> Fallible<()> {
+        let interner = self.interner;
+        if let (ConstValue::Expr(expr_a), ConstValue::Expr(expr_b)) =
+            (&a.data(interner).value, &b.data(interner).value)
+        {
+            let snapshot = self.table.snapshot();
+            let goals_len = self.goals.len();
+            if Zip::zip_with(self, expr_a, expr_b).is_ok() {
+                self.table.commit(snapshot);
+                return Ok(());
+            }
+            self.table.rollback_to(snapshot);
+            self.goals.truncate(goals_len);
+        }
+
+        if self.is_known_const(a) && self.is_known_const(b) {
+            return Err(NoSolution);
+        }
+        Ok(self.goals.push(InEnvironment::new(
+            self.environment,
+            GoalData::CannotProve.intern(interner),
+        )))
+    }
+
+    /// Whether `c`, once resolved, does not depend on inference variables
+    /// or on const projections, which could make it equal to other consts.
+    fn is_known_const(&self, c: &Const<I>) -> bool {
+        match &c.data(self.interner).value {
+            ConstValue::InferenceVar(_) | ConstValue::Projection(_) => false,
+            ConstValue::BoundVar(_) | ConstValue::Placeholder(_) | ConstValue::Concrete(_) => true,
+            ConstValue::Expr(ConstExpr::Unary(_, a)) => self.is_known_const(a),
+            ConstValue::Expr(ConstExpr::Binary(_, a, b)) => {
+                self.is_known_const(a) && self.is_known_const(b)
+            }
+        }
+    }
+
     #[instrument(level = "debug", skip(self))]
     fn unify_var_const(&mut self, var: InferenceVar, c: &Const<I>) -> Fallible<()> {
         let interner = self.interner;
@@ -524,24 +887,22 @@ impl<'t, I: Interner> Unifier<'t, I> {
     }
 
     fn push_lifetime_eq_goals(&mut self, a: Lifetime<I>, b: Lifetime<I>) {
+        self.push_lifetime_outlives_goal(a.clone(), b.clone());
+        self.push_lifetime_outlives_goal(b, a);
+    }
+
+    /// Pushes a goal requiring `a: b`.
+    fn push_lifetime_outlives_goal(&mut self, a: Lifetime<I>, b: Lifetime<I>) {
         self.goals.push(InEnvironment::new(
             self.environment,
-            WhereClause::LifetimeOutlives(LifetimeOutlives {
-                a: a.clone(),
-                b: b.clone(),
-            })
-            .cast(self.interner),
-        ));
-        self.goals.push(InEnvironment::new(
-            self.environment,
-            WhereClause::LifetimeOutlives(LifetimeOutlives { a: b, b: a }).cast(self.interner),
+            WhereClause::LifetimeOutlives(LifetimeOutlives { a, b }).cast(self.interner),
         ));
     }
 }
 
 impl<'i, I: Interner> Zipper<'i, I> for Unifier<'i, I> {
     fn zip_tys(&mut self, a: &Ty<I>, b: &Ty<I>) -> Fallible<()> {
-        self.unify_ty_ty(a, b)
+        self.relate_ty_ty(Variance::Invariant, a, b)
     }
 
     fn zip_lifetimes(&mut self, a: &Lifetime<I>, b: &Lifetime<I>) -> Fallible<()> {
@@ -799,3 +1160,44 @@ where
         self.interner()
     }
 }
+
+/// Replaces the free lifetimes in a type with fresh lifetime variables in
+/// `universe_index`; see `Unifier::relate_var_ty`.
+struct Generalize<'t, 'i, I: Interner> {
+    table: &'t mut InferenceTable<I>,
+    interner: &'i I,
+    universe_index: UniverseIndex,
+}
+
+impl<'i, I: Interner> Folder<'i, I> for Generalize<'_, 'i, I>
+where
+    I: 'i,
+{
+    fn as_dyn(&mut self) -> &mut dyn Folder<'i, I> {
+        self
+    }
+
+    fn fold_lifetime(
+        &mut self,
+        lifetime: &Lifetime<I>,
+        _outer_binder: DebruijnIndex,
+    ) -> Fallible<Lifetime<I>> {
+        let interner = self.interner;
+        match lifetime.data(interner) {
+            // Lifetimes bound within the type, e.g. by a `for<'a>`, stay as they are.
+            LifetimeData::BoundVar(_) => Ok(lifetime.clone()),
+            _ => Ok(self
+                .table
+                .new_variable(self.universe_index)
+                .to_lifetime(interner)),
+        }
+    }
+
+    fn interner(&self) -> &'i I {
+        self.interner
+    }
+
+    fn target_interner(&self) -> &'i I {
+        self.interner()
+    }
+}
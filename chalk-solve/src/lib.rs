@@ -49,6 +49,9 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     /// Returns the datum for the associated type with the given id.
     fn associated_ty_data(&self, ty: AssocTypeId<I>) -> Arc<AssociatedTyDatum<I>>;
 
+    /// Returns the datum for the associated const with the given id.
+    fn associated_const_data(&self, id: AssocConstId<I>) -> Arc<AssociatedConstDatum<I>>;
+
     /// Returns the datum for the definition with the given id.
     fn trait_datum(&self, trait_id: TraitId<I>) -> Arc<TraitDatum<I>>;
 
@@ -67,21 +70,57 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     /// Returns the representation for the ADT definition with the given id.
     fn adt_repr(&self, id: AdtId<I>) -> AdtRepr;
 
+    /// Returns the variances of the generic parameters of the ADT with the
+    /// given id, in the order of its substitution. By default, all the
+    /// parameters are invariant.
+    fn adt_variance(&self, adt_id: AdtId<I>) -> Vec<Variance> {
+        let adt_datum = self.adt_datum(adt_id);
+        vec![Variance::Invariant; adt_datum.binders.len(self.interner())]
+    }
+
     /// Returns the datum for the fn definition with the given id.
     fn fn_def_datum(&self, fn_def_id: FnDefId<I>) -> Arc<FnDefDatum<I>>;
 
+    /// Returns the variances of the generic parameters of the fn definition
+    /// with the given id, in the order of its substitution. By default, all
+    /// the parameters are invariant.
+    fn fn_def_variance(&self, fn_def_id: FnDefId<I>) -> Vec<Variance> {
+        let fn_def_datum = self.fn_def_datum(fn_def_id);
+        vec![Variance::Invariant; fn_def_datum.binders.len(self.interner())]
+    }
+
+    /// Returns the associated fns with the given name, declared in any trait
+    /// or inherent impl.
+    fn associated_fns(&self, name: &I::Identifier) -> Vec<Arc<AssociatedFnDatum<I>>>;
+
+    /// Returns the datum for the associated fn with the given id, or `None`
+    /// if it is a free fn.
+    fn associated_fn_data(&self, fn_def_id: FnDefId<I>) -> Option<Arc<AssociatedFnDatum<I>>>;
+
     /// Returns the datum for the impl with the given id.
     fn impl_datum(&self, impl_id: ImplId<I>) -> Arc<ImplDatum<I>>;
 
     /// Returns the `AssociatedTyValue` with the given id.
     fn associated_ty_value(&self, id: AssociatedTyValueId<I>) -> Arc<AssociatedTyValue<I>>;
 
+    /// Returns the `AssociatedConstValue` with the given id.
+    fn associated_const_value(&self, id: AssociatedConstValueId<I>)
+        -> Arc<AssociatedConstValue<I>>;
+
     /// Returns the `OpaqueTyDatum` with the given id.
     fn opaque_ty_data(&self, id: OpaqueTyId<I>) -> Arc<OpaqueTyDatum<I>>;
 
     /// Returns the "hidden type" corresponding with the opaque type.
     fn hidden_opaque_type(&self, id: OpaqueTyId<I>) -> Ty<I>;
 
+    /// Returns the variances of the generic parameters of the opaque type
+    /// with the given id, in the order of its substitution. By default, all
+    /// the parameters are invariant.
+    fn opaque_ty_variance(&self, id: OpaqueTyId<I>) -> Vec<Variance> {
+        let opaque_ty_data = self.opaque_ty_data(id);
+        vec![Variance::Invariant; opaque_ty_data.bound.binders.len(self.interner())]
+    }
+
     /// Returns a list of potentially relevant impls for a given
     /// trait-id; we also supply the type parameters that we are
     /// trying to match (if known: these parameters may contain
@@ -109,6 +148,13 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     ///   impls actually written by users need to be checked.
     fn local_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>>;
 
+    /// Returns the impls of upstream crates that the local impls returned by
+    /// `local_impls_to_coherence_check` must be checked against. The upstream
+    /// impls are not checked against each other.
+    fn upstream_impls_to_coherence_check(&self, _trait_id: TraitId<I>) -> Vec<ImplId<I>> {
+        Vec::new()
+    }
+
     /// Returns true if there is an explicit impl of the auto trait
     /// `auto_trait_id` for the type `ty`. This is part of
     /// the auto trait handling -- if there is no explicit impl given
@@ -119,6 +165,9 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     /// Returns id of a trait lang item, if found
     fn well_known_trait_id(&self, well_known_trait: WellKnownTrait) -> Option<TraitId<I>>;
 
+    /// Returns id of an ADT lang item, if found
+    fn well_known_adt_id(&self, well_known_adt: WellKnownAdt) -> Option<AdtId<I>>;
+
     /// Calculates program clauses from an env. This is intended to call the
     /// `program_clauses_for_env` function and then possibly cache the clauses.
     fn program_clauses_for_env(&self, environment: &Environment<I>) -> ProgramClauses<I>;
@@ -128,8 +177,17 @@ pub trait RustIrDatabase<I: Interner>: Debug {
     /// Check if a trait is object safe
     fn is_object_safe(&self, trait_id: TraitId<I>) -> bool;
 
-    /// Gets the `ClosureKind` for a given closure and substitution.
-    fn closure_kind(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> ClosureKind;
+    /// Gets the `ClosureKind` for a given closure and substitution, or `None`
+    /// if it is not known yet, e.g. because the upvars of the closure have
+    /// not been analyzed yet. In that case, the closure implements `FnOnce`,
+    /// but its `Fn` and `FnMut` impls depend on a `ClosureKind` goal, which
+    /// is ambiguous until the kind is known. The pending goals are found with
+    /// `solve::closure_kinds::closure_kind_obligations`.
+    fn closure_kind(
+        &self,
+        closure_id: ClosureId<I>,
+        substs: &Substitution<I>,
+    ) -> Option<ClosureKind>;
 
     /// Gets the inputs and output for a given closure id and substitution. We
     /// pass both the `ClosureId` and it's `Substituion` to give implementors
@@ -179,6 +237,12 @@ pub trait RustIrDatabase<I: Interner>: Debug {
         sanitize_debug_name(|f| I::debug_assoc_type_id(assoc_ty_id, f))
     }
 
+    /// Retrieves the name of an associated const. No uniqueness guarantees,
+    /// but must a valid Rust identifier.
+    fn assoc_const_name(&self, assoc_const_id: AssocConstId<I>) -> String {
+        sanitize_debug_name(|f| I::debug_assoc_const_id(assoc_const_id, f))
+    }
+
     /// Retrieves the name of an opaque type. No uniqueness guarantees, but must
     /// a valid Rust identifier.
     fn opaque_type_name(&self, opaque_ty_id: OpaqueTyId<I>) -> String {
@@ -89,6 +89,15 @@ where
             .unwrap()
             .extend(ids.into_iter().map(Into::into));
     }
+
+    /// Records an associated fn, and the trait that declares it, if any,
+    /// since trait fns are written as part of their trait.
+    fn record_associated_fn(&self, datum: &AssociatedFnDatum<I>) {
+        self.record(datum.id);
+        if let AssociatedFnContainer::Trait(trait_id) = datum.container {
+            self.record(trait_id);
+        }
+    }
 }
 
 impl<I, DB, P> RustIrDatabase<I> for LoggingRustIrDatabase<I, DB, P>
@@ -110,6 +119,15 @@ where
         ty_datum
     }
 
+    fn associated_const_data(
+        &self,
+        id: chalk_ir::AssocConstId<I>,
+    ) -> Arc<crate::rust_ir::AssociatedConstDatum<I>> {
+        let const_datum = self.ws.db().associated_const_data(id);
+        self.record(const_datum.trait_id);
+        const_datum
+    }
+
     fn trait_datum(&self, trait_id: TraitId<I>) -> Arc<TraitDatum<I>> {
         self.record(trait_id);
         self.ws.db().trait_datum(trait_id)
@@ -138,6 +156,11 @@ where
         self.ws.db().adt_repr(id)
     }
 
+    fn adt_variance(&self, adt_id: AdtId<I>) -> Vec<Variance> {
+        self.record(adt_id);
+        self.ws.db().adt_variance(adt_id)
+    }
+
     fn impl_datum(&self, impl_id: ImplId<I>) -> Arc<ImplDatum<I>> {
         self.record(impl_id);
         self.ws.db().impl_datum(impl_id)
@@ -148,6 +171,11 @@ where
         self.ws.db().hidden_opaque_type(id)
     }
 
+    fn opaque_ty_variance(&self, id: OpaqueTyId<I>) -> Vec<Variance> {
+        self.record(id);
+        self.ws.db().opaque_ty_variance(id)
+    }
+
     fn associated_ty_value(
         &self,
         id: crate::rust_ir::AssociatedTyValueId<I>,
@@ -157,6 +185,15 @@ where
         value
     }
 
+    fn associated_const_value(
+        &self,
+        id: crate::rust_ir::AssociatedConstValueId<I>,
+    ) -> Arc<crate::rust_ir::AssociatedConstValue<I>> {
+        let value = self.ws.db().associated_const_value(id);
+        self.record(value.impl_id);
+        value
+    }
+
     fn opaque_ty_data(&self, id: OpaqueTyId<I>) -> Arc<OpaqueTyDatum<I>> {
         self.record(id);
         self.ws.db().opaque_ty_data(id)
@@ -179,6 +216,13 @@ where
         self.ws.db().local_impls_to_coherence_check(trait_id)
     }
 
+    fn upstream_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>> {
+        self.record(trait_id);
+        let impl_ids = self.ws.db().upstream_impls_to_coherence_check(trait_id);
+        self.record_all(impl_ids.iter().copied());
+        impl_ids
+    }
+
     fn impl_provided_for(&self, auto_trait_id: TraitId<I>, ty: &TyKind<I>) -> bool {
         self.record(auto_trait_id);
         if let TyKind::Adt(adt_id, _) = ty {
@@ -196,6 +240,12 @@ where
         trait_id
     }
 
+    fn well_known_adt_id(&self, well_known_adt: crate::rust_ir::WellKnownAdt) -> Option<AdtId<I>> {
+        let adt_id = self.ws.db().well_known_adt_id(well_known_adt);
+        adt_id.map(|id| self.record(id));
+        adt_id
+    }
+
     fn program_clauses_for_env(
         &self,
         environment: &chalk_ir::Environment<I>,
@@ -219,6 +269,10 @@ where
         self.ws.db().assoc_type_name(assoc_ty_id)
     }
 
+    fn assoc_const_name(&self, assoc_const_id: chalk_ir::AssocConstId<I>) -> String {
+        self.ws.db().assoc_const_name(assoc_const_id)
+    }
+
     fn opaque_type_name(&self, opaque_ty_id: OpaqueTyId<I>) -> String {
         self.ws.db().opaque_type_name(opaque_ty_id)
     }
@@ -233,11 +287,36 @@ where
         self.ws.db().fn_def_datum(fn_def_id)
     }
 
+    fn associated_fns(&self, name: &I::Identifier) -> Vec<Arc<AssociatedFnDatum<I>>> {
+        let fns = self.ws.db().associated_fns(name);
+        for datum in &fns {
+            self.record_associated_fn(datum);
+        }
+        fns
+    }
+
+    fn associated_fn_data(&self, fn_def_id: FnDefId<I>) -> Option<Arc<AssociatedFnDatum<I>>> {
+        let datum = self.ws.db().associated_fn_data(fn_def_id);
+        if let Some(datum) = &datum {
+            self.record_associated_fn(datum);
+        }
+        datum
+    }
+
+    fn fn_def_variance(&self, fn_def_id: FnDefId<I>) -> Vec<Variance> {
+        self.record(fn_def_id);
+        self.ws.db().fn_def_variance(fn_def_id)
+    }
+
     fn fn_def_name(&self, fn_def_id: FnDefId<I>) -> String {
         self.ws.db().fn_def_name(fn_def_id)
     }
 
-    fn closure_kind(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> ClosureKind {
+    fn closure_kind(
+        &self,
+        closure_id: ClosureId<I>,
+        substs: &Substitution<I>,
+    ) -> Option<ClosureKind> {
         // TODO: record closure IDs
         self.ws.db().closure_kind(closure_id, substs)
     }
@@ -350,6 +429,13 @@ where
         self.db.associated_ty_data(ty)
     }
 
+    fn associated_const_data(
+        &self,
+        id: chalk_ir::AssocConstId<I>,
+    ) -> Arc<crate::rust_ir::AssociatedConstDatum<I>> {
+        self.db.associated_const_data(id)
+    }
+
     fn trait_datum(&self, trait_id: TraitId<I>) -> Arc<TraitDatum<I>> {
         self.db.trait_datum(trait_id)
     }
@@ -374,6 +460,10 @@ where
         self.db.adt_repr(id)
     }
 
+    fn adt_variance(&self, adt_id: AdtId<I>) -> Vec<Variance> {
+        self.db.adt_variance(adt_id)
+    }
+
     fn impl_datum(&self, impl_id: ImplId<I>) -> Arc<ImplDatum<I>> {
         self.db.impl_datum(impl_id)
     }
@@ -385,6 +475,13 @@ where
         self.db.associated_ty_value(id)
     }
 
+    fn associated_const_value(
+        &self,
+        id: crate::rust_ir::AssociatedConstValueId<I>,
+    ) -> Arc<crate::rust_ir::AssociatedConstValue<I>> {
+        self.db.associated_const_value(id)
+    }
+
     fn opaque_ty_data(&self, id: OpaqueTyId<I>) -> Arc<OpaqueTyDatum<I>> {
         self.db.opaque_ty_data(id)
     }
@@ -393,6 +490,10 @@ where
         self.db.hidden_opaque_type(id)
     }
 
+    fn opaque_ty_variance(&self, id: OpaqueTyId<I>) -> Vec<Variance> {
+        self.db.opaque_ty_variance(id)
+    }
+
     fn impls_for_trait(
         &self,
         trait_id: TraitId<I>,
@@ -406,6 +507,10 @@ where
         self.db.local_impls_to_coherence_check(trait_id)
     }
 
+    fn upstream_impls_to_coherence_check(&self, trait_id: TraitId<I>) -> Vec<ImplId<I>> {
+        self.db.upstream_impls_to_coherence_check(trait_id)
+    }
+
     fn impl_provided_for(&self, auto_trait_id: TraitId<I>, ty: &TyKind<I>) -> bool {
         self.db.impl_provided_for(auto_trait_id, ty)
     }
@@ -417,6 +522,10 @@ where
         self.db.well_known_trait_id(well_known_trait)
     }
 
+    fn well_known_adt_id(&self, well_known_adt: crate::rust_ir::WellKnownAdt) -> Option<AdtId<I>> {
+        self.db.well_known_adt_id(well_known_adt)
+    }
+
     fn program_clauses_for_env(
         &self,
         environment: &chalk_ir::Environment<I>,
@@ -444,6 +553,10 @@ where
         self.db.assoc_type_name(assoc_ty_id)
     }
 
+    fn assoc_const_name(&self, assoc_const_id: chalk_ir::AssocConstId<I>) -> String {
+        self.db.assoc_const_name(assoc_const_id)
+    }
+
     fn opaque_type_name(&self, opaque_ty_id: OpaqueTyId<I>) -> String {
         self.db.opaque_type_name(opaque_ty_id)
     }
@@ -452,11 +565,27 @@ where
         self.db.fn_def_datum(fn_def_id)
     }
 
+    fn fn_def_variance(&self, fn_def_id: FnDefId<I>) -> Vec<Variance> {
+        self.db.fn_def_variance(fn_def_id)
+    }
+
+    fn associated_fns(&self, name: &I::Identifier) -> Vec<Arc<AssociatedFnDatum<I>>> {
+        self.db.associated_fns(name)
+    }
+
+    fn associated_fn_data(&self, fn_def_id: FnDefId<I>) -> Option<Arc<AssociatedFnDatum<I>>> {
+        self.db.associated_fn_data(fn_def_id)
+    }
+
     fn fn_def_name(&self, fn_def_id: FnDefId<I>) -> String {
         self.db.fn_def_name(fn_def_id)
     }
 
-    fn closure_kind(&self, closure_id: ClosureId<I>, substs: &Substitution<I>) -> ClosureKind {
+    fn closure_kind(
+        &self,
+        closure_id: ClosureId<I>,
+        substs: &Substitution<I>,
+    ) -> Option<ClosureKind> {
         // TODO: record closure IDs
         self.db.closure_kind(closure_id, substs)
     }
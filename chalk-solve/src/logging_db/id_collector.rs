@@ -1,10 +1,11 @@
 use super::RecordedItemId;
+use crate::rust_ir::AssociatedFnContainer;
 use crate::RustIrDatabase;
 use chalk_ir::{
     interner::Interner,
     visit::Visitor,
     visit::{SuperVisit, Visit},
-    AliasTy, DebruijnIndex, TyKind, WhereClause,
+    AliasTy, Const, ConstValue, DebruijnIndex, TyKind, WhereClause,
 };
 use std::collections::BTreeSet;
 
@@ -52,6 +53,14 @@ pub fn collect_unrecorded_ids<'i, I: Interner, DB: RustIrDatabase<I>>(
                     .db
                     .fn_def_datum(fn_def)
                     .visit_with(&mut collector, DebruijnIndex::INNERMOST);
+                if let Some(assoc_fn_datum) = collector.db.associated_fn_data(fn_def) {
+                    match &assoc_fn_datum.container {
+                        AssociatedFnContainer::Trait(trait_id) => collector.record(*trait_id),
+                        AssociatedFnContainer::InherentImpl(self_ty) => {
+                            self_ty.visit_with(&mut collector, DebruijnIndex::INNERMOST)
+                        }
+                    }
+                }
             }
             RecordedItemId::Generator(_generator_id) => unimplemented!(),
             RecordedItemId::Trait(trait_id) => {
@@ -65,6 +74,19 @@ pub fn collect_unrecorded_ids<'i, I: Interner, DB: RustIrDatabase<I>>(
                         .visit_with(&mut collector, DebruijnIndex::INNERMOST);
                     assoc_ty_datum.visit_with(&mut collector, DebruijnIndex::INNERMOST)
                 }
+                for assoc_const_id in &trait_datum.associated_const_ids {
+                    collector
+                        .db
+                        .associated_const_data(*assoc_const_id)
+                        .ty
+                        .visit_with(&mut collector, DebruijnIndex::INNERMOST);
+                }
+                for assoc_fn_id in &trait_datum.associated_fn_ids {
+                    collector
+                        .db
+                        .fn_def_datum(*assoc_fn_id)
+                        .visit_with(&mut collector, DebruijnIndex::INNERMOST);
+                }
             }
             RecordedItemId::OpaqueTy(opaque_id) => {
                 collector
@@ -82,6 +104,10 @@ pub fn collect_unrecorded_ids<'i, I: Interner, DB: RustIrDatabase<I>>(
                     let assoc_ty_value = collector.db.associated_ty_value(*id);
                     assoc_ty_value.visit_with(&mut collector, DebruijnIndex::INNERMOST);
                 }
+                for id in &impl_datum.associated_const_value_ids {
+                    let assoc_const_value = collector.db.associated_const_value(*id);
+                    assoc_const_value.visit_with(&mut collector, DebruijnIndex::INNERMOST);
+                }
                 impl_datum.visit_with(&mut collector, DebruijnIndex::INNERMOST);
             }
         }
@@ -144,6 +170,16 @@ where
         ty.super_visit_with(self, outer_binder)
     }
 
+    fn visit_const(&mut self, constant: &Const<I>, outer_binder: DebruijnIndex) -> Self::Result {
+        if let ConstValue::Projection(projection) = &constant.data(self.db.interner()).value {
+            let assoc_const_datum = self
+                .db
+                .associated_const_data(projection.associated_const_id);
+            self.record(assoc_const_datum.trait_id)
+        }
+        constant.super_visit_with(self.as_dyn(), outer_binder)
+    }
+
     fn visit_where_clause(
         &mut self,
         where_clause: &WhereClause<I>,
@@ -8,9 +8,9 @@ use chalk_ir::fold::shift::Shift;
 use chalk_ir::interner::{Interner, TargetInterner};
 use chalk_ir::{
     visit::{Visit, VisitResult},
-    AdtId, AliasEq, AliasTy, AssocTypeId, Binders, DebruijnIndex, FnDefId, GenericArg, ImplId,
-    OpaqueTyId, ProjectionTy, QuantifiedWhereClause, Substitution, ToGenericArg, TraitId, TraitRef,
-    Ty, TyKind, VariableKind, WhereClause, WithKind,
+    AdtId, AliasEq, AliasTy, AssocConstId, AssocTypeId, Binders, BoundVar, Const, DebruijnIndex,
+    FnDefId, GenericArg, ImplId, OpaqueTyId, ProjectionTy, QuantifiedWhereClause, Scalar,
+    Substitution, ToGenericArg, TraitId, TraitRef, Ty, TyKind, VariableKind, WhereClause, WithKind,
 };
 use std::iter;
 
@@ -21,12 +21,20 @@ pub struct AssociatedTyValueId<I: Interner>(pub I::DefId);
 chalk_ir::id_visit!(AssociatedTyValueId);
 chalk_ir::id_fold!(AssociatedTyValueId);
 
+/// Identifier for an "associated const value" found in some impl.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssociatedConstValueId<I: Interner>(pub I::DefId);
+
+chalk_ir::id_visit!(AssociatedConstValueId);
+chalk_ir::id_fold!(AssociatedConstValueId);
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Visit)]
 pub struct ImplDatum<I: Interner> {
     pub polarity: Polarity,
     pub binders: Binders<ImplDatumBound<I>>,
     pub impl_type: ImplType,
     pub associated_ty_value_ids: Vec<AssociatedTyValueId<I>>,
+    pub associated_const_value_ids: Vec<AssociatedConstValueId<I>>,
 }
 
 impl<I: Interner> ImplDatum<I> {
@@ -118,6 +126,9 @@ chalk_ir::const_visit!(AdtFlags);
 pub struct AdtRepr {
     pub repr_c: bool,
     pub repr_packed: bool,
+    /// The integer type given by e.g. `#[repr(u8)]`, which is the type of
+    /// the discriminant of an enum.
+    pub repr_int: Option<Scalar>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -202,6 +213,42 @@ pub struct FnDefDatumBound<I: Interner> {
     pub where_clauses: Vec<QuantifiedWhereClause<I>>,
 }
 
+/// An associated fn, declared in a trait or in an inherent impl:
+///
+/// ```ignore
+/// impl<T> Vec<T> {
+///     fn get<U>(&self, index: U) -> Option<&T>;
+/// }
+/// ```
+///
+/// The signature of the fn is the [`FnDefDatum`] of `id`. Like for
+/// associated types, its binders are the parameters of the fn (`U`)
+/// followed by those of the container: the parameters of the inherent impl
+/// (`T`), or the `Self` type and parameters of the trait. The where clauses
+/// of a trait fn include `Self: Trait<..>`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssociatedFnDatum<I: Interner> {
+    pub id: FnDefId<I>,
+
+    /// The name of the fn, without the path of its container.
+    pub name: I::Identifier,
+
+    pub container: AssociatedFnContainer<I>,
+
+    /// Whether the fn is a method, i.e. has a `self` parameter. The type of
+    /// the receiver is then the first argument of the fn.
+    pub has_receiver: bool,
+}
+
+/// Where an associated fn is declared.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AssociatedFnContainer<I: Interner> {
+    Trait(TraitId<I>),
+    /// An inherent impl, with its self type, bound by the parameters of the
+    /// impl.
+    InherentImpl(Binders<Ty<I>>),
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// A rust intermediate representation (rust_ir) of a Trait Definition. For
 /// example, given the following rust code:
@@ -240,6 +287,10 @@ pub struct TraitDatum<I: Interner> {
 
     pub associated_ty_ids: Vec<AssocTypeId<I>>,
 
+    pub associated_const_ids: Vec<AssocConstId<I>>,
+
+    pub associated_fn_ids: Vec<FnDefId<I>>,
+
     /// If this is a well-known trait, which one? If `None`, this is a regular,
     /// user-defined trait.
     pub well_known: Option<WellKnownTrait>,
@@ -262,6 +313,30 @@ pub enum WellKnownTrait {
     Unsize,
     Unpin,
     CoerceUnsized,
+    /// The trait `Deref`, with the associated type `Target`. References
+    /// implement it for their referent.
+    Deref,
+    /// The trait `DerefMut`, a subtrait of `Deref`. `&mut T` implements it.
+    DerefMut,
+    /// The trait `Generator<R>`, with the associated types `Yield` and
+    /// `Return`, in that order. Generators implement it for their resume
+    /// type `R`.
+    Generator,
+    /// The trait `DiscriminantKind`, with the associated type
+    /// `Discriminant`. Every type implements it.
+    DiscriminantKind,
+    /// The trait `Pointee`, with the associated type `Metadata`: the
+    /// metadata of pointers to the type. Every type implements it.
+    Pointee,
+}
+
+/// A list of the ADTs that are "well known" to chalk: the built-in impls of
+/// well-known traits refer to them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub enum WellKnownAdt {
+    /// The struct `DynMetadata<Dyn>`, which is the metadata of pointers to
+    /// the trait object type `Dyn`.
+    DynMetadata,
 }
 
 chalk_ir::const_visit!(WellKnownTrait);
@@ -279,6 +354,37 @@ impl<I: Interner> TraitDatum<I> {
         self.flags.coinductive
     }
 
+    pub fn is_alias(&self) -> bool {
+        self.flags.alias
+    }
+
+    /// The bounds of a trait alias, e.g. `Self: Foo<T>` and `Self: Bar` for
+    /// `trait Alias<T> = Foo<T> + Bar where T: Baz`: the where clauses whose
+    /// self type is `Self`. This is what `dyn Alias<T>` expands to.
+    pub fn alias_bounds(&self, interner: &I) -> Binders<Vec<QuantifiedWhereClause<I>>> {
+        self.binders.map_ref(|td| {
+            td.where_clauses
+                .iter()
+                .filter(|qwc| {
+                    let self_ty = match qwc.skip_binders() {
+                        WhereClause::Implemented(trait_ref) => {
+                            trait_ref.self_type_parameter(interner)
+                        }
+                        WhereClause::AliasEq(alias_eq) => {
+                            alias_eq.alias.self_type_parameter(interner)
+                        }
+                        WhereClause::LifetimeOutlives(..) | WhereClause::TypeOutlives(..) => {
+                            return false
+                        }
+                    };
+                    // `Self` is ^1.0, as we're one binder in.
+                    self_ty.bound_var(interner) == Some(BoundVar::new(DebruijnIndex::ONE, 0))
+                })
+                .cloned()
+                .collect()
+        })
+    }
+
     /// Gives access to the where clauses of the trait, quantified over the type parameters of the trait:
     ///
     /// ```ignore
@@ -332,6 +438,13 @@ pub struct TraitFlags {
     pub non_enumerable: bool,
 
     pub coinductive: bool,
+
+    /// A trait alias, e.g. `trait Alias<T> = Foo<T> + Bar where T: Baz`. Its
+    /// bounds are represented as where clauses on `Self`, e.g. `Self:
+    /// Foo<T>`, next to its other where clauses. A type implements the alias
+    /// exactly when all its where clauses hold; trait aliases have no impls
+    /// and no associated items.
+    pub alias: bool,
 }
 
 chalk_ir::const_visit!(TraitFlags);
@@ -620,6 +733,49 @@ pub struct AssociatedTyValueBound<I: Interner> {
     pub ty: Ty<I>,
 }
 
+/// Represents an associated const declaration found inside of a trait:
+///
+/// ```notrust
+/// trait Foo<P1..Pn> { // P0 is Self
+///     const N: T;
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AssociatedConstDatum<I: Interner> {
+    /// The trait this associated const is defined in.
+    pub trait_id: TraitId<I>,
+
+    /// The ID of this associated const
+    pub id: AssocConstId<I>,
+
+    /// Name of this associated const.
+    pub name: I::Identifier,
+
+    /// The type of the const. The binders are the parameters `P0..Pn` of
+    /// the trait.
+    pub ty: Binders<Ty<I>>,
+}
+
+/// Represents the *value* of an associated const that is assigned
+/// from within some impl.
+///
+/// ```ignore
+/// impl Len for Foo {
+///     const N: usize = 3; // <-- represents this line!
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, Visit)]
+pub struct AssociatedConstValue<I: Interner> {
+    /// Impl in which this associated const value is found.
+    pub impl_id: ImplId<I>,
+
+    /// Associated const being defined.
+    pub associated_const_id: AssocConstId<I>,
+
+    /// The value of the const. The binders are the parameters of the impl.
+    pub value: Binders<Const<I>>,
+}
+
 /// Represents the bounds for an `impl Trait` type.
 ///
 /// ```ignore
@@ -647,26 +803,37 @@ pub struct OpaqueTyDatumBound<I: Interner> {
 /// Represents a generator type.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner)]
 pub struct GeneratorDatum<I: Interner> {
+    // Can the generator be moved (is Unpin or not)
+    pub movability: Movability,
     /// All of the nested types for this generator. The `Binder`
     /// represents the types and lifetimes that this generator is generic over -
     /// this behaves in the same way as `AdtDatun.binders`
     pub input_output: Binders<GeneratorInputOutputDatum<I>>,
 }
 
+/// Whether a generator can be moved once it has been resumed. Movable
+/// generators implement `Unpin`; `static` generators do not.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Movability {
+    Static,
+    Movable,
+}
+chalk_ir::copy_fold!(Movability);
+
 /// The nested types for a generator. This always appears inside a `GeneratorDatum`
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Fold, HasInterner)]
 pub struct GeneratorInputOutputDatum<I: Interner> {
     /// The generator resume type - a value of this type
     /// is supplied by the caller when resuming the generator.
-    /// Currently, this plays no rule in goal resolution.
+    /// The generator implements `Generator<R>` for this type `R`.
     pub resume_type: Ty<I>,
     /// The generator yield type - a value of this type
     /// is supplied by the generator during a yield.
-    /// Currently, this plays no role in goal resolution.
+    /// This is the normalized type of `<G as Generator<R>>::Yield`.
     pub yield_type: Ty<I>,
     /// The generator return type - a value of this type
     /// is supplied by the generator when it returns.
-    /// Currently, this plays no role in goal resolution
+    /// This is the normalized type of `<G as Generator<R>>::Return`.
     pub return_type: Ty<I>,
     /// The upvars stored by the generator. These represent
     /// types captured from the generator's environment,
@@ -723,12 +890,4 @@ impl Polarity {
     }
 }
 
-/// Indicates the "most permissive" Fn-like trait that the closure implements.
-/// If the closure kind for a closure is FnMut, for example, then the closure
-/// implements FnMut and FnOnce.
-#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
-pub enum ClosureKind {
-    Fn,
-    FnMut,
-    FnOnce,
-}
+pub use chalk_ir::ClosureKind;
@@ -4,7 +4,17 @@ use chalk_ir::interner::Interner;
 use chalk_ir::*;
 use std::fmt;
 
+pub mod autoderef;
+pub mod closure_kinds;
+pub mod method_probe;
+pub mod proof_tree;
+pub mod regions;
+pub mod stats;
 pub mod truncate;
+pub mod why_not;
+
+use proof_tree::ProofTree;
+use stats::QueryStats;
 
 /// A (possible) solution for a proposed goal.
 #[derive(Clone, Debug, PartialEq, Eq, HasInterner)]
@@ -204,6 +214,37 @@ where
         f: &mut dyn FnMut(SubstitutionResult<Canonical<ConstrainedSubst<I>>>, bool) -> bool,
     ) -> bool;
 
+    /// Attempts to solve the given goal like `solve`, but also records
+    /// how the solution was derived: for each goal, the program clauses
+    /// that were tried and the subgoals they led to. This is meant for
+    /// debugging why a goal does or does not hold, and can be much slower
+    /// than `solve`.
+    ///
+    /// Solvers that cannot record their derivations return `None`, which
+    /// is what the default implementation does.
+    fn explain(
+        &mut self,
+        _program: &dyn RustIrDatabase<I>,
+        _goal: &UCanonical<InEnvironment<Goal<I>>>,
+    ) -> Option<ProofTree<I>> {
+        None
+    }
+
+    /// Starts or stops recording statistics about each query made to the
+    /// solver, such as how long it took and how much work the solver did.
+    /// Recording is off by default. The statistics can be retrieved with
+    /// `take_stats`.
+    ///
+    /// Solvers that do not collect statistics ignore this, which is what the
+    /// default implementation does.
+    fn record_stats(&mut self, _enabled: bool) {}
+
+    /// Returns the statistics recorded for the queries made since recording
+    /// was enabled, or since the last call to `take_stats`, oldest first.
+    fn take_stats(&mut self) -> Vec<QueryStats<I>> {
+        vec![]
+    }
+
     /// A convenience method for when one doesn't need the actual solution,
     /// only whether or not one exists.
     fn has_unique_solution(
@@ -0,0 +1,229 @@
+//! Autoderef: computes the types that a type successively dereferences to,
+//! the way rustc does for method calls and field accesses. See
+//! [`autoderef`].
+
+use crate::infer::ucanonicalize::UniverseMapExt;
+use crate::infer::{InferenceTable, ParameterEnaVariableExt};
+use crate::rust_ir::WellKnownTrait;
+use crate::solve::{Solution, Solver};
+use crate::RustIrDatabase;
+use chalk_derive::{Fold, HasInterner};
+use chalk_ir::cast::Cast;
+use chalk_ir::interner::{Interner, TargetInterner};
+use chalk_ir::*;
+
+/// How many times a type is dereferenced before giving up. This guards
+/// against `Deref` impls that produce ever larger types.
+pub const MAX_AUTODEREF_STEPS: usize = 16;
+
+/// The types that a type successively dereferences to. See [`autoderef`].
+#[derive(Clone, Debug, PartialEq, Eq, Fold, HasInterner)]
+pub struct AutoderefChain<I: Interner> {
+    /// The dereferenced types, in order. The type that is dereferenced is not
+    /// included.
+    pub steps: Vec<AutoderefStep<I>>,
+
+    /// Why there are no more steps.
+    pub end: AutoderefEnd,
+}
+
+/// A step of an [`AutoderefChain`].
+#[derive(Clone, Debug, PartialEq, Eq, Fold, HasInterner)]
+pub struct AutoderefStep<I: Interner> {
+    /// The dereferenced type.
+    pub ty: Ty<I>,
+
+    /// How the previous type was dereferenced to `ty`.
+    pub kind: AutoderefKind,
+}
+
+/// How a type is dereferenced.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AutoderefKind {
+    /// The type is a reference or a raw pointer.
+    Builtin,
+
+    /// Through the `Deref` impl of the type, or a `Deref` bound on it in the
+    /// environment, i.e. by resolving `<T as Deref>::Target`.
+    Overloaded,
+}
+
+/// Why an [`AutoderefChain`] ends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AutoderefEnd {
+    /// The last type cannot be dereferenced.
+    NoDeref,
+
+    /// Whether the last type can be dereferenced, or what to, is ambiguous,
+    /// e.g. because it is an inference variable.
+    Ambiguous,
+
+    /// The last type dereferences to itself or to one of the previous types.
+    Cycle,
+
+    /// The last type can be dereferenced, but the chain already has
+    /// `MAX_AUTODEREF_STEPS` steps.
+    Overflow,
+}
+
+chalk_ir::copy_fold!(AutoderefKind);
+chalk_ir::copy_fold!(AutoderefEnd);
+
+/// Computes the autoderef chain of `ty`: references and raw pointers are
+/// dereferenced to their pointee, other types to the `Target` of their
+/// `Deref` impl, until a type cannot be dereferenced any more.
+///
+/// rustc does not dereference raw pointers for method calls; callers that
+/// implement method lookup should stop at the first raw pointer.
+pub fn autoderef<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    solver: &mut dyn Solver<I>,
+    ty: &UCanonical<InEnvironment<Ty<I>>>,
+) -> Canonical<AutoderefChain<I>> {
+    let interner = db.interner();
+    let (mut infer, _, InEnvironment { environment, goal }) =
+        InferenceTable::from_canonical(interner, ty.universes, &ty.canonical);
+    let chain = Autoderef {
+        db,
+        solver,
+        infer: &mut infer,
+        environment: &environment,
+        include_raw_pointers: true,
+    }
+    .chain(goal);
+    infer.canonicalize(interner, &chain).quantified
+}
+
+/// Dereferences types within an existing inference table.
+pub(crate) struct Autoderef<'a, I: Interner> {
+    pub(crate) db: &'a dyn RustIrDatabase<I>,
+    pub(crate) solver: &'a mut dyn Solver<I>,
+    pub(crate) infer: &'a mut InferenceTable<I>,
+    pub(crate) environment: &'a Environment<I>,
+    pub(crate) include_raw_pointers: bool,
+}
+
+impl<I: Interner> Autoderef<'_, I> {
+    /// Returns the autoderef chain of `ty`; the inference variables of the
+    /// steps belong to the inference table.
+    pub(crate) fn chain(&mut self, ty: Ty<I>) -> AutoderefChain<I> {
+        let interner = self.db.interner();
+        let mut steps: Vec<AutoderefStep<I>> = vec![];
+        let mut seen = vec![self.infer.canonicalize(interner, &ty).quantified];
+        let mut current = ty;
+        let end = loop {
+            let (next, kind) = match self.deref(&current) {
+                Ok(step) => step,
+                Err(end) => break end,
+            };
+            let canonical = self.infer.canonicalize(interner, &next).quantified;
+            if seen.contains(&canonical) {
+                break AutoderefEnd::Cycle;
+            }
+            if steps.len() == MAX_AUTODEREF_STEPS {
+                break AutoderefEnd::Overflow;
+            }
+            seen.push(canonical);
+            steps.push(AutoderefStep {
+                ty: next.clone(),
+                kind,
+            });
+            current = next;
+        };
+        AutoderefChain { steps, end }
+    }
+
+    /// Dereferences `ty` once: references (and raw pointers, if included)
+    /// are dereferenced directly, other types through their `Deref` impl, if
+    /// any.
+    fn deref(&mut self, ty: &Ty<I>) -> Result<(Ty<I>, AutoderefKind), AutoderefEnd> {
+        let interner = self.db.interner();
+        let ty = self
+            .infer
+            .normalize_ty_shallow(interner, ty)
+            .unwrap_or_else(|| ty.clone());
+        match ty.kind(interner) {
+            TyKind::Ref(_, _, referent) => return Ok((referent.clone(), AutoderefKind::Builtin)),
+            TyKind::Raw(_, pointee) if self.include_raw_pointers => {
+                return Ok((pointee.clone(), AutoderefKind::Builtin))
+            }
+            TyKind::InferenceVar(..) => return Err(AutoderefEnd::Ambiguous),
+            _ => {}
+        }
+
+        let deref_trait = self
+            .db
+            .well_known_trait_id(WellKnownTrait::Deref)
+            .ok_or(AutoderefEnd::NoDeref)?;
+        let target_id = self
+            .db
+            .trait_datum(deref_trait)
+            .associated_ty_ids
+            .first()
+            .copied()
+            .ok_or(AutoderefEnd::NoDeref)?;
+        let substitution = Substitution::from1(interner, ty);
+        let alias = AliasTy::Projection(ProjectionTy {
+            associated_ty_id: target_id,
+            substitution: substitution.clone(),
+        });
+        // The target may name the placeholders of the environment, e.g. when
+        // it is `<T as Deref>::Target` for a `T: Deref` bound.
+        let max_universe = self.infer.max_universe();
+        let target = self.infer.new_variable(max_universe).to_ty(interner);
+        let normalize: Goal<I> = DomainGoal::Normalize(Normalize {
+            alias: alias.clone(),
+            ty: target.clone(),
+        })
+        .cast(interner);
+        match self.solve(normalize) {
+            Err(AutoderefEnd::NoDeref) => {}
+            result => return result.map(|()| (target, AutoderefKind::Overloaded)),
+        }
+
+        // No impl applies, but the type may still have a `Deref` bound in the
+        // environment. `AliasEq` would also hold for types that do not
+        // implement `Deref` at all, hence the `Implemented` goal first.
+        let implemented: Goal<I> = TraitRef {
+            trait_id: deref_trait,
+            substitution,
+        }
+        .cast(interner);
+        self.solve(implemented)?;
+        let alias_eq: Goal<I> = AliasEq {
+            alias,
+            ty: target.clone(),
+        }
+        .cast(interner);
+        self.solve(alias_eq)?;
+        Ok((target, AutoderefKind::Overloaded))
+    }
+
+    /// Solves `goal` in the environment and unifies its inference variables
+    /// with the unique solution, if there is one.
+    fn solve(&mut self, goal: Goal<I>) -> Result<(), AutoderefEnd> {
+        let interner = self.db.interner();
+        let canonical = self
+            .infer
+            .canonicalize(interner, &InEnvironment::new(self.environment, goal));
+        let u_canonical = self.infer.u_canonicalize(interner, &canonical.quantified);
+        let subst = match self.solver.solve(self.db, &u_canonical.quantified) {
+            Some(Solution::Unique(subst)) => subst,
+            Some(Solution::Ambig(_)) => return Err(AutoderefEnd::Ambiguous),
+            None => return Err(AutoderefEnd::NoDeref),
+        };
+        let subst = u_canonical.universes.map_from_canonical(interner, &subst);
+        let ConstrainedSubst { subst, .. } = self.infer.instantiate_canonical(interner, &subst);
+        for (i, free_var) in canonical.free_vars.iter().enumerate() {
+            self.infer
+                .unify(
+                    interner,
+                    self.environment,
+                    &free_var.to_generic_arg(interner),
+                    subst.at(interner, i),
+                )
+                .map_err(|_| AutoderefEnd::NoDeref)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//! Deferred closure kinds: finds the closure kind obligations that keep a
+//! goal ambiguous. See [`closure_kind_obligations`].
+
+use crate::solve::proof_tree::{Derivation, ProofTree};
+use crate::solve::{Solution, Solver};
+use crate::RustIrDatabase;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+
+/// Returns the closure kind obligations that `goal` depends on: the
+/// `ClosureKind(C, kind)` goals on closures whose kind is not known yet (see
+/// `RustIrDatabase::closure_kind`). They are what makes `Fn` and `FnMut`
+/// goals on such closures ambiguous. Once the kinds are known, e.g. after
+/// upvar analysis, the caller can check the obligations and solve `goal`
+/// again.
+///
+/// The obligations are found in the proof tree of `goal` (see
+/// `Solver::explain`), in the derivations that can succeed. There are none
+/// if `solver` cannot explain its solutions.
+pub fn closure_kind_obligations<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    solver: &mut dyn Solver<I>,
+    goal: &UCanonical<InEnvironment<Goal<I>>>,
+) -> Vec<UCanonical<InEnvironment<DomainGoal<I>>>> {
+    let mut obligations = vec![];
+    if let Some(proof_tree) = solver.explain(db, goal) {
+        collect_obligations(db.interner(), &proof_tree, &mut obligations);
+    }
+    obligations
+}
+
+fn collect_obligations<I: Interner>(
+    interner: &I,
+    proof_tree: &ProofTree<I>,
+    obligations: &mut Vec<UCanonical<InEnvironment<DomainGoal<I>>>>,
+) {
+    let ambiguous = match &proof_tree.solution {
+        Some(Solution::Ambig(_)) => true,
+        Some(Solution::Unique(_)) => false,
+        None => return,
+    };
+    let UCanonical {
+        canonical: Canonical { binders, value },
+        universes,
+    } = &proof_tree.goal;
+    if let GoalData::DomainGoal(domain_goal @ DomainGoal::ClosureKind(..)) =
+        value.goal.data(interner)
+    {
+        let obligation = UCanonical {
+            canonical: Canonical {
+                binders: binders.clone(),
+                value: InEnvironment::new(&value.environment, domain_goal.clone()),
+            },
+            universes: *universes,
+        };
+        if ambiguous && !obligations.contains(&obligation) {
+            obligations.push(obligation);
+        }
+        return;
+    }
+
+    match &proof_tree.derivation {
+        Derivation::Simplified(subgoals) => {
+            for subgoal in subgoals {
+                collect_obligations(interner, subgoal, obligations);
+            }
+        }
+        Derivation::Clauses(applications) => {
+            for application in applications.iter().filter(|a| a.succeeded) {
+                for subgoal in &application.subgoals {
+                    collect_obligations(interner, subgoal, obligations);
+                }
+            }
+        }
+        Derivation::Floundered | Derivation::Cycle => {}
+    }
+}
@@ -0,0 +1,210 @@
+//! Method probing: finds the methods that a method call `receiver.name(..)`
+//! may refer to. See [`probe_method`].
+
+use crate::infer::InferenceTable;
+use crate::rust_ir::{AssociatedFnContainer, AssociatedFnDatum};
+use crate::solve::autoderef::Autoderef;
+use crate::solve::Solver;
+use crate::RustIrDatabase;
+use chalk_derive::{Fold, HasInterner};
+use chalk_ir::cast::Cast;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use std::iter;
+use std::sync::Arc;
+
+/// A method that a method call may refer to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MethodCandidate<I: Interner> {
+    /// The method; its signature is the `FnDefDatum` of this id.
+    pub fn_def_id: FnDefId<I>,
+
+    /// The trait or inherent impl that declares the method.
+    pub container: AssociatedFnContainer<I>,
+
+    /// How many times the receiver is dereferenced.
+    pub autoderefs: usize,
+
+    /// Whether the dereferenced receiver is then borrowed, and how.
+    pub autoref: Option<Mutability>,
+
+    /// How the method is instantiated for the call.
+    pub instantiation: Canonical<MethodInstantiation<I>>,
+}
+
+/// The instantiation of a [`MethodCandidate`].
+#[derive(Clone, Debug, PartialEq, Eq, Fold, HasInterner)]
+pub struct MethodInstantiation<I: Interner> {
+    /// The receiver, once dereferenced and borrowed. It is passed as the
+    /// `self` argument of the method.
+    pub receiver: Ty<I>,
+
+    /// The substitution for the parameters of the method.
+    pub substitution: Substitution<I>,
+
+    /// What must hold for the method to apply: its where clauses, and the
+    /// goals produced by unifying its `self` parameter with the receiver.
+    /// Together, they are known to be provable, or ambiguous.
+    pub obligations: Vec<InEnvironment<Goal<I>>>,
+}
+
+/// Finds the methods named `name` that can be called on `receiver`, the way
+/// rustc's method lookup does.
+///
+/// The receiver is dereferenced step by step, through references and
+/// `Deref` impls. At each step, the methods whose `self` parameter accepts
+/// the dereferenced receiver are looked up, then those that accept a `&` or
+/// `&mut` borrow of it. Inherent methods are preferred over trait methods.
+/// The candidates found first are returned; there are several of them when
+/// the call is ambiguous. Returns no candidates if no method applies.
+pub fn probe_method<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    solver: &mut dyn Solver<I>,
+    receiver: &UCanonical<InEnvironment<Ty<I>>>,
+    name: &I::Identifier,
+) -> Vec<MethodCandidate<I>> {
+    let methods: Vec<_> = db
+        .associated_fns(name)
+        .into_iter()
+        .filter(|method| method.has_receiver)
+        .collect();
+    if methods.is_empty() {
+        return vec![];
+    }
+
+    let interner = db.interner();
+    let (infer, _, InEnvironment { environment, goal }) =
+        InferenceTable::from_canonical(interner, receiver.universes, &receiver.canonical);
+    let mut probe = MethodProbe {
+        db,
+        solver,
+        infer,
+        environment,
+    };
+
+    let (inherent, traits): (Vec<_>, Vec<_>) = methods
+        .into_iter()
+        .partition(|method| matches!(method.container, AssociatedFnContainer::InherentImpl(_)));
+    for (autoderefs, step) in probe.autoderef_steps(goal).into_iter().enumerate() {
+        for &autoref in &[None, Some(Mutability::Not), Some(Mutability::Mut)] {
+            let receiver = match autoref {
+                None => step.clone(),
+                Some(mutability) => {
+                    let lifetime = probe
+                        .infer
+                        .new_variable(UniverseIndex::root())
+                        .to_lifetime(interner);
+                    TyKind::Ref(mutability, lifetime, step.clone()).intern(interner)
+                }
+            };
+            for methods in &[&inherent, &traits] {
+                let candidates: Vec<_> = methods
+                    .iter()
+                    .filter_map(|method| {
+                        Some(MethodCandidate {
+                            fn_def_id: method.id,
+                            container: method.container.clone(),
+                            autoderefs,
+                            autoref,
+                            instantiation: probe.instantiate(method, &receiver)?,
+                        })
+                    })
+                    .collect();
+                if !candidates.is_empty() {
+                    return candidates;
+                }
+            }
+        }
+    }
+    vec![]
+}
+
+struct MethodProbe<'a, I: Interner> {
+    db: &'a dyn RustIrDatabase<I>,
+    solver: &'a mut dyn Solver<I>,
+    infer: InferenceTable<I>,
+    environment: Environment<I>,
+}
+
+impl<I: Interner> MethodProbe<'_, I> {
+    /// Returns the types that `ty` successively dereferences to, starting
+    /// with `ty` itself. Raw pointers are not dereferenced.
+    fn autoderef_steps(&mut self, ty: Ty<I>) -> Vec<Ty<I>> {
+        let chain = Autoderef {
+            db: self.db,
+            solver: &mut *self.solver,
+            infer: &mut self.infer,
+            environment: &self.environment,
+            include_raw_pointers: false,
+        }
+        .chain(ty.clone());
+        iter::once(ty)
+            .chain(chain.steps.into_iter().map(|step| step.ty))
+            .collect()
+    }
+
+    /// Instantiates `method` so that its `self` parameter accepts `receiver`,
+    /// and checks that its obligations may hold.
+    fn instantiate(
+        &mut self,
+        method: &Arc<AssociatedFnDatum<I>>,
+        receiver: &Ty<I>,
+    ) -> Option<Canonical<MethodInstantiation<I>>> {
+        let snapshot = self.infer.snapshot();
+        let instantiation = self.try_instantiate(method, receiver);
+        self.infer.rollback_to(snapshot);
+        instantiation
+    }
+
+    fn try_instantiate(
+        &mut self,
+        method: &Arc<AssociatedFnDatum<I>>,
+        receiver: &Ty<I>,
+    ) -> Option<Canonical<MethodInstantiation<I>>> {
+        let interner = self.db.interner();
+        let fn_def_datum = self.db.fn_def_datum(method.id);
+        let substitution = self.infer.instantiate_binders_existentially(
+            interner,
+            &Binders::new(
+                fn_def_datum.binders.binders.clone(),
+                fn_def_datum.binders.identity_substitution(interner),
+            ),
+        );
+        let bound = fn_def_datum.binders.substitute(interner, &substitution);
+        let inputs_and_output = self
+            .infer
+            .instantiate_binders_existentially(interner, &bound.inputs_and_output);
+        let self_ty = inputs_and_output.argument_types.first()?;
+
+        let unification_goals = self
+            .infer
+            .unify(interner, &self.environment, self_ty, receiver)
+            .ok()?
+            .goals;
+        let obligations: Vec<_> = bound
+            .where_clauses
+            .into_iter()
+            .map(|where_clause| InEnvironment::new(&self.environment, where_clause.cast(interner)))
+            .chain(unification_goals)
+            .collect();
+
+        // The obligations all share the environment of the receiver.
+        let goal = InEnvironment::new(
+            &self.environment,
+            Goal::all(
+                interner,
+                obligations.iter().map(|obligation| obligation.goal.clone()),
+            ),
+        );
+        let goal = self.infer.canonicalize(interner, &goal).quantified;
+        let goal = self.infer.u_canonicalize(interner, &goal).quantified;
+        self.solver.solve(self.db, &goal)?;
+
+        let instantiation = MethodInstantiation {
+            receiver: receiver.clone(),
+            substitution,
+            obligations,
+        };
+        Some(self.infer.canonicalize(interner, &instantiation).quantified)
+    }
+}
@@ -0,0 +1,145 @@
+//! Proof trees, which record how a solver arrived at the solution for a
+//! goal. See [`Solver::explain`](super::Solver::explain).
+
+use crate::solve::Solution;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use std::fmt;
+
+/// The derivation of the solution to a goal: the goal itself, what the
+/// solver concluded, and how it got there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofTree<I: Interner> {
+    /// The goal that was solved.
+    pub goal: UCanonical<InEnvironment<Goal<I>>>,
+
+    /// The solution that was found, or `None` if the goal could not be
+    /// proven.
+    pub solution: Option<Solution<I>>,
+
+    /// How the solution was derived.
+    pub derivation: Derivation<I>,
+}
+
+/// The step the solver took to solve a goal in a [`ProofTree`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Derivation<I: Interner> {
+    /// The goal was broken down into subgoals (e.g. for `G1, G2`,
+    /// `forall<T> { G }` or `if (C) { G }`), each of which was solved in turn.
+    Simplified(Vec<ProofTree<I>>),
+
+    /// The goal is a domain goal, and was solved by trying each of the
+    /// program clauses that could apply to it.
+    Clauses(Vec<ClauseApplication<I>>),
+
+    /// The goal was too ambiguous to enumerate the clauses that could apply
+    /// to it, e.g. `?T: Foo`.
+    Floundered,
+
+    /// The goal depends on itself. The solution is the one from the
+    /// enclosing attempt to solve the same goal (or, for coinductive goals,
+    /// the goal is assumed to hold).
+    Cycle,
+}
+
+/// An attempt to prove a goal using a program clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClauseApplication<I: Interner> {
+    /// The clause that was applied.
+    pub clause: ProgramClause<I>,
+
+    /// Where the clause came from.
+    pub source: ClauseSource,
+
+    /// The conditions of the clause, as they were solved.
+    pub subgoals: Vec<ProofTree<I>>,
+
+    /// Whether the clause applies, i.e. whether the goal unified with its
+    /// consequence and its conditions could be proven. Failed branches have
+    /// this set to `false`.
+    pub succeeded: bool,
+}
+
+/// Where a program clause that was applied in a [`ProofTree`] came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClauseSource {
+    /// The clause comes from the environment of the goal, e.g. from a where
+    /// clause.
+    Environment,
+
+    /// The clause comes from the program, e.g. from an impl or from one of
+    /// the built-in rules.
+    Program,
+}
+
+impl<I: Interner> ProofTree<I> {
+    pub fn display<'a>(&'a self, interner: &'a I) -> ProofTreeDisplay<'a, I> {
+        ProofTreeDisplay {
+            tree: self,
+            interner,
+        }
+    }
+}
+
+/// Displays a [`ProofTree`] as an indented outline: each goal is followed by
+/// its solution, and each clause that was tried is listed under the goal it
+/// was applied to, together with its subgoals.
+pub struct ProofTreeDisplay<'a, I: Interner> {
+    tree: &'a ProofTree<I>,
+    interner: &'a I,
+}
+
+impl<'a, I: Interner> ProofTreeDisplay<'a, I> {
+    fn fmt_tree(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        tree: &ProofTree<I>,
+        depth: usize,
+    ) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        write!(f, "{}{:?}: ", indent, tree.goal.canonical.value.goal)?;
+        match &tree.solution {
+            Some(solution) => write!(f, "{}", solution.display(self.interner))?,
+            None => write!(f, "No possible solution")?,
+        }
+        match &tree.derivation {
+            Derivation::Simplified(subgoals) => {
+                writeln!(f)?;
+                for subgoal in subgoals {
+                    self.fmt_tree(f, subgoal, depth + 1)?;
+                }
+            }
+            Derivation::Clauses(applications) => {
+                writeln!(f)?;
+                for application in applications {
+                    let source = match application.source {
+                        ClauseSource::Environment => "environment",
+                        ClauseSource::Program => "program",
+                    };
+                    write!(
+                        f,
+                        "{}  using {} clause {:?}",
+                        indent, source, application.clause
+                    )?;
+                    if application.succeeded {
+                        writeln!(f)?;
+                    } else {
+                        writeln!(f, " (failed)")?;
+                    }
+                    for subgoal in &application.subgoals {
+                        self.fmt_tree(f, subgoal, depth + 2)?;
+                    }
+                }
+            }
+            Derivation::Floundered => writeln!(f, " (floundered)")?,
+            Derivation::Cycle => writeln!(f, " (cycle)")?,
+        }
+        Ok(())
+    }
+}
+
+impl<'a, I: Interner> fmt::Display for ProofTreeDisplay<'a, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, self.tree, 0)
+    }
+}
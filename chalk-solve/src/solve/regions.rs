@@ -0,0 +1,432 @@
+//! Region resolution: checks whether the lifetime constraints of a solution
+//! can be satisfied, and simplifies them. See [`resolve_region_constraints`].
+//!
+//! The lifetimes that appear in the constraints are of two sorts:
+//!
+//! * *universal* lifetimes, i.e. `'static` and placeholders (which come from
+//!   a `forall` in the goal). A constraint between two universal lifetimes
+//!   must hold for every value of the placeholders, so it has to be implied
+//!   by the outlives clauses of its environment.
+//! * *variables*, i.e. inference variables and the canonical variables of a
+//!   solution. The caller is free to choose their values, so any constraints
+//!   on them can be satisfied -- as long as the constraints they imply on the
+//!   universal lifetimes are. For example, `'a: '?0` and `'?0: 'b` require
+//!   `'a: 'b`.
+//!
+//! A variable cannot name the placeholders of the universes above its own.
+//! If it has to outlive one of them, it must outlive `'static` instead; for
+//! example, in `exists<'x> { forall<'a> { ... } }`, `'x: 'a` requires
+//! `'x: 'static`, and so `'x` cannot be equal to `'a`.
+//!
+//! Type outlives constraints are first broken down into the lifetimes and
+//! types that appear in the type, following the rules of RFC 1214 (e.g.
+//! `&'a T: 'b` requires `'a: 'b` and `T: 'b`).
+
+use crate::solve::Solution;
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::fmt;
+
+/// The error returned by [`resolve_region_constraints`] if the constraints
+/// cannot be satisfied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionError<I: Interner> {
+    /// A constraint between universal lifetimes (or a placeholder type and a
+    /// universal lifetime) that is required by the constraints, but not
+    /// implied by the environment. This need not be one of the original
+    /// constraints; for example, `'a: '?0, '?0: 'b` results in `'a: 'b`.
+    pub constraint: InEnvironment<Constraint<I>>,
+}
+
+impl<I: Interner> fmt::Display for RegionError<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the lifetime constraint `{:?}` cannot be satisfied",
+            self.constraint.goal
+        )
+    }
+}
+
+/// Resolves the lifetime constraints of a solution: returns an error if
+/// they cannot be satisfied, and otherwise a minimal set of constraints that
+/// is equivalent to them. The constraints that remain all involve lifetime
+/// variables, or types whose outlives relations can't be broken down (like
+/// projections).
+///
+/// The constraints are grouped by environment, and each group is resolved
+/// separately. `binders` are the canonical binders the constraints are
+/// under; they give the universes of the variables.
+pub fn resolve_region_constraints<I: Interner>(
+    interner: &I,
+    binders: &CanonicalVarKinds<I>,
+    constraints: &Constraints<I>,
+) -> Result<Constraints<I>, RegionError<I>> {
+    let mut groups: Vec<(&Environment<I>, Vec<&Constraint<I>>)> = vec![];
+    for constraint in constraints.iter(interner) {
+        match groups
+            .iter_mut()
+            .find(|(environment, _)| **environment == constraint.environment)
+        {
+            Some((_, group)) => group.push(&constraint.goal),
+            None => groups.push((&constraint.environment, vec![&constraint.goal])),
+        }
+    }
+
+    let mut resolved = vec![];
+    for (environment, group) in groups {
+        let resolver = RegionResolver::new(interner, binders, environment);
+        resolved.extend(
+            resolver
+                .resolve(group)?
+                .into_iter()
+                .map(|constraint| InEnvironment::new(environment, constraint)),
+        );
+    }
+    Ok(Constraints::from_iter(interner, resolved))
+}
+
+impl<I: Interner> Solution<I> {
+    /// Resolves the lifetime constraints of a unique solution using
+    /// [`resolve_region_constraints`]. Ambiguous solutions carry no
+    /// constraints, and are returned unchanged.
+    pub fn resolve_regions(self, interner: &I) -> Result<Self, RegionError<I>> {
+        match self {
+            Solution::Unique(canonical) => {
+                let Canonical { binders, value } = canonical;
+                let constraints =
+                    resolve_region_constraints(interner, &binders, &value.constraints)?;
+                Ok(Solution::Unique(Canonical {
+                    binders,
+                    value: ConstrainedSubst {
+                        subst: value.subst,
+                        constraints,
+                    },
+                }))
+            }
+            Solution::Ambig(_) => Ok(self),
+        }
+    }
+}
+
+/// Resolves the constraints for a single environment.
+struct RegionResolver<'a, I: Interner> {
+    interner: &'a I,
+    binders: &'a CanonicalVarKinds<I>,
+    environment: &'a Environment<I>,
+
+    /// The lifetime outlives clauses of the environment: `'a: 'b` for each
+    /// `'b` in `given_outlives['a]`.
+    given_outlives: FxHashMap<Lifetime<I>, Vec<Lifetime<I>>>,
+
+    /// The type outlives clauses of the environment.
+    given_type_outlives: Vec<(Ty<I>, Lifetime<I>)>,
+}
+
+impl<'a, I: Interner> RegionResolver<'a, I> {
+    fn new(
+        interner: &'a I,
+        binders: &'a CanonicalVarKinds<I>,
+        environment: &'a Environment<I>,
+    ) -> Self {
+        let mut given_outlives: FxHashMap<_, Vec<_>> = FxHashMap::default();
+        let mut given_type_outlives = vec![];
+        for clause in environment.clauses.iter(interner) {
+            let ProgramClauseData(implication) = clause.data(interner);
+            if !implication.binders.is_empty(interner) {
+                continue;
+            }
+            let implication = implication.skip_binders();
+            if !implication.conditions.is_empty(interner) {
+                continue;
+            }
+            match &implication.consequence {
+                DomainGoal::Holds(WhereClause::LifetimeOutlives(LifetimeOutlives { a, b })) => {
+                    given_outlives.entry(a.clone()).or_default().push(b.clone())
+                }
+                DomainGoal::Holds(WhereClause::TypeOutlives(TypeOutlives { ty, lifetime })) => {
+                    given_type_outlives.push((ty.clone(), lifetime.clone()))
+                }
+                _ => {}
+            }
+        }
+        RegionResolver {
+            interner,
+            binders,
+            environment,
+            given_outlives,
+            given_type_outlives,
+        }
+    }
+
+    fn resolve(
+        &self,
+        constraints: Vec<&Constraint<I>>,
+    ) -> Result<Vec<Constraint<I>>, RegionError<I>> {
+        // Break down the type outlives constraints.
+        let mut lifetime_outlives = vec![];
+        let mut type_outlives = vec![];
+        for constraint in constraints {
+            match constraint {
+                Constraint::LifetimeOutlives(a, b) => {
+                    lifetime_outlives.push((a.clone(), b.clone()))
+                }
+                Constraint::TypeOutlives(ty, lifetime) => self.push_type_outlives(
+                    ty,
+                    lifetime,
+                    &mut lifetime_outlives,
+                    &mut type_outlives,
+                ),
+            }
+        }
+
+        let mut required: FxHashMap<Lifetime<I>, Vec<Lifetime<I>>> = FxHashMap::default();
+        for (a, b) in &lifetime_outlives {
+            required.entry(a.clone()).or_default().push(b.clone());
+        }
+
+        // A variable that has to outlive a placeholder it cannot name must
+        // outlive `'static`.
+        let mut must_outlive_static = vec![];
+        for (a, _) in &lifetime_outlives {
+            if !self.is_variable(a) || must_outlive_static.contains(a) {
+                continue;
+            }
+            let unnameable = self
+                .reachable(a, |l| match required.get(l) {
+                    Some(ls) if l == a || self.is_variable(l) => &ls[..],
+                    _ => &[],
+                })
+                .iter()
+                .any(|b| !self.can_name(a, b));
+            if unnameable {
+                must_outlive_static.push(a.clone());
+            }
+        }
+        let static_lifetime = LifetimeData::Static.intern(self.interner);
+        for a in must_outlive_static {
+            required
+                .entry(a.clone())
+                .or_default()
+                .push(static_lifetime.clone());
+            lifetime_outlives.push((a, static_lifetime.clone()));
+        }
+
+        // Check the lifetime constraints that are required between
+        // universal lifetimes, going through any variables.
+        let mut checked = FxHashSet::default();
+        for (a, _) in &lifetime_outlives {
+            if self.is_variable(a) || !checked.insert(a) {
+                continue;
+            }
+            for b in self.reachable(a, |l| match required.get(l) {
+                Some(ls) if l == a || self.is_variable(l) => &ls[..],
+                _ => &[],
+            }) {
+                if !self.is_variable(&b) && !self.given_lifetime_outlives(a, &b) {
+                    return Err(self.error(Constraint::LifetimeOutlives(a.clone(), b)));
+                }
+            }
+        }
+
+        // Check the type outlives constraints on placeholder types.
+        let mut resolved_type_outlives = vec![];
+        for (ty, lifetime) in type_outlives {
+            if matches!(ty.kind(self.interner), TyKind::Placeholder(_))
+                && !self.is_variable(&lifetime)
+            {
+                if !self.given_type_outlives(&ty, &lifetime) {
+                    return Err(self.error(Constraint::TypeOutlives(ty, lifetime)));
+                }
+            } else if !resolved_type_outlives.contains(&(ty.clone(), lifetime.clone())) {
+                resolved_type_outlives.push((ty, lifetime));
+            }
+        }
+
+        // What remains are the constraints on variables. Drop the trivial
+        // ones, and those that are implied by the others. A placeholder
+        // outlives any variable that cannot name it, as far as the caller
+        // can tell, since the variable's value cannot depend on it.
+        let mut resolved_outlives: Vec<(Lifetime<I>, Lifetime<I>)> = vec![];
+        for (a, b) in lifetime_outlives {
+            if (self.is_variable(&a) || self.is_variable(&b))
+                && a != b
+                && !self.is_static(&a)
+                && self.can_name(&b, &a)
+                && !resolved_outlives.contains(&(a.clone(), b.clone()))
+            {
+                resolved_outlives.push((a, b));
+            }
+        }
+        let mut i = 0;
+        while i < resolved_outlives.len() {
+            let (a, b) = resolved_outlives.remove(i);
+            if self.implied_by(&resolved_outlives, &a, &b) {
+                continue;
+            }
+            resolved_outlives.insert(i, (a, b));
+            i += 1;
+        }
+
+        Ok(resolved_outlives
+            .into_iter()
+            .map(|(a, b)| Constraint::LifetimeOutlives(a, b))
+            .chain(
+                resolved_type_outlives
+                    .into_iter()
+                    .map(|(ty, lifetime)| Constraint::TypeOutlives(ty, lifetime)),
+            )
+            .collect())
+    }
+
+    /// Breaks down `ty: lifetime` into constraints on the lifetimes and
+    /// types that appear in `ty`. Types whose outlives relations depend on
+    /// more than their components (like projections, `dyn Trait` or
+    /// function pointers) are kept as they are.
+    fn push_type_outlives(
+        &self,
+        ty: &Ty<I>,
+        lifetime: &Lifetime<I>,
+        lifetime_outlives: &mut Vec<(Lifetime<I>, Lifetime<I>)>,
+        type_outlives: &mut Vec<(Ty<I>, Lifetime<I>)>,
+    ) {
+        let interner = self.interner;
+        match ty.kind(interner) {
+            TyKind::Ref(_, a, ty) => {
+                lifetime_outlives.push((a.clone(), lifetime.clone()));
+                self.push_type_outlives(ty, lifetime, lifetime_outlives, type_outlives);
+            }
+            TyKind::Raw(_, ty) | TyKind::Slice(ty) | TyKind::Array(ty, _) => {
+                self.push_type_outlives(ty, lifetime, lifetime_outlives, type_outlives)
+            }
+            TyKind::Adt(_, substitution)
+            | TyKind::Tuple(_, substitution)
+            | TyKind::FnDef(_, substitution) => {
+                for arg in substitution.iter(interner) {
+                    match arg.data(interner) {
+                        GenericArgData::Ty(ty) => {
+                            self.push_type_outlives(ty, lifetime, lifetime_outlives, type_outlives)
+                        }
+                        GenericArgData::Lifetime(a) => {
+                            lifetime_outlives.push((a.clone(), lifetime.clone()))
+                        }
+                        GenericArgData::Const(_) => {}
+                    }
+                }
+            }
+            TyKind::Scalar(_)
+            | TyKind::Str
+            | TyKind::Never
+            | TyKind::Foreign(_)
+            | TyKind::Error => {}
+            _ => type_outlives.push((ty.clone(), lifetime.clone())),
+        }
+    }
+
+    fn is_variable(&self, lifetime: &Lifetime<I>) -> bool {
+        match lifetime.data(self.interner) {
+            LifetimeData::InferenceVar(_) | LifetimeData::BoundVar(_) => true,
+            LifetimeData::Placeholder(_) | LifetimeData::Static => false,
+            LifetimeData::Phantom(..) => unreachable!(),
+        }
+    }
+
+    /// The universe of a variable, if known. Inference variables are not
+    /// tracked, and are assumed to be able to name every placeholder.
+    fn universe(&self, lifetime: &Lifetime<I>) -> Option<UniverseIndex> {
+        match lifetime.data(self.interner) {
+            LifetimeData::BoundVar(bound_var) => self
+                .binders
+                .as_slice(self.interner)
+                .get(bound_var.index)
+                .map(|kind| *kind.skip_kind()),
+            _ => None,
+        }
+    }
+
+    /// Whether `lifetime` can be named by the variable `variable`. Anything
+    /// can be named by a lifetime that is not a variable.
+    fn can_name(&self, variable: &Lifetime<I>, lifetime: &Lifetime<I>) -> bool {
+        match (self.universe(variable), lifetime.data(self.interner)) {
+            (Some(universe), LifetimeData::Placeholder(placeholder)) => placeholder.ui <= universe,
+            _ => true,
+        }
+    }
+
+    fn is_static(&self, lifetime: &Lifetime<I>) -> bool {
+        matches!(lifetime.data(self.interner), LifetimeData::Static)
+    }
+
+    /// Returns the lifetimes reachable from `start` (not including `start`
+    /// itself, unless it is part of a cycle), following the edges returned
+    /// by `successors`.
+    fn reachable<'e>(
+        &self,
+        start: &Lifetime<I>,
+        successors: impl Fn(&Lifetime<I>) -> &'e [Lifetime<I>],
+    ) -> Vec<Lifetime<I>>
+    where
+        I: 'e,
+    {
+        let mut visited = FxHashSet::default();
+        let mut reachable = vec![];
+        let mut stack: Vec<_> = successors(start).iter().collect();
+        while let Some(lifetime) = stack.pop() {
+            if visited.insert(lifetime) {
+                reachable.push(lifetime.clone());
+                stack.extend(successors(lifetime));
+            }
+        }
+        reachable
+    }
+
+    /// Whether `a: b` holds for all values of the placeholders, given the
+    /// outlives clauses of the environment.
+    fn given_lifetime_outlives(&self, a: &Lifetime<I>, b: &Lifetime<I>) -> bool {
+        if a == b || self.is_static(a) {
+            return true;
+        }
+        self.reachable(a, |l| {
+            self.given_outlives.get(l).map(|ls| &ls[..]).unwrap_or(&[])
+        })
+        .iter()
+        .any(|l| l == b || self.is_static(l))
+    }
+
+    /// Whether `ty: lifetime` holds for all values of the placeholders, given
+    /// the outlives clauses of the environment.
+    fn given_type_outlives(&self, ty: &Ty<I>, lifetime: &Lifetime<I>) -> bool {
+        self.given_type_outlives
+            .iter()
+            .any(|(given_ty, given_lifetime)| {
+                given_ty == ty && self.given_lifetime_outlives(given_lifetime, lifetime)
+            })
+    }
+
+    /// Whether `a: b` follows from `constraints` and the outlives clauses of
+    /// the environment.
+    fn implied_by(
+        &self,
+        constraints: &[(Lifetime<I>, Lifetime<I>)],
+        a: &Lifetime<I>,
+        b: &Lifetime<I>,
+    ) -> bool {
+        let mut edges: FxHashMap<&Lifetime<I>, Vec<Lifetime<I>>> = FxHashMap::default();
+        for (c, d) in constraints {
+            edges.entry(c).or_default().push(d.clone());
+        }
+        for (c, ds) in &self.given_outlives {
+            edges.entry(c).or_default().extend(ds.iter().cloned());
+        }
+        self.reachable(a, |l| edges.get(l).map(|ls| &ls[..]).unwrap_or(&[]))
+            .iter()
+            .any(|l| l == b || self.is_static(l))
+    }
+
+    fn error(&self, constraint: Constraint<I>) -> RegionError<I> {
+        RegionError {
+            constraint: InEnvironment::new(self.environment, constraint),
+        }
+    }
+}
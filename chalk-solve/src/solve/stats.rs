@@ -0,0 +1,97 @@
+//! Statistics about the work a solver did to answer each query, for
+//! profiling. See [`Solver::record_stats`](super::Solver::record_stats).
+
+use chalk_ir::interner::Interner;
+use chalk_ir::*;
+use std::fmt;
+use std::time::Duration;
+
+/// Statistics for a single query, i.e. a call to one of the `solve` methods
+/// of a [`Solver`](super::Solver).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryStats<I: Interner> {
+    /// The goal that was solved.
+    pub goal: UCanonical<InEnvironment<Goal<I>>>,
+
+    /// The wall time spent solving the goal. For `solve_multiple`, this
+    /// excludes the time spent in the callback.
+    pub time: Duration,
+
+    /// The statistics specific to the solver that answered the query.
+    pub details: SolverStats<I>,
+}
+
+/// The solver-specific part of [`QueryStats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolverStats<I: Interner> {
+    Slg(SlgStats<I>),
+    Recursive(RecursiveStats),
+}
+
+/// Statistics for a query answered by the SLG solver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlgStats<I: Interner> {
+    /// The tables that were created while answering the query. Tables that
+    /// already existed from earlier queries are reused, and not listed.
+    pub tables: Vec<TableStats<I>>,
+
+    /// How many times the search for an answer exceeded its quantum, and
+    /// had to be resumed.
+    pub quantum_exceeded: usize,
+}
+
+/// Statistics for a table of the SLG solver.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableStats<I: Interner> {
+    /// The goal of the table.
+    pub goal: UCanonical<InEnvironment<Goal<I>>>,
+
+    /// How many strands were created for the table, including strands
+    /// that were forked off or created to refine an answer.
+    pub strands: usize,
+
+    /// How many answers the table has.
+    pub answers: usize,
+}
+
+/// Statistics for a query answered by the recursive solver.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecursiveStats {
+    /// How many goals were added to the search graph.
+    pub search_graph_size: usize,
+
+    /// How many goals were answered from the cache.
+    pub cache_hits: usize,
+
+    /// How many times a goal had to be solved again because it was part of
+    /// a cycle, until its solution reached a fixed point.
+    pub fixpoint_iterations: usize,
+
+    /// The maximum depth reached by the stack of goals being solved.
+    pub max_stack_depth: usize,
+}
+
+impl<I: Interner> fmt::Display for QueryStats<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:?}: {:?}", self.goal.canonical.value.goal, self.time)?;
+        match &self.details {
+            SolverStats::Slg(stats) => {
+                writeln!(f, "  tables created: {}", stats.tables.len())?;
+                for table in &stats.tables {
+                    writeln!(
+                        f,
+                        "    {:?}: strands: {}, answers: {}",
+                        table.goal.canonical.value.goal, table.strands, table.answers
+                    )?;
+                }
+                writeln!(f, "  quantum exceeded: {}", stats.quantum_exceeded)
+            }
+            SolverStats::Recursive(stats) => {
+                writeln!(f, "  search graph size: {}", stats.search_graph_size)?;
+                writeln!(f, "  cache hits: {}", stats.cache_hits)?;
+                writeln!(f, "  fixpoint iterations: {}", stats.fixpoint_iterations)?;
+                writeln!(f, "  max stack depth: {}", stats.max_stack_depth)
+            }
+        }
+    }
+}
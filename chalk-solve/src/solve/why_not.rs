@@ -0,0 +1,427 @@
+//! Failure analysis: explains why a goal cannot be proven, by going through
+//! the program clauses that could prove it and finding out why each of them
+//! does not apply. See [`why_not`].
+
+use crate::clauses::builder::ClauseBuilder;
+use crate::clauses::program_clauses::ToProgramClauses;
+use crate::clauses::program_clauses_for_goal;
+use crate::infer::InferenceTable;
+use crate::solve::Solver;
+use crate::RustIrDatabase;
+use chalk_ir::interner::{HasInterner, Interner};
+use chalk_ir::*;
+use std::fmt;
+
+/// How many levels of unprovable conditions are analyzed before giving up.
+const MAX_DEPTH: usize = 4;
+
+/// Why a goal could not be proven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailureAnalysis<I: Interner> {
+    /// The goal that could not be proven.
+    pub goal: UCanonical<InEnvironment<Goal<I>>>,
+
+    /// Why it could not be proven.
+    pub reason: FailureReason<I>,
+}
+
+/// The reason given by a [`FailureAnalysis`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailureReason<I: Interner> {
+    /// The goal is a domain goal, and none of the program clauses that could
+    /// prove it apply. Lists each of those clauses, with the reason it was
+    /// rejected. For `Implemented` goals, this includes the clauses for
+    /// every impl of the trait.
+    Clauses(Vec<RejectedClause<I>>),
+
+    /// The goal is made up of other goals (e.g. `G1, G2` or
+    /// `forall<T> { G }`), and this one could not be proven.
+    Subgoal(Box<FailureAnalysis<I>>),
+
+    /// No further explanation is available, either because the goal is not
+    /// a domain goal (e.g. `not { G }`), or because the analysis has gone too
+    /// deep.
+    Unknown,
+}
+
+/// A program clause that could not be used to prove a goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedClause<I: Interner> {
+    /// The clause that was rejected.
+    pub clause: ProgramClause<I>,
+
+    /// Why it was rejected.
+    pub rejection: ClauseRejection<I>,
+}
+
+/// Why a [`RejectedClause`] does not apply.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClauseRejection<I: Interner> {
+    /// The goal does not unify with the consequence of the clause.
+    Mismatch {
+        /// The consequence of the clause.
+        consequence: Canonical<DomainGoal<I>>,
+
+        /// For `Implemented` goals, the first parameter of the goal's trait
+        /// reference that does not unify with the consequence.
+        parameters: Option<ParameterMismatch<I>>,
+    },
+
+    /// The goal unifies with the consequence of the clause, but one of its
+    /// conditions cannot be proven.
+    Unprovable(FailureAnalysis<I>),
+
+    /// Each condition of the clause can be proven on its own, but not all of
+    /// them together.
+    ConditionsNotProvable,
+}
+
+/// A pair of corresponding parameters of two trait references that do not
+/// unify, e.g. `Rc<u32>` and `Box<?0>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterMismatch<I: Interner> {
+    /// The parameter from the goal.
+    pub goal: Canonical<GenericArg<I>>,
+
+    /// The parameter from the consequence of the clause.
+    pub consequence: Canonical<GenericArg<I>>,
+}
+
+/// Analyzes why `goal` cannot be proven, using `solver` to check which of
+/// its subgoals hold. Returns `None` if `goal` can in fact be proven (or is
+/// ambiguous).
+///
+/// For a domain goal, this walks the program clauses that could prove it,
+/// and reports for each of them either the unification mismatch that rules
+/// it out, or the first of its conditions that cannot be proven -- together
+/// with the analysis of why that condition cannot be proven, and so on.
+pub fn why_not<I: Interner>(
+    db: &dyn RustIrDatabase<I>,
+    solver: &mut dyn Solver<I>,
+    goal: &UCanonical<InEnvironment<Goal<I>>>,
+) -> Option<FailureAnalysis<I>> {
+    if solver.solve(db, goal).is_some() {
+        return None;
+    }
+    Some(WhyNot { db, solver }.analyze(goal, 0))
+}
+
+struct WhyNot<'a, I: Interner> {
+    db: &'a dyn RustIrDatabase<I>,
+    solver: &'a mut dyn Solver<I>,
+}
+
+impl<I: Interner> WhyNot<'_, I> {
+    fn analyze(
+        &mut self,
+        goal: &UCanonical<InEnvironment<Goal<I>>>,
+        depth: usize,
+    ) -> FailureAnalysis<I> {
+        let reason = if depth >= MAX_DEPTH {
+            FailureReason::Unknown
+        } else {
+            self.analyze_reason(goal, depth)
+        };
+        FailureAnalysis {
+            goal: goal.clone(),
+            reason,
+        }
+    }
+
+    fn analyze_reason(
+        &mut self,
+        canonical_goal: &UCanonical<InEnvironment<Goal<I>>>,
+        depth: usize,
+    ) -> FailureReason<I> {
+        let interner = self.db.interner();
+        let (mut infer, _, InEnvironment { environment, goal }) = InferenceTable::from_canonical(
+            interner,
+            canonical_goal.universes,
+            &canonical_goal.canonical,
+        );
+
+        match goal.data(interner) {
+            GoalData::DomainGoal(domain_goal) => {
+                self.analyze_clauses(&mut infer, canonical_goal, &environment, domain_goal, depth)
+            }
+            GoalData::All(goals) => {
+                for goal in goals.iter(interner) {
+                    let goal = canonicalize(&mut infer, interner, &environment, goal.clone());
+                    if self.solver.solve(self.db, &goal).is_none() {
+                        return FailureReason::Subgoal(Box::new(self.analyze(&goal, depth)));
+                    }
+                }
+                FailureReason::Unknown
+            }
+            GoalData::Quantified(QuantifierKind::ForAll, subgoal) => {
+                let subgoal = infer.instantiate_binders_universally(interner, subgoal);
+                let subgoal = canonicalize(&mut infer, interner, &environment, subgoal);
+                FailureReason::Subgoal(Box::new(self.analyze(&subgoal, depth)))
+            }
+            GoalData::Quantified(QuantifierKind::Exists, subgoal) => {
+                let subgoal = infer.instantiate_binders_existentially(interner, subgoal);
+                let subgoal = canonicalize(&mut infer, interner, &environment, subgoal);
+                FailureReason::Subgoal(Box::new(self.analyze(&subgoal, depth)))
+            }
+            GoalData::Implies(clauses, subgoal) => {
+                let environment =
+                    environment.add_clauses(interner, clauses.iter(interner).cloned());
+                let subgoal = canonicalize(&mut infer, interner, &environment, subgoal.clone());
+                FailureReason::Subgoal(Box::new(self.analyze(&subgoal, depth)))
+            }
+            _ => FailureReason::Unknown,
+        }
+    }
+
+    fn analyze_clauses(
+        &mut self,
+        infer: &mut InferenceTable<I>,
+        canonical_goal: &UCanonical<InEnvironment<Goal<I>>>,
+        environment: &Environment<I>,
+        goal: &DomainGoal<I>,
+        depth: usize,
+    ) -> FailureReason<I> {
+        let interner = self.db.interner();
+
+        // Look up the clauses using the canonical form of the goal, like the
+        // solvers do.
+        let canonical_domain_goal = match canonical_goal.canonical.value.goal.data(interner) {
+            GoalData::DomainGoal(domain_goal) => domain_goal,
+            _ => return FailureReason::Unknown,
+        };
+        let mut clauses = match program_clauses_for_goal(
+            self.db,
+            environment,
+            canonical_domain_goal,
+            &canonical_goal.canonical.binders,
+        ) {
+            Ok(clauses) => clauses,
+            Err(Floundered) => return FailureReason::Unknown,
+        };
+
+        // The clauses above only include the impls that could plausibly
+        // apply; to explain why the others don't, add the clauses for all
+        // impls of the trait.
+        if let DomainGoal::Holds(WhereClause::Implemented(trait_ref)) = goal {
+            let mut impl_clauses = vec![];
+            let builder = &mut ClauseBuilder::new(self.db, &mut impl_clauses);
+            let trait_datum = self.db.trait_datum(trait_ref.trait_id);
+            let (parameters, binders) = fully_general_parameters(interner, &trait_datum.binders);
+            for impl_id in
+                self.db
+                    .impls_for_trait(trait_ref.trait_id, parameters.as_slice(interner), &binders)
+            {
+                self.db
+                    .impl_datum(impl_id)
+                    .to_program_clauses(builder, environment);
+            }
+            for clause in impl_clauses {
+                if !clauses.contains(&clause) {
+                    clauses.push(clause);
+                }
+            }
+        }
+
+        let rejected = clauses
+            .into_iter()
+            .map(|clause| {
+                let snapshot = infer.snapshot();
+                let rejection = self.analyze_clause(infer, environment, goal, &clause, depth);
+                infer.rollback_to(snapshot);
+                RejectedClause { clause, rejection }
+            })
+            .collect();
+        FailureReason::Clauses(rejected)
+    }
+
+    fn analyze_clause(
+        &mut self,
+        infer: &mut InferenceTable<I>,
+        environment: &Environment<I>,
+        goal: &DomainGoal<I>,
+        clause: &ProgramClause<I>,
+        depth: usize,
+    ) -> ClauseRejection<I> {
+        let interner = self.db.interner();
+        let ProgramClauseData(implication) = clause.data(interner);
+        let implication = infer.instantiate_binders_existentially(interner, implication);
+
+        let unification_goals =
+            match infer.unify(interner, environment, goal, &implication.consequence) {
+                Ok(result) => result.goals,
+                Err(NoSolution) => {
+                    let parameters = mismatched_parameters(
+                        infer,
+                        interner,
+                        environment,
+                        goal,
+                        &implication.consequence,
+                    );
+                    let consequence = infer.canonicalize(interner, &implication.consequence);
+                    return ClauseRejection::Mismatch {
+                        consequence: consequence.quantified,
+                        parameters,
+                    };
+                }
+            };
+
+        let conditions = implication
+            .conditions
+            .iter(interner)
+            .cloned()
+            .map(|condition| InEnvironment::new(environment, condition))
+            .chain(unification_goals);
+        for condition in conditions {
+            let condition = canonicalize(
+                infer,
+                interner,
+                &condition.environment,
+                condition.goal.clone(),
+            );
+            if self.solver.solve(self.db, &condition).is_none() {
+                return ClauseRejection::Unprovable(self.analyze(&condition, depth + 1));
+            }
+        }
+        ClauseRejection::ConditionsNotProvable
+    }
+}
+
+/// Given an `Implemented` goal that does not unify with the consequence of a
+/// clause, finds the first pair of parameters of the trait references that
+/// do not unify.
+fn mismatched_parameters<I: Interner>(
+    infer: &mut InferenceTable<I>,
+    interner: &I,
+    environment: &Environment<I>,
+    goal: &DomainGoal<I>,
+    consequence: &DomainGoal<I>,
+) -> Option<ParameterMismatch<I>> {
+    let (goal, consequence) = match (goal, consequence) {
+        (
+            DomainGoal::Holds(WhereClause::Implemented(goal)),
+            DomainGoal::Holds(WhereClause::Implemented(consequence)),
+        ) => (goal, consequence),
+        _ => return None,
+    };
+
+    let snapshot = infer.snapshot();
+    let mut mismatch = None;
+    for (a, b) in goal
+        .substitution
+        .iter(interner)
+        .zip(consequence.substitution.iter(interner))
+    {
+        if infer.unify(interner, environment, a, b).is_err() {
+            mismatch = Some(ParameterMismatch {
+                goal: infer.canonicalize(interner, a).quantified,
+                consequence: infer.canonicalize(interner, b).quantified,
+            });
+            break;
+        }
+    }
+    infer.rollback_to(snapshot);
+    mismatch
+}
+
+fn canonicalize<I: Interner>(
+    infer: &mut InferenceTable<I>,
+    interner: &I,
+    environment: &Environment<I>,
+    goal: Goal<I>,
+) -> UCanonical<InEnvironment<Goal<I>>> {
+    let canonical = infer
+        .canonicalize(interner, &InEnvironment::new(environment, goal))
+        .quantified;
+    infer.u_canonicalize(interner, &canonical).quantified
+}
+
+/// Returns parameters for a trait reference with the given binders that
+/// consist only of variables, so that they could match any impl.
+fn fully_general_parameters<I: Interner>(
+    interner: &I,
+    binders: &Binders<impl HasInterner<Interner = I>>,
+) -> (Substitution<I>, CanonicalVarKinds<I>) {
+    let parameters = binders
+        .binders
+        .iter(interner)
+        .enumerate()
+        .map(|p| p.to_generic_arg(interner));
+    let binders = binders
+        .binders
+        .iter(interner)
+        .map(|kind| CanonicalVarKind::new(kind.clone(), UniverseIndex::root()));
+    (
+        Substitution::from_iter(interner, parameters),
+        CanonicalVarKinds::from_iter(interner, binders),
+    )
+}
+
+/// Displays the analysis as an indented outline, with one line for each
+/// rejected clause, followed by the reasons for the rejection.
+impl<I: Interner> FailureAnalysis<I> {
+    fn fmt_analysis(
+        f: &mut fmt::Formatter<'_>,
+        analysis: &FailureAnalysis<I>,
+        depth: usize,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "{}`{:?}` is not provable",
+            "  ".repeat(depth),
+            analysis.goal.canonical.value.goal
+        )?;
+        Self::fmt_reason(f, &analysis.reason, depth + 1)
+    }
+
+    fn fmt_reason(
+        f: &mut fmt::Formatter<'_>,
+        reason: &FailureReason<I>,
+        depth: usize,
+    ) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match reason {
+            FailureReason::Clauses(rejected) if rejected.is_empty() => {
+                writeln!(f, "{}no clauses apply", indent)
+            }
+            FailureReason::Clauses(rejected) => {
+                for RejectedClause { clause, rejection } in rejected {
+                    write!(f, "{}clause `{:?}` rejected: ", indent, clause)?;
+                    match rejection {
+                        ClauseRejection::Mismatch {
+                            parameters: Some(mismatch),
+                            ..
+                        } => writeln!(
+                            f,
+                            "`{:?}` does not unify with `{:?}`",
+                            mismatch.goal.value, mismatch.consequence.value
+                        )?,
+                        ClauseRejection::Mismatch {
+                            consequence,
+                            parameters: None,
+                        } => writeln!(f, "goal does not unify with `{:?}`", consequence.value)?,
+                        ClauseRejection::Unprovable(analysis) => {
+                            writeln!(
+                                f,
+                                "`{:?}` is not provable",
+                                analysis.goal.canonical.value.goal
+                            )?;
+                            Self::fmt_reason(f, &analysis.reason, depth + 1)?;
+                        }
+                        ClauseRejection::ConditionsNotProvable => {
+                            writeln!(f, "its conditions are not provable together")?
+                        }
+                    }
+                }
+                Ok(())
+            }
+            FailureReason::Subgoal(analysis) => Self::fmt_analysis(f, analysis, depth),
+            FailureReason::Unknown => Ok(()),
+        }
+    }
+}
+
+impl<I: Interner> fmt::Display for FailureAnalysis<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::fmt_analysis(f, self, 0)
+    }
+}
@@ -413,13 +413,19 @@ where
                     &impl_datum,
                 )
             }
-            WellKnownTrait::Clone | WellKnownTrait::Unpin => true,
+            WellKnownTrait::Clone
+            | WellKnownTrait::Unpin
+            | WellKnownTrait::Deref
+            | WellKnownTrait::DerefMut => true,
             // You can't add a manual implementation for the following traits:
             WellKnownTrait::Fn
             | WellKnownTrait::FnOnce
             | WellKnownTrait::FnMut
             | WellKnownTrait::Unsize
-            | WellKnownTrait::Sized => false,
+            | WellKnownTrait::Sized
+            | WellKnownTrait::Generator
+            | WellKnownTrait::DiscriminantKind
+            | WellKnownTrait::Pointee => false,
         };
 
         if is_legal {
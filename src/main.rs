@@ -1,11 +1,11 @@
 #[macro_use]
 extern crate serde_derive;
 
-use std::fs::File;
-use std::io::Read;
+use std::path::Path;
 use std::process::exit;
 
 use chalk_integration::db::ChalkDatabase;
+use chalk_integration::files::SourceFiles;
 use chalk_integration::interner::ChalkIr;
 use chalk_integration::lowering::*;
 use chalk_integration::query::LoweringDatabase;
@@ -23,39 +23,88 @@ chalk repl
 
 Usage:
   chalk [options]
+  chalk fmt [--check] <path>...
   chalk (-h | --help)
 
 Options:
-  --help              Show this screen.
-  --program=PATH      Specifies the path to the `.chalk` file containing traits/impls.
-  --goal=GOAL         Specifies a goal to evaluate (may be given more than once).
-  --overflow-depth=N  Specifies the overflow depth [default: 10].
-  --multiple          Output multiple answers instead of ambiguous solution.
+  --help                Show this screen.
+  --program=PATH        Specifies the path to the `.chalk` file containing traits/impls.
+  --goal=GOAL           Specifies a goal to evaluate (may be given more than once).
+  --solver=SOLVER       Specifies the solver to use, `slg` or `recursive` [default: slg].
+  --max-size=N          Specifies the maximum size of goals for the SLG solver [default: 10].
+  --expected-answers=N  Specifies the number of answers the SLG solver is expected to find.
+  --overflow-depth=N    Specifies the overflow depth of the recursive solver [default: 100].
+  --no-cache            Disables the cache of the recursive solver.
+  --multiple            Output multiple answers instead of ambiguous solution.
+  --check               With `fmt`, lists the files that are not formatted instead of formatting them.
 ";
 
 /// This struct represents the various command line options available.
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct Args {
     flag_program: Option<String>,
     flag_goal: Vec<String>,
+    flag_solver: String,
+    flag_max_size: usize,
+    flag_expected_answers: Option<usize>,
     flag_overflow_depth: usize,
+    flag_no_cache: bool,
     flag_multiple: bool,
+    flag_check: bool,
+    cmd_fmt: bool,
+    arg_path: Vec<String>,
 }
 
 /// A loaded and parsed program.
 struct LoadedProgram {
-    text: String,
+    files: SourceFiles,
     db: ChalkDatabase,
+    record_stats: bool,
 }
 
 impl LoadedProgram {
-    /// Creates a new Program struct, given a `.chalk` file as a String and
-    /// a [`SolverChoice`].
+    /// Creates a new Program struct, given the `.chalk` files the program is
+    /// made of and a [`SolverChoice`]. If the program does not pass lowering
+    /// and checking, the errors are reported on stderr.
     ///
     /// [`SolverChoice`]: struct.solve.SolverChoice.html
-    fn new(text: String, solver_choice: SolverChoice) -> Result<LoadedProgram> {
-        let db = ChalkDatabase::with(&text, solver_choice);
-        Ok(LoadedProgram { text, db })
+    fn new(files: SourceFiles, solver_choice: SolverChoice) -> Result<LoadedProgram> {
+        let db = ChalkDatabase::with_files(files.clone(), solver_choice);
+        if let Err(e) = db.checked_program() {
+            eprint!("{}", e.render(&files));
+            Err("aborting due to previous error")?;
+        }
+        Ok(LoadedProgram {
+            files,
+            db,
+            record_stats: false,
+        })
+    }
+
+    /// Prints the source files of the program.
+    fn print(&self) {
+        let several_files = self.files.iter().nth(1).is_some();
+        for file in self.files.iter() {
+            if several_files {
+                println!("// {}", file.name);
+            }
+            println!("{}", file.text);
+        }
+    }
+
+    /// Switches the program to a different solver. Statistics recorded by
+    /// the previous solver are discarded.
+    fn set_solver_choice(&mut self, solver_choice: SolverChoice) {
+        self.db.set_solver_choice(solver_choice);
+        self.db.record_stats(self.record_stats);
+    }
+
+    /// Starts or stops recording statistics for the goals solved with the
+    /// program; see [`LoadedProgram::stats`]. Recording is off by default,
+    /// as the statistics of each goal are kept until they are printed.
+    fn set_record_stats(&mut self, record_stats: bool) {
+        self.record_stats = record_stats;
+        self.db.record_stats(record_stats);
     }
 
     /// Parse a goal and attempt to solve it, using the specified solver.
@@ -97,12 +146,55 @@ impl LoadedProgram {
             }
         } else {
             match self.db.solve(&peeled_goal) {
-                Some(v) => println!("{}\n", v.display(&ChalkIr)),
+                // The solver leaves the lifetime constraints unchecked.
+                Some(v) => match v.resolve_regions(&ChalkIr) {
+                    Ok(v) => println!("{}\n", v.display(&ChalkIr)),
+                    Err(e) => println!("No possible solution: {}.\n", e),
+                },
                 None => println!("No possible solution.\n"),
             }
         }
         Ok(())
     }
+
+    /// Parse a goal and explain how it is solved, printing the proof tree
+    /// recorded by the solver.
+    fn explain(&self, text: &str) -> Result<()> {
+        let program = self.db.checked_program()?;
+        let goal = lower_goal(&*chalk_parse::parse_goal(text)?, &*program)?;
+        let peeled_goal = goal.into_peeled_goal(self.db.interner());
+        let proof_tree = self.db.explain(&peeled_goal);
+        println!("{}", proof_tree.display(&ChalkIr));
+        Ok(())
+    }
+
+    /// Parse a goal and explain why it cannot be proven.
+    fn why_not(&self, text: &str) -> Result<()> {
+        let program = self.db.checked_program()?;
+        let goal = lower_goal(&*chalk_parse::parse_goal(text)?, &*program)?;
+        let peeled_goal = goal.into_peeled_goal(self.db.interner());
+        match self.db.why_not(&peeled_goal) {
+            Some(analysis) => println!("{}", analysis),
+            None => println!("The goal can be proven.\n"),
+        }
+        Ok(())
+    }
+
+    /// Print the statistics for the queries made to the solver since the
+    /// last time they were printed.
+    fn stats(&self) {
+        if !self.record_stats {
+            println!("Statistics are not being recorded; use `stats on` to record them.\n");
+            return;
+        }
+        let stats = self.db.take_stats();
+        if stats.is_empty() {
+            println!("No goals have been solved since the last `stats` command.\n");
+        }
+        for query in stats {
+            println!("{}", query);
+        }
+    }
 }
 
 fn run() -> Result<()> {
@@ -111,16 +203,23 @@ fn run() -> Result<()> {
         .and_then(|d| d.deserialize())
         .unwrap_or_else(|e| e.exit());
 
-    // Validate arguments.
-    if args.flag_overflow_depth == 0 {
-        eprintln!("error: overflow depth must be at least 1");
-        exit(1);
+    if args.cmd_fmt {
+        return format_files(&args.arg_path, args.flag_check);
     }
 
+    // Validate arguments.
+    let mut solver_choice = match args.solver_choice() {
+        Ok(solver_choice) => solver_choice,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        }
+    };
+
     // Load the .chalk file, if given.
     let mut prog = None;
     if let Some(program) = &args.flag_program {
-        match load_program(args, program) {
+        match load_program(solver_choice, &[program]) {
             Ok(p) => prog = Some(p),
             Err(err) => {
                 eprintln!("error loading program: {}", err);
@@ -132,7 +231,7 @@ fn run() -> Result<()> {
     if args.flag_goal.is_empty() {
         // The user specified no goal. Enter interactive mode.
         readline_loop(&mut rustyline::Editor::new(), "?- ", |rl, line| {
-            if let Err(e) = process(args, line, rl, &mut prog) {
+            if let Err(e) = process(args, line, rl, &mut solver_choice, &mut prog) {
                 eprintln!("error: {}", e);
             }
         })
@@ -159,6 +258,34 @@ fn run() -> Result<()> {
     }
 }
 
+/// Formats the `.chalk` files at `paths` in place. With `check`, the files
+/// are left untouched; those that are not formatted are listed, and an error
+/// is returned if there are any.
+fn format_files(paths: &[String], check: bool) -> Result<()> {
+    let mut unformatted = 0;
+    for path in paths {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("error reading `{}`: {}", path, e))?;
+        let program = chalk_parse::parse_program(&text)
+            .map_err(|e| format!("error parsing `{}`: {}", path, e))?;
+        let formatted = chalk_parse::format::format_program(&program, &text);
+        if formatted == text {
+            continue;
+        }
+        if check {
+            println!("`{}` is not formatted", path);
+            unformatted += 1;
+        } else {
+            std::fs::write(path, formatted)
+                .map_err(|e| format!("error writing `{}`: {}", path, e))?;
+        }
+    }
+    if unformatted > 0 {
+        Err(format!("{} file(s) are not formatted", unformatted))?;
+    }
+    Ok(())
+}
+
 /// Reads input lines from the user. Lines start with the string given by `prompt`.
 /// Each line the user enters is passed to the function `f` for processing.
 ///
@@ -190,13 +317,13 @@ where
 }
 
 /// Process a single command. `args` is a struct containing the command-line
-/// arguments, and `prog` is a parsed `.chalk` file.
-// TODO: Could we pass in an Options struct or something? The Args struct
-// still has Strings where it should have Enums... (e.g. solver_choice)
+/// arguments, `solver_choice` is the solver used for the programs that are
+/// loaded, and `prog` is a parsed `.chalk` file.
 fn process(
     args: &Args,
     command: &str,
     rl: &mut rustyline::Editor<()>,
+    solver_choice: &mut SolverChoice,
     prog: &mut Option<LoadedProgram>,
 ) -> Result<()> {
     if command.is_empty() {
@@ -207,17 +334,35 @@ fn process(
         help()
     } else if command == "program" {
         // Load a .chalk file via stdin, until EOF is found.
-        let chalk_prog = LoadedProgram::new(read_program(rl)?, args.solver_choice())?;
-        // Let's do a sanity check before going forward.
-        let _ = chalk_prog.db.checked_program()?;
+        let mut files = SourceFiles::new();
+        files.add_with_includes("<stdin>", read_program(rl)?)?;
+        let chalk_prog = LoadedProgram::new(files, *solver_choice)?;
         *prog = Some(chalk_prog);
-    } else if command.starts_with("load ") {
-        // Load a .chalk file.
-        let filename = &command["load ".len()..];
-        let chalk_prog = load_program(args, filename)?;
-        // Let's do a sanity check before going forward.
-        let _ = chalk_prog.db.checked_program()?;
+    } else if let Some(filenames) = command.strip_prefix("load ") {
+        // Load one or more .chalk files.
+        let filenames: Vec<_> = filenames.split_whitespace().collect();
+        let chalk_prog = load_program(*solver_choice, &filenames)?;
         *prog = Some(chalk_prog);
+    } else if command == "solver" {
+        // Print out the solver in use.
+        println!("{:?}", solver_choice);
+    } else if let Some(options) = command.strip_prefix("solver ") {
+        // Switch to another solver, for the current program as well as for
+        // the programs loaded later.
+        *solver_choice = args.with_solver_options(options)?;
+        if let Some(prog) = prog {
+            prog.set_solver_choice(*solver_choice);
+        }
+    } else if let Some(setting) = command.strip_prefix("stats ") {
+        // Start or stop recording statistics for the current program.
+        let record_stats = match setting.trim() {
+            "on" => true,
+            "off" => false,
+            _ => Err("expected `stats on` or `stats off`")?,
+        };
+        prog.as_mut()
+            .ok_or("no program currently loaded; type 'help' to see available commands")?
+            .set_record_stats(record_stats);
     } else if command.starts_with("debug ") {
         match command.split_whitespace().nth(1) {
             Some(level) => std::env::set_var("CHALK_DEBUG", level),
@@ -235,11 +380,20 @@ fn process(
         prog.db.with_program(|_| -> Result<()> {
             match command {
                 // Print out the loaded program.
-                "print" => println!("{}", prog.text),
+                "print" => prog.print(),
 
                 // TODO: Write a line of documentation here.
                 "lowered" => println!("{:#?}", prog.db.environment()),
 
+                // Print out the statistics for the goals solved so far.
+                "stats" => prog.stats(),
+
+                _ if command.starts_with("explain ") => {
+                    prog.explain(&command["explain ".len()..])?
+                }
+
+                _ if command.starts_with("whynot ") => prog.why_not(&command["whynot ".len()..])?,
+
                 // Assume this is a goal.
                 // TODO: Print out "type 'help' to see available commands" if it
                 // fails to parse?
@@ -252,13 +406,13 @@ fn process(
     Ok(())
 }
 
-/// Load the file into a string, and parse it.
-// TODO: Could we pass in an Options struct or something? The Args struct
-// still has Strings where it should have Enums... (e.g. solver_choice)
-fn load_program(args: &Args, filename: &str) -> Result<LoadedProgram> {
-    let mut text = String::new();
-    File::open(filename)?.read_to_string(&mut text)?;
-    Ok(LoadedProgram::new(text, args.solver_choice())?)
+/// Load the files, along with the files they include, and parse them.
+fn load_program(
+    solver_choice: SolverChoice,
+    filenames: &[impl AsRef<Path>],
+) -> Result<LoadedProgram> {
+    let files = SourceFiles::load(filenames)?;
+    LoadedProgram::new(files, solver_choice)
 }
 
 /// Print out help for commands in interpreter mode.
@@ -267,10 +421,19 @@ fn help() {
     println!("Commands:");
     println!("  help          print this output");
     println!("  program       provide a program via stdin");
-    println!("  load <file>   load program from <file>");
+    println!("  load <file>.. load program from one or more files");
     println!("  print         print the current program");
     println!("  lowered       print the lowered program");
     println!("  <goal>        attempt to solve <goal>");
+    println!("  explain <goal> explain how <goal> is solved");
+    println!("  whynot <goal>  explain why <goal> cannot be proven");
+    println!("  stats         print solver statistics for the goals solved since the last `stats`");
+    println!("  stats on|off  start or stop recording solver statistics (off by default)");
+    println!("  solver        print the solver in use");
+    println!("  solver <name> [options]");
+    println!("                switch to the `slg` or `recursive` solver; the options are");
+    println!("                `max-size=N`, `expected-answers=N`, `overflow-depth=N`");
+    println!("                and `no-cache`, and default to the command line flags");
     println!("  debug <level> set debug level to <level>");
 }
 
@@ -287,11 +450,61 @@ fn read_program(rl: &mut rustyline::Editor<()>) -> Result<String> {
 }
 
 impl Args {
-    fn solver_choice(&self) -> SolverChoice {
-        SolverChoice::SLG {
-            max_size: self.flag_overflow_depth,
-            expected_answers: None,
+    /// Returns the solver selected by the solver flags, or an error if they
+    /// are invalid.
+    fn solver_choice(&self) -> Result<SolverChoice> {
+        match &self.flag_solver[..] {
+            "slg" => {
+                if self.flag_max_size == 0 {
+                    Err("max size must be at least 1")?;
+                }
+                Ok(SolverChoice::slg(
+                    self.flag_max_size,
+                    self.flag_expected_answers,
+                ))
+            }
+            "recursive" => {
+                if self.flag_overflow_depth == 0 {
+                    Err("overflow depth must be at least 1")?;
+                }
+                Ok(SolverChoice::Recursive {
+                    overflow_depth: self.flag_overflow_depth,
+                    caching_enabled: !self.flag_no_cache,
+                })
+            }
+            solver => Err(format!(
+                "unknown solver `{}`; expected `slg` or `recursive`",
+                solver
+            ))?,
+        }
+    }
+
+    /// Returns the solver selected by the arguments of the `solver` command,
+    /// i.e. a solver name followed by options in the same form as the solver
+    /// flags, without the leading dashes. Options that are not given
+    /// default to the command line flags.
+    fn with_solver_options(&self, options: &str) -> Result<SolverChoice> {
+        let mut args = self.clone();
+        let mut options = options.split_whitespace();
+        args.flag_solver = options.next().ok_or("no solver given")?.to_string();
+        for option in options {
+            let (name, value) = match option.find('=') {
+                Some(index) => (&option[..index], Some(&option[index + 1..])),
+                None => (option, None),
+            };
+            let parse_value = || -> Result<usize> {
+                let value = value.ok_or_else(|| format!("option `{}` needs a value", name))?;
+                Ok(value.parse()?)
+            };
+            match name {
+                "max-size" => args.flag_max_size = parse_value()?,
+                "expected-answers" => args.flag_expected_answers = Some(parse_value()?),
+                "overflow-depth" => args.flag_overflow_depth = parse_value()?,
+                "no-cache" if value.is_none() => args.flag_no_cache = true,
+                _ => Err(format!("unknown solver option `{}`", option))?,
+            }
         }
+        args.solver_choice()
     }
 }
 
@@ -0,0 +1,71 @@
+#[test]
+fn test_trait_assoc_fns() {
+    // Test printing the associated fns of a trait, with each kind of receiver.
+    reparse_test!(
+        program {
+            struct Box<T> { }
+            trait Foo<T> {
+                type Assoc;
+                fn new() -> Self;
+                fn by_ref(&self) -> T;
+                fn by_mut(&mut self, arg: T);
+                fn by_value(self) -> Self;
+                fn boxed<'a>(self: Box<Self>, arg: &'a T);
+            }
+        }
+    );
+}
+
+#[test]
+fn test_trait_assoc_fn_with_generics() {
+    // Test the parameters and where clauses of trait fns, which share their
+    // binders with the parameters of the trait.
+    reparse_test!(
+        program {
+            trait Bar { }
+            trait Foo<T> where T: Bar {
+                fn convert<U, V>(&self, arg: U) -> V where U: Bar, V: Foo<T>;
+            }
+        }
+    );
+}
+
+#[test]
+fn test_inherent_impl() {
+    // Test printing an inherent impl, whose where clauses are written on
+    // each of its fns.
+    reparse_test!(
+        program {
+            trait Clone { }
+            struct Foo<T> { }
+            impl<T> Foo<T> where T: Clone {
+                fn new() -> Foo<T>;
+                fn get<U>(&self, index: U) -> T where U: Clone;
+                fn set(&mut self, value: T);
+                fn into_inner(self) -> T;
+            }
+        }
+    );
+}
+
+#[test]
+fn test_several_inherent_impls() {
+    // Test that the fns of different inherent impls are written as separate
+    // impls, after the other items.
+    reparse_test!(
+        program {
+            struct Foo { }
+            struct Bar<'a> { }
+            trait Baz {
+                fn baz(&self);
+            }
+            impl Foo {
+                fn foo(&self);
+            }
+            impl<'a> Bar<'a> {
+                fn bar(&'a self) -> Foo;
+                fn new() -> Bar<'a>;
+            }
+        }
+    );
+}
@@ -19,7 +19,24 @@ fn test_const_generics() {
 }
 
 #[test]
-#[ignore]
+fn test_assoc_consts() {
+    // Test we render associated consts and const projections correctly.
+    reparse_test!(
+        program {
+            struct Foo<const N> { }
+            trait Len<T> {
+                const N: usize;
+            }
+            trait Bar { }
+            impl<T, const C> Len<T> for Foo<C> {
+                const N: usize = C;
+            }
+            impl<T, const C> Bar for Foo<<Foo<C> as Len<T>>::N> { }
+        }
+    );
+}
+
+#[test]
 fn test_basic_const_values_in_impls() {
     // Test we render const values correctly in impls.
     reparse_test!(
@@ -34,7 +51,6 @@ fn test_basic_const_values_in_impls() {
 }
 
 #[test]
-#[ignore]
 fn test_basic_const_values_in_opaque_ty_values() {
     // Test we render const values correctly in opaque type values.
     reparse_test!(
@@ -46,7 +62,6 @@ fn test_basic_const_values_in_opaque_ty_values() {
 }
 
 #[test]
-#[ignore]
 fn test_basic_const_values_in_assoc_ty_values() {
     // Test we render const values correctly in associated type values.
     reparse_test!(
@@ -61,3 +76,41 @@ fn test_basic_const_values_in_assoc_ty_values() {
         }
     );
 }
+
+#[test]
+fn test_typed_const_values() {
+    // Test we render typed const parameters and values correctly.
+    reparse_test!(
+        program {
+            struct Flag<const B: bool> { }
+            struct Letter<const C: char> { }
+            struct Offset<const O: i8> { }
+            trait Bar { }
+            impl Bar for Flag<true> { }
+            impl Bar for Letter<'a'> { }
+            impl Bar for Offset<-3> { }
+            impl Bar for [u8; 3] { }
+        }
+    );
+}
+
+#[test]
+fn test_const_exprs() {
+    // Test we render const expressions correctly.
+    reparse_test!(
+        program {
+            struct Foo<const N> { }
+            struct Flag<const B: bool> { }
+            trait Bar { }
+            trait Len {
+                const N: usize;
+            }
+            impl<const N> Bar for Foo<{ N + 1 }> { }
+            impl<const N> Bar for [u8; N * (2 - N) % 3] { }
+            impl<const B: bool> Bar for Flag<{ !B }> { }
+            impl<const N> Len for Foo<N> {
+                const N: usize = N / 2;
+            }
+        }
+    );
+}
@@ -96,3 +96,13 @@ fn test_fn_as_type_in_struct_field() {
         }
     );
 }
+
+#[test]
+fn test_fn_variance() {
+    reparse_test!(
+        program {
+            #[variance(Contravariant)]
+            fn foo<'a>(arg: &'a u32);
+        }
+    );
+}
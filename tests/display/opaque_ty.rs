@@ -132,3 +132,16 @@ fn multiple_bounds() {
         }
     );
 }
+
+#[test]
+fn opaque_ty_variance() {
+    reparse_test!(
+        program {
+            struct Foo<'a> {}
+            trait Bar {}
+            impl<'a> Bar for Foo<'a> {}
+            #[variance(Covariant)]
+            opaque type Baz<'a>: Bar = Foo<'a>;
+        }
+    );
+}
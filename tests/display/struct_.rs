@@ -102,6 +102,18 @@ fn test_struct_repr() {
     );
 }
 
+#[test]
+fn test_struct_lang() {
+    reparse_test!(
+        program {
+            #[upstream]
+            #[lang(dyn_metadata)]
+            #[repr(C)]
+            struct DynMetadata<Dyn> {}
+        }
+    );
+}
+
 #[test]
 fn test_struct_repr_with_flags() {
     // Test printing both a repr and a flag (to ensure we get the ordering between them right).
@@ -113,3 +125,18 @@ fn test_struct_repr_with_flags() {
         }
     );
 }
+
+#[test]
+fn test_struct_variance() {
+    // Variances are only printed if some parameter is not invariant.
+    reparse_test!(
+        program {
+            #[variance(Covariant, Contravariant)]
+            struct Foo<'a, T> {}
+
+            #[repr(C)]
+            #[variance(Invariant, Covariant)]
+            struct Bar<T, U> {}
+        }
+    );
+}
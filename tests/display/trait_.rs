@@ -32,6 +32,27 @@ fn test_trait_where_clauses() {
     );
 }
 
+#[test]
+fn test_trait_alias() {
+    // Test printing trait aliases, whose bounds are printed as where clauses
+    reparse_test!(
+        program {
+            trait Foo<T> {}
+            trait Bar {}
+            trait Baz {}
+            trait Alias<T> = Foo<T> + Bar where T: Baz;
+            trait Empty = ;
+        }
+        produces {
+            trait Foo<T> {}
+            trait Bar {}
+            trait Baz {}
+            trait Alias<T> = where Self: Foo<T>, Self: Bar, T: Baz;
+            trait Empty = ;
+        }
+    );
+}
+
 #[test]
 fn test_basic_trait_impl() {
     // Test simplest trait implementation
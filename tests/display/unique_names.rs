@@ -78,6 +78,27 @@ where
     fn adt_repr(&self, id: chalk_ir::AdtId<I>) -> chalk_solve::rust_ir::AdtRepr {
         self.db.adt_repr(id)
     }
+    fn adt_variance(&self, adt_id: chalk_ir::AdtId<I>) -> Vec<chalk_ir::Variance> {
+        self.db.adt_variance(adt_id)
+    }
+    fn fn_def_variance(&self, fn_def_id: chalk_ir::FnDefId<I>) -> Vec<chalk_ir::Variance> {
+        self.db.fn_def_variance(fn_def_id)
+    }
+    fn associated_fns(
+        &self,
+        name: &I::Identifier,
+    ) -> Vec<std::sync::Arc<chalk_solve::rust_ir::AssociatedFnDatum<I>>> {
+        self.db.associated_fns(name)
+    }
+    fn associated_fn_data(
+        &self,
+        fn_def_id: chalk_ir::FnDefId<I>,
+    ) -> Option<std::sync::Arc<chalk_solve::rust_ir::AssociatedFnDatum<I>>> {
+        self.db.associated_fn_data(fn_def_id)
+    }
+    fn opaque_ty_variance(&self, id: chalk_ir::OpaqueTyId<I>) -> Vec<chalk_ir::Variance> {
+        self.db.opaque_ty_variance(id)
+    }
     fn fn_def_datum(
         &self,
         fn_def_id: chalk_ir::FnDefId<I>,
@@ -96,6 +117,18 @@ where
     ) -> std::sync::Arc<chalk_solve::rust_ir::AssociatedTyValue<I>> {
         self.db.associated_ty_value(id)
     }
+    fn associated_const_data(
+        &self,
+        id: chalk_ir::AssocConstId<I>,
+    ) -> std::sync::Arc<chalk_solve::rust_ir::AssociatedConstDatum<I>> {
+        self.db.associated_const_data(id)
+    }
+    fn associated_const_value(
+        &self,
+        id: chalk_solve::rust_ir::AssociatedConstValueId<I>,
+    ) -> std::sync::Arc<chalk_solve::rust_ir::AssociatedConstValue<I>> {
+        self.db.associated_const_value(id)
+    }
     fn generator_datum(
         &self,
         generator_id: chalk_ir::GeneratorId<I>,
@@ -131,6 +164,12 @@ where
     ) -> Vec<chalk_ir::ImplId<I>> {
         self.db.local_impls_to_coherence_check(trait_id)
     }
+    fn upstream_impls_to_coherence_check(
+        &self,
+        trait_id: chalk_ir::TraitId<I>,
+    ) -> Vec<chalk_ir::ImplId<I>> {
+        self.db.upstream_impls_to_coherence_check(trait_id)
+    }
     fn impl_provided_for(
         &self,
         auto_trait_id: chalk_ir::TraitId<I>,
@@ -144,6 +183,12 @@ where
     ) -> Option<chalk_ir::TraitId<I>> {
         self.db.well_known_trait_id(well_known_trait)
     }
+    fn well_known_adt_id(
+        &self,
+        well_known_adt: chalk_solve::rust_ir::WellKnownAdt,
+    ) -> Option<chalk_ir::AdtId<I>> {
+        self.db.well_known_adt_id(well_known_adt)
+    }
     fn program_clauses_for_env(
         &self,
         environment: &chalk_ir::Environment<I>,
@@ -160,7 +205,7 @@ where
         &self,
         closure_id: chalk_ir::ClosureId<I>,
         substs: &chalk_ir::Substitution<I>,
-    ) -> chalk_solve::rust_ir::ClosureKind {
+    ) -> Option<chalk_solve::rust_ir::ClosureKind> {
         self.db.closure_kind(closure_id, substs)
     }
     fn closure_inputs_and_output(
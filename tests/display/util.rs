@@ -194,6 +194,7 @@ pub fn reparse_into_different_test<'a>(
             e, program_text
         )
     });
+    format_test(program_text, &original_program);
     let target_db = chalk_integration::db::ChalkDatabase::with(target_text, <_>::default());
     let target_program = target_db.program_ir().unwrap_or_else(|e| {
         panic!(
@@ -210,13 +211,16 @@ pub fn reparse_into_different_test<'a>(
             e, output_text
         )
     });
-    if output_program != target_program {
+    if without_spans(&output_program) != without_spans(&target_program) {
         panic!(
             "WriteProgram produced different program.\n\
              Diff:\n{}\n\
              Source:\n{}\n{}\
              New Source:\n{}\n",
-            program_diff(&target_program, &output_program),
+            program_diff(
+                &without_spans(&target_program),
+                &without_spans(&output_program)
+            ),
             program_text,
             if target_text != program_text {
                 format!(
@@ -240,6 +244,53 @@ pub fn reparse_into_different_test<'a>(
     }
 }
 
+/// Formats the input with `chalk_parse::format`, failing if the formatted
+/// program doesn't lower to the same program as the input, or if formatting
+/// it again changes it.
+fn format_test(program_text: &str, original_program: &Program) {
+    let format = |text: &str| {
+        let program = chalk_parse::parse_program(text)
+            .unwrap_or_else(|e| panic!("unable to parse program:\n{}\nSource:\n{}\n", e, text));
+        chalk_parse::format::format_program(&program, text)
+    };
+    let formatted_text = format(program_text);
+    let formatted_db = chalk_integration::db::ChalkDatabase::with(&formatted_text, <_>::default());
+    let formatted_program = formatted_db.program_ir().unwrap_or_else(|e| {
+        panic!(
+            "error lowering formatted program:\n{}\nFormatted source:\n{}\n",
+            e, formatted_text
+        )
+    });
+    if without_spans(&formatted_program) != without_spans(original_program) {
+        panic!(
+            "Formatting produced a different program.\n\
+             Diff:\n{}\n\
+             Source:\n{}\n\
+             Formatted source:\n{}\n",
+            program_diff(
+                &without_spans(original_program),
+                &without_spans(&formatted_program)
+            ),
+            program_text,
+            formatted_text
+        );
+    }
+    assert_eq!(
+        format(&formatted_text),
+        formatted_text,
+        "formatting a formatted program changed it"
+    );
+}
+
+/// Clears the source spans recorded in a `Program`, which are expected to
+/// differ between the original and the reprinted program text.
+fn without_spans(program: &Program) -> Program {
+    Program {
+        item_spans: Default::default(),
+        ..program.clone()
+    }
+}
+
 /// Tests that a string matches a given regex pattern, erroring out if it
 /// doesn't.
 ///
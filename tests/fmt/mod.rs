@@ -0,0 +1,238 @@
+//! Tests for `chalk_parse::format`, which `chalk fmt` uses. The programs of
+//! the display tests are formatted as well, and checked to lower to the same
+//! programs; see `display::util::reparse_into_different_test`.
+
+fn format(text: &str) -> String {
+    let program = chalk_parse::parse_program(text).unwrap();
+    chalk_parse::format::format_program(&program, text)
+}
+
+/// Checks that `text` is formatted as `expected`, and that `expected` is
+/// already formatted.
+fn assert_formats(text: &str, expected: &str) {
+    assert_eq!(format(text), expected);
+    assert_eq!(format(expected), expected);
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    assert_formats(
+        "
+// A comment.
+struct Foo { }   // Trailing text.   
+struct Bar { }
+
+
+//! Several blank lines become one.
+   // Comments are indented like the items.
+trait Baz { }
+",
+        "// A comment.
+struct Foo { }
+// Trailing text.
+struct Bar { }
+
+//! Several blank lines become one.
+// Comments are indented like the items.
+trait Baz { }
+",
+    );
+}
+
+#[test]
+fn items() {
+    assert_formats(
+        "
+#[upstream] #[repr(C)] struct Foo<T> where T: Clone { a: T, b: [u8; 3] }
+enum Option<T> { None, Some(T), Pair { a: T, b: T } }
+#[object_safe] #[lang(deref)] trait Deref { fn get(&self) -> &'static Self::Target; type Target; }
+impl<T> Deref for Box<T> { type Target = T; }
+#[lang(sized)] trait Sized { }
+#[upstream] impl !Send for Foo<u8> { }
+impl<T> Vec<T> { fn push(&mut self, value: T) -> (); }
+extern \"Rust\" fn foo<'a>(x: &'a (u8,), args: ...) -> fn(u8) -> ();
+closure bar(&self,) { u8; Foo<u8> }
+extern type Ext;
+",
+        "#[upstream]
+#[repr(C)]
+struct Foo<T> where T: Clone {
+    a: T,
+    b: [u8; 3],
+}
+enum Option<T> {
+    None,
+    Some(T),
+    Pair { a: T, b: T },
+}
+#[object_safe]
+#[lang(deref)]
+trait Deref {
+    type Target;
+    fn get(&self) -> &'static Self::Target;
+}
+impl<T> Deref for Box<T> {
+    type Target = T;
+}
+#[lang(sized)]
+trait Sized { }
+#[upstream]
+impl !Send for Foo<u8> { }
+impl<T> Vec<T> {
+    fn push(&mut self, value: T);
+}
+fn foo<'a>(x: &'a (u8,), args: ...) -> fn(u8);
+closure bar(&self,) { u8; Foo<u8> }
+extern type Ext;
+",
+    );
+}
+
+#[test]
+fn consts() {
+    assert_formats(
+        "
+struct S<const N, const B: bool> { }
+impl Len for S<-3, true> { const N: usize = ((1 + 2)) * -N - (4 - <T as Len>::N); }
+impl Foo for S<{N}, {2 * (N + 1)}> { }
+",
+        "struct S<const N, const B: bool> { }
+impl Len for S<-3, true> {
+    const N: usize = (1 + 2) * -N - (4 - <T as Len>::N);
+}
+impl Foo for S<{ N }, { 2 * (N + 1) }> { }
+",
+    );
+}
+
+#[test]
+fn clauses() {
+    assert_formats(
+        "
+forall<T> { T: Foo if T: Bar, (T: Baz, exists<U> { U = T }) }
+forall<> { Normalize(<u8 as Len>::N -> { -1 }) }
+forall<T> { FromEnv(T: Foo) if if (T: Bar; forall<'a> { T: Baz<'a> :- T: 'a }) { not { T: Qux } } }
+",
+        "forall<T> { T: Foo if T: Bar, (T: Baz, exists<U> { U = T }) }
+forall { Normalize(<u8 as Len>::N -> { -1 }) }
+forall<T> { FromEnv(T: Foo) if if (T: Bar; forall<'a> { T: Baz<'a> :- T: 'a }) { not { T: Qux } } }
+",
+    );
+}
+
+#[test]
+fn modules_and_crates() {
+    assert_formats(
+        "
+crate std { mod vec { struct Vec<T> { } } }
+crate app depends std {
+    use std::vec::Vec as V;
+
+    #[upstream] mod empty { }
+}
+include \"other.chalk\";
+",
+        "crate std {
+    mod vec {
+        struct Vec<T> { }
+    }
+}
+crate app depends std {
+    use std::vec::Vec as V;
+
+    #[upstream]
+    mod empty { }
+}
+include \"other.chalk\";
+",
+    );
+}
+
+#[test]
+fn templates() {
+    assert_formats(
+        "
+for T in [u8, Vec<u16>, { N + 1 }] { impl Copy for T { } for U in [] { } }
+",
+        "for T in [u8, Vec<u16>, { N + 1 }] {
+    impl Copy for T { }
+    for U in [] { }
+}
+",
+    );
+}
+
+#[test]
+fn closure_kinds() {
+    assert_formats(
+        "
+closure foo(self,) { }
+closure bar( _ , a: u8) -> u32 { }
+",
+        "closure foo(self,) { }
+closure bar(_, a: u8) -> u32 { }
+",
+    );
+}
+
+#[test]
+fn trait_aliases() {
+    assert_formats(
+        "
+#[upstream] trait Alias<T> = Foo<T>+Bar where T: Baz;
+trait Empty =   where Self: Foo<u8> ;
+",
+        "#[upstream]
+trait Alias<T> = Foo<T> + Bar where T: Baz;
+trait Empty = where Self: Foo<u8>;
+",
+    );
+}
+
+#[test]
+fn generator_movability() {
+    assert_formats(
+        "
+static   generator foo<T>[resume = (), yield = T] -> u8 {
+    upvars [T] witnesses []
+}
+#[lang(generator)] trait Generator<R> { type Yield; type Return; }
+",
+        "static generator foo<T>[resume = (), yield = T] -> u8 {
+    upvars [T]
+    witnesses []
+}
+#[lang(generator)]
+trait Generator<R> {
+    type Yield;
+    type Return;
+}
+",
+    );
+}
+
+#[test]
+fn adt_lang_items_and_int_reprs() {
+    assert_formats(
+        "
+#[repr(u8)] #[repr(C)]   enum Small { A, B }
+#[lang(dyn_metadata)] #[repr(C)] struct DynMetadata<Dyn> { }
+",
+        "#[repr(C)]
+#[repr(u8)]
+enum Small {
+    A,
+    B,
+}
+#[lang(dyn_metadata)]
+#[repr(C)]
+struct DynMetadata<Dyn> { }
+",
+    );
+}
+
+#[test]
+fn libstd_is_formatted() {
+    let libstd = include_str!("../../libstd.chalk");
+    assert_eq!(format(libstd), libstd);
+}
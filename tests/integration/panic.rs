@@ -1,5 +1,5 @@
 use chalk_integration::interner::{ChalkIr, RawId};
-use chalk_integration::SolverChoice;
+use chalk_integration::{Identifier, SolverChoice};
 use chalk_ir::*;
 use chalk_solve::rust_ir::*;
 use chalk_solve::RustIrDatabase;
@@ -70,8 +70,11 @@ impl RustIrDatabase<ChalkIr> for MockDatabase {
                 fundamental: false,
                 non_enumerable: false,
                 coinductive: false,
+                alias: false,
             },
             associated_ty_ids: vec![],
+            associated_const_ids: vec![],
+            associated_fn_ids: vec![],
             well_known: None,
         })
     }
@@ -105,6 +108,7 @@ impl RustIrDatabase<ChalkIr> for MockDatabase {
             binders,
             impl_type: ImplType::Local,
             associated_ty_value_ids: vec![],
+            associated_const_value_ids: vec![],
         })
     }
 
@@ -115,6 +119,20 @@ impl RustIrDatabase<ChalkIr> for MockDatabase {
         unimplemented!()
     }
 
+    fn associated_const_data(
+        &self,
+        id: AssocConstId<ChalkIr>,
+    ) -> Arc<AssociatedConstDatum<ChalkIr>> {
+        unimplemented!()
+    }
+
+    fn associated_const_value(
+        &self,
+        id: AssociatedConstValueId<ChalkIr>,
+    ) -> Arc<AssociatedConstValue<ChalkIr>> {
+        unimplemented!()
+    }
+
     fn opaque_ty_data(&self, id: OpaqueTyId<ChalkIr>) -> Arc<OpaqueTyDatum<ChalkIr>> {
         unimplemented!()
     }
@@ -147,6 +165,29 @@ impl RustIrDatabase<ChalkIr> for MockDatabase {
         unimplemented!()
     }
 
+    fn adt_variance(&self, adt_id: AdtId<ChalkIr>) -> Vec<Variance> {
+        unimplemented!()
+    }
+
+    fn fn_def_variance(&self, fn_def_id: FnDefId<ChalkIr>) -> Vec<Variance> {
+        unimplemented!()
+    }
+
+    fn associated_fns(&self, name: &Identifier) -> Vec<Arc<AssociatedFnDatum<ChalkIr>>> {
+        unimplemented!()
+    }
+
+    fn associated_fn_data(
+        &self,
+        fn_def_id: FnDefId<ChalkIr>,
+    ) -> Option<Arc<AssociatedFnDatum<ChalkIr>>> {
+        unimplemented!()
+    }
+
+    fn opaque_ty_variance(&self, id: OpaqueTyId<ChalkIr>) -> Vec<Variance> {
+        unimplemented!()
+    }
+
     fn fn_def_datum(&self, fn_def_id: FnDefId<ChalkIr>) -> Arc<FnDefDatum<ChalkIr>> {
         unimplemented!()
     }
@@ -189,6 +230,10 @@ impl RustIrDatabase<ChalkIr> for MockDatabase {
         unimplemented!()
     }
 
+    fn well_known_adt_id(&self, well_known_adt: WellKnownAdt) -> Option<AdtId<ChalkIr>> {
+        unimplemented!()
+    }
+
     fn program_clauses_for_env(
         &self,
         environment: &Environment<ChalkIr>,
@@ -224,7 +269,7 @@ impl RustIrDatabase<ChalkIr> for MockDatabase {
         &self,
         closure_id: ClosureId<ChalkIr>,
         substs: &Substitution<ChalkIr>,
-    ) -> ClosureKind {
+    ) -> Option<ClosureKind> {
         unimplemented!()
     }
 
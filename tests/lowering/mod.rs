@@ -1,6 +1,9 @@
 use chalk_integration::db::ChalkDatabase;
+use chalk_integration::error::ChalkErrorKind;
+use chalk_integration::files::SourceFiles;
 use chalk_integration::query::LoweringDatabase;
 use chalk_integration::SolverChoice;
+use chalk_parse::ast::{FileId, Item};
 
 #[test]
 fn lower_success() {
@@ -96,6 +99,32 @@ fn negative_impl() {
     }
 }
 
+#[test]
+fn trait_alias_impl() {
+    lowering_error! {
+        program {
+            trait Foo { }
+            trait Alias = Foo;
+
+            impl Alias for i32 { }
+        }
+        error_msg {
+            "cannot implement trait alias `Alias`"
+        }
+    }
+
+    lowering_error! {
+        program {
+            trait Alias = ;
+
+            impl !Alias for i32 { }
+        }
+        error_msg {
+            "cannot implement trait alias `Alias`"
+        }
+    }
+}
+
 #[test]
 fn invalid_name() {
     lowering_error! {
@@ -220,6 +249,55 @@ fn atc_accounting() {
     });
 }
 
+#[test]
+fn incorrect_number_of_variances() {
+    lowering_error! {
+        program {
+            #[variance(Covariant)]
+            struct Foo<'a, T> { }
+        }
+        error_msg {
+            "`Foo` has 2 parameters, but 1 variances were declared"
+        }
+    };
+
+    lowering_error! {
+        program {
+            #[variance(Covariant, Invariant)]
+            fn foo<T>(arg: T);
+        }
+        error_msg {
+            "`foo` has 1 parameters, but 2 variances were declared"
+        }
+    };
+}
+
+#[test]
+fn variance_names_are_not_keywords() {
+    lowering_success! {
+        program {
+            struct Covariant { }
+            struct Contravariant { }
+            trait Invariant { }
+            trait Subtype { }
+            #[variance(Invariant, Covariant)]
+            struct Foo<'a, T> { }
+            impl Subtype for Covariant { }
+            impl<'a, T> Invariant for Foo<'a, T> where T: Subtype { }
+        }
+    }
+
+    lowering_error! {
+        program {
+            #[variance(Bivariant)]
+            struct Foo<T> { }
+        }
+        error_msg {
+            "parse error: expected `Invariant`, `Covariant` or `Contravariant`"
+        }
+    }
+}
+
 #[test]
 fn check_variable_kinds() {
     lowering_error! {
@@ -503,7 +581,7 @@ fn scalars() {
         }
 
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 }
@@ -527,7 +605,7 @@ fn raw_pointers() {
             struct *const i32 { }
         }
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 
@@ -537,7 +615,7 @@ fn raw_pointers() {
             impl Foo for *i32 { }
         }
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 }
@@ -561,7 +639,7 @@ fn refs() {
         }
 
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 }
@@ -587,7 +665,7 @@ fn slices() {
         }
 
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 }
@@ -635,7 +713,7 @@ fn arrays() {
         }
 
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 
@@ -659,7 +737,7 @@ fn arrays() {
         }
 
         error_msg {
-            "parse error: UnrecognizedToken"
+            "parse error: Unrecognized token"
         }
     }
 }
@@ -814,3 +892,548 @@ fn algebraic_data_types() {
         }
     }
 }
+
+#[test]
+fn modules() {
+    lowering_success! {
+        program {
+            mod a {
+                struct Foo { }
+                mod b {
+                    trait Bar { }
+                    impl Bar for super::Foo { }
+                }
+            }
+            use a::b;
+            struct Baz<T> where T: b::Bar { field: a::Foo }
+        }
+    }
+
+    lowering_error! {
+        program {
+            mod a {
+                struct Foo { }
+            }
+            trait Bar { }
+            impl Bar for Foo { }
+        }
+        error_msg {
+            "invalid parameter name `Foo`"
+        }
+    }
+
+    lowering_error! {
+        program {
+            mod a {
+                trait Bar { }
+            }
+            struct Foo { }
+            impl a::b::Bar for Foo { }
+        }
+        error_msg {
+            "invalid trait name `a::b::Bar`"
+        }
+    }
+}
+
+#[test]
+fn associated_fns() {
+    lowering_success! {
+        program {
+            struct Foo { }
+            struct Vec<T> { }
+            struct Box<T> { }
+            trait Iterator<'a> {
+                type Item;
+                fn next(&mut self) -> Self;
+                fn count(self) -> usize;
+                fn by_lifetime(&'a self);
+                fn by_box(self: Box<Self>);
+            }
+            impl<T> Vec<T> where T: Iterator<'static> {
+                fn new() -> Self;
+                fn get<U>(&self, index: U) -> T;
+                fn iter<'b>(&'b self) -> &'b [T];
+            }
+            impl Foo { }
+        }
+    }
+
+    lowering_error! {
+        program {
+            impl Foo {
+                fn new() -> Self;
+            }
+        }
+        error_msg {
+            "invalid parameter name `Foo`"
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Foo { }
+            impl Foo {
+                fn bar(&self);
+                fn bar(self);
+            }
+        }
+        error_msg {
+            "duplicate definitions of associated fn `bar`"
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Foo<T> { }
+            impl<T> Foo<T> {
+                fn bar<T>(&self);
+            }
+        }
+        error_msg {
+            "duplicate or shadowed parameters"
+        }
+    }
+}
+
+#[test]
+fn associated_consts() {
+    lowering_success! {
+        program {
+            struct Foo { }
+            struct Array<const N> { }
+            trait Len<T> {
+                const N: usize;
+            }
+            impl<T> Len<T> for Foo {
+                const N: usize = 3;
+            }
+            impl<const C> Len<Foo> for Array<C> {
+                const N: usize = C;
+            }
+            impl<T> Len<T> for [T; <Foo as Len<T>>::N] {
+                const N: usize = <Foo as Len<T>>::N;
+            }
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Foo { }
+            trait Len { }
+            impl Len for Foo {
+                const N: usize = 3;
+            }
+        }
+        error_msg {
+            "no associated const `N` defined in trait"
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Foo { }
+            trait Len {
+                const N: usize;
+            }
+            impl Len for [Foo; <Foo as Len>::M] { }
+        }
+        error_msg {
+            "no associated const `M` defined in trait"
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Foo { }
+            trait Len {
+                const N: usize;
+            }
+            impl Len for [Foo; <Foo as Len>::N<Foo>] { }
+        }
+        error_msg {
+            "associated const `N` cannot have parameters"
+        }
+    }
+}
+
+#[test]
+fn const_values() {
+    lowering_success! {
+        program {
+            struct Flag<const B: bool> { }
+            struct Letter<const C: char> { }
+            struct Offset<const O: i8> { }
+            struct Size<const N> { }
+            trait Trait { }
+            trait Negated { }
+            impl Trait for Flag<true> { }
+            impl Trait for Letter<'x'> { }
+            impl Trait for Offset<-128> { }
+            impl Trait for Size<{ 3 + 4 * 2 }> { }
+            impl<const N> Trait for [Size<N>; N - 1] { }
+            impl<const B: bool> Negated for Flag<{ !B }> { }
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Offset<const O: i8> { }
+            trait Trait { }
+            impl Trait for Offset<128> { }
+        }
+        error_msg {
+            "integer literal `128` is out of range for its type"
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Foo { }
+            trait Trait { }
+            impl Trait for [Foo; -1] { }
+        }
+        error_msg {
+            "integer literal `-1` is out of range for its type"
+        }
+    }
+
+    lowering_error! {
+        program {
+            struct Size<const N> { }
+            trait Trait { }
+            impl Trait for Size<340282366920938463463374607431768211456> { }
+        }
+        error_msg {
+            "parse error: integer literal is too large"
+        }
+    }
+}
+
+/// The source files of a program made of a single `test.chalk` file.
+fn test_files(program_text: &str) -> SourceFiles {
+    let mut files = SourceFiles::new();
+    files.add("test.chalk", program_text);
+    files
+}
+
+#[test]
+fn error_spans() {
+    let program_text = "struct Foo { }\nimpl Bar for Foo { }\n";
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(error.kind(), ChalkErrorKind::Lowering);
+    assert_eq!(
+        error.render(&files),
+        "\
+error: invalid trait name `Bar`
+ --> test.chalk:2:6
+  |
+2 | impl Bar for Foo { }
+  |      ^^^
+"
+    );
+
+    let program_text =
+        "trait Foo { }\nstruct Bar { }\nimpl Foo for Bar { }\nimpl Foo for Bar { }\n";
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(error.kind(), ChalkErrorKind::Coherence);
+    assert_eq!(
+        error.render(&files),
+        "\
+error: overlapping impls of trait `Foo`
+ --> test.chalk:4:1
+  |
+4 | impl Foo for Bar { }
+  | ^^^^^^^^^^^^^^^^
+ ::: test.chalk:3:1
+  |
+3 | impl Foo for Bar { }
+  | ---------------- first impl here
+"
+    );
+
+    let program_text = "trait Foo<T> { }\nimpl<T> Foo<u32> for T { }\nimpl<U> Foo<U> for i32 { }\n";
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(error.kind(), ChalkErrorKind::Coherence);
+    assert_eq!(
+        error.render(&files),
+        "\
+error: overlapping impls of trait `Foo`
+ --> test.chalk:3:1
+  |
+3 | impl<U> Foo<U> for i32 { }
+  | ^^^^^^^^^^^^^^^^^^^^^^
+ ::: test.chalk:2:1
+  |
+2 | impl<T> Foo<u32> for T { }
+  | ---------------------- first impl here
+  = note: both impls apply with the substitution `[?0 := i32, ?1 := u32]`
+"
+    );
+
+    let program_text = "\
+trait Foo where Self: Bar { }
+trait Bar { }
+struct Baz { }
+impl Foo for Baz { }
+";
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(error.kind(), ChalkErrorKind::WellFormedness);
+    assert_eq!(
+        error.render(&files),
+        "\
+error: trait impl for `Foo` does not meet well-formedness requirements
+ --> test.chalk:4:1
+  |
+4 | impl Foo for Baz { }
+  | ^^^^^^^^^^^^^^^^
+ ::: test.chalk:1:7
+  |
+1 | trait Foo where Self: Bar { }
+  |       --- trait defined here
+"
+    );
+}
+
+#[test]
+fn template_error_spans() {
+    // The expanded items point back to the template, and the arguments to
+    // the argument list.
+    let program_text = "\
+trait Foo { }
+for T in [u8, Bar] {
+    impl Foo for T { }
+}
+";
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(
+        error.render(&files),
+        "\
+error: invalid parameter name `Bar`
+ --> test.chalk:2:15
+  |
+2 | for T in [u8, Bar] {
+  |               ^^^
+"
+    );
+
+    let program_text = "\
+for T in [u8, u16] {
+    impl Foo for T { }
+}
+";
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(
+        error.render(&files),
+        "\
+error: invalid trait name `Foo`
+ --> test.chalk:2:10
+  |
+2 |     impl Foo for T { }
+  |          ^^^
+"
+    );
+}
+
+#[test]
+fn syntax_error_recovery() {
+    let program_text = "\
+struct Foo { x: }
+trait Bar { fn a(; fn b(&self); }
+struct Baz { }
+impl Bar for Baz { type X = ; }
+impl Qux for Baz { }
+struct Unterminated {
+";
+    // The items are skipped up to the next `;` or `}`, or, within traits
+    // and impls, up to the next `;`.
+    let (program, errors) = chalk_parse::parse_file_recovering(FileId(0), program_text);
+    let names: Vec<_> = program
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::AdtDefn(defn) => defn.name.to_string(),
+            Item::TraitDefn(defn) => format!("{} {:?}", defn.name, defn.assoc_fn_defns.len()),
+            Item::Impl(defn) => defn.trait_ref.trait_name.to_string(),
+            item => panic!("unexpected item {:?}", item),
+        })
+        .collect();
+    assert_eq!(names, vec!["Bar 1", "Baz", "Bar", "Qux"]);
+    let positions: Vec<_> = errors
+        .iter()
+        .map(|error| {
+            let span = error.span.unwrap();
+            &program_text[span.lo..]
+        })
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            "}\ntrait Bar { fn a(; fn b(&self); }\nstruct Baz { }\nimpl Bar for Baz { type X = ; }\nimpl Qux for Baz { }\nstruct Unterminated {\n",
+            "; fn b(&self); }\nstruct Baz { }\nimpl Bar for Baz { type X = ; }\nimpl Qux for Baz { }\nstruct Unterminated {\n",
+            "; }\nimpl Qux for Baz { }\nstruct Unterminated {\n",
+            "\n",
+        ]
+    );
+    assert!(errors[3]
+        .message
+        .starts_with("parse error: Unrecognized EOF"));
+
+    // The recovered items are lowered, and the errors found while lowering
+    // them are reported after the syntax errors.
+    let files = test_files(program_text);
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    let kinds: Vec<_> = error.errors().map(|error| error.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ChalkErrorKind::Parse,
+            ChalkErrorKind::Parse,
+            ChalkErrorKind::Parse,
+            ChalkErrorKind::Parse,
+            ChalkErrorKind::Lowering,
+        ]
+    );
+    assert_eq!(
+        error.errors().last().unwrap().render(&files),
+        "\
+error: invalid trait name `Qux`
+ --> test.chalk:5:6
+  |
+5 | impl Qux for Baz { }
+  |      ^^^
+"
+    );
+    assert!(error
+        .render(&files)
+        .starts_with("error: parse error: Unrecognized token"));
+
+    // The lexer cannot recover from an unexpected character, but the items
+    // before it are still returned.
+    let program_text = "\
+struct Foo { }
+trait Bar { }
+struct Baz { x: $ }
+struct Qux { }
+";
+    let (program, errors) = chalk_parse::parse_file_recovering(FileId(0), program_text);
+    assert_eq!(program.items.len(), 2);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "parse error: Invalid token at 45");
+    assert_eq!(
+        &program_text[errors[0].span.unwrap().lo..],
+        "$ }\nstruct Qux { }\n"
+    );
+}
+
+#[test]
+fn includes() {
+    let mut files = SourceFiles::new();
+    files.add(
+        "main.chalk",
+        "include \"lib/core.chalk\";\nstruct Foo { }\nimpl Clone for Foo { }\n",
+    );
+    files.add(
+        "lib/core.chalk",
+        "include \"../main.chalk\";\ntrait Clone { }\n",
+    );
+    let db = ChalkDatabase::with_files(files, SolverChoice::default());
+    assert!(db.checked_program().is_ok());
+
+    let mut files = SourceFiles::new();
+    files.add("main.chalk", "include \"core.chalk\";\nstruct Foo { }\n");
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(
+        error.render(&files),
+        "\
+error: cannot find included file `core.chalk`
+ --> main.chalk:1:1
+  |
+1 | include \"core.chalk\";
+  | ^^^^^^^^^^^^^^^^^^^^^
+"
+    );
+
+    let mut files = SourceFiles::new();
+    files.add("main.chalk", "include \"core.chalk\";\nstruct Foo { }\n");
+    files.add("core.chalk", "trait Clone { }\nimpl Copy for u32 { }\n");
+    let db = ChalkDatabase::with_files(files.clone(), SolverChoice::default());
+    let error = db.checked_program().unwrap_err();
+    assert_eq!(
+        error.render(&files),
+        "\
+error: invalid trait name `Copy`
+ --> core.chalk:2:6
+  |
+2 | impl Copy for u32 { }
+  |      ^^^^
+"
+    );
+}
+
+/// A temporary directory, removed when dropped so that a failing test does
+/// not leave it behind.
+struct TempDir(std::path::PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = std::path::Path;
+
+    fn deref(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn load_included_files() {
+    let dir = TempDir::new("chalk-includes");
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(
+        dir.join("main.chalk"),
+        "include \"lib/core.chalk\";\nstruct Foo { }\nimpl Clone for Foo { }\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("lib/core.chalk"), "trait Clone { }\n").unwrap();
+
+    let files = SourceFiles::load(&[dir.join("main.chalk")]).unwrap();
+    let names: Vec<_> = files.iter().map(|file| file.name.clone()).collect();
+    assert_eq!(
+        names,
+        vec![
+            dir.join("main.chalk").to_string_lossy().into_owned(),
+            dir.join("lib/core.chalk").to_string_lossy().into_owned(),
+        ]
+    );
+    let db = ChalkDatabase::with_files(files, SolverChoice::default());
+    assert!(db.checked_program().is_ok());
+
+    let error = SourceFiles::load(&[dir.join("missing.chalk")]).unwrap_err();
+    assert!(error.to_string().starts_with("cannot read `"));
+}
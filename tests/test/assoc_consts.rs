@@ -0,0 +1,222 @@
+//! Tests related to associated consts and const projections.
+
+use super::*;
+
+#[test]
+fn normalize_assoc_const() {
+    test! {
+        program {
+            trait Len {
+                const N: usize;
+            }
+
+            struct Foo {}
+            struct Bar {}
+
+            impl Len for Foo {
+                const N: usize = 3;
+            }
+        }
+
+        goal {
+            Normalize(<Foo as Len>::N -> 3)
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Normalize(<Foo as Len>::N -> 4)
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<const C> {
+                Normalize(<Foo as Len>::N -> C)
+            }
+        } yields {
+            "Unique; substitution [?0 := 3], lifetime constraints []"
+        }
+
+        goal {
+            exists<const C> {
+                Normalize(<Bar as Len>::N -> C)
+            }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn generic_assoc_const() {
+    test! {
+        program {
+            trait Len {
+                const N: usize;
+            }
+
+            struct Wrapper<const C> {}
+            struct Foo {}
+
+            impl<const C> Len for Wrapper<C> {
+                const N: usize = C;
+            }
+        }
+
+        goal {
+            Normalize(<Wrapper<5> as Len>::N -> 5)
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<const C> {
+                Normalize(<Wrapper<C> as Len>::N -> C)
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            exists<const C> {
+                Normalize(<Wrapper<C> as Len>::N -> 2)
+            }
+        } yields {
+            "Unique; substitution [?0 := 2], lifetime constraints []"
+        }
+    }
+}
+
+#[test]
+fn const_projection_in_array_length() {
+    test! {
+        program {
+            trait Len {
+                const N: usize;
+            }
+            trait Foo {}
+
+            struct A {}
+            struct B {}
+
+            impl Len for A {
+                const N: usize = 3;
+            }
+            impl Len for B {
+                const N: usize = 4;
+            }
+
+            impl Foo for [u8; 3] {}
+        }
+
+        goal {
+            [u8; <A as Len>::N]: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            [u8; <B as Len>::N]: Foo
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<T> {
+                [u8; <T as Len>::N]: Foo
+            }
+        } yields {
+            "Unique; substitution [?0 := A], lifetime constraints []"
+        }
+
+        goal {
+            [u8; <A as Len>::N] = [u8; <A as Len>::N]
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn const_projection_in_generic_args() {
+    test! {
+        program {
+            trait Len {
+                const N: usize;
+            }
+            trait Trait {}
+
+            struct S<const C> {}
+            struct A {}
+
+            impl Len for A {
+                const N: usize = 3;
+            }
+
+            impl Trait for S<3> {}
+        }
+
+        goal {
+            S<<A as Len>::N>: Trait
+        } yields {
+            "Unique"
+        }
+
+        // As with associated types, unifying a variable with a projection
+        // does not normalize it.
+        goal {
+            exists<const C> {
+                S<C> = S<<A as Len>::N>
+            }
+        } yields {
+            "Unique; substitution [?0 := <A as Len>::N], lifetime constraints []"
+        }
+
+        goal {
+            exists<const C> {
+                S<C> = S<<A as Len>::N>, S<C>: Trait
+            }
+        } yields {
+            "Unique; substitution [?0 := <A as Len>::N], lifetime constraints []"
+        }
+    }
+}
+
+#[test]
+fn const_projection_in_impl_where_clauses() {
+    test! {
+        program {
+            trait Len {
+                const N: usize;
+            }
+            trait Small {}
+
+            struct A {}
+            struct B {}
+            struct S<const C> {}
+
+            impl Len for A {
+                const N: usize = 1;
+            }
+            impl Len for B {
+                const N: usize = 8;
+            }
+
+            impl Small for S<1> {}
+            impl<T> Small for [T; 0] where T: Len, S<<T as Len>::N>: Small {}
+        }
+
+        goal {
+            [A; 0]: Small
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            [B; 0]: Small
+        } yields {
+            "No possible solution"
+        }
+    }
+}
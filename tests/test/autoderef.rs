@@ -0,0 +1,281 @@
+//! Tests for the built-in `Deref` and `DerefMut` impls, and for autoderef
+//! chains computed with `autoderef`.
+
+use super::*;
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::interner::ChalkIr;
+use chalk_integration::lowering::{lower_goal, lower_ty};
+use chalk_integration::SolverChoice;
+use chalk_ir::*;
+use chalk_solve::ext::GoalExt;
+use chalk_solve::solve::autoderef::MAX_AUTODEREF_STEPS;
+use chalk_solve::RustIrDatabase;
+
+const PROGRAM: &str = "
+    #[lang(deref)]
+    trait Deref {
+        type Target;
+    }
+
+    #[lang(deref_mut)]
+    trait DerefMut where Self: Deref { }
+
+    struct Foo { }
+    struct Box<T> { }
+    struct Cycle { }
+    struct Ambiguous { }
+
+    impl<T> Deref for Box<T> {
+        type Target = T;
+    }
+
+    impl Deref for Cycle {
+        type Target = Cycle;
+    }
+
+    trait Target { }
+    impl Target for Foo { }
+    impl Target for Cycle { }
+
+    impl<T> Deref for Ambiguous where T: Target {
+        type Target = T;
+    }
+";
+
+/// Computes the autoderef chain of `ty`, and describes each step as
+/// `kind ty`, followed by the reason the chain ends.
+fn autoderef(program: &str, ty: &str) -> Vec<String> {
+    let db = ChalkDatabase::with(program, SolverChoice::default());
+    db.with_program(|program| {
+        let interner = db.interner();
+        let ty = lower_ty(&chalk_parse::parse_ty(ty).unwrap(), program).unwrap();
+        let ty = UCanonical {
+            canonical: Canonical {
+                value: InEnvironment::new(&Environment::new(interner), ty),
+                binders: CanonicalVarKinds::empty(interner),
+            },
+            universes: 1,
+        };
+        describe_chain(&db, &ty)
+    })
+}
+
+/// Like `autoderef`, but for the self type of the trait goal that `goal`
+/// peels down to, in the environment of that goal. This is how types with
+/// `Deref` bounds in the environment are written, e.g.
+/// `forall<T> { if (T: Deref) { T: Target } }`.
+fn autoderef_in_env(program: &str, solver_choice: SolverChoice, goal: &str) -> Vec<String> {
+    let db = ChalkDatabase::with(program, solver_choice);
+    db.with_program(|program| {
+        let interner = db.interner();
+        let goal = lower_goal(&chalk_parse::parse_goal(goal).unwrap(), program).unwrap();
+        let UCanonical {
+            canonical: Canonical { binders, value },
+            universes,
+        } = goal.into_peeled_goal(interner);
+        let ty = match value.goal.data(interner) {
+            GoalData::DomainGoal(DomainGoal::Holds(WhereClause::Implemented(trait_ref))) => {
+                trait_ref.self_type_parameter(interner)
+            }
+            goal => panic!("expected a trait goal, found {:?}", goal),
+        };
+        let ty = UCanonical {
+            canonical: Canonical {
+                value: InEnvironment::new(&value.environment, ty),
+                binders,
+            },
+            universes,
+        };
+        describe_chain(&db, &ty)
+    })
+}
+
+fn describe_chain(db: &ChalkDatabase, ty: &UCanonical<InEnvironment<Ty<ChalkIr>>>) -> Vec<String> {
+    let chain = db.autoderef(ty).value;
+    chain
+        .steps
+        .iter()
+        .map(|step| format!("{:?} {:?}", step.kind, step.ty))
+        .chain(Some(format!("{:?}", chain.end)))
+        .collect()
+}
+
+#[test]
+fn builtin_deref_impls() {
+    test! {
+        program {
+            #[lang(deref)]
+            trait Deref {
+                type Target;
+            }
+
+            #[lang(deref_mut)]
+            trait DerefMut where Self: Deref { }
+
+            struct Foo { }
+        }
+
+        goal {
+            forall<'a> {
+                &'a Foo: Deref
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                exists<T> {
+                    Normalize(<&'a mut Foo as Deref>::Target -> T)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Foo]"
+        }
+
+        goal {
+            forall<'a> {
+                &'a mut Foo: DerefMut
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                &'a Foo: DerefMut
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            Foo: Deref
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            *const Foo: Deref
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn autoderef_references_and_impls() {
+    assert_eq!(autoderef(PROGRAM, "Foo"), vec!["NoDeref"]);
+    assert_eq!(
+        autoderef(PROGRAM, "&'static (&'static mut Foo)"),
+        vec!["Builtin (&'static mut Foo)", "Builtin Foo", "NoDeref"]
+    );
+    assert_eq!(
+        autoderef(PROGRAM, "Box<&'static Box<Foo>>"),
+        vec![
+            "Overloaded (&'static Box<Foo>)",
+            "Builtin Box<Foo>",
+            "Overloaded Foo",
+            "NoDeref"
+        ]
+    );
+}
+
+#[test]
+fn autoderef_raw_pointers() {
+    assert_eq!(
+        autoderef(PROGRAM, "Box<*const Box<Foo>>"),
+        vec![
+            "Overloaded (*const Box<Foo>)",
+            "Builtin Box<Foo>",
+            "Overloaded Foo",
+            "NoDeref"
+        ]
+    );
+}
+
+#[test]
+fn autoderef_cycle() {
+    assert_eq!(autoderef(PROGRAM, "Cycle"), vec!["Cycle"]);
+    assert_eq!(
+        autoderef(PROGRAM, "Box<Cycle>"),
+        vec!["Overloaded Cycle", "Cycle"]
+    );
+}
+
+#[test]
+fn autoderef_ambiguous() {
+    // `Ambiguous` could dereference to either `Foo` or `Cycle`.
+    assert_eq!(autoderef(PROGRAM, "Ambiguous"), vec!["Ambiguous"]);
+    assert_eq!(
+        autoderef(PROGRAM, "&'static Ambiguous"),
+        vec!["Builtin Ambiguous", "Ambiguous"]
+    );
+}
+
+#[test]
+fn autoderef_overflow() {
+    // `S0` dereferences to `S1`, which dereferences to `S2`, and so on.
+    let mut program = PROGRAM.to_string();
+    for i in 0..=MAX_AUTODEREF_STEPS {
+        program.push_str(&format!(
+            "struct S{0} {{ }} impl Deref for S{0} {{ type Target = S{1}; }}",
+            i,
+            i + 1
+        ));
+    }
+    program.push_str(&format!("struct S{} {{ }}", MAX_AUTODEREF_STEPS + 1));
+
+    let chain = autoderef(&program, "S0");
+    assert_eq!(chain.len(), MAX_AUTODEREF_STEPS + 1);
+    assert_eq!(chain[0], "Overloaded S1");
+    assert_eq!(
+        chain[MAX_AUTODEREF_STEPS - 1],
+        format!("Overloaded S{}", MAX_AUTODEREF_STEPS)
+    );
+    assert_eq!(chain[MAX_AUTODEREF_STEPS], "Overflow");
+
+    let chain = autoderef(&program, "S1");
+    assert_eq!(chain.len(), MAX_AUTODEREF_STEPS + 1);
+    assert_eq!(chain[MAX_AUTODEREF_STEPS], "NoDeref");
+}
+
+#[test]
+fn autoderef_deref_bounds() {
+    const PROGRAM: &str = "
+        #[lang(deref)]
+        trait Deref {
+            type Target;
+        }
+
+        struct Foo { }
+        trait Target { }
+    ";
+
+    for solver_choice in [SolverChoice::slg_default(), SolverChoice::recursive()] {
+        assert_eq!(
+            autoderef_in_env(
+                PROGRAM,
+                solver_choice,
+                "forall<T> { if (T: Deref) { T: Target } }"
+            ),
+            vec!["Overloaded (Deref::Target)<!1_0>", "NoDeref"]
+        );
+        assert_eq!(
+            autoderef_in_env(PROGRAM, solver_choice, "forall<T> { T: Target }"),
+            vec!["NoDeref"]
+        );
+    }
+
+    // `<T as Deref>::Target` is both `Foo` and the placeholder
+    // `(Deref::Target)<T>`. Only the recursive solver prefers the bound.
+    let goal = "forall<T> { if (T: Deref<Target = Foo>) { T: Target } }";
+    assert_eq!(
+        autoderef_in_env(PROGRAM, SolverChoice::slg_default(), goal),
+        vec!["Ambiguous"]
+    );
+    assert_eq!(
+        autoderef_in_env(PROGRAM, SolverChoice::recursive(), goal),
+        vec!["Overloaded Foo", "NoDeref"]
+    );
+}
@@ -280,3 +280,103 @@ fn closure_implements_fn_traits() {
         }
     }
 }
+
+#[test]
+fn closure_with_deferred_kind() {
+    test! {
+        program {
+            #[lang(fn_once)]
+            trait FnOnce<Args> {
+                type Output;
+            }
+
+            #[lang(fn_mut)]
+            trait FnMut<Args> where Self: FnOnce<Args> { }
+
+            #[lang(fn)]
+            trait Fn<Args> where Self: FnMut<Args> { }
+
+            closure foo(_, a: u8) -> u32 {}
+        }
+
+        // Every closure implements `FnOnce`, whatever its kind
+        goal {
+            foo: FnOnce<(u8,)>
+        } yields {
+            "Unique"
+        }
+        goal {
+            Normalize(<foo as FnOnce<(u8,)>>::Output -> u32)
+        } yields {
+            "Unique"
+        }
+
+        // The other `Fn` traits depend on the kind, which is not known yet
+        goal {
+            foo: Fn<(u8,)>
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+        goal {
+            exists<A> {
+                foo: FnMut<A>
+            }
+        } yields {
+            "Ambiguous; definite substitution [?0 := 1<Uint(U8)>]"
+        }
+        goal {
+            foo: FnMut<(u16,)>
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn closure_kind_obligations() {
+    let program_text = "
+        #[lang(fn_once)]
+        trait FnOnce<Args> {
+            type Output;
+        }
+
+        #[lang(fn_mut)]
+        trait FnMut<Args> where Self: FnOnce<Args> { }
+
+        #[lang(fn)]
+        trait Fn<Args> where Self: FnMut<Args> { }
+
+        closure foo(_, a: u8) -> u32 {}
+        closure bar(&self, a: u8) -> u32 {}
+    ";
+    for solver_choice in [SolverChoice::slg_default(), SolverChoice::recursive()] {
+        let db = ChalkDatabase::with(program_text, solver_choice);
+        let obligations = |goal_text: &str| -> Vec<String> {
+            db.with_program(|program| {
+                let goal =
+                    lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), program).unwrap();
+                let peeled_goal = goal.into_peeled_goal(db.interner());
+                db.closure_kind_obligations(&peeled_goal)
+                    .into_iter()
+                    .map(|obligation| format!("{:?}", obligation.canonical.value.goal))
+                    .collect()
+            })
+        };
+
+        // The ambiguous `Fn` goals are waiting on the kind of the closure
+        assert_eq!(
+            obligations("foo: Fn<(u8,)>"),
+            ["ClosureKind({closure:ClosureId(#3)}, Fn)"]
+        );
+        assert_eq!(
+            obligations("exists<A> { foo: FnMut<A> }"),
+            ["ClosureKind({closure:ClosureId(#3)}, FnMut)"]
+        );
+
+        // `FnOnce` does not depend on the kind, and the kinds that are known
+        // leave no obligations
+        assert!(obligations("foo: FnOnce<(u8,)>").is_empty());
+        assert!(obligations("bar: Fn<(u8,)>").is_empty());
+        assert!(obligations("foo: FnMut<(u16,)>").is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::test::*;
+
+#[test]
+fn coerce_unsized_references() {
+    test! {
+        program {
+            #[lang(unsize)]
+            trait Unsize<T> {}
+
+            #[lang(coerce_unsized)]
+            trait CoerceUnsized<T> {}
+        }
+
+        goal {
+            forall<'a> {
+                &'a [u8; 3]: CoerceUnsized<&'a [u8]>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a, 'b> {
+                &'a mut [u8; 3]: CoerceUnsized<&'b [u8]>
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        goal {
+            forall<'a, 'b> {
+                &'a mut [u8; 3]: CoerceUnsized<&'b mut [u8]>
+            }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        // Mutability can't be gained.
+        goal {
+            forall<'a> {
+                &'a [u8; 3]: CoerceUnsized<&'a mut [u8]>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        // The pointee must be unsizable.
+        goal {
+            forall<'a> {
+                &'a [u8; 3]: CoerceUnsized<&'a [u16]>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<'a> {
+                exists<T> {
+                    &'a [T; 3]: CoerceUnsized<&'a [u8]>
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := Uint(U8)]"
+        }
+
+        goal {
+            forall<'a> {
+                exists<T> {
+                    &'a [u8; 3]: CoerceUnsized<T>
+                }
+            }
+        } yields_first[SolverChoice::slg(3, None)] {
+            "Floundered"
+        }
+    }
+}
+
+#[test]
+fn coerce_unsized_raw_pointers() {
+    test! {
+        program {
+            #[lang(unsize)]
+            trait Unsize<T> {}
+
+            #[lang(coerce_unsized)]
+            trait CoerceUnsized<T> {}
+        }
+
+        goal {
+            *mut [u8; 3]: CoerceUnsized<*mut [u8]>
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            *mut [u8; 3]: CoerceUnsized<*const [u8]>
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            *const [u8; 3]: CoerceUnsized<*mut [u8]>
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<'a> {
+                &'a mut [u8; 3]: CoerceUnsized<*mut [u8]>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                &'a [u8; 3]: CoerceUnsized<*const [u8]>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                &'a [u8; 3]: CoerceUnsized<*mut [u8]>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        // Raw pointers can't be coerced into references.
+        goal {
+            forall<'a> {
+                *const [u8; 3]: CoerceUnsized<&'a [u8]>
+            }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn coerce_unsized_smart_pointers() {
+    test! {
+        program {
+            #[lang(unsize)]
+            trait Unsize<T> {}
+
+            #[lang(coerce_unsized)]
+            trait CoerceUnsized<T> {}
+
+            #[lang(sized)]
+            trait Sized {}
+
+            #[object_safe]
+            trait Foo {}
+
+            struct Rc<T> {
+                ptr: *mut T,
+            }
+
+            struct Wrapper<T> {
+                extra: u8,
+                rc: Rc<T>,
+            }
+
+            impl<T, U> CoerceUnsized<Rc<U>> for Rc<T> where T: Unsize<U> {}
+            impl<T, U> CoerceUnsized<Wrapper<U>> for Wrapper<T> where T: Unsize<U> {}
+
+            impl Foo for u8 {}
+        }
+
+        goal {
+            forall<T> {
+                Rc<[T; 3]>: CoerceUnsized<Rc<[T]>>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Wrapper<[u8; 3]>: CoerceUnsized<Wrapper<[u8]>>
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                Rc<u8>: CoerceUnsized<Rc<dyn Foo + 'a>>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Rc<[u8; 3]>: CoerceUnsized<Rc<[u16]>>
+        } yields {
+            "No possible solution"
+        }
+    }
+}
@@ -587,3 +587,160 @@ fn fundamental_type_multiple_parameters() {
         }
     }
 }
+
+#[test]
+fn multiple_crates() {
+    // Sibling crates can implement an upstream trait for their own types.
+    lowering_success! {
+        program {
+            crate core {
+                trait Clone { }
+            }
+            crate a depends core {
+                struct A { }
+                impl core::Clone for A { }
+            }
+            crate b depends core {
+                struct B { }
+                impl core::Clone for B { }
+            }
+        }
+    }
+
+    // The types of a sibling crate are not local.
+    lowering_error! {
+        program {
+            crate core {
+                trait Clone { }
+            }
+            crate a depends core {
+                struct A { }
+            }
+            crate b depends core, a {
+                impl core::Clone for a::A { }
+            }
+        } error_msg {
+            "impl for trait `core::Clone` violates the orphan rules"
+        }
+    }
+
+    // Nor are they local to the root crate.
+    lowering_error! {
+        program {
+            crate core {
+                trait Clone { }
+                struct Vec<T> { }
+            }
+            impl core::Clone for core::Vec<u32> { }
+        } error_msg {
+            "impl for trait `core::Clone` violates the orphan rules"
+        }
+    }
+
+    // A crate sees the impls of the crates it depends on.
+    lowering_error! {
+        program {
+            crate a {
+                trait Foo<U> { }
+                impl<T> Foo<u32> for T { }
+            }
+            crate b depends a {
+                struct B { }
+                impl<U> a::Foo<U> for B { }
+            }
+        } error_msg {
+            "overlapping impls of trait `a::Foo`"
+        }
+    }
+
+    // The root crate sees the impls of all the crates.
+    lowering_error! {
+        program {
+            crate a {
+                trait Foo<U> { }
+                impl<T> Foo<u32> for T { }
+            }
+            struct B { }
+            impl<U> a::Foo<U> for B { }
+        } error_msg {
+            "overlapping impls of trait `a::Foo`"
+        }
+    }
+
+    // `depends` is not a keyword.
+    lowering_success! {
+        program {
+            crate depends {
+                trait depends { }
+            }
+            crate a depends depends {
+                struct A { }
+                impl depends::depends for A { }
+            }
+        }
+    }
+
+    // Crates can only depend on the crates declared before them.
+    lowering_error! {
+        program {
+            crate a depends b { }
+            crate b { }
+        } error_msg {
+            "invalid crate name `b`"
+        }
+    }
+}
+
+#[test]
+fn fundamental_types_across_crates() {
+    lowering_success! {
+        program {
+            crate core {
+                #[fundamental]
+                struct Box<T> { }
+                trait Clone { }
+            }
+            crate a depends core {
+                struct A { }
+                impl core::Clone for core::Box<A> { }
+            }
+        }
+    }
+
+    lowering_error! {
+        program {
+            crate core {
+                struct Vec<T> { }
+                trait Clone { }
+            }
+            crate a depends core {
+                struct A { }
+                impl core::Clone for core::Vec<A> { }
+            }
+        } error_msg {
+            "impl for trait `core::Clone` violates the orphan rules"
+        }
+    }
+}
+
+#[test]
+fn reexports_across_crates() {
+    lowering_success! {
+        program {
+            crate core {
+                trait Clone { }
+            }
+            crate alloc depends core {
+                use core::Clone;
+                struct Vec<T> { }
+                impl<T> Clone for Vec<T> where T: Clone { }
+            }
+            crate std depends core, alloc {
+                use alloc::Clone;
+                use alloc::Vec;
+                struct String { }
+                impl Clone for String { }
+            }
+        }
+    }
+}
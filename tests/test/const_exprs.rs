@@ -0,0 +1,222 @@
+//! Tests related to typed const values and const expressions.
+
+use super::*;
+
+#[test]
+fn typed_const_values() {
+    test! {
+        program {
+            struct Flag<const B: bool> {}
+            struct Letter<const C: char> {}
+            struct Offset<const O: i8> {}
+
+            trait Trait {}
+
+            impl Trait for Flag<true> {}
+            impl Trait for Letter<'a'> {}
+            impl Trait for Offset<-3> {}
+        }
+
+        goal {
+            exists<const B: bool> {
+                Flag<B>: Trait
+            }
+        } yields {
+            "Unique; substitution [?0 := true], lifetime constraints []"
+        }
+
+        goal {
+            Flag<false>: Trait
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<const C: char> {
+                Letter<C>: Trait
+            }
+        } yields {
+            "Unique; substitution [?0 := 'a'], lifetime constraints []"
+        }
+
+        goal {
+            Offset<-3>: Trait
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Offset<3>: Trait
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn const_types_must_match() {
+    test! {
+        program {
+            struct S<const N> {}
+            struct T<const N: u8> {}
+        }
+
+        goal {
+            exists<const N> {
+                S<N> = S<3>
+            }
+        } yields {
+            "Unique; substitution [?0 := 3], lifetime constraints []"
+        }
+
+        goal {
+            exists<const N: u8> {
+                S<N> = S<3>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            [u8; 3u8] = [u8; 3]
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            [u8; 3usize] = [u8; 3]
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn evaluate_const_exprs() {
+    test! {
+        program {
+            struct S<const N: u8> {}
+
+            trait Foo {}
+
+            impl Foo for [u8; 4] {}
+            impl Foo for S<255> {}
+        }
+
+        goal {
+            [u8; 3 + 1]: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            [u8; 2 * (5 - 3)]: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            [u8; 9 / 2 % 3]: Foo
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            [u8; 14 / 2 % 5 + 2]: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            S<{ 254 + 1 }>: Foo
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            S<{ !0 }>: Foo
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn unevaluated_const_exprs() {
+    test! {
+        program {
+            struct S<const N> {}
+
+            trait Foo {}
+
+            impl<const N> Foo for S<{ N + 1 }> {}
+        }
+
+        goal {
+            forall<const N> {
+                S<{ N + 1 }>: Foo
+            }
+        } yields {
+            "Unique"
+        }
+
+        // Expressions are only compared structurally, so this cannot tell
+        // that the impl applies with `?0 := N + 1`.
+        goal {
+            forall<const N> {
+                S<{ N + 2 }>: Foo
+            }
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+
+        // The impl applies if `?0 + 1` evaluates to `3`, which cannot be
+        // inverted.
+        goal {
+            S<3>: Foo
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+
+        goal {
+            exists<const N> {
+                [u8; N + 1] = [u8; 4]
+            }
+        } yields {
+            "Ambiguous; no inference guidance"
+        }
+    }
+}
+
+#[test]
+fn overflowing_const_exprs() {
+    test! {
+        program {
+            struct S<const N: u8> {}
+
+            trait Foo {}
+
+            impl Foo for S<0> {}
+        }
+
+        // `255 + 1` overflows, so it is left unevaluated, and is not
+        // equal to any value.
+        goal {
+            S<{ 255 + 1 }>: Foo
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            S<{ 1 / 0 }>: Foo
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            S<{ 1 - 1 }>: Foo
+        } yields {
+            "Unique"
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+
+#[test]
+fn discriminant_kind() {
+    test! {
+        program {
+            #[lang(discriminant_kind)]
+            trait DiscriminantKind {
+                type Discriminant;
+            }
+
+            enum Default { A, B }
+            #[repr(u8)]
+            enum Small { A, B }
+            #[repr(C)]
+            #[repr(i32)]
+            enum Large { A, B }
+            struct Foo { }
+
+            generator gen<>[resume = (), yield = ()] {
+                upvars []
+                witnesses []
+            }
+        }
+
+        goal {
+            exists<T> {
+                Normalize(<Default as DiscriminantKind>::Discriminant -> T)
+            }
+        } yields {
+            "Unique; substitution [?0 := Int(Isize)]"
+        }
+
+        goal {
+            <Small as DiscriminantKind>::Discriminant = u8
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <Large as DiscriminantKind>::Discriminant = i32
+        } yields {
+            "Unique"
+        }
+
+        // Types without a discriminant
+        goal {
+            <Foo as DiscriminantKind>::Discriminant = u8
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                <&'a Default as DiscriminantKind>::Discriminant = u8
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <gen as DiscriminantKind>::Discriminant = u8
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T> {
+                T: DiscriminantKind
+            }
+        } yields {
+            "Unique"
+        }
+
+        // The discriminant of a type parameter is not known
+        goal {
+            forall<T> {
+                <T as DiscriminantKind>::Discriminant = u8
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<T> {
+                T: DiscriminantKind
+            }
+        } yields_first[SolverChoice::slg(3, None)] {
+            "Floundered"
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Tests for the proof trees recorded by `Solver::explain`.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::interner::ChalkIr;
+use chalk_integration::SolverChoice;
+use chalk_solve::ext::*;
+use chalk_solve::solve::proof_tree::{Derivation, ProofTree};
+use chalk_solve::RustIrDatabase;
+
+const PROGRAM: &str = "
+    struct Foo { }
+    struct Bar { }
+    struct Vec<T> { }
+    trait Clone { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+";
+
+fn explain(solver_choice: SolverChoice, goal_text: &str) -> (ProofTree<ChalkIr>, String) {
+    let db = ChalkDatabase::with(PROGRAM, solver_choice);
+    db.with_program(|program| {
+        let goal = chalk_integration::lowering::lower_goal(
+            &chalk_parse::parse_goal(goal_text).unwrap(),
+            program,
+        )
+        .unwrap();
+        let peeled_goal = goal.into_peeled_goal(db.interner());
+        let proof_tree = db.explain(&peeled_goal);
+        let display = proof_tree.display(db.interner()).to_string();
+        (proof_tree, display)
+    })
+}
+
+#[test]
+fn explain_success() {
+    let (proof_tree, display) = explain(SolverChoice::recursive(), "Vec<Foo>: Clone");
+    assert!(proof_tree.solution.unwrap().is_unique());
+    assert_eq!(
+        display,
+        "\
+Implemented(Vec<Foo>: Clone): Unique; substitution [], lifetime constraints []
+  using program clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone) (failed)
+    FromEnv(Vec<Foo>: Clone): No possible solution
+  using program clause for<type> Implemented(Vec<^0.0>: Clone) :- ForAll<> { Implemented(^1.0: Clone) }
+    Implemented(Foo: Clone): Unique; substitution [], lifetime constraints []
+      using program clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone) (failed)
+        FromEnv(Foo: Clone): No possible solution
+      using program clause for<> Implemented(Foo: Clone)
+"
+    );
+}
+
+#[test]
+fn explain_failure() {
+    let (proof_tree, _) = explain(SolverChoice::recursive(), "Vec<Bar>: Clone");
+    assert!(proof_tree.solution.is_none());
+    let applications = match proof_tree.derivation {
+        Derivation::Clauses(applications) => applications,
+        derivation => panic!("unexpected derivation {:?}", derivation),
+    };
+    assert_eq!(applications.len(), 2);
+    assert!(applications
+        .iter()
+        .all(|application| !application.succeeded));
+    assert!(applications[1].subgoals[0].solution.is_none());
+}
+
+#[test]
+fn explain_environment_clause() {
+    let (_, display) = explain(
+        SolverChoice::recursive(),
+        "forall<T> { if (FromEnv(T: Clone)) { T: Clone } }",
+    );
+    assert_eq!(
+        display,
+        "\
+Implemented(!1_0: Clone): Unique; substitution [], lifetime constraints []
+  using program clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone)
+    FromEnv(!1_0: Clone): Unique; substitution [], lifetime constraints []
+      using environment clause for<> FromEnv(!1_0: Clone)
+  using program clause for<> Implemented(Foo: Clone) (failed)
+  using program clause for<type> Implemented(Vec<^0.0>: Clone) :- ForAll<> { Implemented(^1.0: Clone) } (failed)
+  using environment clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone)
+    FromEnv(!1_0: Clone): Unique; substitution [], lifetime constraints []
+      using environment clause for<> FromEnv(!1_0: Clone)
+"
+    );
+}
+
+#[test]
+fn explain_slg_success() {
+    let (proof_tree, display) = explain(SolverChoice::slg_default(), "Vec<Foo>: Clone");
+    assert!(proof_tree.solution.unwrap().is_unique());
+    assert_eq!(
+        display,
+        "\
+Implemented(Vec<Foo>: Clone): Unique; substitution [], lifetime constraints []
+  using program clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone) (failed)
+    FromEnv(Vec<Foo>: Clone): No possible solution
+  using program clause for<type> Implemented(Vec<^0.0>: Clone) :- ForAll<> { Implemented(^1.0: Clone) }
+    ForAll<> { Implemented(Foo: Clone) }: Unique; substitution [], lifetime constraints []
+      Implemented(Foo: Clone): Unique; substitution [], lifetime constraints []
+        using program clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone) (failed)
+          FromEnv(Foo: Clone): No possible solution
+        using program clause for<> Implemented(Foo: Clone)
+"
+    );
+}
+
+#[test]
+fn explain_slg_failure() {
+    let (proof_tree, _) = explain(SolverChoice::slg_default(), "Vec<Bar>: Clone");
+    assert!(proof_tree.solution.is_none());
+    let applications = match proof_tree.derivation {
+        Derivation::Clauses(applications) => applications,
+        derivation => panic!("unexpected derivation {:?}", derivation),
+    };
+    assert_eq!(applications.len(), 2);
+    assert!(applications
+        .iter()
+        .all(|application| !application.succeeded));
+}
+
+#[test]
+fn explain_slg_negation() {
+    let (proof_tree, display) = explain(SolverChoice::slg_default(), "not { Bar: Clone }");
+    assert!(proof_tree.solution.unwrap().is_unique());
+    assert_eq!(
+        display,
+        "\
+not { Implemented(Bar: Clone) }: Unique; substitution [], lifetime constraints []
+  Implemented(Bar: Clone): No possible solution
+    using program clause for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone) (failed)
+      FromEnv(Bar: Clone): No possible solution
+"
+    );
+}
@@ -87,3 +87,107 @@ fn generator_test() {
         }
     }
 }
+
+#[test]
+fn generator_trait() {
+    test! {
+        program {
+            #[lang(generator)]
+            trait Generator<R> {
+                type Yield;
+                type Return;
+            }
+
+            struct Resume {}
+            struct Yielded {}
+            struct Returned {}
+
+            generator gen<T>[resume = Resume, yield = Yielded] -> T {
+                upvars []
+                witnesses []
+            }
+        }
+
+        goal {
+            forall<T> {
+                gen<T>: Generator<Resume>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T> {
+                gen<T>: Generator<Yielded>
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<R> {
+                gen<u32>: Generator<R>
+            }
+        } yields {
+            "Unique; substitution [?0 := Resume]"
+        }
+
+        goal {
+            forall<T> {
+                Normalize(<gen<T> as Generator<Resume>>::Yield -> Yielded)
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            exists<T> {
+                Normalize(<gen<Returned> as Generator<Resume>>::Return -> T)
+            }
+        } yields {
+            "Unique; substitution [?0 := Returned]"
+        }
+
+        goal {
+            exists<T> {
+                T: Generator<Resume>
+            }
+        } yields_first[SolverChoice::slg(3, None)] {
+            "Floundered"
+        }
+    }
+}
+
+#[test]
+fn generator_movability_unpin() {
+    test! {
+        program {
+            #[auto] #[lang(unpin)] trait Unpin { }
+
+            struct NotUnpin {}
+            impl !Unpin for NotUnpin {}
+
+            generator movable_gen<>[resume = (), yield = ()] {
+                upvars [NotUnpin]
+                witnesses []
+            }
+
+            static generator static_gen<>[resume = (), yield = ()] {
+                upvars []
+                witnesses []
+            }
+        }
+
+        goal {
+            movable_gen: Unpin
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            static_gen: Unpin
+        } yields {
+            "No possible solution"
+        }
+    }
+}
@@ -677,3 +677,50 @@ fn unify_types_in_impl() {
         }
     }
 }
+
+#[test]
+fn recursive_solver_multiple_answers() {
+    test! {
+        program {
+            struct Foo { }
+            struct Bar { }
+            struct Vec<T> { }
+            trait Clone { }
+            impl<T> Clone for Vec<T> where T: Clone { }
+            impl Clone for Foo { }
+            impl Clone for Bar { }
+        }
+
+        goal {
+            exists<T> { T: Clone }
+        } yields_all[SolverChoice::recursive()] {
+            "Ambiguous(for<?U0> { substitution [?0 := Vec<^0.0>], lifetime constraints [] })",
+            "substitution [?0 := Foo], lifetime constraints []",
+            "substitution [?0 := Bar], lifetime constraints []"
+        }
+
+        goal {
+            exists<T> { Vec<T>: Clone }
+        } yields_all[SolverChoice::recursive()] {
+            "Ambiguous(for<?U0> { substitution [?0 := ^0.0], lifetime constraints [] })"
+        }
+
+        goal {
+            Vec<Foo>: Clone
+        } yields_all[SolverChoice::recursive()] {
+            "substitution [], lifetime constraints []"
+        }
+
+        goal {
+            Vec<Vec<Bar>>: Clone
+        } yields_all[SolverChoice::recursive()] {
+            "substitution [], lifetime constraints []"
+        }
+
+        goal {
+            exists<T> { Vec<Vec<T>>: Clone }
+        } yields_all[SolverChoice::recursive()] {
+            "Ambiguous(for<?U0> { substitution [?0 := ^0.0], lifetime constraints [] })"
+        }
+    }
+}
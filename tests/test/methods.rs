@@ -0,0 +1,221 @@
+//! Tests for method probing with `probe_method`.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::lowering::lower_ty;
+use chalk_integration::SolverChoice;
+use chalk_ir::*;
+use chalk_solve::rust_ir::AssociatedFnContainer;
+use chalk_solve::RustIrDatabase;
+
+const PROGRAM: &str = "
+    #[lang(deref)]
+    trait Deref {
+        type Target;
+    }
+
+    struct Foo { }
+    struct Bar { }
+    struct Box<T> { }
+    struct Vec<T> { }
+
+    impl<T> Deref for Box<T> {
+        type Target = T;
+    }
+
+    impl Foo {
+        fn by_ref(&self);
+        fn by_mut(&mut self);
+        fn by_value(self);
+        fn new() -> Self;
+    }
+
+    impl<T> Vec<T> {
+        fn len(&self) -> usize;
+    }
+
+    trait Show {
+        fn show(&self);
+    }
+    impl Show for Foo { }
+    impl<T> Show for Vec<T> where T: Show { }
+
+    trait Named {
+        fn by_ref(&self);
+        fn name(&self);
+    }
+    impl Named for Foo { }
+
+    trait Titled {
+        fn name(&self);
+    }
+    impl Titled for Foo { }
+";
+
+/// Probes the method `name` on `receiver`, and describes each candidate as
+/// `method container autoderefs autoref receiver`.
+fn probe(program: &str, receiver: &str, name: &str) -> Vec<String> {
+    let db = ChalkDatabase::with(program, SolverChoice::default());
+    db.with_program(|program| {
+        let interner = db.interner();
+        let ty = lower_ty(&chalk_parse::parse_ty(receiver).unwrap(), program).unwrap();
+        let receiver = UCanonical {
+            canonical: Canonical {
+                value: InEnvironment::new(&Environment::new(interner), ty),
+                binders: CanonicalVarKinds::empty(interner),
+            },
+            universes: 1,
+        };
+        db.probe_method(&receiver, name)
+            .into_iter()
+            .map(|candidate| {
+                let container = match candidate.container {
+                    AssociatedFnContainer::Trait(trait_id) => format!("Trait({:?})", trait_id),
+                    AssociatedFnContainer::InherentImpl(_) => "InherentImpl".to_owned(),
+                };
+                format!(
+                    "{:?} {} {} {:?} {:?}",
+                    candidate.fn_def_id,
+                    container,
+                    candidate.autoderefs,
+                    candidate.autoref,
+                    candidate.instantiation.value.receiver
+                )
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn inherent_methods() {
+    assert_eq!(
+        probe(PROGRAM, "Foo", "by_value"),
+        vec!["by_value InherentImpl 0 None Foo"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "Foo", "by_mut"),
+        vec!["by_mut InherentImpl 0 Some(Mut) (&'^0.0 mut Foo)"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "(&'static Foo)", "by_ref"),
+        vec!["by_ref InherentImpl 0 None (&'static Foo)"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "Vec<u32>", "len"),
+        vec!["len InherentImpl 0 Some(Not) (&'^0.0 Vec<Uint(U32)>)"]
+    );
+}
+
+#[test]
+fn fns_without_receiver() {
+    assert!(probe(PROGRAM, "Foo", "new").is_empty());
+    assert!(probe(PROGRAM, "Foo", "missing").is_empty());
+    assert!(probe(PROGRAM, "Bar", "by_ref").is_empty());
+}
+
+#[test]
+fn autoderef() {
+    assert_eq!(
+        probe(PROGRAM, "&'static (&'static Foo)", "by_mut"),
+        vec!["by_mut InherentImpl 2 Some(Mut) (&'^0.0 mut Foo)"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "&'static (&'static Foo)", "by_value"),
+        vec!["by_value InherentImpl 2 None Foo"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "Box<Foo>", "by_ref"),
+        vec!["by_ref InherentImpl 1 Some(Not) (&'^0.0 Foo)"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "Box<&'static Box<Foo>>", "by_value"),
+        vec!["by_value InherentImpl 3 None Foo"]
+    );
+}
+
+#[test]
+fn trait_methods() {
+    assert_eq!(
+        probe(PROGRAM, "Foo", "show"),
+        vec!["show Trait(Show) 0 Some(Not) (&'^0.0 Foo)"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "&'static (&'static Vec<Foo>)", "show"),
+        vec!["show Trait(Show) 1 None (&'static Vec<Foo>)"]
+    );
+    assert_eq!(
+        probe(PROGRAM, "Box<Vec<Foo>>", "show"),
+        vec!["show Trait(Show) 1 Some(Not) (&'^0.0 Vec<Foo>)"]
+    );
+
+    // The where clauses of the impl do not hold.
+    assert!(probe(PROGRAM, "Vec<Bar>", "show").is_empty());
+}
+
+#[test]
+fn inherent_methods_come_first() {
+    // `Named::by_ref` is shadowed by the inherent method.
+    assert_eq!(
+        probe(PROGRAM, "Foo", "by_ref"),
+        vec!["by_ref InherentImpl 0 Some(Not) (&'^0.0 Foo)"]
+    );
+}
+
+#[test]
+fn ambiguous_methods() {
+    assert_eq!(
+        probe(PROGRAM, "Foo", "name"),
+        vec![
+            "name Trait(Named) 0 Some(Not) (&'^0.0 Foo)",
+            "name Trait(Titled) 0 Some(Not) (&'^0.0 Foo)",
+        ]
+    );
+}
+
+#[test]
+fn deref_cycles() {
+    let program = "
+        #[lang(deref)]
+        trait Deref {
+            type Target;
+        }
+
+        struct A { }
+        struct B { }
+        impl Deref for A {
+            type Target = B;
+        }
+        impl Deref for B {
+            type Target = A;
+        }
+
+        impl B {
+            fn method(&self);
+        }
+
+        struct C { }
+        impl C {
+            fn other(&self);
+        }
+    ";
+    assert_eq!(
+        probe(program, "A", "method"),
+        vec!["method InherentImpl 1 Some(Not) (&'^0.0 B)"]
+    );
+    assert!(probe(program, "A", "other").is_empty());
+}
+
+#[test]
+fn without_deref() {
+    let program = "
+        struct Foo { }
+        struct Box<T> { }
+        impl Foo {
+            fn method(&self);
+        }
+    ";
+    assert_eq!(
+        probe(program, "(&'static Foo)", "method"),
+        vec!["method InherentImpl 0 None (&'static Foo)"]
+    );
+    assert!(probe(program, "Box<Foo>", "method").is_empty());
+}
@@ -83,8 +83,9 @@ macro_rules! parse_test_data {
     };
 
     // goal { G } yields_all { "Y1", "Y2", ... , "YN" } -- test that the SLG
-    // solver gets exactly N answers in this order (the recursive solver can't
-    // return multiple answers)
+    // solver gets exactly N answers in this order (the recursive solver's
+    // answers differ, so they are tested separately with
+    // `yields_all[SolverChoice::recursive()]`)
     (@program[$program:tt] @parsed_goals[$($parsed_goals:tt)*] @unparsed_goals[
         goal $goal:tt yields_all { $($expected:expr),* }
         $($unparsed_goals:tt)*
@@ -237,14 +238,6 @@ fn solve_goal(program_text: &str, goals: Vec<(&str, SolverChoice, TestGoal)>, co
         };
 
         for (goal_text, solver_choice, expected) in goals {
-            match (&solver_choice, &expected) {
-                (SolverChoice::Recursive { .. }, TestGoal::All(_))
-                | (SolverChoice::Recursive { .. }, TestGoal::First(_)) => {
-                    panic!("cannot test the recursive solver with yields_first or yields_all");
-                }
-                _ => {}
-            };
-
             if db.solver_choice() != solver_choice {
                 db.set_solver_choice(solver_choice);
             }
@@ -322,32 +315,47 @@ fn solve_goal(program_text: &str, goals: Vec<(&str, SolverChoice, TestGoal)>, co
 }
 
 mod arrays;
+mod assoc_consts;
 mod auto_traits;
+mod autoderef;
 mod closures;
+mod coerce_unsized;
 mod coherence_goals;
 mod coinduction;
+mod const_exprs;
 mod constants;
 mod cycle;
+mod discriminant_kind;
 mod existential_types;
+mod explain;
 mod fn_def;
 mod foreign_types;
 mod generators;
 mod implied_bounds;
 mod impls;
+mod methods;
 mod misc;
+mod modules;
 mod negation;
 mod never;
 mod numerics;
 mod object_safe;
 mod opaque_types;
+mod pointee;
 mod projection;
 mod refs;
+mod regions;
 mod scalars;
 mod slices;
 mod statics;
+mod stats;
 mod string;
+mod subtype;
+mod templates;
+mod trait_aliases;
 mod tuples;
 mod unify;
 mod unpin;
 mod unsize;
 mod wf_goals;
+mod why_not;
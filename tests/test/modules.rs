@@ -0,0 +1,158 @@
+//! Tests for programs split into modules, and for the paths that refer to
+//! their items.
+
+use super::*;
+
+#[test]
+fn same_name_in_different_modules() {
+    test! {
+        program {
+            mod a {
+                struct Foo { }
+            }
+            mod b {
+                struct Foo { }
+            }
+            trait Bar { }
+            impl Bar for a::Foo { }
+        }
+
+        goal {
+            a::Foo: Bar
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            b::Foo: Bar
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<T> { T: Bar }
+        } yields {
+            "Unique; substitution [?0 := a::Foo]"
+        }
+    }
+}
+
+#[test]
+fn use_imports() {
+    test! {
+        program {
+            mod std {
+                mod vec {
+                    struct Vec<T> { }
+                }
+                trait Clone { }
+                impl<T> Clone for vec::Vec<T> where T: Clone { }
+            }
+            use std::vec::Vec;
+            use std::Clone as StdClone;
+            struct Foo { }
+            impl StdClone for Foo { }
+        }
+
+        goal {
+            Vec<Foo>: StdClone
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            std::vec::Vec<Foo>: std::Clone
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn super_and_crate_paths() {
+    test! {
+        program {
+            struct Foo { }
+            mod a {
+                trait Bar { }
+                mod b {
+                    struct Baz { }
+                    impl super::Bar for crate::Foo { }
+                    impl super::Bar for Baz { }
+                }
+            }
+        }
+
+        goal {
+            Foo: a::Bar
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            a::b::Baz: crate::a::Bar
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn crate_root_is_a_prelude() {
+    test! {
+        program {
+            #[lang(sized)]
+            trait Sized { }
+            struct Foo { }
+            mod a {
+                struct Bar<T> where T: Sized { }
+                trait Baz { }
+                impl Baz for Bar<Foo> { }
+            }
+        }
+
+        goal {
+            a::Bar<Foo>: a::Baz
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn upstream_modules() {
+    test! {
+        program {
+            #[upstream]
+            mod std {
+                struct Vec<T> { }
+                trait Clone { }
+            }
+            struct Foo { }
+        }
+
+        goal {
+            forall<T> { IsUpstream(std::Vec<T>) }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            IsLocal(std::Vec<Foo>)
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            LocalImplAllowed(std::Vec<u32>: std::Clone)
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            LocalImplAllowed(Foo: std::Clone)
+        } yields {
+            "Unique"
+        }
+    }
+}
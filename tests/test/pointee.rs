@@ -0,0 +1,144 @@
+use super::*;
+
+#[test]
+fn pointee_metadata() {
+    test! {
+        program {
+            #[lang(sized)]
+            trait Sized { }
+
+            #[lang(pointee)]
+            trait Pointee {
+                type Metadata;
+            }
+
+            #[object_safe]
+            trait Foo { }
+
+            #[lang(dyn_metadata)]
+            struct DynMetadata<Dyn> { }
+
+            struct Unit { }
+            struct Sized1 { a: u8, b: u32 }
+            struct SliceTail<T> { a: u8, tail: [T] }
+            struct Nested { a: u8, tail: SliceTail<u8> }
+            struct StrTail { tail: str }
+            enum Enum { A, B(u8) }
+        }
+
+        goal {
+            exists<T> {
+                Normalize(<[u8] as Pointee>::Metadata -> T)
+            }
+        } yields {
+            "Unique; substitution [?0 := Uint(Usize)]"
+        }
+
+        goal {
+            <str as Pointee>::Metadata = usize
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                exists<T> {
+                    Normalize(<dyn Foo + 'a as Pointee>::Metadata -> T)
+                }
+            }
+        } yields {
+            "Unique; substitution [?0 := DynMetadata<dyn for<type> [for<> Implemented(^1.0: Foo)] + '!1_0>]"
+        }
+
+        // Sized types
+        goal {
+            <u8 as Pointee>::Metadata = ()
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <Unit as Pointee>::Metadata = ()
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <Sized1 as Pointee>::Metadata = ()
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <Enum as Pointee>::Metadata = ()
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'a> {
+                <&'a [u8] as Pointee>::Metadata = ()
+            }
+        } yields {
+            "Unique"
+        }
+
+        // The metadata of a struct or a tuple is the one of its tail
+        goal {
+            <SliceTail<u32> as Pointee>::Metadata = usize
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <Nested as Pointee>::Metadata = usize
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <StrTail as Pointee>::Metadata = usize
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <(u8, [u8]) as Pointee>::Metadata = usize
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            <(u8, u32) as Pointee>::Metadata = ()
+        } yields {
+            "Unique"
+        }
+
+        // Type parameters
+        goal {
+            forall<T> {
+                if (T: Sized) {
+                    <T as Pointee>::Metadata = ()
+                }
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T> {
+                <T as Pointee>::Metadata = ()
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<T> {
+                T: Pointee
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Tests for `chalk_solve::solve::regions`, which resolves the lifetime
+//! constraints of solutions.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::interner::ChalkIr;
+use chalk_integration::SolverChoice;
+use chalk_solve::ext::*;
+use chalk_solve::RustIrDatabase;
+
+/// Solves `goal`, resolves the lifetime constraints of the solution and
+/// checks that the result is `expected`.
+fn check_regions(program: &str, goal: &str, expected: &str) {
+    let db = ChalkDatabase::with(program, SolverChoice::slg_default());
+    db.with_program(|program| {
+        let goal = chalk_integration::lowering::lower_goal(
+            &chalk_parse::parse_goal(goal).unwrap(),
+            program,
+        )
+        .unwrap();
+        let peeled_goal = goal.into_peeled_goal(db.interner());
+        let solution = db.solve(&peeled_goal).expect("goal should be provable");
+        let actual = match solution.resolve_regions(db.interner()) {
+            Ok(solution) => format!("{}", solution.display(&ChalkIr)),
+            Err(e) => format!("error: {}", e),
+        };
+        assert_eq!(actual, expected);
+    })
+}
+
+#[test]
+fn unrelated_placeholders() {
+    check_regions(
+        "",
+        "forall<'a, 'b> { Subtype(&'a u32, &'b u32) }",
+        "error: the lifetime constraint `'!1_0: '!1_1` cannot be satisfied",
+    );
+}
+
+#[test]
+fn implied_by_environment() {
+    check_regions(
+        "",
+        "forall<'a, 'b> { if ('a: 'b) { Subtype(&'a u32, &'b u32) } }",
+        "Unique; substitution [], lifetime constraints []",
+    );
+
+    // The outlives relation is transitive.
+    check_regions(
+        "",
+        "forall<'a, 'b, 'c> { if ('a: 'b; 'b: 'c) { Subtype(&'a u32, &'c u32) } }",
+        "Unique; substitution [], lifetime constraints []",
+    );
+
+    check_regions(
+        "",
+        "forall<'a, 'b, 'c> { if ('a: 'b; 'b: 'c) { Subtype(&'c u32, &'a u32) } }",
+        "error: the lifetime constraint `'!1_2: '!1_0` cannot be satisfied",
+    );
+}
+
+#[test]
+fn static_lifetime() {
+    check_regions(
+        "",
+        "forall<'a> { Subtype(&'static u32, &'a u32) }",
+        "Unique; substitution [], lifetime constraints []",
+    );
+
+    check_regions(
+        "",
+        "forall<'a> { Subtype(&'a u32, &'static u32) }",
+        "error: the lifetime constraint `'!1_0: 'static` cannot be satisfied",
+    );
+
+    // If `'a` outlives `'static`, it outlives everything.
+    check_regions(
+        "",
+        "forall<'a, 'b> { if ('a: 'static) { Subtype(&'a u32, &'b u32) } }",
+        "Unique; substitution [], lifetime constraints []",
+    );
+}
+
+#[test]
+fn through_variables() {
+    check_regions(
+        "",
+        "forall<'a, 'b> { exists<'x> { Subtype(&'a u32, &'x u32), Subtype(&'x u32, &'b u32) } }",
+        "error: the lifetime constraint `'!1_0: '!1_1` cannot be satisfied",
+    );
+
+    check_regions(
+        "",
+        "forall<'a, 'b> { if ('a: 'b) { exists<'x> { Subtype(&'a u32, &'x u32), Subtype(&'x u32, &'b u32) } } }",
+        "Unique; for<?U1> { substitution [?0 := '^0.0], lifetime constraints [InEnvironment { environment: Env([for<> '!1_0: '!1_1]), goal: '!1_0: '^0.0 }, InEnvironment { environment: Env([for<> '!1_0: '!1_1]), goal: '^0.0: '!1_1 }] }",
+    );
+
+    // Constraints on the variables of the solution are kept.
+    check_regions(
+        "",
+        "forall<'a> { exists<T> { Subtype(T, &'a u32) } }",
+        "Unique; for<?U1> { substitution [?0 := (&'^0.0 Uint(U32))], lifetime constraints [InEnvironment { environment: Env([]), goal: '^0.0: '!1_0 }] }",
+    );
+}
+
+#[test]
+fn redundant_constraints() {
+    // `'x: 'a` follows from `'x: 'y` and `'y: 'a`, and `'static: 'y` always
+    // holds.
+    check_regions(
+        "",
+        "forall<'a> { exists<'x, 'y> { Subtype(&'x u32, &'y u32), Subtype(&'y u32, &'a u32), Subtype(&'x u32, &'a u32), Subtype(&'x u32, &'a u32), Subtype(&'static u32, &'y u32) } }",
+        "Unique; for<?U1,?U1> { substitution [?0 := '^0.0, ?1 := '^0.1], lifetime constraints [InEnvironment { environment: Env([]), goal: '^0.0: '^0.1 }, InEnvironment { environment: Env([]), goal: '^0.1: '!1_0 }] }",
+    );
+}
+
+#[test]
+fn type_outlives() {
+    let program = "
+        trait Foo<'a> { }
+        impl<'a, 'b, T> Foo<'b> for &'a T where T: 'a, 'a: 'b { }
+    ";
+
+    check_regions(
+        program,
+        "forall<'a, 'b, T> { &'a T: Foo<'b> }",
+        "error: the lifetime constraint `'!1_0: '!1_1` cannot be satisfied",
+    );
+
+    check_regions(
+        program,
+        "forall<'a, 'b, T> { if ('a: 'b) { &'a T: Foo<'b> } }",
+        "error: the lifetime constraint `!1_2: '!1_0` cannot be satisfied",
+    );
+
+    check_regions(
+        program,
+        "forall<'a, 'b, T> { if ('a: 'b; T: 'a) { &'a T: Foo<'b> } }",
+        "Unique; substitution [], lifetime constraints []",
+    );
+
+    // `&'c u32: 'b` is broken down into `'c: 'b`.
+    check_regions(
+        program,
+        "forall<'a, 'b, 'c> { if ('a: 'b; 'c: 'a) { &'a &'c u32: Foo<'b> } }",
+        "Unique; substitution [], lifetime constraints []",
+    );
+}
+
+#[test]
+fn unnameable_placeholders() {
+    // `'x` cannot name `'a`, so it has to outlive `'static` to outlive it.
+    check_regions(
+        "",
+        "exists<'x> { forall<'a> { Subtype(&'x u32, &'a u32) } }",
+        "Unique; for<?U0> { substitution [?0 := '^0.0], lifetime constraints \
+         [InEnvironment { environment: Env([]), goal: '^0.0: 'static }] }",
+    );
+
+    // ... and cannot be equal to it.
+    check_regions(
+        "",
+        "exists<'x> { forall<'a> { Subtype(&'x u32, &'a u32), Subtype(&'a u32, &'x u32) } }",
+        "error: the lifetime constraint `'!1_0: 'static` cannot be satisfied",
+    );
+
+    // `'a` outlives `'x` whatever `'x` is.
+    check_regions(
+        "",
+        "exists<'x> { forall<'a> { Subtype(&'a u32, &'x u32) } }",
+        "Unique; for<?U0> { substitution [?0 := '^0.0], lifetime constraints [] }",
+    );
+
+    // `'x` can name `'a` here.
+    check_regions(
+        "",
+        "forall<'a> { exists<'x> { Subtype(&'x u32, &'a u32), Subtype(&'a u32, &'x u32) } }",
+        "Unique; for<?U1> { substitution [?0 := '^0.0], lifetime constraints \
+         [InEnvironment { environment: Env([]), goal: '^0.0: '!1_0 }, \
+         InEnvironment { environment: Env([]), goal: '!1_0: '^0.0 }] }",
+    );
+}
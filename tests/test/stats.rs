@@ -0,0 +1,144 @@
+//! Tests for the statistics recorded by `Solver::record_stats`.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::interner::ChalkIr;
+use chalk_integration::SolverChoice;
+use chalk_solve::ext::*;
+use chalk_solve::solve::stats::{QueryStats, RecursiveStats, SolverStats};
+use chalk_solve::RustIrDatabase;
+
+const PROGRAM: &str = "
+    struct Foo { }
+    struct Vec<T> { }
+    trait Clone { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+";
+
+/// Solves each of `goals` in turn, then passes the statistics recorded for
+/// them to `check`.
+fn check_stats(
+    program: &str,
+    solver_choice: SolverChoice,
+    record: bool,
+    goals: &[&str],
+    check: impl FnOnce(Vec<QueryStats<ChalkIr>>),
+) {
+    let db = ChalkDatabase::with(program, solver_choice);
+    db.with_program(|program| {
+        db.record_stats(record);
+        for goal_text in goals {
+            let goal = chalk_integration::lowering::lower_goal(
+                &chalk_parse::parse_goal(goal_text).unwrap(),
+                program,
+            )
+            .unwrap();
+            let peeled_goal = goal.into_peeled_goal(db.interner());
+            db.solve(&peeled_goal);
+        }
+        check(db.take_stats())
+    })
+}
+
+fn recursive_stats(query: &QueryStats<ChalkIr>) -> &RecursiveStats {
+    match &query.details {
+        SolverStats::Recursive(stats) => stats,
+        SolverStats::Slg(_) => panic!("expected recursive solver stats"),
+    }
+}
+
+#[test]
+fn stats_not_recorded_by_default() {
+    for solver_choice in [SolverChoice::slg_default(), SolverChoice::recursive()].iter() {
+        check_stats(PROGRAM, *solver_choice, false, &["Foo: Clone"], |stats| {
+            assert!(stats.is_empty())
+        });
+    }
+}
+
+#[test]
+fn stats_slg() {
+    check_stats(
+        PROGRAM,
+        SolverChoice::slg_default(),
+        true,
+        &["Vec<Foo>: Clone", "Vec<Foo>: Clone", "Vec<Vec<Foo>>: Clone"],
+        |stats| {
+            assert_eq!(stats.len(), 3);
+            let slg_stats: Vec<_> = stats
+                .iter()
+                .map(|query| match &query.details {
+                    SolverStats::Slg(stats) => stats,
+                    SolverStats::Recursive(_) => panic!("expected SLG solver stats"),
+                })
+                .collect();
+
+            // The first table is the one for the query itself, which has
+            // an answer.
+            let first = &slg_stats[0];
+            assert_eq!(
+                format!("{:?}", first.tables[0].goal.canonical.value.goal),
+                "Implemented(Vec<Foo>: Clone)"
+            );
+            assert!(first.tables[0].strands > 0);
+            assert!(first.tables[0].answers > 0);
+            assert!(first.tables.len() > 1);
+
+            // The second query reuses the tables created by the first.
+            assert!(slg_stats[1].tables.is_empty());
+
+            // A new query creates new tables, but reuses the ones it has in
+            // common with the first query.
+            let third = &slg_stats[2];
+            assert!(!third.tables.is_empty());
+            assert!(third
+                .tables
+                .iter()
+                .all(|table| first.tables.iter().all(|t| t.goal != table.goal)));
+        },
+    );
+}
+
+#[test]
+fn stats_recursive() {
+    check_stats(
+        PROGRAM,
+        SolverChoice::recursive(),
+        true,
+        &["Vec<Foo>: Clone", "Vec<Foo>: Clone"],
+        |stats| {
+            assert_eq!(stats.len(), 2);
+            assert_eq!(
+                recursive_stats(&stats[0]),
+                &RecursiveStats {
+                    search_graph_size: 4,
+                    cache_hits: 0,
+                    fixpoint_iterations: 0,
+                    max_stack_depth: 3,
+                }
+            );
+
+            // The second query is answered from the cache.
+            assert_eq!(
+                recursive_stats(&stats[1]),
+                &RecursiveStats {
+                    search_graph_size: 0,
+                    cache_hits: 1,
+                    fixpoint_iterations: 0,
+                    max_stack_depth: 0,
+                }
+            );
+        },
+    );
+}
+
+#[test]
+fn stats_recursive_fixpoint() {
+    check_stats(
+        PROGRAM,
+        SolverChoice::recursive(),
+        true,
+        &["exists<T> { T: Clone }"],
+        |stats| assert!(recursive_stats(&stats[0]).fixpoint_iterations > 0),
+    );
+}
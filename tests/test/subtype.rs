@@ -0,0 +1,187 @@
+use super::*;
+
+#[test]
+fn subtype_refs() {
+    test! {
+        program { }
+
+        // `&'a T` is covariant in both `'a` and `T`.
+        goal {
+            forall<'a, 'b, T> { Subtype(&'a T, &'b T) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(&'static &'a u32, &'static &'b u32) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        goal {
+            forall<'a> { Subtype(&'a u32, &'a u32) }
+        } yields {
+            "Unique; substitution [], lifetime constraints []"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(&'a u32, &'b i32) }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn subtype_mut_refs() {
+    test! {
+        program { }
+
+        // `&'a mut T` is covariant in `'a`, but invariant in `T`.
+        goal {
+            forall<'a, 'b, T> { Subtype(&'a mut T, &'b mut T) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(&'static mut &'a u32, &'static mut &'b u32) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }, InEnvironment { environment: Env([]), goal: '!1_1: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a> { Subtype(&'a mut u32, &'a u32) }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn subtype_fn_ptrs() {
+    test! {
+        program { }
+
+        // Function pointers are contravariant in their arguments and
+        // covariant in their return type.
+        goal {
+            forall<'a, 'b> { Subtype(fn(&'a u32), fn(&'b u32)) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_1: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(fn() -> &'a u32, fn() -> &'b u32) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(fn(&'a u32) -> &'a u32, fn(&'b u32) -> &'b u32) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }, InEnvironment { environment: Env([]), goal: '!1_1: '!1_0 }]"
+        }
+
+        // A higher-ranked function pointer is a subtype of any of its
+        // instantiations.
+        goal {
+            forall<'a> { Subtype(for<'x> fn(&'x u32), fn(&'a u32)) }
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn subtype_adts() {
+    test! {
+        program {
+            #[variance(Covariant)]
+            struct Co<'a> { }
+
+            #[variance(Contravariant)]
+            struct Contra<'a> { }
+
+            struct Inv<'a> { }
+
+            #[variance(Covariant, Invariant)]
+            struct Pair<T, U> { }
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(Co<'a>, Co<'b>) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(Contra<'a>, Contra<'b>) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_1: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(Inv<'a>, Inv<'b>) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }, InEnvironment { environment: Env([]), goal: '!1_1: '!1_0 }]"
+        }
+
+        goal {
+            forall<'a, 'b> { Subtype(Pair<&'a u32, &'a u32>, Pair<&'b u32, &'a u32>) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '!1_1 }]"
+        }
+
+        // Subtyping is contravariant inside of a contravariant parameter.
+        goal {
+            forall<'a, 'b> { Subtype(fn(Co<'a>), fn(Co<'b>)) }
+        } yields {
+            "Unique; substitution [], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_1: '!1_0 }]"
+        }
+    }
+}
+
+#[test]
+fn subtype_inference_vars() {
+    test! {
+        program { }
+
+        // The variable is bound to a type with fresh lifetimes, which are
+        // then related to those of the other type.
+        goal {
+            forall<'a> { exists<T> { Subtype(T, &'a u32) } }
+        } yields {
+            "Unique; for<?U1> { substitution [?0 := (&'^0.0 Uint(U32))], lifetime constraints [InEnvironment { environment: Env([]), goal: '^0.0: '!1_0 }] }"
+        }
+
+        goal {
+            forall<'a> { exists<T> { Subtype(&'a u32, T) } }
+        } yields {
+            "Unique; for<?U1> { substitution [?0 := (&'^0.0 Uint(U32))], lifetime constraints [InEnvironment { environment: Env([]), goal: '!1_0: '^0.0 }] }"
+        }
+    }
+}
+
+#[test]
+fn subtype_is_not_a_keyword() {
+    test! {
+        program {
+            struct Covariant { }
+            trait Subtype { }
+            impl Subtype for Covariant { }
+        }
+
+        goal {
+            Covariant: Subtype
+        } yields {
+            "Unique; substitution [], lifetime constraints []"
+        }
+
+        goal {
+            Subtype(Covariant, Covariant)
+        } yields {
+            "Unique; substitution [], lifetime constraints []"
+        }
+    }
+}
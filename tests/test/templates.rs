@@ -0,0 +1,102 @@
+//! Tests for `for T in [...] { ... }` templates, whose items are repeated
+//! for each of the arguments.
+
+use super::*;
+
+#[test]
+fn template_impls() {
+    test! {
+        program {
+            trait Copy { }
+            struct Vec<T> { }
+            struct Foo { }
+            for T in [u8, u16, Vec<Foo>] {
+                impl Copy for T { }
+            }
+        }
+
+        goal {
+            u16: Copy
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Vec<Foo>: Copy
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            u32: Copy
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            Foo: Copy
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn template_names() {
+    test! {
+        program {
+            trait Clone { }
+            trait Copy where Self: Clone { }
+            struct Vec<T> { }
+            struct Box<T> { }
+            for Tr in [Clone, Copy] {
+                for P in [Vec, Box] {
+                    impl<T> Tr for P<T> where T: Tr { }
+                }
+                impl Tr for u8 { }
+            }
+        }
+
+        goal {
+            Box<Vec<u8>>: Copy
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Vec<u16>: Clone
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn template_consts() {
+    test! {
+        program {
+            struct Foo<const N> { }
+            trait Small { }
+            for N in [0, 1, 2] {
+                impl Small for Foo<N> { }
+            }
+            mod m {
+                for T in [] {
+                    struct Never { }
+                }
+            }
+        }
+
+        goal {
+            Foo<2>: Small
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Foo<3>: Small
+        } yields {
+            "No possible solution"
+        }
+    }
+}
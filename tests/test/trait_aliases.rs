@@ -0,0 +1,248 @@
+//! Tests for trait aliases, e.g. `trait Alias<T> = Foo<T> + Bar where T: Baz;`.
+
+use super::*;
+
+#[test]
+fn trait_alias_goals() {
+    test! {
+        program {
+            trait Foo<T> { }
+            trait Bar { }
+            trait Baz { }
+            trait Alias<T> = Foo<T> + Bar where T: Baz;
+
+            struct A { }
+            struct B { }
+            struct C { }
+
+            impl Baz for A { }
+            impl Foo<A> for B { }
+            impl Foo<C> for B { }
+            impl Bar for B { }
+            impl Foo<A> for C { }
+        }
+
+        goal {
+            B: Alias<A>
+        } yields {
+            "Unique"
+        }
+
+        // `C: Baz` does not hold.
+        goal {
+            B: Alias<C>
+        } yields {
+            "No possible solution"
+        }
+
+        // `C: Bar` does not hold.
+        goal {
+            C: Alias<A>
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            exists<T> { B: Alias<T> }
+        } yields {
+            "Unique; substitution [?0 := A]"
+        }
+    }
+}
+
+#[test]
+fn trait_alias_assumptions() {
+    test! {
+        program {
+            trait Foo<T> { }
+            trait Bar { }
+            trait Baz { }
+            trait Alias<T> = Foo<T> + Bar where T: Baz;
+        }
+
+        goal {
+            forall<T, U> {
+                if (T: Alias<U>) {
+                    T: Foo<U>
+                }
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T, U> {
+                if (T: Alias<U>) {
+                    T: Bar
+                }
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T, U> {
+                if (T: Alias<U>) {
+                    U: Baz
+                }
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<T, U> {
+                if (T: Alias<U>) {
+                    U: Bar
+                }
+            }
+        } yields {
+            "No possible solution"
+        }
+    }
+}
+
+#[test]
+fn trait_alias_assoc_type_bounds() {
+    test! {
+        program {
+            trait Iterator { type Item; }
+            trait IntIterator = Iterator<Item = u32>;
+
+            struct Counter { }
+            struct Chars { }
+
+            impl Iterator for Counter { type Item = u32; }
+            impl Iterator for Chars { type Item = char; }
+        }
+
+        goal {
+            Counter: IntIterator
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            Chars: IntIterator
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<T> {
+                if (T: IntIterator) {
+                    <T as Iterator>::Item = u32
+                }
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn nested_trait_aliases() {
+    test! {
+        program {
+            trait Foo { }
+            trait Bar { }
+            trait Baz { }
+            trait FooBar = Foo + Bar;
+            trait FooBarBaz = FooBar + Baz;
+
+            struct A { }
+            struct B { }
+
+            impl Foo for A { }
+            impl Bar for A { }
+            impl Baz for A { }
+            impl Foo for B { }
+            impl Baz for B { }
+        }
+
+        goal {
+            A: FooBarBaz
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            B: FooBarBaz
+        } yields {
+            "No possible solution"
+        }
+
+        goal {
+            forall<T> {
+                if (T: FooBarBaz) {
+                    T: Bar
+                }
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
+
+#[test]
+fn dyn_trait_aliases() {
+    test! {
+        program {
+            trait Foo<T> { }
+            trait Bar { }
+            trait Baz { }
+            #[auto] trait Send { }
+            trait Alias<T> = Foo<T> + Send + Bar;
+            trait Nested = Alias<u32> + Baz;
+        }
+
+        goal {
+            forall<'s> {
+                dyn Alias<u32> + 's: Foo<u32>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'s> {
+                dyn Alias<u32> + 's: Send
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'s> {
+                dyn Alias<u32> + 's: Alias<u32>
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'s> {
+                dyn Nested + 's: Bar
+            }
+        } yields {
+            "Unique"
+        }
+
+        goal {
+            forall<'s> {
+                dyn Alias<u32> + 's: Baz
+            }
+        } yields {
+            "No possible solution"
+        }
+
+        // The aliases are replaced by their bounds, so both are the same type.
+        goal {
+            forall<'s> {
+                (dyn Alias<u32> + Baz + 's) = (dyn Nested + 's)
+            }
+        } yields {
+            "Unique"
+        }
+    }
+}
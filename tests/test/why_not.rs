@@ -0,0 +1,97 @@
+//! Tests for the failure analysis done by `why_not`.
+
+use chalk_integration::db::ChalkDatabase;
+use chalk_integration::lowering::lower_goal;
+use chalk_integration::SolverChoice;
+use chalk_solve::ext::*;
+use chalk_solve::solve::why_not::{ClauseRejection, FailureReason};
+use chalk_solve::RustIrDatabase;
+
+const PROGRAM: &str = "
+    struct Foo { }
+    struct Bar { }
+    struct Vec<T> { }
+    struct Rc<T> { }
+    struct Box<T> { }
+    trait Clone { }
+    trait Show { }
+    impl Clone for Foo { }
+    impl<T> Clone for Vec<T> where T: Clone { }
+    impl<T> Show for Box<T> { }
+";
+
+fn why_not(solver_choice: SolverChoice, goal_text: &str) -> Option<String> {
+    let db = ChalkDatabase::with(PROGRAM, solver_choice);
+    db.with_program(|program| {
+        let goal = lower_goal(&chalk_parse::parse_goal(goal_text).unwrap(), program).unwrap();
+        let peeled_goal = goal.into_peeled_goal(db.interner());
+        db.why_not(&peeled_goal)
+            .map(|analysis| analysis.to_string())
+    })
+}
+
+#[test]
+fn why_not_provable() {
+    assert_eq!(why_not(SolverChoice::default(), "Vec<Foo>: Clone"), None);
+}
+
+#[test]
+fn why_not_unprovable_condition() {
+    for solver_choice in [SolverChoice::slg_default(), SolverChoice::recursive()] {
+        assert_eq!(
+            why_not(solver_choice, "Vec<Bar>: Clone").unwrap(),
+            "\
+`Implemented(Vec<Bar>: Clone)` is not provable
+  clause `for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone)` rejected: `FromEnv(Vec<Bar>: Clone)` is not provable
+    no clauses apply
+  clause `for<type> Implemented(Vec<^0.0>: Clone) :- ForAll<> { Implemented(^1.0: Clone) }` rejected: `ForAll<> { Implemented(Bar: Clone) }` is not provable
+    `Implemented(Bar: Clone)` is not provable
+      clause `for<type> Implemented(^0.0: Clone) :- FromEnv(^0.0: Clone)` rejected: `FromEnv(Bar: Clone)` is not provable
+        no clauses apply
+      clause `for<> Implemented(Foo: Clone)` rejected: `Bar` does not unify with `Foo`
+      clause `for<type> Implemented(Vec<^0.0>: Clone) :- ForAll<> { Implemented(^1.0: Clone) }` rejected: `Bar` does not unify with `Vec<^0.0>`
+  clause `for<> Implemented(Foo: Clone)` rejected: `Vec<Bar>` does not unify with `Foo`
+"
+        );
+    }
+}
+
+#[test]
+fn why_not_mismatch() {
+    assert_eq!(
+        why_not(SolverChoice::default(), "Rc<Foo>: Show").unwrap(),
+        "\
+`Implemented(Rc<Foo>: Show)` is not provable
+  clause `for<type> Implemented(^0.0: Show) :- FromEnv(^0.0: Show)` rejected: `FromEnv(Rc<Foo>: Show)` is not provable
+    no clauses apply
+  clause `for<type> Implemented(Box<^0.0>: Show)` rejected: `Rc<Foo>` does not unify with `Box<^0.0>`
+"
+    );
+}
+
+#[test]
+fn why_not_subgoal() {
+    let db = ChalkDatabase::with(PROGRAM, SolverChoice::default());
+    db.with_program(|program| {
+        let goal = lower_goal(
+            &chalk_parse::parse_goal("Foo: Clone, Bar: Clone").unwrap(),
+            program,
+        )
+        .unwrap();
+        let analysis = db.why_not(&goal.into_peeled_goal(db.interner())).unwrap();
+        let analysis = match analysis.reason {
+            FailureReason::Subgoal(analysis) => analysis,
+            reason => panic!("unexpected reason {:?}", reason),
+        };
+        assert_eq!(
+            format!("{:?}", analysis.goal.canonical.value.goal),
+            "Implemented(Bar: Clone)"
+        );
+        match analysis.reason {
+            FailureReason::Clauses(rejected) => assert!(rejected
+                .iter()
+                .any(|r| matches!(r.rejection, ClauseRejection::Mismatch { .. }))),
+            reason => panic!("unexpected reason {:?}", reason),
+        }
+    });
+}